@@ -83,4 +83,55 @@ impl RedisCache {
             .await?;
         Ok(())
     }
+
+    // 通用字符串键：仅当不存在时写入（SET NX EX），返回是否写入成功
+    pub async fn set_nx_ex(
+        &self,
+        key: &str,
+        value: &str,
+        expire_secs: usize,
+    ) -> Result<bool, redis::RedisError> {
+        let mut conn = self.client.get_async_connection().await?;
+        let res: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("NX")
+            .arg("EX")
+            .arg(expire_secs)
+            .query_async(&mut conn)
+            .await?;
+        Ok(res.is_some())
+    }
+
+    // 固定窗口计数：自增并在首次写入时设置过期，返回窗口内的累计次数
+    pub async fn incr_ex(&self, key: &str, expire_secs: usize) -> Result<i64, redis::RedisError> {
+        let mut conn = self.client.get_async_connection().await?;
+        let count: i64 = conn.incr(key, 1).await?;
+        if count == 1 {
+            let _: () = conn.expire(key, expire_secs).await?;
+        }
+        Ok(count)
+    }
+
+    pub async fn get_string(&self, key: &str) -> Result<Option<String>, redis::RedisError> {
+        let mut conn = self.client.get_async_connection().await?;
+        conn.get(key).await
+    }
+
+    pub async fn set_string_ex(
+        &self,
+        key: &str,
+        value: &str,
+        expire_secs: usize,
+    ) -> Result<(), redis::RedisError> {
+        let mut conn = self.client.get_async_connection().await?;
+        let _: () = conn.set_ex(key, value, expire_secs).await?;
+        Ok(())
+    }
+
+    pub async fn delete_key(&self, key: &str) -> Result<(), redis::RedisError> {
+        let mut conn = self.client.get_async_connection().await?;
+        let _: () = conn.del(key).await?;
+        Ok(())
+    }
 }
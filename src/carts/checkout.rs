@@ -0,0 +1,88 @@
+use crate::{
+    errors::CustomError,
+    models::{
+        carts::CartCheckoutInput,
+        orders::{OrderCreateInput, OrderItemCreateInput, OrderOutNew},
+        users::UserToken,
+    },
+    AppState,
+};
+use ntex::web::{
+    types::{Json, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/cart/checkout",
+    tag = "购物车",
+    request_body = CartCheckoutInput,
+    responses((status = 201, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn checkout_cart(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<CartCheckoutInput>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    // 锁定 ACTIVE 购物车，避免重复结算
+    let cart_id: Option<i64> = sqlx::query_scalar(
+        "SELECT cart_id FROM carts WHERE user_id=$1 AND status='ACTIVE' FOR UPDATE"
+    )
+    .bind(user_token.user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(cart_id) = cart_id else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("购物车为空".into()));
+    };
+    let rows = sqlx::query(
+        "SELECT ci.food_id, ci.quantity, f.food_name, \
+         COALESCE(f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED', FALSE) AS available \
+         FROM cart_items ci LEFT JOIN foods f ON f.food_id=ci.food_id WHERE ci.cart_id=$1 ORDER BY ci.added_at, ci.id"
+    )
+    .bind(cart_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("购物车为空".into()));
+    }
+    let unavailable: Vec<String> = rows
+        .iter()
+        .filter(|r| !r.get::<bool, _>("available"))
+        .map(|r| r.try_get::<Option<String>, _>("food_name").ok().flatten().unwrap_or_default())
+        .collect();
+    if !unavailable.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest(format!("以下菜品已下架，请先移除：{}", unavailable.join("、"))));
+    }
+    let input = OrderCreateInput {
+        group_id: data.group_id,
+        invite_code: data.invite_code.clone(),
+        goal_time: data.goal_time,
+        items: rows
+            .iter()
+            .map(|r| OrderItemCreateInput {
+                food_id: r.get("food_id"),
+                quantity: Some(r.get("quantity")),
+            })
+            .collect(),
+        points_cost: data.points_cost,
+        points_reward: data.points_reward,
+    };
+    let out = crate::orders::new::insert_order(&mut tx, user_token.user_id, &input).await?;
+    sqlx::query("UPDATE carts SET status='SETTLED', updated_at=NOW() WHERE cart_id=$1")
+        .bind(cart_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    crate::orders::new::spawn_order_push(state.db_pool.clone(), out.order_id, "cart checkout");
+
+    Ok(HttpResponse::Created().json(&out))
+}
@@ -0,0 +1,78 @@
+pub mod view;
+pub mod update;
+pub mod checkout; // 购物车结算为订单
+
+use crate::{
+    errors::CustomError,
+    models::carts::{CartItemOut, CartOut, CartStatusEnum},
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Row};
+
+// 获取（或按需创建）用户当前 ACTIVE 购物车
+pub async fn active_cart_id(
+    conn: &mut PgConnection,
+    user_id: i64,
+    create: bool,
+) -> Result<Option<i64>, CustomError> {
+    if create {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO carts (user_id, status) VALUES ($1,'ACTIVE') \
+             ON CONFLICT (user_id) WHERE status='ACTIVE' DO UPDATE SET updated_at=NOW() RETURNING cart_id"
+        )
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        return Ok(Some(id));
+    }
+    let id: Option<i64> = sqlx::query_scalar("SELECT cart_id FROM carts WHERE user_id=$1 AND status='ACTIVE'")
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+// 读取购物车明细并组装输出（无 ACTIVE 购物车时返回空车）
+pub async fn load_cart(conn: &mut PgConnection, user_id: i64) -> Result<CartOut, CustomError> {
+    let cart = sqlx::query("SELECT cart_id, updated_at FROM carts WHERE user_id=$1 AND status='ACTIVE'")
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(cart) = cart else {
+        return Ok(CartOut {
+            cart_id: None,
+            status: CartStatusEnum::ACTIVE,
+            items: Vec::new(),
+            total_quantity: 0,
+            updated_at: None,
+        });
+    };
+    let cart_id: i64 = cart.get("cart_id");
+    let items: Vec<CartItemOut> = sqlx::query(
+        "SELECT ci.id, ci.food_id, ci.quantity, ci.added_at, f.food_name, f.food_photo, \
+         COALESCE(f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED', FALSE) AS available \
+         FROM cart_items ci LEFT JOIN foods f ON f.food_id=ci.food_id WHERE ci.cart_id=$1 ORDER BY ci.added_at, ci.id"
+    )
+    .bind(cart_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|r| CartItemOut {
+        id: r.get("id"),
+        food_id: r.get("food_id"),
+        food_name: r.try_get::<Option<String>, _>("food_name").ok().flatten(),
+        food_photo: r.try_get::<Option<String>, _>("food_photo").ok().flatten(),
+        quantity: r.get("quantity"),
+        available: r.get("available"),
+        added_at: r.get::<DateTime<Utc>, _>("added_at"),
+    })
+    .collect();
+    let total_quantity = items.iter().map(|i| i.quantity as i64).sum();
+    Ok(CartOut {
+        cart_id: Some(cart_id),
+        status: CartStatusEnum::ACTIVE,
+        items,
+        total_quantity,
+        updated_at: cart.try_get("updated_at").ok(),
+    })
+}
@@ -0,0 +1,168 @@
+use crate::{
+    errors::CustomError,
+    models::{
+        carts::{CartItemAddInput, CartItemUpdateInput, CartOut},
+        users::UserToken,
+    },
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/cart/items",
+    tag = "购物车",
+    request_body = CartItemAddInput,
+    responses((status = 200, body = CartOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_cart_item(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<CartItemAddInput>,
+) -> Result<impl Responder, CustomError> {
+    let qty = data.quantity.unwrap_or(1);
+    if qty <= 0 {
+        return Err(CustomError::BadRequest("数量必须大于0".into()));
+    }
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    let available = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM foods WHERE food_id=$1 AND is_del=0 AND food_status='NORMAL' AND apply_status='APPROVED')"
+    )
+    .bind(data.food_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if !available {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜品不存在或暂不可点".into()));
+    }
+    let cart_id = super::active_cart_id(&mut tx, user_token.user_id, true)
+        .await?
+        .ok_or_else(|| CustomError::InternalError("购物车创建失败".into()))?;
+    // 同一菜品合并数量
+    sqlx::query(
+        "INSERT INTO cart_items (cart_id, food_id, quantity) VALUES ($1,$2,$3) \
+         ON CONFLICT (cart_id, food_id) DO UPDATE SET quantity = cart_items.quantity + EXCLUDED.quantity"
+    )
+    .bind(cart_id)
+    .bind(data.food_id)
+    .bind(qty)
+    .execute(&mut *tx)
+    .await?;
+    let out = super::load_cart(&mut tx, user_token.user_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/cart/items/{item_id}",
+    tag = "购物车",
+    params(("item_id" = i64, Path, description = "购物车明细ID")),
+    request_body = CartItemUpdateInput,
+    responses((status = 200, body = CartOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_cart_item(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    item_id: Path<i64>,
+    data: Json<CartItemUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    let res = if data.quantity <= 0 {
+        sqlx::query(
+            "DELETE FROM cart_items ci USING carts c WHERE ci.id=$1 AND ci.cart_id=c.cart_id AND c.user_id=$2 AND c.status='ACTIVE'"
+        )
+        .bind(*item_id)
+        .bind(user_token.user_id)
+        .execute(&mut *tx)
+        .await?
+    } else {
+        sqlx::query(
+            "UPDATE cart_items ci SET quantity=$3 FROM carts c WHERE ci.id=$1 AND ci.cart_id=c.cart_id AND c.user_id=$2 AND c.status='ACTIVE'"
+        )
+        .bind(*item_id)
+        .bind(user_token.user_id)
+        .bind(data.quantity)
+        .execute(&mut *tx)
+        .await?
+    };
+    if res.rows_affected() == 0 {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("购物车明细不存在".into()));
+    }
+    touch_cart(&mut tx, user_token.user_id).await?;
+    let out = super::load_cart(&mut tx, user_token.user_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cart/items/{item_id}",
+    tag = "购物车",
+    params(("item_id" = i64, Path, description = "购物车明细ID")),
+    responses((status = 200, body = CartOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn remove_cart_item(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    item_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    let res = sqlx::query(
+        "DELETE FROM cart_items ci USING carts c WHERE ci.id=$1 AND ci.cart_id=c.cart_id AND c.user_id=$2 AND c.status='ACTIVE'"
+    )
+    .bind(*item_id)
+    .bind(user_token.user_id)
+    .execute(&mut *tx)
+    .await?;
+    if res.rows_affected() == 0 {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("购物车明细不存在".into()));
+    }
+    touch_cart(&mut tx, user_token.user_id).await?;
+    let out = super::load_cart(&mut tx, user_token.user_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/cart",
+    tag = "购物车",
+    responses((status = 200, body = CartOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn clear_cart(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    // 清空：当前购物车标记为 CLEARED，下次加购时新建 ACTIVE 购物车
+    sqlx::query("UPDATE carts SET status='CLEARED', updated_at=NOW() WHERE user_id=$1 AND status='ACTIVE'")
+        .bind(user_token.user_id)
+        .execute(&mut *tx)
+        .await?;
+    let out = super::load_cart(&mut tx, user_token.user_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+async fn touch_cart(conn: &mut sqlx::PgConnection, user_id: i64) -> Result<(), CustomError> {
+    sqlx::query("UPDATE carts SET updated_at=NOW() WHERE user_id=$1 AND status='ACTIVE'")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+use crate::{errors::CustomError, models::{carts::CartOut, users::UserToken}, AppState};
+use ntex::web::{types::State, HttpResponse, Responder};
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/cart",
+    tag = "购物车",
+    responses((status = 200, body = CartOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_cart(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let out = super::load_cart(&mut conn, user_token.user_id).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
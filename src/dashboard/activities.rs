@@ -71,6 +71,8 @@ pub async fn get_group_activities(
     // ORDER_CREATED      -> 订单创建（聚合菜名）
     // ORDER_ACCEPTED     -> 状态历史里 to_status=ACCEPTED
     // ORDER_FINISHED     -> 状态历史里 to_status=FINISHED
+    // ORDER_OVERDUE      -> 状态历史里 to_status=OVERDUE（到期提醒 from=to 不计入）
+    // ORDER_COMMENTED    -> 订单评论（ref_id 为订单ID，ref_name 为评论摘要）
     // FOOD_APPLIED       -> 菜品申请（submit_role=ORDERING_APPLY）
     // FOOD_CREATED       -> RECEIVING 创建的菜品
     // FOOD_APPROVED      -> 审核通过（food_audit_logs.action=2）
@@ -104,6 +106,7 @@ pub async fn get_group_activities(
                     WHEN osh.to_status='ACCEPTED' THEN 'ORDER_ACCEPTED' 
                     WHEN osh.to_status='FINISHED' THEN 'ORDER_FINISHED'
                     WHEN osh.to_status='CANCELLED' THEN 'ORDER_CANCELED'
+                    WHEN osh.to_status='OVERDUE' THEN 'ORDER_OVERDUE'
                     ELSE 'ORDER_OTHER'
                 END AS event_type,
                 osh.changed_at AS occurred_at,
@@ -114,9 +117,25 @@ pub async fn get_group_activities(
             FROM order_status_history osh
             JOIN orders o ON osh.order_id=o.order_id
             WHERE o.group_id=$1
-              AND osh.to_status IN ('ACCEPTED','FINISHED','CANCELLED')
+              AND osh.to_status IN ('ACCEPTED','FINISHED','CANCELLED','OVERDUE')
+              AND osh.from_status IS DISTINCT FROM osh.to_status
               AND osh.changed_at < $2
 
+            UNION ALL
+            -- 订单评论
+            SELECT 
+                oc.order_id AS ref_id,
+                oc.user_id AS actor_user_id,
+                'ORDER_COMMENTED' AS event_type,
+                oc.created_at AS occurred_at,
+                LEFT(COALESCE(NULLIF(oc.content,''),'[图片]'), 50) AS ref_name,
+                NULL::int AS point_amount,
+                NULL::text AS point_tx_type,
+                NULL::int AS point_balance_after
+            FROM order_comments oc
+            JOIN orders o ON oc.order_id=o.order_id
+            WHERE o.group_id=$1 AND oc.is_del=0 AND oc.created_at < $2
+
             UNION ALL
             -- 菜品申请 & 创建
             SELECT 
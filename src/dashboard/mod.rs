@@ -1,2 +1,3 @@
 pub mod activities;
 pub mod metrics; // 排名、我的订单树、积分之旅等
+pub mod nutrition; // 营养摄入汇总
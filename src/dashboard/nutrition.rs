@@ -0,0 +1,204 @@
+use crate::{errors::CustomError, models::users::UserToken, services::groups::ensure_member, AppState};
+use chrono::{Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use ntex::web::{
+    types::{Query, State},
+    HttpResponse, Responder,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const MAX_BUCKETS: i64 = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+pub enum NutritionPeriodEnum {
+    DAY,
+    WEEK,
+}
+
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct NutritionSummaryQuery {
+    /// 仅统计该组订单，缺省统计我下的全部订单
+    pub group_id: Option<i64>,
+    /// 汇总粒度，默认 DAY
+    pub period: Option<NutritionPeriodEnum>,
+    /// 开始日期（本地，含），默认 DAY 为近7天、WEEK 为近8周
+    pub from: Option<NaiveDate>,
+    /// 结束日期（本地，含），默认今天
+    pub to: Option<NaiveDate>,
+    /// 本地时区相对UTC的分钟偏移，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NutritionBucketOut {
+    /// 日期；WEEK 为当周周一
+    pub date: NaiveDate,
+    pub order_count: i64,
+    pub servings: i64,
+    pub calories: i64,
+    pub protein: f64,
+    pub fat: f64,
+    pub carbs: f64,
+    /// 未填写热量的份数（不计入合计）
+    pub servings_without_nutrition: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NutritionSummaryOut {
+    pub period: NutritionPeriodEnum,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub buckets: Vec<NutritionBucketOut>,
+    pub total: NutritionBucketOut,
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+fn bucket_start(date: NaiveDate, period: NutritionPeriodEnum) -> NaiveDate {
+    match period {
+        NutritionPeriodEnum::DAY => date,
+        NutritionPeriodEnum::WEEK => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+    }
+}
+
+fn empty_bucket(date: NaiveDate) -> NutritionBucketOut {
+    NutritionBucketOut {
+        date,
+        order_count: 0,
+        servings: 0,
+        calories: 0,
+        protein: 0.0,
+        fat: 0.0,
+        carbs: 0.0,
+        servings_without_nutrition: 0,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/dashboard/my/nutrition",
+    tag = "看板",
+    summary = "按天/周汇总我已完成订单的营养摄入（份数 × 每份营养）",
+    params(NutritionSummaryQuery),
+    responses((status = 200, body = NutritionSummaryOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_my_nutrition(
+    user: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<NutritionSummaryQuery>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let period = query.period.unwrap_or(NutritionPeriodEnum::DAY);
+    let tz_offset_minutes = query.tz_offset_minutes.unwrap_or(480);
+    let offset = FixedOffset::east_opt(tz_offset_minutes * 60)
+        .ok_or_else(|| CustomError::BadRequest("无效的时区偏移".into()))?;
+    let today = Utc::now().with_timezone(&offset).date_naive();
+    let to = query.to.unwrap_or(today);
+    let from = query.from.unwrap_or_else(|| match period {
+        NutritionPeriodEnum::DAY => to - Duration::days(6),
+        NutritionPeriodEnum::WEEK => bucket_start(to, period) - Duration::weeks(7),
+    });
+    if to < from {
+        return Err(CustomError::BadRequest("结束日期不能早于开始日期".into()));
+    }
+    if (to - from).num_days() >= MAX_BUCKETS {
+        return Err(CustomError::BadRequest(format!("时间范围不能超过{}天", MAX_BUCKETS)));
+    }
+
+    if let Some(gid) = query.group_id {
+        ensure_member(&mut *db.acquire().await?, gid, user.user_id).await?;
+    }
+
+    // 本地日期范围换算为 UTC 时间点：[from 00:00, to+1 00:00)
+    let start = offset
+        .from_local_datetime(&from.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .single()
+        .map(|t| t.with_timezone(&Utc))
+        .ok_or_else(|| CustomError::BadRequest("无效的开始日期".into()))?;
+    let end = start + Duration::days((to - from).num_days() + 1);
+    let trunc = match period {
+        NutritionPeriodEnum::DAY => "day",
+        NutritionPeriodEnum::WEEK => "week",
+    };
+
+    // 完成时间取最后一次状态变更时间
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new("SELECT date_trunc(");
+    qb.push_bind(trunc)
+        .push(", (COALESCE(o.last_status_change_at, o.updated_at) AT TIME ZONE 'UTC') + make_interval(mins => ")
+        .push_bind(tz_offset_minutes)
+        .push("))::date AS bucket, \
+         COUNT(DISTINCT o.order_id)::bigint AS order_count, \
+         COALESCE(SUM(oi.quantity), 0)::bigint AS servings, \
+         COALESCE(SUM(oi.quantity * f.calories), 0)::bigint AS calories, \
+         COALESCE(SUM(oi.quantity * f.protein), 0)::float8 AS protein, \
+         COALESCE(SUM(oi.quantity * f.fat), 0)::float8 AS fat, \
+         COALESCE(SUM(oi.quantity * f.carbs), 0)::float8 AS carbs, \
+         COALESCE(SUM(oi.quantity) FILTER (WHERE f.calories IS NULL), 0)::bigint AS missing \
+         FROM orders o JOIN order_items oi ON oi.order_id=o.order_id JOIN foods f ON f.food_id=oi.food_id \
+         WHERE o.status='FINISHED' AND o.user_id=")
+        .push_bind(user.user_id)
+        .push(" AND COALESCE(o.last_status_change_at, o.updated_at) >= ")
+        .push_bind(start)
+        .push(" AND COALESCE(o.last_status_change_at, o.updated_at) < ")
+        .push_bind(end);
+    if let Some(gid) = query.group_id {
+        qb.push(" AND o.group_id = ").push_bind(gid);
+    }
+    qb.push(" GROUP BY bucket ORDER BY bucket");
+    let rows = qb.build().fetch_all(db).await?;
+
+    let mut by_date: HashMap<NaiveDate, NutritionBucketOut> = HashMap::new();
+    for r in rows {
+        let date: NaiveDate = r.get("bucket");
+        by_date.insert(
+            date,
+            NutritionBucketOut {
+                date,
+                order_count: r.get("order_count"),
+                servings: r.get("servings"),
+                calories: r.get("calories"),
+                protein: round1(r.get("protein")),
+                fat: round1(r.get("fat")),
+                carbs: round1(r.get("carbs")),
+                servings_without_nutrition: r.get("missing"),
+            },
+        );
+    }
+
+    // 补齐无订单的日期/周，便于前端直接绘图
+    let step = match period {
+        NutritionPeriodEnum::DAY => Duration::days(1),
+        NutritionPeriodEnum::WEEK => Duration::weeks(1),
+    };
+    let mut buckets: Vec<NutritionBucketOut> = Vec::new();
+    let mut total = empty_bucket(from);
+    let mut d = bucket_start(from, period);
+    while d <= to {
+        let b = by_date.remove(&d).unwrap_or_else(|| empty_bucket(d));
+        total.order_count += b.order_count;
+        total.servings += b.servings;
+        total.calories += b.calories;
+        total.protein += b.protein;
+        total.fat += b.fat;
+        total.carbs += b.carbs;
+        total.servings_without_nutrition += b.servings_without_nutrition;
+        buckets.push(b);
+        d += step;
+    }
+    total.protein = round1(total.protein);
+    total.fat = round1(total.fat);
+    total.carbs = round1(total.carbs);
+
+    Ok(HttpResponse::Ok().json(&NutritionSummaryOut {
+        period,
+        from,
+        to,
+        buckets,
+        total,
+    }))
+}
@@ -23,7 +23,11 @@ pub enum CustomError {
     InternalError(String),
     BadRequest(String),
     AuthFailed(String),
-    RedisError(String)
+    RedisError(String),
+    /// 请求冲突（如 Idempotency-Key 重复使用但请求体不同）
+    Conflict(String),
+    /// 请求过于频繁
+    TooManyRequests(String),
 }
 
 impl WebResponseError for CustomError {
@@ -35,6 +39,8 @@ impl WebResponseError for CustomError {
             Self::BadRequest(_) => StatusCode::BAD_REQUEST,
             Self::AuthFailed(_) => StatusCode::UNAUTHORIZED,
             Self::RedisError(_) => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
         }
     }
 
@@ -49,6 +55,8 @@ impl WebResponseError for CustomError {
             Self::BadRequest(e) => e.clone(),
             Self::AuthFailed(e) => e.clone(),
             Self::RedisError(e) => e.clone(),
+            Self::Conflict(e) => e.clone(),
+            Self::TooManyRequests(e) => e.clone(),
         };
         let body = ErrorBody { code: status.as_u16(), message: msg };
         HttpResponse::build(status)
@@ -66,6 +74,8 @@ impl fmt::Display for CustomError {
             CustomError::InternalServerError(e) => write!(f, "{e}"),
             CustomError::InternalError(e) => write!(f, "{e}"),
             CustomError::RedisError(e) => write!(f, "{e}"),
+            CustomError::Conflict(e) => write!(f, "{e}"),
+            CustomError::TooManyRequests(e) => write!(f, "{e}"),
         }
     }
 }
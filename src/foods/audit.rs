@@ -0,0 +1,475 @@
+use crate::{
+    errors::CustomError,
+    foods::revisions,
+    models::foods::{
+        ApplyStatusEnum, FoodAuditInput, FoodAuditLogOut, FoodOut, FoodRecord, FoodReviewOut,
+        FoodRevisionSourceEnum, MarkTypeEnum, SubmitRoleEnum, TagRecord,
+    },
+    models::users::UserToken,
+    services::notifications::push_food_notice,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, PgPool, Row};
+use std::sync::Arc;
+
+// food_audit_logs.action
+pub const AUDIT_SUBMIT: i16 = 1;
+pub const AUDIT_APPROVE: i16 = 2;
+pub const AUDIT_REJECT: i16 = 3;
+pub const AUDIT_REQUEST_CHANGES: i16 = 4;
+pub const AUDIT_REVERT: i16 = 5;
+
+pub const FOOD_COLUMNS: &str = "food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id, calories, protein, fat, carbs, dietary_labels";
+
+const REMARK_MAX_CHARS: usize = 255;
+
+#[derive(Debug, Clone, Copy)]
+enum Decision {
+    Approve,
+    Reject,
+    RequestChanges,
+}
+
+// 写入一条审核历史，返回记录ID（供修订历史关联）
+pub async fn insert_audit_log(
+    conn: &mut PgConnection,
+    food_id: i64,
+    action: i16,
+    from_status: Option<ApplyStatusEnum>,
+    to_status: ApplyStatusEnum,
+    acted_by: i64,
+    remark: Option<&str>,
+) -> Result<i64, CustomError> {
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO food_audit_logs (food_id, action, from_status, to_status, acted_by, remark) VALUES ($1,$2,$3,$4,$5,$6) RETURNING id",
+    )
+    .bind(food_id)
+    .bind(action)
+    .bind(from_status)
+    .bind(to_status)
+    .bind(acted_by)
+    .bind(remark)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(id)
+}
+
+// 审核人需为菜品所属组的 RECEIVING / ADMIN 成员；未归属组的菜品按用户角色判断。
+// 提交人不能审核自己的申请（组管理员除外）。
+pub async fn ensure_reviewer(
+    conn: &mut PgConnection,
+    group_id: Option<i64>,
+    created_by: i64,
+    user_id: i64,
+) -> Result<(), CustomError> {
+    let role: Option<String> = match group_id {
+        Some(gid) => sqlx::query_scalar(
+            "SELECT role_in_group::text FROM association_group_members WHERE group_id=$1 AND user_id=$2",
+        )
+        .bind(gid)
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?,
+        None => sqlx::query_scalar("SELECT role::text FROM users WHERE user_id=$1")
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?,
+    };
+    let is_admin = role.as_deref() == Some("ADMIN");
+    if !is_admin && role.as_deref() != Some("RECEIVING") {
+        return Err(CustomError::BadRequest("仅接单方或管理员可审核".into()));
+    }
+    if !is_admin && created_by == user_id {
+        return Err(CustomError::BadRequest("禁止自审".into()));
+    }
+    Ok(())
+}
+
+// 菜品编辑权限（菜谱、图集、库存用量、恢复修订）：创建人、菜品所属组的接单方/管理员，或系统管理员。
+// 同时锁定菜品行，返回菜品所属组。
+pub async fn ensure_food_editor(
+    conn: &mut PgConnection,
+    food_id: i64,
+    user_id: i64,
+) -> Result<Option<i64>, CustomError> {
+    let row = sqlx::query(
+        "SELECT f.group_id, (f.created_by=$2 \
+          OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=f.group_id AND m.user_id=$2 AND m.role_in_group IN ('RECEIVING','ADMIN')) \
+          OR EXISTS(SELECT 1 FROM users u WHERE u.user_id=$2 AND u.role='ADMIN')) AS allowed \
+         FROM foods f WHERE f.food_id=$1 AND f.is_del=0 FOR UPDATE OF f",
+    )
+    .bind(food_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("菜品不存在".into()));
+    };
+    if !row.get::<bool, _>("allowed") {
+        return Err(CustomError::BadRequest("无权限修改".into()));
+    }
+    Ok(row.get("group_id"))
+}
+
+// 提交人修改自己申请的菜品内容（含已通过的菜品）视为重新提交：置为待审核并写入审核历史。
+// 提交人本身可审核（组管理员）时不需要重新审核。所有修改菜品内容的接口在写入后调用；
+// 返回审核历史ID，未触发时为 None。
+pub async fn resubmit_submitter_edit(
+    conn: &mut PgConnection,
+    food_id: i64,
+    user_id: i64,
+    remark: Option<&str>,
+) -> Result<Option<i64>, CustomError> {
+    let rec: FoodRecord = sqlx::query_as(&format!("SELECT {} FROM foods WHERE food_id=$1", FOOD_COLUMNS))
+        .bind(food_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !matches!(rec.submit_role, SubmitRoleEnum::ORDERING_APPLY) || rec.created_by != user_id {
+        return Ok(None);
+    }
+    if ensure_reviewer(conn, rec.group_id, rec.created_by, user_id).await.is_ok() {
+        return Ok(None);
+    }
+    sqlx::query("UPDATE foods SET apply_status='PENDING', food_status='AUDITING', updated_at=NOW() WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    let id = insert_audit_log(conn, food_id, AUDIT_SUBMIT, Some(rec.apply_status), ApplyStatusEnum::PENDING, user_id, remark)
+        .await?;
+    Ok(Some(id))
+}
+
+fn normalize_remark(remark: &Option<String>, required: bool) -> Result<Option<String>, CustomError> {
+    let remark = remark
+        .as_ref()
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    if required && remark.is_none() {
+        return Err(CustomError::BadRequest("请填写审核备注".into()));
+    }
+    if remark.as_ref().is_some_and(|r| r.chars().count() > REMARK_MAX_CHARS) {
+        return Err(CustomError::BadRequest(format!("审核备注不能超过{}字", REMARK_MAX_CHARS)));
+    }
+    Ok(remark)
+}
+
+// 读取菜品并组装 FoodOut（含主标签、全部标签与当前用户标记）
+pub async fn load_food_out(conn: &mut PgConnection, food_id: i64, uid: i64) -> Result<FoodOut, CustomError> {
+    let rec: FoodRecord = sqlx::query_as(&format!("SELECT {} FROM foods WHERE food_id=$1", FOOD_COLUMNS))
+        .bind(food_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let tag_row: Option<TagRecord> = match rec.tag_id {
+        Some(tid) => sqlx::query_as("SELECT * FROM tags WHERE tag_id=$1")
+            .bind(tid)
+            .fetch_optional(&mut *conn)
+            .await?,
+        None => None,
+    };
+    let marks: Vec<MarkTypeEnum> =
+        sqlx::query("SELECT mark_type::text FROM user_food_mark WHERE user_id=$1 AND food_id=$2")
+            .bind(uid)
+            .bind(food_id)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .filter_map(|r| match r.get::<String, _>(0).as_str() {
+                "LIKE" => Some(MarkTypeEnum::LIKE),
+                "NOT_RECOMMEND" => Some(MarkTypeEnum::NOT_RECOMMEND),
+                _ => None,
+            })
+            .collect();
+    let tags = crate::foods::tags::load_food_tags(conn, &[food_id])
+        .await?
+        .remove(&food_id)
+        .unwrap_or_default();
+    let mut out = FoodOut::from((rec, tag_row, marks));
+    out.tags = tags;
+    Ok(out)
+}
+
+fn spawn_food_push(pool: PgPool, food_id: i64, title: &'static str, target: i64) {
+    tokio::spawn(async move {
+        if let Err(e) = push_food_notice(food_id, title, &[target], pool).await {
+            log::warn!("push food audit notice failed: {}", e);
+        }
+    });
+}
+
+async fn review_food(
+    token: &UserToken,
+    state: &AppState,
+    food_id: i64,
+    decision: Decision,
+    data: &FoodAuditInput,
+) -> Result<FoodOut, CustomError> {
+    let remark = normalize_remark(&data.remark, !matches!(decision, Decision::Approve))?;
+    let uid = token.user_id;
+    let mut tx = state.db_pool.begin().await?;
+    let rec: Option<FoodRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM foods WHERE food_id=$1 AND is_del=0 FOR UPDATE",
+        FOOD_COLUMNS
+    ))
+    .bind(food_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let rec = match rec {
+        Some(r) => r,
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("菜品不存在".into()));
+        }
+    };
+    if let Err(e) = ensure_reviewer(&mut tx, rec.group_id, rec.created_by, uid).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    if !matches!(rec.apply_status, ApplyStatusEnum::PENDING) {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该菜品不在待审核状态".into()));
+    }
+    // 拒绝并回退：需存在审核通过过的修订版本
+    let revert_to = if matches!(decision, Decision::Reject) && data.revert.unwrap_or(false) {
+        match revisions::last_approved_revision(&mut tx, food_id).await? {
+            Some(r) => Some(r),
+            None => {
+                tx.rollback().await.ok();
+                return Err(CustomError::BadRequest("该菜品没有审核通过的版本，无法回退".into()));
+            }
+        }
+    } else {
+        None
+    };
+
+    let (action, to_status) = match decision {
+        Decision::Approve => {
+            sqlx::query(
+                "UPDATE foods SET apply_status='APPROVED', food_status='NORMAL', approved_at=NOW(), approved_by=$2, \
+                 apply_remark=COALESCE($3, apply_remark), updated_at=NOW() WHERE food_id=$1",
+            )
+            .bind(food_id)
+            .bind(uid)
+            .bind(&remark)
+            .execute(&mut *tx)
+            .await?;
+            (AUDIT_APPROVE, ApplyStatusEnum::APPROVED)
+        }
+        Decision::Reject => {
+            sqlx::query(
+                "UPDATE foods SET apply_status='REJECTED', food_status='REJECTED', approved_at=NULL, approved_by=NULL, \
+                 apply_remark=$2, updated_at=NOW() WHERE food_id=$1",
+            )
+            .bind(food_id)
+            .bind(&remark)
+            .execute(&mut *tx)
+            .await?;
+            (AUDIT_REJECT, ApplyStatusEnum::REJECTED)
+        }
+        // 保持待审核，等待提交人修改后重新提交
+        Decision::RequestChanges => {
+            sqlx::query("UPDATE foods SET food_status='AUDITING', apply_remark=$2, updated_at=NOW() WHERE food_id=$1")
+                .bind(food_id)
+                .bind(&remark)
+                .execute(&mut *tx)
+                .await?;
+            (AUDIT_REQUEST_CHANGES, ApplyStatusEnum::PENDING)
+        }
+    };
+    let log_id = insert_audit_log(
+        &mut tx,
+        food_id,
+        action,
+        Some(rec.apply_status),
+        to_status,
+        uid,
+        remark.as_deref(),
+    )
+    .await?;
+
+    match &revert_to {
+        // 被拒绝的修改回退到最近一次通过的内容，并恢复上架
+        Some((revision_no, snapshot)) => {
+            revisions::apply_snapshot(&mut tx, food_id, rec.group_id, snapshot).await?;
+            sqlx::query(
+                "UPDATE foods SET apply_status='APPROVED', food_status='NORMAL', approved_at=NOW(), approved_by=$2, updated_at=NOW() WHERE food_id=$1",
+            )
+            .bind(food_id)
+            .bind(uid)
+            .execute(&mut *tx)
+            .await?;
+            let revert_remark = format!("回退到修订版本 #{}", revision_no);
+            let revert_log_id = insert_audit_log(
+                &mut tx,
+                food_id,
+                AUDIT_REVERT,
+                Some(ApplyStatusEnum::REJECTED),
+                ApplyStatusEnum::APPROVED,
+                uid,
+                Some(&revert_remark),
+            )
+            .await?;
+            revisions::record_revision(&mut tx, food_id, FoodRevisionSourceEnum::RESTORE, uid, Some(revert_log_id))
+                .await?;
+        }
+        None => {
+            revisions::record_revision(&mut tx, food_id, FoodRevisionSourceEnum::AUDIT, uid, Some(log_id)).await?;
+        }
+    }
+
+    let out = load_food_out(&mut tx, food_id, uid).await?;
+    tx.commit().await?;
+
+    let title = match decision {
+        Decision::Approve => "菜品审核通过",
+        Decision::Reject if revert_to.is_some() => "菜品修改未通过，已回退到上一通过版本",
+        Decision::Reject => "菜品审核未通过",
+        Decision::RequestChanges => "菜品需修改后重新提交",
+    };
+    spawn_food_push(state.db_pool.clone(), food_id, title, rec.created_by);
+    Ok(out)
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/approve",
+    tag = "菜品",
+    summary = "审核通过菜品申请",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodAuditInput,
+    responses((status = 200, body = FoodOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn approve_food(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodAuditInput>,
+) -> Result<impl Responder, CustomError> {
+    let out = review_food(&token, &state, *id, Decision::Approve, &data).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/reject",
+    tag = "菜品",
+    summary = "拒绝菜品申请（需填写备注；revert=true 时内容回退到最近一次审核通过的版本）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodAuditInput,
+    responses((status = 200, body = FoodOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn reject_food(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodAuditInput>,
+) -> Result<impl Responder, CustomError> {
+    let out = review_food(&token, &state, *id, Decision::Reject, &data).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/request-changes",
+    tag = "菜品",
+    summary = "要求提交人修改菜品申请（需填写备注）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodAuditInput,
+    responses((status = 200, body = FoodOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn request_food_changes(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodAuditInput>,
+) -> Result<impl Responder, CustomError> {
+    let out = review_food(&token, &state, *id, Decision::RequestChanges, &data).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/audit-logs",
+    tag = "菜品",
+    summary = "菜品审核历史",
+    params(("id" = i64, Path, description = "菜品ID")),
+    responses((status = 200, body = [FoodAuditLogOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_food_audit_logs(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    // 提交人或菜品所属组成员可查看
+    let allowed: Option<bool> = sqlx::query_scalar(
+        "SELECT (f.created_by=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=f.group_id AND m.user_id=$2)) \
+         FROM foods f WHERE f.food_id=$1 AND f.is_del=0",
+    )
+    .bind(*id)
+    .bind(token.user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match allowed {
+        Some(true) => {}
+        Some(false) => return Err(CustomError::BadRequest("无权查看该菜品".into())),
+        None => return Err(CustomError::BadRequest("菜品不存在".into())),
+    }
+    let list: Vec<FoodAuditLogOut> = sqlx::query_as(
+        "SELECT l.id, l.food_id, l.action, l.from_status, l.to_status, l.acted_by, u.nick_name AS actor_name, l.remark, l.created_at \
+         FROM food_audit_logs l LEFT JOIN users u ON u.user_id=l.acted_by \
+         WHERE l.food_id=$1 ORDER BY l.created_at DESC, l.id DESC",
+    )
+    .bind(*id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(HttpResponse::Ok().json(&list))
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/food-reviews",
+    tag = "菜品",
+    summary = "组内待审核菜品队列（先提交先审核）",
+    params(("group_id" = i64, Path, description = "组ID")),
+    responses((status = 200, body = [FoodReviewOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_pending_food_reviews(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let role: Option<String> = sqlx::query_scalar(
+        "SELECT role_in_group::text FROM association_group_members WHERE group_id=$1 AND user_id=$2",
+    )
+    .bind(*group_id)
+    .bind(token.user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if !matches!(role.as_deref(), Some("RECEIVING") | Some("ADMIN")) {
+        return Err(CustomError::BadRequest("仅接单方或管理员可审核".into()));
+    }
+    // 等待提交人修改的排在后面
+    let list: Vec<FoodReviewOut> = sqlx::query_as(
+        "SELECT f.food_id, f.food_name, f.food_photo, f.created_by, u.nick_name AS submitter_name, f.apply_remark, \
+         la.action AS last_action, la.remark AS last_remark, COALESCE(la.action=4, FALSE) AS awaiting_changes, \
+         f.created_at, f.updated_at \
+         FROM foods f LEFT JOIN users u ON u.user_id=f.created_by \
+         LEFT JOIN LATERAL (SELECT action, remark FROM food_audit_logs WHERE food_id=f.food_id ORDER BY created_at DESC, id DESC LIMIT 1) la ON TRUE \
+         WHERE f.group_id=$1 AND f.is_del=0 AND f.apply_status='PENDING' \
+         ORDER BY awaiting_changes, f.updated_at ASC LIMIT 200",
+    )
+    .bind(*group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(HttpResponse::Ok().json(&list))
+}
@@ -0,0 +1,571 @@
+// 菜品盲盒：按标签加权抽取并落库（lottery_draws / lottery_draw_results），支持付费重抽、历史与一键下单
+use crate::{
+    errors::CustomError,
+    models::foods::{
+        BlindBoxDrawHistoryQuery, BlindBoxDrawInput, BlindBoxDrawOut, BlindBoxDrawResultOut,
+        BlindBoxFoodSnapshot, BlindBoxRerollInput,
+    },
+    models::orders::{OrderItemCreateInput, OrderPlaceInput, PlacedOrderOut},
+    models::users::UserToken,
+    orders::new::spawn_order_push,
+    orders::reorder::place_items,
+    services::groups::{ensure_member, primary_group_id},
+    AppState,
+};
+use chrono::{DateTime, Utc};
+use ntex::web::{
+    types::{Json, Path, Query, State},
+    HttpResponse, Responder,
+};
+use rand::Rng;
+use sqlx::{PgConnection, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 每次重抽消耗的爱心积分
+const REROLL_COST: i32 = 2;
+/// point_transactions.ref_type 的业务自定义值：4 = 盲盒抽取（重抽扣分记为 OTHER）
+const REF_TYPE_LOTTERY_DRAW: i16 = 4;
+const DEFAULT_AVOID_RECENT_DRAWS: u32 = 3;
+const MAX_AVOID_RECENT_DRAWS: u32 = 20;
+
+pub struct Candidate {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub weight: f64,
+}
+
+// 抽取权重：收藏 ×3；组内 3 天内吃过 ×0.3、7 天内 ×0.6
+pub fn draw_weight(liked: bool, last_ordered_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+    let mut w = 1.0;
+    if liked {
+        w *= 3.0;
+    }
+    if let Some(last) = last_ordered_at {
+        let days = (now - last).num_days();
+        if days < 3 {
+            w *= 0.3;
+        } else if days < 7 {
+            w *= 0.6;
+        }
+    }
+    w
+}
+
+// 按权重不放回抽取 k 个
+pub fn weighted_pick(mut pool: Vec<Candidate>, k: usize) -> Vec<Candidate> {
+    let mut rng = rand::thread_rng();
+    let mut out = Vec::with_capacity(k.min(pool.len()));
+    while out.len() < k && !pool.is_empty() {
+        let total: f64 = pool.iter().map(|c| c.weight).sum();
+        let mut x = rng.gen_range(0.0..total);
+        let mut idx = pool.len() - 1;
+        for (i, c) in pool.iter().enumerate() {
+            if x < c.weight {
+                idx = i;
+                break;
+            }
+            x -= c.weight;
+        }
+        out.push(pool.swap_remove(idx));
+    }
+    out
+}
+
+// 最近 n 次成功抽取中出现过的菜品
+async fn recent_drawn_foods(
+    conn: &mut PgConnection,
+    user_id: i64,
+    group_id: i64,
+    n: u32,
+) -> Result<Vec<i64>, CustomError> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let ids = sqlx::query_scalar(
+        "SELECT DISTINCT r.food_id FROM lottery_draw_results r WHERE r.draw_id IN \
+         (SELECT draw_id FROM lottery_draws WHERE user_id=$1 AND group_id=$2 AND is_success='SUCCESS' \
+          ORDER BY created_at DESC LIMIT $3)",
+    )
+    .bind(user_id)
+    .bind(group_id)
+    .bind(n as i64)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(ids)
+}
+
+// 逐个标签加权抽取。排除自己标记不推荐的菜品与 exclude；优先避开 avoid，若因此无菜可抽则放开。
+async fn pick_foods(
+    conn: &mut PgConnection,
+    user_id: i64,
+    group_id: i64,
+    tags: &[(i64, usize)],
+    mut exclude: Vec<i64>,
+    avoid: &[i64],
+) -> Result<Vec<(i64, Candidate)>, CustomError> {
+    let now = Utc::now();
+    let mut picks: Vec<(i64, Candidate)> = Vec::new();
+    for (tag_id, count) in tags {
+        let rows = sqlx::query(
+            "SELECT f.food_id, f.food_name, f.food_photo, \
+             EXISTS(SELECT 1 FROM user_food_mark um WHERE um.food_id=f.food_id AND um.user_id=$3 AND um.mark_type='LIKE') AS liked, \
+             (SELECT MAX(COALESCE(o.goal_time, o.created_at)) FROM order_items oi JOIN orders o ON o.order_id=oi.order_id \
+              WHERE oi.food_id=f.food_id AND o.group_id=$1 AND o.status NOT IN ('CANCELLED','REJECTED','EXPIRED','SYSTEM_CLOSED')) AS last_ordered_at \
+             FROM foods f JOIN food_tags_map m ON m.food_id=f.food_id \
+             WHERE f.group_id=$1 AND m.tag_id=$2 AND f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED' \
+             AND NOT EXISTS(SELECT 1 FROM user_food_mark um WHERE um.food_id=f.food_id AND um.user_id=$3 AND um.mark_type='NOT_RECOMMEND') \
+             AND NOT (f.food_id = ANY($4))",
+        )
+        .bind(group_id)
+        .bind(*tag_id)
+        .bind(user_id)
+        .bind(&exclude)
+        .fetch_all(&mut *conn)
+        .await?;
+        let (fresh, recent): (Vec<Candidate>, Vec<Candidate>) = rows
+            .into_iter()
+            .map(|r| Candidate {
+                food_id: r.get("food_id"),
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                weight: draw_weight(r.get("liked"), r.get("last_ordered_at"), now),
+            })
+            .partition(|c| !avoid.contains(&c.food_id));
+        let pool = if fresh.is_empty() { recent } else { fresh };
+        for c in weighted_pick(pool, *count) {
+            exclude.push(c.food_id);
+            picks.push((*tag_id, c));
+        }
+    }
+    Ok(picks)
+}
+
+// 写入抽取记录及结果快照；无结果时记为失败
+async fn insert_draw(
+    conn: &mut PgConnection,
+    user_id: i64,
+    group_id: i64,
+    parent_draw_id: Option<i64>,
+    payload: &BlindBoxDrawInput,
+    points_cost: i32,
+    picks: &[(Option<i64>, i64, String, Option<String>)],
+) -> Result<BlindBoxDrawResultOut, CustomError> {
+    let (is_success, fail_reason) = if picks.is_empty() {
+        ("FAIL", Some("没有可抽取的菜品"))
+    } else {
+        ("SUCCESS", None)
+    };
+    let draw_id: i64 = sqlx::query_scalar(
+        "INSERT INTO lottery_draws (user_id, group_id, parent_draw_id, request_payload, points_cost, is_success, fail_reason) \
+         VALUES ($1,$2,$3,$4,$5,$6::lottery_success_enum,$7) RETURNING draw_id",
+    )
+    .bind(user_id)
+    .bind(group_id)
+    .bind(parent_draw_id)
+    .bind(sqlx::types::Json(payload))
+    .bind(points_cost)
+    .bind(is_success)
+    .bind(fail_reason)
+    .fetch_one(&mut *conn)
+    .await?;
+    let mut results = Vec::with_capacity(picks.len());
+    for (tag_id, food_id, food_name, food_photo) in picks {
+        let result_id: i64 = sqlx::query_scalar(
+            "INSERT INTO lottery_draw_results (draw_id, tag_id, food_id, food_name_snapshot, food_photo_snapshot) \
+             VALUES ($1,$2,$3,$4,$5) RETURNING id",
+        )
+        .bind(draw_id)
+        .bind(*tag_id)
+        .bind(*food_id)
+        .bind(food_name)
+        .bind(food_photo.as_deref())
+        .fetch_one(&mut *conn)
+        .await?;
+        results.push(BlindBoxFoodSnapshot {
+            result_id,
+            tag_id: *tag_id,
+            food_id: *food_id,
+            food_name: food_name.clone(),
+            food_photo: food_photo.clone(),
+        });
+    }
+    Ok(BlindBoxDrawResultOut {
+        draw_id,
+        parent_draw_id,
+        points_cost,
+        results,
+        requested_tags: payload.tag_ids.clone(),
+    })
+}
+
+async fn load_results(
+    conn: &mut PgConnection,
+    draw_ids: &[i64],
+) -> Result<HashMap<i64, Vec<BlindBoxFoodSnapshot>>, CustomError> {
+    let rows = sqlx::query(
+        "SELECT id, draw_id, tag_id, food_id, food_name_snapshot, food_photo_snapshot \
+         FROM lottery_draw_results WHERE draw_id = ANY($1) ORDER BY draw_id, id",
+    )
+    .bind(draw_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut map: HashMap<i64, Vec<BlindBoxFoodSnapshot>> = HashMap::new();
+    for r in rows {
+        map.entry(r.get("draw_id")).or_default().push(BlindBoxFoodSnapshot {
+            result_id: r.get("id"),
+            tag_id: r.get("tag_id"),
+            food_id: r.get("food_id"),
+            food_name: r.get::<Option<String>, _>("food_name_snapshot").unwrap_or_default(),
+            food_photo: r.get("food_photo_snapshot"),
+        });
+    }
+    Ok(map)
+}
+
+#[utoipa::path(
+	post,
+	path = "/foods/blind_box/draw",
+	tag = "菜品",
+	summary = "按标签加权抽取菜品（排除不推荐、避开最近抽中）并记录",
+	request_body = BlindBoxDrawInput,
+	responses((status = 200, body = BlindBoxDrawResultOut)),
+	security(("cookie_auth" = []))
+)]
+pub async fn draw_blind_box(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<BlindBoxDrawInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.tag_ids.is_empty() {
+        return Err(CustomError::BadRequest("请选择标签".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    // 获取 group_id，如果为空使用用户的主组
+    let group_id: i64 = match data.group_id {
+        Some(gid) => {
+            if let Err(e) = ensure_member(&mut tx, gid, token.user_id).await {
+                tx.rollback().await.ok();
+                return Err(e);
+            }
+            gid
+        }
+        None => match primary_group_id(&mut tx, token.user_id).await? {
+            Some(gid) => gid,
+            None => {
+                tx.rollback().await.ok();
+                return Err(CustomError::BadRequest("未找到绑定组".into()));
+            }
+        },
+    };
+    let limit_each = data.limit_each.unwrap_or(1).clamp(1, 10) as usize;
+    let avoid_n = data
+        .avoid_recent_draws
+        .unwrap_or(DEFAULT_AVOID_RECENT_DRAWS)
+        .min(MAX_AVOID_RECENT_DRAWS);
+    let avoid = recent_drawn_foods(&mut tx, token.user_id, group_id, avoid_n).await?;
+    let tags: Vec<(i64, usize)> = data.tag_ids.iter().map(|t| (*t, limit_each)).collect();
+    let picks = pick_foods(&mut tx, token.user_id, group_id, &tags, Vec::new(), &avoid).await?;
+    let picks: Vec<(Option<i64>, i64, String, Option<String>)> = picks
+        .into_iter()
+        .map(|(tag_id, c)| (Some(tag_id), c.food_id, c.food_name, c.food_photo))
+        .collect();
+    let mut payload = data.into_inner();
+    payload.group_id = Some(group_id);
+    let out = insert_draw(&mut tx, token.user_id, group_id, None, &payload, 0, &picks).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+	post,
+	path = "/foods/blind_box/draws/{id}/reroll",
+	tag = "菜品",
+	summary = "重抽（整体或指定结果），成功时消耗积分",
+	params(("id" = i64, Path, description = "抽取记录ID")),
+	request_body = BlindBoxRerollInput,
+	responses((status = 200, body = BlindBoxDrawResultOut)),
+	security(("cookie_auth" = []))
+)]
+pub async fn reroll_blind_box(
+    mut token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<BlindBoxRerollInput>,
+) -> Result<impl Responder, CustomError> {
+    let uid = token.user_id;
+    let mut tx = state.db_pool.begin().await?;
+    let draw = sqlx::query(
+        "SELECT user_id, group_id, order_id, superseded_at IS NOT NULL AS superseded, request_payload \
+         FROM lottery_draws WHERE draw_id=$1 FOR UPDATE",
+    )
+    .bind(*id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(draw) = draw else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("抽取记录不存在".into()));
+    };
+    if draw.get::<i64, _>("user_id") != uid {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("无权操作该抽取".into()));
+    }
+    if draw.get::<Option<i64>, _>("order_id").is_some() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该抽取已下单，无法重抽".into()));
+    }
+    if draw.get::<bool, _>("superseded") {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该抽取已被重抽取代，请使用最新结果".into()));
+    }
+    let Some(group_id) = draw.get::<Option<i64>, _>("group_id") else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("旧抽取记录不支持重抽".into()));
+    };
+    let payload: BlindBoxDrawInput = match draw
+        .get::<Option<sqlx::types::Json<BlindBoxDrawInput>>, _>("request_payload")
+    {
+        Some(p) => p.0,
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("旧抽取记录不支持重抽".into()));
+        }
+    };
+
+    // 锁定积分行，先确认余额
+    let love_point: i32 = sqlx::query_scalar("SELECT love_point FROM users WHERE user_id=$1 FOR UPDATE")
+        .bind(uid)
+        .fetch_one(&mut *tx)
+        .await?;
+    if love_point < REROLL_COST {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest(format!("积分不足，重抽需要{}积分", REROLL_COST)));
+    }
+
+    let previous = load_results(&mut tx, &[*id]).await?.remove(&*id).unwrap_or_default();
+    let replace: Vec<&BlindBoxFoodSnapshot> = match &data.result_ids {
+        Some(ids) if !ids.is_empty() => {
+            if ids.iter().any(|rid| !previous.iter().any(|p| p.result_id == *rid)) {
+                tx.rollback().await.ok();
+                return Err(CustomError::BadRequest("结果不属于该抽取".into()));
+            }
+            previous.iter().filter(|p| ids.contains(&p.result_id)).collect()
+        }
+        _ => previous.iter().collect(),
+    };
+    if replace.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("没有可重抽的结果".into()));
+    }
+    let mut tag_counts: Vec<(i64, usize)> = Vec::new();
+    for p in &replace {
+        let Some(tid) = p.tag_id else {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("旧抽取记录不支持重抽".into()));
+        };
+        match tag_counts.iter_mut().find(|(t, _)| *t == tid) {
+            Some(entry) => entry.1 += 1,
+            None => tag_counts.push((tid, 1)),
+        }
+    }
+
+    // 本次抽取中的菜品都不再抽中
+    let exclude: Vec<i64> = previous.iter().map(|p| p.food_id).collect();
+    let avoid_n = payload
+        .avoid_recent_draws
+        .unwrap_or(DEFAULT_AVOID_RECENT_DRAWS)
+        .min(MAX_AVOID_RECENT_DRAWS);
+    let avoid = recent_drawn_foods(&mut tx, uid, group_id, avoid_n).await?;
+    let new_picks = pick_foods(&mut tx, uid, group_id, &tag_counts, exclude, &avoid).await?;
+    if new_picks.len() < replace.len() {
+        // 新菜不足以替换全部选中结果：不扣积分，也不产生新记录
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("没有足够的菜品可重抽".into()));
+    }
+
+    let mut picks: Vec<(Option<i64>, i64, String, Option<String>)> = previous
+        .iter()
+        .filter(|p| !replace.iter().any(|r| r.result_id == p.result_id))
+        .map(|p| (p.tag_id, p.food_id, p.food_name.clone(), p.food_photo.clone()))
+        .collect();
+    picks.extend(
+        new_picks
+            .into_iter()
+            .map(|(tag_id, c)| (Some(tag_id), c.food_id, c.food_name, c.food_photo)),
+    );
+
+    let out = insert_draw(&mut tx, uid, group_id, Some(*id), &payload, REROLL_COST, &picks).await?;
+    // 原记录被新记录取代
+    sqlx::query("UPDATE lottery_draws SET superseded_at=NOW() WHERE draw_id=$1")
+        .bind(*id)
+        .execute(&mut *tx)
+        .await?;
+    let balance_after = love_point - REROLL_COST;
+    sqlx::query("UPDATE users SET love_point=$2 WHERE user_id=$1")
+        .bind(uid)
+        .bind(balance_after)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO point_transactions (user_id, amount, type, ref_type, ref_id, balance_after) VALUES ($1,$2,'OTHER',$3,$4,$5)",
+    )
+    .bind(uid)
+    .bind(-REROLL_COST)
+    .bind(REF_TYPE_LOTTERY_DRAW)
+    .bind(out.draw_id)
+    .bind(balance_after)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // 更新缓存（若 token 已带用户信息）
+    if let Some(mut public) = token.user.take() {
+        public.love_point = balance_after;
+        let _ = state.redis_cache.set_user_public(&public, 3600).await;
+    }
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+	get,
+	path = "/foods/blind_box/draws",
+	tag = "菜品",
+	summary = "我的盲盒抽取历史",
+	params(BlindBoxDrawHistoryQuery),
+	responses((status = 200, body = [BlindBoxDrawOut])),
+	security(("cookie_auth" = []))
+)]
+pub async fn list_blind_box_draws(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<BlindBoxDrawHistoryQuery>,
+) -> Result<impl Responder, CustomError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+    let mut conn = state.db_pool.acquire().await?;
+    let rows = match query.group_id {
+        Some(gid) => {
+            sqlx::query(
+                "SELECT draw_id, group_id, parent_draw_id, points_cost, order_id, superseded_at, (is_success='SUCCESS') AS success, \
+                 fail_reason, request_payload, created_at FROM lottery_draws \
+                 WHERE user_id=$1 AND group_id=$2 ORDER BY created_at DESC, draw_id DESC LIMIT $3 OFFSET $4",
+            )
+            .bind(token.user_id)
+            .bind(gid)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&mut *conn)
+            .await?
+        }
+        None => {
+            sqlx::query(
+                "SELECT draw_id, group_id, parent_draw_id, points_cost, order_id, superseded_at, (is_success='SUCCESS') AS success, \
+                 fail_reason, request_payload, created_at FROM lottery_draws \
+                 WHERE user_id=$1 ORDER BY created_at DESC, draw_id DESC LIMIT $2 OFFSET $3",
+            )
+            .bind(token.user_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&mut *conn)
+            .await?
+        }
+    };
+    let draw_ids: Vec<i64> = rows.iter().map(|r| r.get("draw_id")).collect();
+    let mut results = load_results(&mut conn, &draw_ids).await?;
+    let out: Vec<BlindBoxDrawOut> = rows
+        .into_iter()
+        .map(|r| {
+            let draw_id: i64 = r.get("draw_id");
+            let requested_tags = r
+                .get::<Option<sqlx::types::Json<BlindBoxDrawInput>>, _>("request_payload")
+                .map(|p| p.0.tag_ids)
+                .unwrap_or_default();
+            BlindBoxDrawOut {
+                draw_id,
+                group_id: r.get("group_id"),
+                parent_draw_id: r.get("parent_draw_id"),
+                points_cost: r.get("points_cost"),
+                order_id: r.get("order_id"),
+                superseded_at: r.get("superseded_at"),
+                success: r.get("success"),
+                fail_reason: r.get("fail_reason"),
+                requested_tags,
+                results: results.remove(&draw_id).unwrap_or_default(),
+                created_at: r.get("created_at"),
+            }
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+	post,
+	path = "/foods/blind_box/draws/{id}/order",
+	tag = "菜品",
+	summary = "将抽取结果转为订单（跳过已下架菜品）",
+	params(("id" = i64, Path, description = "抽取记录ID")),
+	request_body = OrderPlaceInput,
+	responses((status = 201, body = PlacedOrderOut)),
+	security(("cookie_auth" = []))
+)]
+pub async fn order_blind_box_draw(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderPlaceInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    let draw = sqlx::query(
+        "SELECT user_id, group_id, order_id, superseded_at IS NOT NULL AS superseded, (is_success='SUCCESS') AS success \
+         FROM lottery_draws WHERE draw_id=$1 FOR UPDATE",
+    )
+    .bind(*id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(draw) = draw else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("抽取记录不存在".into()));
+    };
+    if draw.get::<i64, _>("user_id") != token.user_id {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("无权操作该抽取".into()));
+    }
+    if draw.get::<Option<i64>, _>("order_id").is_some() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该抽取已下单".into()));
+    }
+    if draw.get::<bool, _>("superseded") {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该抽取已被重抽取代，请使用最新结果".into()));
+    }
+    if !draw.get::<bool, _>("success") {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该抽取没有结果".into()));
+    }
+    let mut items: Vec<OrderItemCreateInput> = Vec::new();
+    for r in load_results(&mut tx, &[*id]).await?.remove(&*id).unwrap_or_default() {
+        if !items.iter().any(|i| i.food_id == r.food_id) {
+            items.push(OrderItemCreateInput {
+                food_id: r.food_id,
+                quantity: Some(1),
+            });
+        }
+    }
+    let group_id: Option<i64> = draw.get("group_id");
+    let out = match place_items(&mut tx, token.user_id, group_id, &items, &data).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    sqlx::query("UPDATE lottery_draws SET order_id=$2 WHERE draw_id=$1")
+        .bind(*id)
+        .bind(out.order.order_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    spawn_order_push(state.db_pool.clone(), out.order.order_id, "blind box order");
+    Ok(HttpResponse::Created().json(&out))
+}
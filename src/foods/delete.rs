@@ -1,8 +1,14 @@
-use crate::{errors::CustomError, models::users::UserToken, AppState};
+use crate::{
+    errors::CustomError,
+    foods::tags::{ensure_tag_manager, lock_tag, move_tag_foods},
+    models::{foods::TagDeleteQuery, users::UserToken},
+    AppState,
+};
 use ntex::web::{
-    types::{Path, State},
+    types::{Path, Query, State},
     HttpResponse, Responder,
 };
+use sqlx::PgConnection;
 use std::sync::Arc;
 
 #[utoipa::path(
@@ -14,15 +20,27 @@ use std::sync::Arc;
 	security(("cookie_auth" = []))
 )]
 pub async fn delete_food(
-    _token: UserToken,
+    token: UserToken,
     state: State<Arc<AppState>>,
     id: Path<(i64,)>,
 ) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
-    sqlx::query("UPDATE foods SET food_status='OFF', updated_at=NOW() WHERE food_id=$1")
+    let mut tx = state.db_pool.begin().await?;
+    let updated = sqlx::query("UPDATE foods SET food_status='OFF', updated_at=NOW() WHERE food_id=$1")
         .bind(id.0)
-        .execute(db)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if updated > 0 {
+        crate::foods::revisions::record_revision(
+            &mut tx,
+            id.0,
+            crate::models::foods::FoodRevisionSourceEnum::UPDATE,
+            token.user_id,
+            None,
+        )
         .await?;
+    }
+    tx.commit().await?;
     Ok(HttpResponse::Ok().body("deleted"))
 }
 
@@ -30,20 +48,58 @@ pub async fn delete_food(
 	delete,
 	path = "/food_tags/{id}",
 	tag = "菜品",
-	params(("id"=i64, Path, description="标签ID")),
+	summary = "删除标签（可将菜品改挂到其他标签，子标签上移一级）",
+	params(("id"=i64, Path, description="标签ID"), TagDeleteQuery),
 	responses((status = 200, body = String)),
 	security(("cookie_auth" = []))
 )]
 pub async fn delete_tag(
-    _token: UserToken,
+    token: UserToken,
     state: State<Arc<AppState>>,
     id: Path<(i64,)>,
+    q: Query<TagDeleteQuery>,
 ) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
-    // food_tags_map removed
+    let tag_id = id.0;
+    let mut tx = state.db_pool.begin().await?;
+    let parent_id = match check_tag_delete(&mut tx, tag_id, q.reassign_to, token.user_id).await {
+        Ok(p) => p,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    // 菜品改挂或移除该标签；以该标签为主标签的菜品改用剩余的第一个标签
+    move_tag_foods(&mut tx, tag_id, q.reassign_to, token.user_id).await?;
+    sqlx::query("UPDATE tags SET parent_id=$2 WHERE parent_id=$1")
+        .bind(tag_id)
+        .bind(parent_id)
+        .execute(&mut *tx)
+        .await?;
     sqlx::query("DELETE FROM tags WHERE tag_id=$1")
-        .bind(id.0)
-        .execute(db)
+        .bind(tag_id)
+        .execute(&mut *tx)
         .await?;
+    tx.commit().await?;
     Ok(HttpResponse::Ok().body("deleted"))
 }
+
+// 校验删除：有权限，改挂目标须为同组的其他标签；返回被删标签的父标签
+async fn check_tag_delete(
+    conn: &mut PgConnection,
+    tag_id: i64,
+    reassign_to: Option<i64>,
+    user_id: i64,
+) -> Result<Option<i64>, CustomError> {
+    let (group_id, parent_id) = lock_tag(conn, tag_id).await?;
+    ensure_tag_manager(conn, group_id, user_id).await?;
+    if let Some(target) = reassign_to {
+        if target == tag_id {
+            return Err(CustomError::BadRequest("不能改挂到被删除的标签".into()));
+        }
+        let (target_group, _) = lock_tag(conn, target).await?;
+        if target_group != group_id {
+            return Err(CustomError::BadRequest("改挂标签须与被删除标签同组".into()));
+        }
+    }
+    Ok(parent_id)
+}
@@ -2,3 +2,13 @@ pub mod new;
 pub mod update;
 pub mod delete;
 pub mod view;
+pub mod audit;
+pub mod stats;
+pub mod search;
+pub mod recipe;
+pub mod tags;
+pub mod recommend;
+pub mod blind_box;
+pub mod transfer;
+pub mod nutrition;
+pub mod revisions;
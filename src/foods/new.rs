@@ -2,9 +2,10 @@ use crate::{
     errors::CustomError,
     models::{
         foods::{
-            ApplyStatusEnum, FoodCreateInput, FoodOut, FoodRecord, FoodStatusEnum,
-            FoodTagOut, SubmitRoleEnum, TagRecord,
+            ApplyStatusEnum, DietaryLabelEnum, FoodCreateInput, FoodOut, FoodRecord,
+            FoodRevisionSourceEnum, FoodStatusEnum, FoodTagOut, SubmitRoleEnum, TagRecord,
         },
+        photos::PhotoOwnerEnum,
         users::UserToken,
     },
     AppState,
@@ -16,6 +17,26 @@ use ntex::web::{
 use sqlx::Row;
 use std::sync::Arc;
 
+/// 业务：不同角色提交方式。接单方直接上架，其余作为申请进入待审核。
+pub fn initial_statuses(role: &str) -> (SubmitRoleEnum, ApplyStatusEnum, FoodStatusEnum) {
+    let submit_role = if role == "RECEIVING" {
+        SubmitRoleEnum::RECEIVING_CREATE
+    } else {
+        SubmitRoleEnum::ORDERING_APPLY
+    };
+    let apply_status = if matches!(submit_role, SubmitRoleEnum::RECEIVING_CREATE) {
+        ApplyStatusEnum::APPROVED
+    } else {
+        ApplyStatusEnum::PENDING
+    };
+    let food_status = if matches!(apply_status, ApplyStatusEnum::APPROVED) {
+        FoodStatusEnum::NORMAL
+    } else {
+        FoodStatusEnum::AUDITING
+    };
+    (submit_role, apply_status, food_status)
+}
+
 #[utoipa::path(
 	post,
 	path = "/foods",
@@ -29,6 +50,13 @@ pub async fn create_food(
     data: Json<FoodCreateInput>,
     state: State<Arc<AppState>>,
 ) -> Result<impl Responder, CustomError> {
+    if let Some(items) = &data.ingredient_items {
+        crate::foods::recipe::validate_ingredients(items)?;
+    }
+    if let Some(steps) = &data.step_items {
+        crate::foods::recipe::validate_steps(steps)?;
+    }
+    crate::foods::nutrition::validate_nutrition(data.calories, data.protein, data.fat, data.carbs)?;
     let db = &state.db_pool;
     let mut tx = db.begin().await?;
 
@@ -42,26 +70,11 @@ pub async fn create_food(
     }
     let role = role.unwrap();
 
-    // 业务：不同角色提交方式
-    let submit_role = if role == "RECEIVING" {
-        SubmitRoleEnum::RECEIVING_CREATE
-    } else {
-        SubmitRoleEnum::ORDERING_APPLY
-    };
-    let apply_status = if matches!(submit_role, SubmitRoleEnum::RECEIVING_CREATE) {
-        ApplyStatusEnum::APPROVED
-    } else {
-        ApplyStatusEnum::PENDING
-    };
-    let food_status = if matches!(apply_status, ApplyStatusEnum::APPROVED) {
-        FoodStatusEnum::NORMAL
-    } else {
-        FoodStatusEnum::AUDITING
-    };
+    let (submit_role, apply_status, food_status) = initial_statuses(&role);
 
-    let rec = sqlx::query_as::<_, FoodRecord>(
-		"INSERT INTO foods (food_name, food_photo, ingredients, steps, submit_role, apply_status, food_status, created_by, owner_user_id, group_id, apply_remark, tag_id) \
-		 VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8,$9,$10,$11) RETURNING food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id"
+    let mut rec = sqlx::query_as::<_, FoodRecord>(
+		"INSERT INTO foods (food_name, food_photo, ingredients, steps, submit_role, apply_status, food_status, created_by, owner_user_id, group_id, apply_remark, tag_id, calories, protein, fat, carbs, dietary_labels) \
+		 VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8,$9,$10,$11,$12,$13,$14,$15,$16) RETURNING food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id, calories, protein, fat, carbs, dietary_labels"
 	)
 	.bind(&data.food_name)
 	.bind(&data.food_photo)
@@ -73,10 +86,67 @@ pub async fn create_food(
 	.bind(token.user_id as i64)
 	.bind(data.group_id.map(|v| v as i64))
 	.bind(Option::<String>::None) // apply_remark
-    .bind(data.tag_id)
+    .bind(Option::<i64>::None) // tag_id 由 replace_food_tags 写入
+    .bind(data.calories)
+    .bind(data.protein)
+    .bind(data.fat)
+    .bind(data.carbs)
+    .bind(DietaryLabelEnum::to_db(data.dietary_labels.as_deref().unwrap_or_default()))
 	.fetch_one(&mut *tx)
 	.await?;
 
+    // 多标签
+    let mut tags = Vec::new();
+    if let Some(tag_ids) = crate::foods::tags::requested_tag_ids(data.tag_ids.as_ref(), data.tag_id) {
+        rec.tag_id = crate::foods::tags::replace_food_tags(&mut tx, rec.food_id, rec.group_id, &tag_ids).await?;
+        tags = crate::foods::tags::load_food_tags(&mut tx, &[rec.food_id])
+            .await?
+            .remove(&rec.food_id)
+            .unwrap_or_default();
+    }
+
+    // 结构化食材/步骤（同步覆盖文本字段）
+    if let Some(items) = &data.ingredient_items {
+        crate::foods::recipe::replace_ingredients(&mut tx, rec.food_id, items).await?;
+        rec.ingredients = crate::foods::recipe::ingredients_text(items);
+    }
+    if let Some(steps) = &data.step_items {
+        crate::foods::recipe::replace_steps(&mut tx, rec.food_id, steps).await?;
+        rec.steps = crate::foods::recipe::steps_text(steps);
+    }
+
+    // 申请类菜品记录提交动作，进入待审核队列
+    let mut audit_log_id = None;
+    if matches!(submit_role, SubmitRoleEnum::ORDERING_APPLY) {
+        audit_log_id = Some(crate::foods::audit::insert_audit_log(
+            &mut tx,
+            rec.food_id,
+            crate::foods::audit::AUDIT_SUBMIT,
+            None,
+            ApplyStatusEnum::PENDING,
+            token.user_id as i64,
+            None,
+        )
+        .await?);
+    }
+    // 图片作为图集封面
+    crate::photos::adopt_legacy_cover(
+        &mut tx,
+        PhotoOwnerEnum::FOOD,
+        rec.food_id,
+        rec.food_photo.as_deref(),
+        Some(token.user_id as i64),
+    )
+    .await?;
+    crate::foods::revisions::record_revision(
+        &mut tx,
+        rec.food_id,
+        FoodRevisionSourceEnum::CREATE,
+        token.user_id as i64,
+        audit_log_id,
+    )
+    .await?;
+
     // 查询标签
     let tag_row: Option<TagRecord> = if let Some(tid) = rec.tag_id {
         sqlx::query_as("SELECT * FROM tags WHERE tag_id=$1")
@@ -107,11 +177,15 @@ pub async fn create_food(
         .collect::<Vec<_>>();
 
     tx.commit().await?;
-    let out = FoodOut::from((rec, tag_row, mark_enums));
+    let mut out = FoodOut::from((rec, tag_row, mark_enums));
+    out.tags = tags;
     Ok(HttpResponse::Created().json(&out))
 }
 
-use crate::models::foods::TagCreateInput;
+use crate::{
+    foods::tags::{ensure_unique_tag_name, normalize_tag_name, validate_tag_parent},
+    models::foods::TagCreateInput,
+};
 
 #[utoipa::path(
 	post,
@@ -126,16 +200,29 @@ pub async fn create_tag(
     data: Json<TagCreateInput>,
     state: State<Arc<AppState>>,
 ) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
     let gid = data.group_id.or(token.user.as_ref().and_then(|u| u.group_id));
+    let tag_name = normalize_tag_name(&data.tag_name)?;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_unique_tag_name(&mut tx, &tag_name, gid, None).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    if let Some(pid) = data.parent_id {
+        if let Err(e) = validate_tag_parent(&mut tx, None, gid, pid).await {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    }
     let rec = sqlx::query_as::<_, TagRecord>(
-		"INSERT INTO tags (tag_name, group_id, sort) VALUES ($1,$2,$3) RETURNING tag_id, tag_name, group_id, sort, created_at"
+		"INSERT INTO tags (tag_name, group_id, sort, parent_id) VALUES ($1,$2,$3,$4) RETURNING tag_id, tag_name, group_id, sort, created_at"
 	)
-	.bind(&data.tag_name)
+	.bind(&tag_name)
     .bind(gid)
 	.bind(data.sort)
-	.fetch_one(db)
+	.bind(data.parent_id)
+	.fetch_one(&mut *tx)
 	.await?;
+    tx.commit().await?;
     Ok(HttpResponse::Created().json(&FoodTagOut {
         tag_id: rec.tag_id,
         tag_name: rec.tag_name,
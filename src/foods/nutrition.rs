@@ -0,0 +1,31 @@
+// 菜品营养（每份）与饮食/过敏原标签的校验、解析
+use crate::{errors::CustomError, models::foods::DietaryLabelEnum};
+
+const MAX_CALORIES: i32 = 10000;
+const MAX_GRAMS: f64 = 1000.0;
+
+pub fn validate_nutrition(
+    calories: Option<i32>,
+    protein: Option<f64>,
+    fat: Option<f64>,
+    carbs: Option<f64>,
+) -> Result<(), CustomError> {
+    if calories.is_some_and(|c| !(0..=MAX_CALORIES).contains(&c)) {
+        return Err(CustomError::BadRequest(format!("热量需在0-{}kcal之间", MAX_CALORIES)));
+    }
+    for (name, v) in [("蛋白质", protein), ("脂肪", fat), ("碳水", carbs)] {
+        if v.is_some_and(|g| !g.is_finite() || !(0.0..=MAX_GRAMS).contains(&g)) {
+            return Err(CustomError::BadRequest(format!("{}需在0-{}g之间", name, MAX_GRAMS)));
+        }
+    }
+    Ok(())
+}
+
+// 逗号分隔的标签参数，如 "VEGETARIAN,SPICY"
+pub fn parse_labels(raw: &str) -> Result<Vec<DietaryLabelEnum>, CustomError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| DietaryLabelEnum::parse(s).ok_or_else(|| CustomError::BadRequest(format!("无效的饮食标签: {}", s))))
+        .collect()
+}
@@ -0,0 +1,285 @@
+use crate::{
+    errors::CustomError,
+    models::foods::{
+        FoodIngredientInput, FoodIngredientOut, FoodRecipeInput, FoodRecipeOut, FoodStepInput,
+        FoodStepOut,
+    },
+    models::users::UserToken,
+    upload::upload::validate_upload_url,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+const MAX_INGREDIENTS: usize = 50;
+const MAX_STEPS: usize = 50;
+const MAX_TIMER_SECONDS: i32 = 24 * 3600;
+
+pub fn validate_ingredients(items: &[FoodIngredientInput]) -> Result<(), CustomError> {
+    if items.len() > MAX_INGREDIENTS {
+        return Err(CustomError::BadRequest(format!("食材最多{}项", MAX_INGREDIENTS)));
+    }
+    for it in items {
+        let name_len = it.name.trim().chars().count();
+        if name_len == 0 || name_len > 64 {
+            return Err(CustomError::BadRequest("食材名称长度需在1-64个字符之间".into()));
+        }
+        if it.quantity.is_some_and(|q| !q.is_finite() || q <= 0.0) {
+            return Err(CustomError::BadRequest("食材用量必须大于0".into()));
+        }
+        if it.unit.as_deref().is_some_and(|u| u.trim().chars().count() > 16) {
+            return Err(CustomError::BadRequest("单位不能超过16个字符".into()));
+        }
+        if it.note.as_deref().is_some_and(|n| n.chars().count() > 64) {
+            return Err(CustomError::BadRequest("食材备注不能超过64个字符".into()));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_steps(steps: &[FoodStepInput]) -> Result<(), CustomError> {
+    if steps.len() > MAX_STEPS {
+        return Err(CustomError::BadRequest(format!("步骤最多{}步", MAX_STEPS)));
+    }
+    for st in steps {
+        if st.content.trim().is_empty() {
+            return Err(CustomError::BadRequest("步骤内容不能为空".into()));
+        }
+        if st.content.chars().count() > 1000 {
+            return Err(CustomError::BadRequest("单个步骤不能超过1000字".into()));
+        }
+        if st.timer_seconds.is_some_and(|t| !(1..=MAX_TIMER_SECONDS).contains(&t)) {
+            return Err(CustomError::BadRequest("计时需在1秒到24小时之间".into()));
+        }
+        if let Some(url) = st.photo_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            validate_upload_url(url.trim())?;
+        }
+    }
+    Ok(())
+}
+
+fn format_quantity(q: f64) -> String {
+    if q.fract() == 0.0 {
+        format!("{}", q as i64)
+    } else {
+        format!("{}", (q * 100.0).round() / 100.0)
+    }
+}
+
+// 旧客户端读取的文本形式：食材「鸡蛋 2个、盐 适量」，步骤按行编号
+pub fn ingredients_text(items: &[FoodIngredientInput]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = items
+        .iter()
+        .map(|it| {
+            let amount = match it.quantity {
+                Some(q) => format!("{}{}", format_quantity(q), it.unit.as_deref().unwrap_or("").trim()),
+                None => "适量".to_string(),
+            };
+            format!("{} {}", it.name.trim(), amount)
+        })
+        .collect();
+    Some(parts.join("、"))
+}
+
+pub fn steps_text(steps: &[FoodStepInput]) -> Option<String> {
+    if steps.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = steps
+        .iter()
+        .enumerate()
+        .map(|(i, st)| format!("{}. {}", i + 1, st.content.trim()))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+// 整体替换食材行，并同步 foods.ingredients 文本
+pub async fn replace_ingredients(
+    conn: &mut PgConnection,
+    food_id: i64,
+    items: &[FoodIngredientInput],
+) -> Result<(), CustomError> {
+    sqlx::query("DELETE FROM food_ingredients WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    for (i, it) in items.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO food_ingredients (food_id, sort, name, quantity, unit, note) VALUES ($1,$2,$3,$4,$5,$6)",
+        )
+        .bind(food_id)
+        .bind(i as i32)
+        .bind(it.name.trim())
+        .bind(it.quantity)
+        .bind(it.unit.as_deref().map(str::trim).filter(|u| !u.is_empty()))
+        .bind(it.note.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query("UPDATE foods SET ingredients=$2, updated_at=NOW() WHERE food_id=$1")
+        .bind(food_id)
+        .bind(ingredients_text(items))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// 整体替换步骤，并同步 foods.steps 文本
+pub async fn replace_steps(
+    conn: &mut PgConnection,
+    food_id: i64,
+    steps: &[FoodStepInput],
+) -> Result<(), CustomError> {
+    sqlx::query("DELETE FROM food_steps WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    for (i, st) in steps.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO food_steps (food_id, step_no, content, photo_url, timer_seconds) VALUES ($1,$2,$3,$4,$5)",
+        )
+        .bind(food_id)
+        .bind(i as i32 + 1)
+        .bind(st.content.trim())
+        .bind(st.photo_url.as_deref().map(str::trim).filter(|u| !u.is_empty()))
+        .bind(st.timer_seconds)
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query("UPDATE foods SET steps=$2, updated_at=NOW() WHERE food_id=$1")
+        .bind(food_id)
+        .bind(steps_text(steps))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// 文本食材被直接修改时，结构化食材行已不再对应，整体清除
+pub async fn clear_ingredients(conn: &mut PgConnection, food_id: i64) -> Result<(), CustomError> {
+    sqlx::query("DELETE FROM food_ingredients WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// 文本步骤被直接修改时，结构化步骤已不再对应，整体清除
+pub async fn clear_steps(conn: &mut PgConnection, food_id: i64) -> Result<(), CustomError> {
+    sqlx::query("DELETE FROM food_steps WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+pub async fn load_ingredients(conn: &mut PgConnection, food_id: i64) -> Result<Vec<FoodIngredientOut>, CustomError> {
+    let list = sqlx::query_as::<_, FoodIngredientOut>(
+        "SELECT id, name, quantity, unit, note, sort FROM food_ingredients WHERE food_id=$1 ORDER BY sort, id",
+    )
+    .bind(food_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(list)
+}
+
+pub async fn load_steps(conn: &mut PgConnection, food_id: i64) -> Result<Vec<FoodStepOut>, CustomError> {
+    let list = sqlx::query_as::<_, FoodStepOut>(
+        "SELECT id, step_no, content, photo_url, timer_seconds FROM food_steps WHERE food_id=$1 ORDER BY step_no",
+    )
+    .bind(food_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(list)
+}
+
+async fn load_recipe(conn: &mut PgConnection, food_id: i64) -> Result<FoodRecipeOut, CustomError> {
+    let row = sqlx::query("SELECT ingredients, steps FROM foods WHERE food_id=$1")
+        .bind(food_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(FoodRecipeOut {
+        food_id,
+        ingredients: load_ingredients(conn, food_id).await?,
+        steps: load_steps(conn, food_id).await?,
+        ingredients_text: row.get("ingredients"),
+        steps_text: row.get("steps"),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/recipe",
+    tag = "菜品",
+    summary = "结构化食材与步骤",
+    params(("id" = i64, Path, description = "菜品ID")),
+    responses((status = 200, body = FoodRecipeOut))
+)]
+pub async fn get_food_recipe(
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM foods WHERE food_id=$1 AND is_del=0)")
+        .bind(*id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !exists {
+        return Err(CustomError::BadRequest("未找到菜品".into()));
+    }
+    let out = load_recipe(&mut conn, *id).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/foods/{id}/recipe",
+    tag = "菜品",
+    summary = "整体替换结构化食材/步骤（同步旧文本字段）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodRecipeInput,
+    responses((status = 200, body = FoodRecipeOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_food_recipe(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodRecipeInput>,
+) -> Result<impl Responder, CustomError> {
+    if let Some(items) = &data.ingredients {
+        validate_ingredients(items)?;
+    }
+    if let Some(steps) = &data.steps {
+        validate_steps(steps)?;
+    }
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = crate::foods::audit::ensure_food_editor(&mut tx, *id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    if let Some(items) = &data.ingredients {
+        replace_ingredients(&mut tx, *id, items).await?;
+    }
+    if let Some(steps) = &data.steps {
+        replace_steps(&mut tx, *id, steps).await?;
+    }
+    let audit_log_id = crate::foods::audit::resubmit_submitter_edit(&mut tx, *id, token.user_id, None).await?;
+    crate::foods::revisions::record_revision(
+        &mut tx,
+        *id,
+        crate::models::foods::FoodRevisionSourceEnum::UPDATE,
+        token.user_id,
+        audit_log_id,
+    )
+    .await?;
+    let out = load_recipe(&mut tx, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
@@ -0,0 +1,351 @@
+// “今天吃什么”：按个人/对方标记、历史频次、最近是否吃过与当前餐段为组内可点菜品打分。
+// 打分为纯函数（score_food），同样的输入得到同样的排序与理由，不依赖外部服务。
+use crate::{
+    errors::CustomError,
+    models::foods::{
+        FoodCategory, FoodRecommendQuery, FoodRecommendationOut, FoodRecommendationsOut,
+        RecommendFactorOut,
+    },
+    models::users::UserToken,
+    services::groups::{ensure_member, primary_group_id},
+    AppState,
+};
+use chrono::{DateTime, Duration, FixedOffset, Timelike, Utc};
+use ntex::web::{
+    types::{Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// 统计历史订单的时间窗口
+const HISTORY_DAYS: i64 = 90;
+
+// 各项权重
+const W_MY_LIKE: f64 = 30.0;
+const W_PARTNER_LIKE: f64 = 15.0;
+const W_PARTNER_NOT_RECOMMEND: f64 = -20.0;
+const W_POPULAR_MAX: f64 = 20.0;
+const W_MEAL_MATCH: f64 = 15.0;
+const W_MISSED: f64 = 10.0;
+const W_NEW: f64 = 5.0;
+const W_EATEN_TODAY: f64 = -40.0;
+const W_EATEN_3D: f64 = -25.0;
+const W_EATEN_7D: f64 = -10.0;
+
+/// 单个菜品的打分输入
+#[derive(Debug, Clone, Default)]
+pub struct FoodSignals {
+    pub my_like: bool,
+    pub my_not_recommend: bool,
+    pub partner_likes: i64,
+    pub partner_not_recommends: i64,
+    /// food_stats 完成次数
+    pub completed_orders: i64,
+    /// 组内近 HISTORY_DAYS 天下单次数
+    pub recent_orders: i64,
+    /// 组内近期订单中落在当前餐段的次数
+    pub meal_orders: i64,
+    /// 组内最近一次下单（取期望时间，无则下单时间）
+    pub last_ordered_at: Option<DateTime<Utc>>,
+}
+
+/// 按本地小时划分餐段
+pub fn meal_for_hour(hour: u32) -> FoodCategory {
+    match hour {
+        5..=9 => FoodCategory::Breakfast,
+        10..=13 => FoodCategory::Lunch,
+        14..=16 => FoodCategory::AfternoonTea,
+        17..=20 => FoodCategory::Dinner,
+        _ => FoodCategory::MidnightSnack,
+    }
+}
+
+fn meal_hours(meal: FoodCategory) -> Vec<i32> {
+    (0..24).filter(|h| meal_for_hour(*h as u32) == meal).collect()
+}
+
+fn factor(code: &str, label: String, weight: f64) -> RecommendFactorOut {
+    RecommendFactorOut {
+        code: code.to_string(),
+        label,
+        weight,
+    }
+}
+
+/// 计算得分与各项理由；自己标记“不推荐”的菜品返回 None（不参与推荐）
+pub fn score_food(
+    s: &FoodSignals,
+    meal: FoodCategory,
+    now: DateTime<Utc>,
+) -> Option<(f64, Vec<RecommendFactorOut>)> {
+    if s.my_not_recommend {
+        return None;
+    }
+    let mut factors: Vec<RecommendFactorOut> = Vec::new();
+    if s.my_like {
+        factors.push(factor("MY_LIKE", "你收藏过".into(), W_MY_LIKE));
+    }
+    if s.partner_likes > 0 {
+        let w = W_PARTNER_LIKE * s.partner_likes.min(2) as f64;
+        factors.push(factor("PARTNER_LIKE", format!("{}位组员喜欢", s.partner_likes), w));
+    }
+    if s.partner_not_recommends > 0 {
+        let w = W_PARTNER_NOT_RECOMMEND * s.partner_not_recommends.min(2) as f64;
+        factors.push(factor("PARTNER_NOT_RECOMMEND", format!("{}位组员不推荐", s.partner_not_recommends), w));
+    }
+    if s.completed_orders > 0 {
+        let w = (8.0 * (1.0 + s.completed_orders as f64).ln()).min(W_POPULAR_MAX);
+        factors.push(factor("POPULAR", format!("已做过{}次", s.completed_orders), w));
+    }
+    if s.meal_orders > 0 && s.recent_orders > 0 {
+        let ratio = s.meal_orders as f64 / s.recent_orders as f64;
+        factors.push(factor("MEAL_MATCH", format!("常在{}吃", meal.zh_label()), W_MEAL_MATCH * ratio));
+    }
+    match s.last_ordered_at {
+        Some(last) => {
+            let hours = (now - last).num_hours();
+            if hours < 24 {
+                // 含已预约在未来的订单
+                factors.push(factor("RECENT_REPEAT", "今天已点过".into(), W_EATEN_TODAY));
+            } else if hours < 72 {
+                factors.push(factor("RECENT_REPEAT", format!("{}天前刚吃过", hours / 24), W_EATEN_3D));
+            } else if hours < 24 * 7 {
+                factors.push(factor("RECENT_REPEAT", format!("{}天前吃过", hours / 24), W_EATEN_7D));
+            } else if hours >= 24 * 14 && s.recent_orders >= 2 {
+                factors.push(factor("MISSED", format!("{}天没吃了", hours / 24), W_MISSED));
+            }
+        }
+        None if s.completed_orders == 0 => {
+            factors.push(factor("NEW", "还没尝过".into(), W_NEW));
+        }
+        None => {}
+    }
+    let score = factors.iter().map(|f| f.weight).sum::<f64>();
+    Some(((score * 100.0).round() / 100.0, factors))
+}
+
+// 主理由：权重最高的正向因素
+fn main_reason(factors: &[RecommendFactorOut]) -> String {
+    factors
+        .iter()
+        .filter(|f| f.weight > 0.0)
+        .max_by(|a, b| a.weight.total_cmp(&b.weight))
+        .map(|f| f.label.clone())
+        .unwrap_or_else(|| "换换口味".to_string())
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/recommendations",
+    tag = "菜品",
+    summary = "今天吃什么：按标记、历史、餐段与近期重复为组内可点菜品打分并给出理由",
+    params(FoodRecommendQuery),
+    responses((status = 200, body = FoodRecommendationsOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_food_recommendations(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<FoodRecommendQuery>,
+) -> Result<impl Responder, CustomError> {
+    let uid = token.user_id;
+    let limit = query.limit.unwrap_or(10).clamp(1, 50) as usize;
+    let tz_offset_minutes = query.tz_offset_minutes.unwrap_or(480);
+    let offset = FixedOffset::east_opt(tz_offset_minutes * 60)
+        .ok_or_else(|| CustomError::BadRequest("无效的时区偏移".into()))?;
+    let mut conn = state.db_pool.acquire().await?;
+
+    let group_id: i64 = match query.group_id {
+        Some(gid) => {
+            ensure_member(&mut conn, gid, uid).await?;
+            gid
+        }
+        None => primary_group_id(&mut conn, uid)
+            .await?
+            .ok_or_else(|| CustomError::BadRequest("未找到绑定组".into()))?,
+    };
+
+    let now = Utc::now();
+    let meal = query
+        .meal
+        .unwrap_or_else(|| meal_for_hour(now.with_timezone(&offset).hour()));
+
+    // 候选：组内可点菜品及标记、统计
+    let rows = sqlx::query(
+        "SELECT f.food_id, f.food_name, f.food_photo, \
+         COALESCE(fs.completed_order_count, 0)::bigint AS completed_orders, \
+         EXISTS(SELECT 1 FROM user_food_mark m WHERE m.food_id=f.food_id AND m.user_id=$2 AND m.mark_type='LIKE') AS my_like, \
+         EXISTS(SELECT 1 FROM user_food_mark m WHERE m.food_id=f.food_id AND m.user_id=$2 AND m.mark_type='NOT_RECOMMEND') AS my_not_recommend, \
+         (SELECT COUNT(*) FROM user_food_mark m JOIN association_group_members gm ON gm.user_id=m.user_id AND gm.group_id=$1 \
+          WHERE m.food_id=f.food_id AND m.user_id<>$2 AND m.mark_type='LIKE') AS partner_likes, \
+         (SELECT COUNT(*) FROM user_food_mark m JOIN association_group_members gm ON gm.user_id=m.user_id AND gm.group_id=$1 \
+          WHERE m.food_id=f.food_id AND m.user_id<>$2 AND m.mark_type='NOT_RECOMMEND') AS partner_not_recommends \
+         FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id \
+         WHERE f.group_id=$1 AND f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED'",
+    )
+    .bind(group_id)
+    .bind(uid)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // 组内近期订单：次数、最近时间、当前餐段次数（按请求时区的小时）
+    let history = sqlx::query(
+        "SELECT oi.food_id, COUNT(DISTINCT o.order_id)::bigint AS cnt, \
+         MAX(COALESCE(o.goal_time, o.created_at)) AS last_at, \
+         (COUNT(DISTINCT o.order_id) FILTER (WHERE EXTRACT(HOUR FROM (COALESCE(o.goal_time, o.created_at) AT TIME ZONE 'UTC') \
+            + make_interval(mins => $3))::int = ANY($4)))::bigint AS meal_cnt \
+         FROM order_items oi JOIN orders o ON o.order_id=oi.order_id \
+         WHERE o.group_id=$1 AND o.created_at >= $2 \
+         AND o.status NOT IN ('CANCELLED','REJECTED','EXPIRED','SYSTEM_CLOSED') \
+         GROUP BY oi.food_id",
+    )
+    .bind(group_id)
+    .bind(now - Duration::days(HISTORY_DAYS))
+    .bind(tz_offset_minutes)
+    .bind(meal_hours(meal))
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut history_map: HashMap<i64, (i64, Option<DateTime<Utc>>, i64)> = HashMap::new();
+    for r in history {
+        history_map.insert(r.get("food_id"), (r.get("cnt"), r.get("last_at"), r.get("meal_cnt")));
+    }
+
+    let mut items: Vec<FoodRecommendationOut> = rows
+        .into_iter()
+        .filter_map(|r| {
+            let food_id: i64 = r.get("food_id");
+            let (recent_orders, last_ordered_at, meal_orders) =
+                history_map.get(&food_id).copied().unwrap_or((0, None, 0));
+            let signals = FoodSignals {
+                my_like: r.get("my_like"),
+                my_not_recommend: r.get("my_not_recommend"),
+                partner_likes: r.get("partner_likes"),
+                partner_not_recommends: r.get("partner_not_recommends"),
+                completed_orders: r.get("completed_orders"),
+                recent_orders,
+                meal_orders,
+                last_ordered_at,
+            };
+            let (score, factors) = score_food(&signals, meal, now)?;
+            Some(FoodRecommendationOut {
+                food_id,
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                score,
+                reason: main_reason(&factors),
+                factors,
+            })
+        })
+        .collect();
+    // 同分按菜品ID，保证结果稳定
+    items.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.food_id.cmp(&b.food_id)));
+    items.truncate(limit);
+
+    Ok(HttpResponse::Ok().json(&FoodRecommendationsOut {
+        group_id,
+        meal,
+        meal_label: meal.zh_label().to_string(),
+        items,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(factors: &[RecommendFactorOut]) -> Vec<&str> {
+        factors.iter().map(|f| f.code.as_str()).collect()
+    }
+
+    #[test]
+    fn meal_for_hour_boundaries() {
+        assert_eq!(meal_for_hour(4), FoodCategory::MidnightSnack);
+        assert_eq!(meal_for_hour(5), FoodCategory::Breakfast);
+        assert_eq!(meal_for_hour(9), FoodCategory::Breakfast);
+        assert_eq!(meal_for_hour(10), FoodCategory::Lunch);
+        assert_eq!(meal_for_hour(13), FoodCategory::Lunch);
+        assert_eq!(meal_for_hour(14), FoodCategory::AfternoonTea);
+        assert_eq!(meal_for_hour(16), FoodCategory::AfternoonTea);
+        assert_eq!(meal_for_hour(17), FoodCategory::Dinner);
+        assert_eq!(meal_for_hour(20), FoodCategory::Dinner);
+        assert_eq!(meal_for_hour(21), FoodCategory::MidnightSnack);
+        assert_eq!(meal_for_hour(0), FoodCategory::MidnightSnack);
+    }
+
+    #[test]
+    fn my_not_recommend_is_excluded() {
+        let s = FoodSignals { my_not_recommend: true, my_like: true, ..Default::default() };
+        assert!(score_food(&s, FoodCategory::Lunch, Utc::now()).is_none());
+    }
+
+    #[test]
+    fn untried_food_gets_new_bonus() {
+        let (score, factors) = score_food(&FoodSignals::default(), FoodCategory::Lunch, Utc::now()).unwrap();
+        assert_eq!(codes(&factors), vec!["NEW"]);
+        assert_eq!(score, W_NEW);
+    }
+
+    #[test]
+    fn partner_marks_are_capped_at_two() {
+        let s = FoodSignals { partner_likes: 5, partner_not_recommends: 3, completed_orders: 0, ..Default::default() };
+        let (_, factors) = score_food(&s, FoodCategory::Lunch, Utc::now()).unwrap();
+        let like = factors.iter().find(|f| f.code == "PARTNER_LIKE").unwrap();
+        let not_rec = factors.iter().find(|f| f.code == "PARTNER_NOT_RECOMMEND").unwrap();
+        assert_eq!(like.weight, W_PARTNER_LIKE * 2.0);
+        assert_eq!(not_rec.weight, W_PARTNER_NOT_RECOMMEND * 2.0);
+    }
+
+    #[test]
+    fn popularity_is_capped() {
+        let s = FoodSignals { completed_orders: 10_000, ..Default::default() };
+        let (_, factors) = score_food(&s, FoodCategory::Lunch, Utc::now()).unwrap();
+        let popular = factors.iter().find(|f| f.code == "POPULAR").unwrap();
+        assert_eq!(popular.weight, W_POPULAR_MAX);
+    }
+
+    #[test]
+    fn meal_match_scales_with_ratio() {
+        let s = FoodSignals { recent_orders: 4, meal_orders: 1, completed_orders: 1, ..Default::default() };
+        let (_, factors) = score_food(&s, FoodCategory::Dinner, Utc::now()).unwrap();
+        let m = factors.iter().find(|f| f.code == "MEAL_MATCH").unwrap();
+        assert_eq!(m.weight, W_MEAL_MATCH * 0.25);
+    }
+
+    #[test]
+    fn recent_repeat_penalty_by_age() {
+        let now = Utc::now();
+        let weight_at = |hours: i64, recent_orders: i64| {
+            let s = FoodSignals {
+                completed_orders: 1,
+                recent_orders,
+                last_ordered_at: Some(now - Duration::hours(hours)),
+                ..Default::default()
+            };
+            let (_, factors) = score_food(&s, FoodCategory::Lunch, now).unwrap();
+            factors
+                .iter()
+                .find(|f| f.code == "RECENT_REPEAT" || f.code == "MISSED")
+                .map(|f| (f.code.clone(), f.weight))
+        };
+        assert_eq!(weight_at(23, 1), Some(("RECENT_REPEAT".to_string(), W_EATEN_TODAY)));
+        assert_eq!(weight_at(24, 1), Some(("RECENT_REPEAT".to_string(), W_EATEN_3D)));
+        assert_eq!(weight_at(72, 1), Some(("RECENT_REPEAT".to_string(), W_EATEN_7D)));
+        assert_eq!(weight_at(24 * 7, 1), None);
+        assert_eq!(weight_at(24 * 14, 1), None);
+        assert_eq!(weight_at(24 * 14, 2), Some(("MISSED".to_string(), W_MISSED)));
+    }
+
+    #[test]
+    fn future_order_counts_as_today() {
+        let now = Utc::now();
+        let s = FoodSignals {
+            completed_orders: 1,
+            last_ordered_at: Some(now + Duration::hours(5)),
+            ..Default::default()
+        };
+        let (_, factors) = score_food(&s, FoodCategory::Lunch, now).unwrap();
+        assert!(factors.iter().any(|f| f.code == "RECENT_REPEAT" && f.weight == W_EATEN_TODAY));
+    }
+}
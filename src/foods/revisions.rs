@@ -0,0 +1,425 @@
+// 菜品修订历史：每次内容/状态变更后保存完整快照（food_revisions），支持列表、对比与恢复。
+// 快照在变更写入后读取，修订号按菜品递增；内容未变化的更新不产生修订。
+use crate::{
+    errors::CustomError,
+    foods::{
+        audit::{
+            ensure_food_editor, ensure_reviewer, insert_audit_log, load_food_out, resubmit_submitter_edit, AUDIT_REVERT,
+            FOOD_COLUMNS,
+        },
+        recipe, tags,
+    },
+    models::foods::{
+        ApplyStatusEnum, FoodIngredientInput, FoodOut, FoodRecord, FoodRevisionDiffOut,
+        FoodRevisionDiffQuery, FoodRevisionFieldDiff, FoodRevisionOut, FoodRevisionRestoreInput,
+        FoodRevisionSnapshot, FoodRevisionSourceEnum, FoodStepInput,
+    },
+    models::photos::PhotoOwnerEnum,
+    models::users::UserToken,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{types::Json as SqlJson, PgConnection, Row};
+use std::sync::Arc;
+
+// 参与对比的快照字段（顺序即输出顺序）
+const CONTENT_FIELDS: [&str; 7] = [
+    "food_name",
+    "food_photo",
+    "ingredients",
+    "steps",
+    "ingredient_items",
+    "step_items",
+    "tags",
+];
+const STATUS_FIELDS: [&str; 2] = ["food_status", "apply_status"];
+
+/// 读取菜品当前内容快照
+pub async fn load_snapshot(conn: &mut PgConnection, food_id: i64) -> Result<FoodRevisionSnapshot, CustomError> {
+    let row = sqlx::query(
+        "SELECT food_name, food_photo, ingredients, steps, food_status, apply_status FROM foods WHERE food_id=$1",
+    )
+    .bind(food_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let ingredient_items = recipe::load_ingredients(conn, food_id)
+        .await?
+        .into_iter()
+        .map(|i| FoodIngredientInput {
+            name: i.name,
+            quantity: i.quantity,
+            unit: i.unit,
+            note: i.note,
+        })
+        .collect();
+    let step_items = recipe::load_steps(conn, food_id)
+        .await?
+        .into_iter()
+        .map(|s| FoodStepInput {
+            content: s.content,
+            photo_url: s.photo_url,
+            timer_seconds: s.timer_seconds,
+        })
+        .collect();
+    let tags = tags::load_food_tags(conn, &[food_id])
+        .await?
+        .remove(&food_id)
+        .unwrap_or_default();
+    Ok(FoodRevisionSnapshot {
+        food_name: row.get("food_name"),
+        food_photo: row.get("food_photo"),
+        ingredients: row.get("ingredients"),
+        steps: row.get("steps"),
+        ingredient_items,
+        step_items,
+        tags,
+        food_status: row.get("food_status"),
+        apply_status: row.get("apply_status"),
+    })
+}
+
+fn diff_fields(
+    before: Option<&FoodRevisionSnapshot>,
+    after: &FoodRevisionSnapshot,
+    fields: &[&str],
+) -> Vec<FoodRevisionFieldDiff> {
+    let before = before
+        .and_then(|b| serde_json::to_value(b).ok())
+        .unwrap_or(serde_json::Value::Null);
+    let after = serde_json::to_value(after).unwrap_or(serde_json::Value::Null);
+    fields
+        .iter()
+        .filter_map(|f| {
+            let b = before.get(*f).cloned().unwrap_or(serde_json::Value::Null);
+            let a = after.get(*f).cloned().unwrap_or(serde_json::Value::Null);
+            (b != a).then(|| FoodRevisionFieldDiff {
+                field: f.to_string(),
+                before: b,
+                after: a,
+            })
+        })
+        .collect()
+}
+
+fn all_fields() -> Vec<&'static str> {
+    CONTENT_FIELDS.iter().chain(STATUS_FIELDS.iter()).copied().collect()
+}
+
+/// 按当前数据写入一条修订；与上一修订完全一致时不写入，返回 None
+pub async fn record_revision(
+    conn: &mut PgConnection,
+    food_id: i64,
+    source: FoodRevisionSourceEnum,
+    user_id: i64,
+    audit_log_id: Option<i64>,
+) -> Result<Option<i32>, CustomError> {
+    let snapshot = load_snapshot(conn, food_id).await?;
+    let prev = sqlx::query(
+        "SELECT revision_no, snapshot FROM food_revisions WHERE food_id=$1 ORDER BY revision_no DESC LIMIT 1",
+    )
+    .bind(food_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (prev_no, prev_snapshot) = match prev {
+        Some(r) => (r.get::<i32, _>("revision_no"), Some(r.get::<SqlJson<FoodRevisionSnapshot>, _>("snapshot").0)),
+        None => (0, None),
+    };
+    let changed: Vec<String> = diff_fields(prev_snapshot.as_ref(), &snapshot, &all_fields())
+        .into_iter()
+        .map(|d| d.field)
+        .collect();
+    if prev_snapshot.is_some() && changed.is_empty() {
+        return Ok(None);
+    }
+    let revision_no = prev_no + 1;
+    sqlx::query(
+        "INSERT INTO food_revisions (food_id, revision_no, source, changed_fields, snapshot, audit_log_id, created_by) \
+         VALUES ($1,$2,$3,$4,$5,$6,$7)",
+    )
+    .bind(food_id)
+    .bind(revision_no)
+    .bind(source.as_str())
+    .bind(&changed)
+    .bind(SqlJson(&snapshot))
+    .bind(audit_log_id)
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(Some(revision_no))
+}
+
+async fn load_revision_snapshot(
+    conn: &mut PgConnection,
+    food_id: i64,
+    revision_no: i32,
+) -> Result<FoodRevisionSnapshot, CustomError> {
+    let snapshot: Option<SqlJson<FoodRevisionSnapshot>> =
+        sqlx::query_scalar("SELECT snapshot FROM food_revisions WHERE food_id=$1 AND revision_no=$2")
+            .bind(food_id)
+            .bind(revision_no)
+            .fetch_optional(&mut *conn)
+            .await?;
+    snapshot
+        .map(|s| s.0)
+        .ok_or_else(|| CustomError::BadRequest(format!("修订版本 #{} 不存在", revision_no)))
+}
+
+/// 最近一次审核通过状态下的修订（用于拒绝时回退）
+pub async fn last_approved_revision(
+    conn: &mut PgConnection,
+    food_id: i64,
+) -> Result<Option<(i32, FoodRevisionSnapshot)>, CustomError> {
+    let row = sqlx::query(
+        "SELECT revision_no, snapshot FROM food_revisions WHERE food_id=$1 AND snapshot->>'apply_status'='APPROVED' \
+         ORDER BY revision_no DESC LIMIT 1",
+    )
+    .bind(food_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(row.map(|r| (r.get("revision_no"), r.get::<SqlJson<FoodRevisionSnapshot>, _>("snapshot").0)))
+}
+
+/// 将快照中的内容（名称、图片、食材、步骤、标签）写回菜品；状态由调用方决定。
+/// 已删除或不再属于该组的标签会被忽略。
+pub async fn apply_snapshot(
+    conn: &mut PgConnection,
+    food_id: i64,
+    group_id: Option<i64>,
+    snapshot: &FoodRevisionSnapshot,
+) -> Result<(), CustomError> {
+    if snapshot.ingredient_items.is_empty() {
+        recipe::clear_ingredients(conn, food_id).await?;
+    } else {
+        recipe::replace_ingredients(conn, food_id, &snapshot.ingredient_items).await?;
+    }
+    if snapshot.step_items.is_empty() {
+        recipe::clear_steps(conn, food_id).await?;
+    } else {
+        recipe::replace_steps(conn, food_id, &snapshot.step_items).await?;
+    }
+    sqlx::query("UPDATE foods SET food_name=$2, food_photo=$3, ingredients=$4, steps=$5, updated_at=NOW() WHERE food_id=$1")
+        .bind(food_id)
+        .bind(&snapshot.food_name)
+        .bind(&snapshot.food_photo)
+        .bind(&snapshot.ingredients)
+        .bind(&snapshot.steps)
+        .execute(&mut *conn)
+        .await?;
+    // 图集封面跟随快照中的图片；快照无图时仍以图集封面回填
+    match snapshot.food_photo.as_deref() {
+        Some(url) => crate::photos::adopt_legacy_cover(conn, PhotoOwnerEnum::FOOD, food_id, Some(url), None).await?,
+        None => crate::photos::sync_legacy_field(conn, PhotoOwnerEnum::FOOD, food_id).await?,
+    }
+
+    let wanted: Vec<i64> = snapshot.tags.iter().map(|t| t.tag_id).collect();
+    let existing: Vec<i64> = sqlx::query_scalar(
+        "SELECT tag_id FROM tags WHERE tag_id = ANY($1) AND (group_id IS NULL OR group_id IS NOT DISTINCT FROM $2)",
+    )
+    .bind(&wanted)
+    .bind(group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let tag_ids: Vec<i64> = wanted.into_iter().filter(|id| existing.contains(id)).collect();
+    tags::replace_food_tags(conn, food_id, group_id, &tag_ids).await?;
+    Ok(())
+}
+
+// 提交人或菜品所属组成员可查看
+async fn ensure_viewer(conn: &mut PgConnection, food_id: i64, user_id: i64) -> Result<(), CustomError> {
+    let allowed: Option<bool> = sqlx::query_scalar(
+        "SELECT (f.created_by=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=f.group_id AND m.user_id=$2)) \
+         FROM foods f WHERE f.food_id=$1 AND f.is_del=0",
+    )
+    .bind(food_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match allowed {
+        Some(true) => Ok(()),
+        Some(false) => Err(CustomError::BadRequest("无权查看该菜品".into())),
+        None => Err(CustomError::BadRequest("菜品不存在".into())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/revisions",
+    tag = "菜品",
+    summary = "菜品修订历史（新到旧，含每次修订后的完整快照）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    responses((status = 200, body = [FoodRevisionOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_food_revisions(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_viewer(&mut conn, *id, token.user_id).await?;
+    let rows = sqlx::query(
+        "SELECT r.revision_no, r.source, r.changed_fields, r.snapshot, r.audit_log_id, r.created_by, u.nick_name, r.created_at \
+         FROM food_revisions r LEFT JOIN users u ON u.user_id=r.created_by \
+         WHERE r.food_id=$1 ORDER BY r.revision_no DESC LIMIT 100",
+    )
+    .bind(*id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let list: Vec<FoodRevisionOut> = rows
+        .into_iter()
+        .map(|r| FoodRevisionOut {
+            revision_no: r.get("revision_no"),
+            source: FoodRevisionSourceEnum::parse(r.get::<String, _>("source").as_str()),
+            changed_fields: r.get("changed_fields"),
+            audit_log_id: r.get("audit_log_id"),
+            created_by: r.get("created_by"),
+            creator_name: r.get("nick_name"),
+            created_at: r.get("created_at"),
+            snapshot: r.get::<SqlJson<FoodRevisionSnapshot>, _>("snapshot").0,
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(&list))
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/revisions/diff",
+    tag = "菜品",
+    summary = "对比两个修订版本的字段差异",
+    params(("id" = i64, Path, description = "菜品ID"), FoodRevisionDiffQuery),
+    responses((status = 200, body = FoodRevisionDiffOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn diff_food_revisions(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    query: Query<FoodRevisionDiffQuery>,
+) -> Result<impl Responder, CustomError> {
+    let food_id = *id;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_viewer(&mut conn, food_id, token.user_id).await?;
+    let to = match query.to {
+        Some(no) => no,
+        None => sqlx::query_scalar::<_, Option<i32>>("SELECT MAX(revision_no) FROM food_revisions WHERE food_id=$1")
+            .bind(food_id)
+            .fetch_one(&mut *conn)
+            .await?
+            .ok_or_else(|| CustomError::BadRequest("该菜品暂无修订记录".into()))?,
+    };
+    let from = query.from.or(Some(to - 1)).filter(|no| *no > 0);
+    if from.is_some_and(|f| f >= to) {
+        return Err(CustomError::BadRequest("起始版本需早于结束版本".into()));
+    }
+    let after = load_revision_snapshot(&mut conn, food_id, to).await?;
+    let before = match from {
+        Some(no) => Some(load_revision_snapshot(&mut conn, food_id, no).await?),
+        None => None,
+    };
+    Ok(HttpResponse::Ok().json(&FoodRevisionDiffOut {
+        food_id,
+        from_revision: from,
+        to_revision: to,
+        changes: diff_fields(before.as_ref(), &after, &all_fields()),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/revisions/{revision_no}/restore",
+    tag = "菜品",
+    summary = "将菜品内容恢复到指定修订版本（提交人恢复未通过的申请视为重新提交；审核人恢复到已通过版本时同时恢复上架）",
+    params(
+        ("id" = i64, Path, description = "菜品ID"),
+        ("revision_no" = i32, Path, description = "修订号")
+    ),
+    request_body = FoodRevisionRestoreInput,
+    responses((status = 200, body = FoodOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn restore_food_revision(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i32)>,
+    data: Json<FoodRevisionRestoreInput>,
+) -> Result<impl Responder, CustomError> {
+    let (food_id, revision_no) = *path;
+    let uid = token.user_id;
+    let remark = data
+        .remark
+        .as_deref()
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|r| r.chars().take(255).collect::<String>());
+    let mut tx = state.db_pool.begin().await?;
+    let rec: Option<FoodRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM foods WHERE food_id=$1 AND is_del=0 FOR UPDATE",
+        FOOD_COLUMNS
+    ))
+    .bind(food_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(rec) = rec else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜品不存在".into()));
+    };
+    if let Err(e) = ensure_food_editor(&mut tx, food_id, uid).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    let target = match load_revision_snapshot(&mut tx, food_id, revision_no).await {
+        Ok(s) => s,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let current = load_snapshot(&mut tx, food_id).await?;
+    if diff_fields(Some(&current), &target, &CONTENT_FIELDS).is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("内容与当前版本一致，无需恢复".into()));
+    }
+
+    apply_snapshot(&mut tx, food_id, rec.group_id, &target).await?;
+
+    // 状态：提交人恢复申请内容（含已通过的菜品）-> 重新提交，与直接修改一致；审核人恢复到已通过版本 -> 恢复上架
+    let is_reviewer = ensure_reviewer(&mut tx, rec.group_id, rec.created_by, uid).await.is_ok();
+    let resubmitted = resubmit_submitter_edit(&mut tx, food_id, uid, remark.as_deref()).await?;
+    let audit_log_id = if resubmitted.is_some() {
+        resubmitted
+    } else if is_reviewer
+        && matches!(target.apply_status, ApplyStatusEnum::APPROVED)
+        && !matches!(rec.apply_status, ApplyStatusEnum::APPROVED)
+    {
+        sqlx::query(
+            "UPDATE foods SET apply_status='APPROVED', food_status='NORMAL', approved_at=NOW(), approved_by=$2, updated_at=NOW() WHERE food_id=$1",
+        )
+        .bind(food_id)
+        .bind(uid)
+        .execute(&mut *tx)
+        .await?;
+        let remark = remark.unwrap_or_else(|| format!("恢复到修订版本 #{}", revision_no));
+        Some(
+            insert_audit_log(
+                &mut tx,
+                food_id,
+                AUDIT_REVERT,
+                Some(rec.apply_status),
+                ApplyStatusEnum::APPROVED,
+                uid,
+                Some(&remark),
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+    record_revision(&mut tx, food_id, FoodRevisionSourceEnum::RESTORE, uid, audit_log_id).await?;
+
+    let out = load_food_out(&mut tx, food_id, uid).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
@@ -0,0 +1,286 @@
+use crate::{
+    errors::CustomError,
+    models::foods::{
+        ApplyStatusEnum, FoodSearchHitOut, FoodSearchQuery, FoodStatusEnum, FoodSuggestOut,
+        FoodSuggestQuery,
+    },
+    models::users::UserToken,
+    services::groups::ensure_member,
+    AppState,
+};
+use ntex::web::{
+    types::{Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Postgres, QueryBuilder, Row};
+use std::sync::Arc;
+
+const KEYWORD_MAX_CHARS: usize = 64;
+const SNIPPET_CHARS: usize = 40;
+
+// 关键词的 tsquery：q_all 全部二元词命中，q_any 任一命中（容忍错字）
+const SEARCH_CTE: &str = "WITH q AS (SELECT kw, \
+     plainto_tsquery('simple', zh_bigrams(kw)) AS q_all, \
+     (SELECT to_tsquery('simple', string_agg(quote_literal(t), ' | ')) \
+      FROM unnest(tsvector_to_array(to_tsvector('simple', zh_bigrams(kw)))) AS t) AS q_any \
+     FROM (SELECT ";
+
+fn normalize_keyword(q: &str) -> Result<String, CustomError> {
+    let kw = q.trim();
+    if kw.is_empty() {
+        return Err(CustomError::BadRequest("请输入搜索关键词".into()));
+    }
+    if kw.chars().count() > KEYWORD_MAX_CHARS {
+        return Err(CustomError::BadRequest(format!("关键词不能超过{}字", KEYWORD_MAX_CHARS)));
+    }
+    Ok(kw.to_string())
+}
+
+fn is_cjk(c: char) -> bool {
+    ('\u{4e00}'..='\u{9fff}').contains(&c)
+}
+
+// 与数据库 zh_bigrams 一致的切分：中文连续段取相邻两字，其余按字母数字分词
+fn highlight_terms(kw: &str) -> Vec<Vec<char>> {
+    let lower: Vec<char> = kw.chars().flat_map(char::to_lowercase).collect();
+    let mut terms: Vec<Vec<char>> = vec![lower.clone()];
+    let mut i = 0;
+    while i < lower.len() {
+        let c = lower[i];
+        let start = i;
+        if is_cjk(c) {
+            while i < lower.len() && is_cjk(lower[i]) {
+                i += 1;
+            }
+            let run = &lower[start..i];
+            if run.len() == 1 {
+                terms.push(run.to_vec());
+            } else {
+                terms.extend(run.windows(2).map(|w| w.to_vec()));
+            }
+        } else if c.is_alphanumeric() {
+            while i < lower.len() && lower[i].is_alphanumeric() && !is_cjk(lower[i]) {
+                i += 1;
+            }
+            terms.push(lower[start..i].to_vec());
+        } else {
+            i += 1;
+        }
+    }
+    terms.retain(|t| !t.is_empty());
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+fn escape_html(c: char, out: &mut String) {
+    match c {
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '&' => out.push_str("&amp;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(c),
+    }
+}
+
+// 命中部分以 <em> 包裹；max_chars 为 Some 时截取首个命中附近的片段。无命中返回 None。
+fn highlight(text: &str, terms: &[Vec<char>], max_chars: Option<usize>) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let mut marked = vec![false; chars.len()];
+    for term in terms {
+        if term.len() > lower.len() {
+            continue;
+        }
+        for start in 0..=lower.len() - term.len() {
+            if lower[start..start + term.len()] == term[..] {
+                marked[start..start + term.len()].fill(true);
+            }
+        }
+    }
+    let first = marked.iter().position(|m| *m)?;
+    let (from, to) = match max_chars {
+        Some(n) => {
+            let from = first.saturating_sub(n / 4);
+            (from, (from + n).min(chars.len()))
+        }
+        None => (0, chars.len()),
+    };
+    let mut out = String::new();
+    if from > 0 {
+        out.push('…');
+    }
+    let mut in_mark = false;
+    for (c, m) in chars.iter().zip(marked.iter()).take(to).skip(from) {
+        if *m && !in_mark {
+            out.push_str("<em>");
+            in_mark = true;
+        } else if !*m && in_mark {
+            out.push_str("</em>");
+            in_mark = false;
+        }
+        escape_html(*c, &mut out);
+    }
+    if in_mark {
+        out.push_str("</em>");
+    }
+    if to < chars.len() {
+        out.push('…');
+    }
+    Some(out)
+}
+
+fn plain_html(text: &str) -> String {
+    let mut out = String::new();
+    text.chars().for_each(|c| escape_html(c, &mut out));
+    out
+}
+
+// 可见范围：指定组需为成员；否则为自己所在组及自己创建的菜品
+async fn push_scope(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    conn: &mut PgConnection,
+    group_id: Option<i64>,
+    user_id: i64,
+) -> Result<(), CustomError> {
+    match group_id {
+        Some(gid) => {
+            ensure_member(conn, gid, user_id).await?;
+            qb.push(" AND f.group_id = ").push_bind(gid);
+        }
+        None => {
+            qb.push(" AND (f.created_by = ")
+                .push_bind(user_id)
+                .push(" OR f.group_id IN (SELECT group_id FROM association_group_members WHERE user_id = ")
+                .push_bind(user_id)
+                .push("))");
+        }
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/search",
+    tag = "菜品",
+    summary = "菜品全文搜索（名称/食材/步骤，按相关度排序并高亮）",
+    params(FoodSearchQuery),
+    responses((status = 200, body = [FoodSearchHitOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn search_foods(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<FoodSearchQuery>,
+) -> Result<impl Responder, CustomError> {
+    let kw = normalize_keyword(&query.q)?;
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let mut conn = state.db_pool.acquire().await?;
+
+    // 得分：任一二元词命中的覆盖度 + 全部命中加分 + 名称前缀/包含加分 + 名称三元组相似度（错字）
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(SEARCH_CTE);
+    qb.push_bind(kw.clone()).push(
+        "::text AS kw) k) \
+         SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.apply_status, \
+         (COALESCE(ts_rank_cd(f.search_vector, q.q_any), 0) \
+          + CASE WHEN f.search_vector @@ q.q_all THEN 1 ELSE 0 END \
+          + CASE WHEN f.food_name ILIKE q.kw || '%' THEN 1 WHEN f.food_name ILIKE '%' || q.kw || '%' THEN 0.5 ELSE 0 END \
+          + word_similarity(q.kw, f.food_name))::real AS score \
+         FROM foods f, q \
+         WHERE f.is_del=0 \
+         AND (f.search_vector @@ q.q_any OR f.food_name ILIKE '%' || q.kw || '%' \
+              OR f.ingredients ILIKE '%' || q.kw || '%' OR q.kw <% f.food_name)",
+    );
+    push_scope(&mut qb, &mut conn, query.group_id, token.user_id).await?;
+    if query.only_active.unwrap_or(false) {
+        qb.push(" AND f.food_status='NORMAL' AND f.apply_status='APPROVED'");
+    }
+    qb.push(" ORDER BY score DESC, f.food_id DESC LIMIT ").push_bind(limit);
+    let rows = qb.build().fetch_all(&mut *conn).await?;
+
+    let terms = highlight_terms(&kw);
+    let hits: Vec<FoodSearchHitOut> = rows
+        .into_iter()
+        .map(|r| {
+            let food_name: String = r.get("food_name");
+            let ingredients: Option<String> = r.get("ingredients");
+            let steps: Option<String> = r.get("steps");
+            let (snippet, snippet_field) = match ingredients
+                .as_deref()
+                .and_then(|t| highlight(t, &terms, Some(SNIPPET_CHARS)))
+            {
+                Some(s) => (Some(s), Some("ingredients".to_string())),
+                None => match steps.as_deref().and_then(|t| highlight(t, &terms, Some(SNIPPET_CHARS))) {
+                    Some(s) => (Some(s), Some("steps".to_string())),
+                    None => (None, None),
+                },
+            };
+            FoodSearchHitOut {
+                food_id: r.get("food_id"),
+                name_highlight: highlight(&food_name, &terms, None).unwrap_or_else(|| plain_html(&food_name)),
+                food_name,
+                food_photo: r.get("food_photo"),
+                food_status: r.get::<FoodStatusEnum, _>("food_status"),
+                apply_status: r.get::<ApplyStatusEnum, _>("apply_status"),
+                score: r.get("score"),
+                snippet,
+                snippet_field,
+            }
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(&hits))
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/suggest",
+    tag = "菜品",
+    summary = "点菜联想：按名称前缀/包含/相似度返回可点菜品",
+    params(FoodSuggestQuery),
+    responses((status = 200, body = [FoodSuggestOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn suggest_foods(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<FoodSuggestQuery>,
+) -> Result<impl Responder, CustomError> {
+    let kw = normalize_keyword(&query.q)?;
+    let limit = query.limit.unwrap_or(10).clamp(1, 20);
+    let mut conn = state.db_pool.acquire().await?;
+
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(SEARCH_CTE);
+    qb.push_bind(kw.clone()).push(
+        "::text AS kw) k) \
+         SELECT f.food_id, f.food_name, f.food_photo, \
+         CASE WHEN f.food_name ILIKE q.kw || '%' THEN 0 WHEN f.food_name ILIKE '%' || q.kw || '%' THEN 1 ELSE 2 END AS tier, \
+         word_similarity(q.kw, f.food_name) AS sim \
+         FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id, q \
+         WHERE f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED' \
+         AND (f.food_name ILIKE '%' || q.kw || '%' OR q.kw <% f.food_name \
+              OR to_tsvector('simple', zh_bigrams(f.food_name)) @@ q.q_all)",
+    );
+    push_scope(&mut qb, &mut conn, query.group_id, token.user_id).await?;
+    // 同档内常点的排前面
+    qb.push(" ORDER BY tier, sim DESC, COALESCE(fs.total_order_count, 0) DESC, f.food_id DESC LIMIT ")
+        .push_bind(limit);
+    let rows = qb.build().fetch_all(&mut *conn).await?;
+
+    let terms = highlight_terms(&kw);
+    let out: Vec<FoodSuggestOut> = rows
+        .into_iter()
+        .map(|r| {
+            let food_name: String = r.get("food_name");
+            FoodSuggestOut {
+                food_id: r.get("food_id"),
+                name_highlight: highlight(&food_name, &terms, None).unwrap_or_else(|| plain_html(&food_name)),
+                food_name,
+                food_photo: r.get("food_photo"),
+            }
+        })
+        .collect();
+    Ok(HttpResponse::Ok().json(&out))
+}
@@ -0,0 +1,36 @@
+use crate::{
+    errors::CustomError,
+    models::foods::FoodStatsRebuildOut,
+    models::users::UserToken,
+    services::food_stats,
+    AppState,
+};
+use ntex::web::{types::State, HttpResponse, Responder};
+use std::{sync::Arc, time::Instant};
+
+#[utoipa::path(
+    post,
+    path = "/admin/food-stats/rebuild",
+    tag = "菜品",
+    summary = "按订单明细与状态历史全量重建菜品统计（仅管理员）",
+    responses((status = 200, body = FoodStatsRebuildOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn rebuild_food_stats(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let role: Option<String> = sqlx::query_scalar("SELECT role::text FROM users WHERE user_id=$1")
+        .bind(token.user_id)
+        .fetch_optional(&state.db_pool)
+        .await?;
+    if role.as_deref() != Some("ADMIN") {
+        return Err(CustomError::BadRequest("仅管理员可操作".into()));
+    }
+    let started = Instant::now();
+    let foods = food_stats::rebuild_all(&state.db_pool).await?;
+    Ok(HttpResponse::Ok().json(&FoodStatsRebuildOut {
+        foods,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    }))
+}
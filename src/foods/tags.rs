@@ -0,0 +1,349 @@
+// 菜品多标签（food_tags_map）读写；foods.tag_id 保留为主标签（取第一个），兼容旧客户端
+// 标签支持同组内的父子层级（tags.parent_id），如 中餐 → 川菜
+use crate::{
+    errors::CustomError,
+    foods::revisions::record_revision,
+    models::foods::{FoodRevisionSourceEnum, FoodTagOut, TagOut},
+};
+use sqlx::{PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+
+pub const MAX_TAGS_PER_FOOD: usize = 10;
+/// 标签层级上限（顶级为第 1 层）
+pub const MAX_TAG_DEPTH: i32 = 3;
+const TAG_NAME_MAX_CHARS: usize = 64;
+
+// tag_ids 优先；只传旧字段 tag_id 时视为单标签。去重并保持顺序。
+pub fn requested_tag_ids(tag_ids: Option<&Vec<i64>>, tag_id: Option<i64>) -> Option<Vec<i64>> {
+    let raw = match (tag_ids, tag_id) {
+        (Some(ids), _) => ids.clone(),
+        (None, Some(tid)) => vec![tid],
+        (None, None) => return None,
+    };
+    let mut out: Vec<i64> = Vec::with_capacity(raw.len());
+    for id in raw {
+        if !out.contains(&id) {
+            out.push(id);
+        }
+    }
+    Some(out)
+}
+
+// 整体替换菜品标签，标签需为公共标签或与菜品同组；返回主标签
+pub async fn replace_food_tags(
+    conn: &mut PgConnection,
+    food_id: i64,
+    group_id: Option<i64>,
+    tag_ids: &[i64],
+) -> Result<Option<i64>, CustomError> {
+    if tag_ids.len() > MAX_TAGS_PER_FOOD {
+        return Err(CustomError::BadRequest(format!("每个菜品最多{}个标签", MAX_TAGS_PER_FOOD)));
+    }
+    if !tag_ids.is_empty() {
+        let valid: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM tags WHERE tag_id = ANY($1) AND (group_id IS NULL OR group_id IS NOT DISTINCT FROM $2)",
+        )
+        .bind(tag_ids)
+        .bind(group_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        if valid != tag_ids.len() as i64 {
+            return Err(CustomError::BadRequest("标签不存在或不属于该组".into()));
+        }
+    }
+    sqlx::query("DELETE FROM food_tags_map WHERE food_id=$1")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("INSERT INTO food_tags_map (food_id, tag_id) SELECT $1, UNNEST($2::bigint[]) ON CONFLICT DO NOTHING")
+        .bind(food_id)
+        .bind(tag_ids)
+        .execute(&mut *conn)
+        .await?;
+    let primary = tag_ids.first().copied();
+    sqlx::query("UPDATE foods SET tag_id=$2, updated_at=NOW() WHERE food_id=$1")
+        .bind(food_id)
+        .bind(primary)
+        .execute(&mut *conn)
+        .await?;
+    Ok(primary)
+}
+
+// 批量读取菜品标签：主标签（foods.tag_id）在前，其余按标签 sort 降序。
+// 与 replace_food_tags“第一个为主标签”一致，读出后原样写回（修订恢复、导出导入、复制）不会改变主标签。
+pub async fn load_food_tags(
+    conn: &mut PgConnection,
+    food_ids: &[i64],
+) -> Result<HashMap<i64, Vec<FoodTagOut>>, CustomError> {
+    let mut map: HashMap<i64, Vec<FoodTagOut>> = HashMap::new();
+    if food_ids.is_empty() {
+        return Ok(map);
+    }
+    let rows = sqlx::query(
+        "SELECT m.food_id, t.tag_id, t.tag_name FROM food_tags_map m JOIN tags t ON t.tag_id=m.tag_id \
+         JOIN foods f ON f.food_id=m.food_id \
+         WHERE m.food_id = ANY($1) ORDER BY m.food_id, (t.tag_id IS NOT DISTINCT FROM f.tag_id) DESC, t.sort DESC NULLS LAST, t.tag_id",
+    )
+    .bind(food_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    for r in rows {
+        map.entry(r.get("food_id")).or_default().push(FoodTagOut {
+            tag_id: r.get("tag_id"),
+            tag_name: r.get("tag_name"),
+        });
+    }
+    Ok(map)
+}
+
+pub fn normalize_tag_name(name: &str) -> Result<String, CustomError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CustomError::BadRequest("标签名称不能为空".into()));
+    }
+    if name.chars().count() > TAG_NAME_MAX_CHARS {
+        return Err(CustomError::BadRequest(format!("标签名称不能超过{}个字符", TAG_NAME_MAX_CHARS)));
+    }
+    Ok(name.to_string())
+}
+
+// 锁定标签，返回 (group_id, parent_id)
+pub async fn lock_tag(conn: &mut PgConnection, tag_id: i64) -> Result<(Option<i64>, Option<i64>), CustomError> {
+    let row = sqlx::query("SELECT group_id, parent_id FROM tags WHERE tag_id=$1 FOR UPDATE")
+        .bind(tag_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    match row {
+        Some(r) => Ok((r.get("group_id"), r.get("parent_id"))),
+        None => Err(CustomError::BadRequest("标签不存在".into())),
+    }
+}
+
+// 权限：公共标签仅系统管理员；组标签为组内接单方/管理员或系统管理员
+pub async fn ensure_tag_manager(conn: &mut PgConnection, group_id: Option<i64>, user_id: i64) -> Result<(), CustomError> {
+    let allowed: bool = sqlx::query_scalar(
+        "SELECT (EXISTS(SELECT 1 FROM users u WHERE u.user_id=$2 AND u.role='ADMIN') \
+          OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=$1 AND m.user_id=$2 AND m.role_in_group IN ('RECEIVING','ADMIN')))",
+    )
+    .bind(group_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !allowed {
+        return Err(CustomError::BadRequest("无权限管理该标签".into()));
+    }
+    Ok(())
+}
+
+// 组内（公共标签之间）名称唯一；exclude 为正在重命名的标签
+pub async fn ensure_unique_tag_name(
+    conn: &mut PgConnection,
+    tag_name: &str,
+    group_id: Option<i64>,
+    exclude: Option<i64>,
+) -> Result<(), CustomError> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM tags WHERE tag_name=$1 AND group_id IS NOT DISTINCT FROM $2 AND tag_id IS DISTINCT FROM $3)",
+    )
+    .bind(tag_name)
+    .bind(group_id)
+    .bind(exclude)
+    .fetch_one(&mut *conn)
+    .await?;
+    if exists {
+        return Err(CustomError::BadRequest(format!("已存在同名标签“{}”，可使用合并", tag_name)));
+    }
+    Ok(())
+}
+
+// 祖先链（含自身），自下而上
+async fn ancestor_ids(conn: &mut PgConnection, tag_id: i64) -> Result<Vec<i64>, CustomError> {
+    let ids = sqlx::query_scalar(
+        "WITH RECURSIVE up AS ( \
+           SELECT tag_id, parent_id, 1 AS lvl FROM tags WHERE tag_id=$1 \
+           UNION ALL SELECT t.tag_id, t.parent_id, up.lvl+1 FROM tags t JOIN up ON t.tag_id=up.parent_id WHERE up.lvl < 32 \
+         ) SELECT tag_id FROM up ORDER BY lvl",
+    )
+    .bind(tag_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(ids)
+}
+
+// 子树层数（含自身）
+async fn subtree_height(conn: &mut PgConnection, tag_id: i64) -> Result<i32, CustomError> {
+    let height = sqlx::query_scalar(
+        "WITH RECURSIVE down AS ( \
+           SELECT tag_id, 1 AS lvl FROM tags WHERE tag_id=$1 \
+           UNION ALL SELECT t.tag_id, down.lvl+1 FROM tags t JOIN down ON t.parent_id=down.tag_id WHERE down.lvl < 32 \
+         ) SELECT COALESCE(MAX(lvl), 0) FROM down",
+    )
+    .bind(tag_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(height)
+}
+
+/// 校验父标签：须同组、不能成环、层级不超过上限。tag_id 为空表示新建标签
+pub async fn validate_tag_parent(
+    conn: &mut PgConnection,
+    tag_id: Option<i64>,
+    group_id: Option<i64>,
+    parent_id: i64,
+) -> Result<(), CustomError> {
+    let parent_group: Option<Option<i64>> = sqlx::query_scalar("SELECT group_id FROM tags WHERE tag_id=$1")
+        .bind(parent_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    match parent_group {
+        None => return Err(CustomError::BadRequest("父标签不存在".into())),
+        Some(g) if g != group_id => return Err(CustomError::BadRequest("父标签须与标签同组".into())),
+        Some(_) => {}
+    }
+    let chain = ancestor_ids(conn, parent_id).await?;
+    if tag_id.is_some_and(|id| chain.contains(&id)) {
+        return Err(CustomError::BadRequest("不能移动到自身或其子标签下".into()));
+    }
+    let height = match tag_id {
+        Some(id) => subtree_height(conn, id).await?,
+        None => 1,
+    };
+    if chain.len() as i32 + height > MAX_TAG_DEPTH {
+        return Err(CustomError::BadRequest(format!("标签层级最多{}层", MAX_TAG_DEPTH)));
+    }
+    Ok(())
+}
+
+// target 是否为 tag 自身或其子孙
+pub async fn is_descendant_or_self(conn: &mut PgConnection, tag_id: i64, target: i64) -> Result<bool, CustomError> {
+    Ok(ancestor_ids(conn, target).await?.contains(&tag_id))
+}
+
+/// 将使用 from 标签的菜品改挂到 to（None 表示仅移除），主标签随之调整；
+/// 受影响菜品记录修订，返回受影响菜品数
+pub async fn move_tag_foods(
+    conn: &mut PgConnection,
+    from: i64,
+    to: Option<i64>,
+    user_id: i64,
+) -> Result<usize, CustomError> {
+    let affected: Vec<i64> = sqlx::query_scalar(
+        "SELECT food_id FROM food_tags_map WHERE tag_id=$1 UNION SELECT food_id FROM foods WHERE tag_id=$1",
+    )
+    .bind(from)
+    .fetch_all(&mut *conn)
+    .await?;
+    if affected.is_empty() {
+        return Ok(0);
+    }
+    if let Some(to) = to {
+        sqlx::query("INSERT INTO food_tags_map (food_id, tag_id) SELECT UNNEST($1::bigint[]), $2 ON CONFLICT DO NOTHING")
+            .bind(&affected)
+            .bind(to)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("UPDATE foods SET tag_id=$2, updated_at=NOW() WHERE tag_id=$1")
+            .bind(from)
+            .bind(to)
+            .execute(&mut *conn)
+            .await?;
+    }
+    sqlx::query("DELETE FROM food_tags_map WHERE tag_id=$1")
+        .bind(from)
+        .execute(&mut *conn)
+        .await?;
+    // 主标签被移除的菜品改用剩余的第一个标签
+    sqlx::query(
+        "UPDATE foods f SET tag_id = (SELECT m.tag_id FROM food_tags_map m JOIN tags t ON t.tag_id=m.tag_id \
+         WHERE m.food_id=f.food_id ORDER BY t.sort DESC NULLS LAST, t.tag_id LIMIT 1), updated_at=NOW() \
+         WHERE f.tag_id=$1",
+    )
+    .bind(from)
+    .execute(&mut *conn)
+    .await?;
+    for food_id in &affected {
+        record_revision(conn, *food_id, FoodRevisionSourceEnum::UPDATE, user_id, None).await?;
+    }
+    Ok(affected.len())
+}
+
+struct TagNode {
+    tag_id: i64,
+    tag_name: String,
+    group_id: Option<i64>,
+    parent_id: Option<i64>,
+    sort: Option<i32>,
+}
+
+// 先序输出子树，返回子树内菜品集合（用于去重计数）
+fn walk_tags(
+    idx: usize,
+    depth: i32,
+    nodes: &[TagNode],
+    children: &HashMap<Option<i64>, Vec<usize>>,
+    foods_by_tag: &HashMap<i64, HashSet<i64>>,
+    out: &mut Vec<TagOut>,
+) -> HashSet<i64> {
+    let node = &nodes[idx];
+    let mut foods = foods_by_tag.get(&node.tag_id).cloned().unwrap_or_default();
+    let pos = out.len();
+    out.push(TagOut {
+        tag_id: node.tag_id,
+        tag_name: node.tag_name.clone(),
+        group_id: node.group_id,
+        parent_id: node.parent_id,
+        depth,
+        sort: node.sort,
+        food_count: foods.len() as i64,
+        total_food_count: 0,
+    });
+    for &child in children.get(&Some(node.tag_id)).into_iter().flatten() {
+        foods.extend(walk_tags(child, depth + 1, nodes, children, foods_by_tag, out));
+    }
+    out[pos].total_food_count = foods.len() as i64;
+    foods
+}
+
+/// 标签树（先序平铺），同级按 sort 降序；group_id 为空时返回全部标签
+pub async fn load_tag_tree(conn: &mut PgConnection, group_id: Option<i64>) -> Result<Vec<TagOut>, CustomError> {
+    let nodes: Vec<TagNode> = sqlx::query(
+        "SELECT tag_id, tag_name, group_id, parent_id, sort FROM tags WHERE ($1::bigint IS NULL OR group_id=$1) \
+         ORDER BY sort DESC NULLS LAST, tag_id",
+    )
+    .bind(group_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|r| TagNode {
+        tag_id: r.get("tag_id"),
+        tag_name: r.get("tag_name"),
+        group_id: r.get("group_id"),
+        parent_id: r.get("parent_id"),
+        sort: r.get("sort"),
+    })
+    .collect();
+    let tag_ids: Vec<i64> = nodes.iter().map(|n| n.tag_id).collect();
+    let mut foods_by_tag: HashMap<i64, HashSet<i64>> = HashMap::new();
+    let rows = sqlx::query(
+        "SELECT m.tag_id, m.food_id FROM food_tags_map m JOIN foods f ON f.food_id=m.food_id \
+         WHERE m.tag_id = ANY($1) AND f.is_del=0",
+    )
+    .bind(&tag_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    for r in rows {
+        foods_by_tag.entry(r.get("tag_id")).or_default().insert(r.get("food_id"));
+    }
+    // 父标签不在结果中时按顶级处理
+    let known: HashSet<i64> = tag_ids.iter().copied().collect();
+    let mut children: HashMap<Option<i64>, Vec<usize>> = HashMap::new();
+    for (i, n) in nodes.iter().enumerate() {
+        let parent = n.parent_id.filter(|p| known.contains(p));
+        children.entry(parent).or_default().push(i);
+    }
+    let mut out = Vec::with_capacity(nodes.len());
+    for &root in children.get(&None).into_iter().flatten() {
+        walk_tags(root, 0, &nodes, &children, &foods_by_tag, &mut out);
+    }
+    Ok(out)
+}
@@ -0,0 +1,550 @@
+// 菜谱导出/导入（可移植 JSON 包，按名称引用标签）与跨组复制
+use crate::{
+    errors::CustomError,
+    foods::{new::initial_statuses, nutrition, recipe, revisions, tags},
+    models::foods::{
+        ApplyStatusEnum, DietaryLabelEnum, FoodBundle, FoodBundleItem, FoodBundleTag, FoodCopyInput,
+        FoodImportConflictOut, FoodImportInput, FoodImportReportOut, FoodImportedOut,
+        FoodIngredientInput, FoodOut, FoodRecord, FoodRevisionSourceEnum, FoodStepInput, ImportDuplicateEnum,
+        SubmitRoleEnum, TagRecord,
+    },
+    models::photos::PhotoOwnerEnum,
+    models::users::UserToken,
+    services::groups::ensure_member,
+    upload::upload::validate_upload_url,
+    AppState,
+};
+use chrono::Utc;
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+const FOOD_BUNDLE_VERSION: i32 = 1;
+const MAX_IMPORT_FOODS: usize = 500;
+
+fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+// 批量组装菜品包条目（含结构化食材/步骤与标签名称）
+async fn bundle_items(
+    conn: &mut PgConnection,
+    food_ids: &[i64],
+) -> Result<Vec<FoodBundleItem>, CustomError> {
+    let foods = sqlx::query(
+        "SELECT food_id, food_name, food_photo, ingredients, steps, calories, protein, fat, carbs, dietary_labels \
+         FROM foods WHERE food_id = ANY($1) ORDER BY food_id",
+    )
+    .bind(food_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut ingredients: HashMap<i64, Vec<FoodIngredientInput>> = HashMap::new();
+    for r in sqlx::query(
+        "SELECT food_id, name, quantity, unit, note FROM food_ingredients WHERE food_id = ANY($1) ORDER BY food_id, sort, id",
+    )
+    .bind(food_ids)
+    .fetch_all(&mut *conn)
+    .await?
+    {
+        ingredients.entry(r.get("food_id")).or_default().push(FoodIngredientInput {
+            name: r.get("name"),
+            quantity: r.get("quantity"),
+            unit: r.get("unit"),
+            note: r.get("note"),
+        });
+    }
+    let mut steps: HashMap<i64, Vec<FoodStepInput>> = HashMap::new();
+    for r in sqlx::query(
+        "SELECT food_id, content, photo_url, timer_seconds FROM food_steps WHERE food_id = ANY($1) ORDER BY food_id, step_no",
+    )
+    .bind(food_ids)
+    .fetch_all(&mut *conn)
+    .await?
+    {
+        steps.entry(r.get("food_id")).or_default().push(FoodStepInput {
+            content: r.get("content"),
+            photo_url: r.get("photo_url"),
+            timer_seconds: r.get("timer_seconds"),
+        });
+    }
+    let mut food_tags = tags::load_food_tags(conn, food_ids).await?;
+    Ok(foods
+        .into_iter()
+        .map(|r| {
+            let food_id: i64 = r.get("food_id");
+            FoodBundleItem {
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                ingredients: r.get("ingredients"),
+                steps: r.get("steps"),
+                ingredient_items: ingredients.remove(&food_id).unwrap_or_default(),
+                step_items: steps.remove(&food_id).unwrap_or_default(),
+                tags: food_tags
+                    .remove(&food_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|t| t.tag_name)
+                    .collect(),
+                calories: r.get("calories"),
+                protein: r.get("protein"),
+                fat: r.get("fat"),
+                carbs: r.get("carbs"),
+                dietary_labels: DietaryLabelEnum::from_db(&r.get::<Vec<String>, _>("dietary_labels")),
+            }
+        })
+        .collect())
+}
+
+// 导入上下文：目标组可用标签、已有菜名与冲突报告
+struct ImportCtx {
+    group_id: i64,
+    user_id: i64,
+    role: String,
+    create_missing_tags: bool,
+    on_duplicate: ImportDuplicateEnum,
+    tag_ids: HashMap<String, i64>,
+    names: HashSet<String>,
+    report: FoodImportReportOut,
+}
+
+impl ImportCtx {
+    async fn load(
+        conn: &mut PgConnection,
+        group_id: i64,
+        user_id: i64,
+        create_missing_tags: bool,
+        on_duplicate: ImportDuplicateEnum,
+        dry_run: bool,
+    ) -> Result<Self, CustomError> {
+        let role: String = sqlx::query_scalar("SELECT role::text FROM users WHERE user_id=$1")
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| CustomError::BadRequest("用户不存在".into()))?;
+        // 组内标签优先于同名公共标签
+        let tag_rows = sqlx::query(
+            "SELECT tag_id, tag_name FROM tags WHERE group_id=$1 OR group_id IS NULL ORDER BY (group_id IS NULL), tag_id",
+        )
+        .bind(group_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        let mut tag_ids = HashMap::new();
+        for r in tag_rows {
+            tag_ids.entry(name_key(&r.get::<String, _>("tag_name"))).or_insert(r.get("tag_id"));
+        }
+        let names: HashSet<String> =
+            sqlx::query_scalar::<_, String>("SELECT food_name FROM foods WHERE group_id=$1 AND is_del=0")
+                .bind(group_id)
+                .fetch_all(&mut *conn)
+                .await?
+                .iter()
+                .map(|n| name_key(n))
+                .collect();
+        Ok(Self {
+            group_id,
+            user_id,
+            role,
+            create_missing_tags,
+            on_duplicate,
+            tag_ids,
+            names,
+            report: FoodImportReportOut {
+                dry_run,
+                imported: Vec::new(),
+                skipped_count: 0,
+                created_tags: Vec::new(),
+                conflicts: Vec::new(),
+            },
+        })
+    }
+
+    fn conflict(&mut self, food_name: Option<&str>, kind: &str, detail: String, resolution: &str) {
+        self.report.conflicts.push(FoodImportConflictOut {
+            food_name: food_name.map(str::to_string),
+            kind: kind.to_string(),
+            detail,
+            resolution: resolution.to_string(),
+        });
+    }
+
+    // 标签名称映射到目标组；缺失时按配置创建或丢弃。
+    // food_name 为空表示包内声明的标签（不属于具体菜品，不受单菜标签数限制）。
+    // 名称不合法或超出单菜标签数的标签会被丢弃并写入冲突报告。
+    async fn resolve_tags(
+        &mut self,
+        conn: &mut PgConnection,
+        food_name: Option<&str>,
+        names: &[String],
+    ) -> Result<Vec<i64>, CustomError> {
+        let mut ids: Vec<i64> = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        for name in names {
+            let name = match tags::normalize_tag_name(name) {
+                Ok(n) => n,
+                Err(e) => {
+                    let detail = format!("标签「{}」无效：{}", name.trim(), e);
+                    self.conflict(food_name, "INVALID_TAG", detail, "TAG_DROPPED");
+                    continue;
+                }
+            };
+            let key = name_key(&name);
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            if food_name.is_some() && ids.len() >= tags::MAX_TAGS_PER_FOOD {
+                let detail = format!("每个菜品最多{}个标签，「{}」未导入", tags::MAX_TAGS_PER_FOOD, name);
+                self.conflict(food_name, "TOO_MANY_TAGS", detail, "TAG_DROPPED");
+                continue;
+            }
+            let tag_id = match self.tag_ids.get(&key) {
+                Some(id) => *id,
+                None if self.create_missing_tags => {
+                    let id: i64 = sqlx::query_scalar(
+                        "INSERT INTO tags (tag_name, group_id) VALUES ($1,$2) RETURNING tag_id",
+                    )
+                    .bind(&name)
+                    .bind(self.group_id)
+                    .fetch_one(&mut *conn)
+                    .await?;
+                    self.tag_ids.insert(key, id);
+                    let detail = format!("标签「{}」不存在", name);
+                    self.report.created_tags.push(name);
+                    self.conflict(None, "MISSING_TAG", detail, "TAG_CREATED");
+                    id
+                }
+                None => {
+                    let detail = format!("标签「{}」不存在", name);
+                    self.conflict(food_name, "MISSING_TAG", detail, "TAG_DROPPED");
+                    continue;
+                }
+            };
+            if !ids.contains(&tag_id) {
+                ids.push(tag_id);
+            }
+        }
+        Ok(ids)
+    }
+
+    // 导入单个菜品；被跳过时返回 None
+    async fn import_item(
+        &mut self,
+        conn: &mut PgConnection,
+        item: &FoodBundleItem,
+        rename: Option<&str>,
+    ) -> Result<Option<FoodRecord>, CustomError> {
+        let source_name = item.food_name.trim();
+        let mut food_name = rename.map(str::trim).unwrap_or(source_name).to_string();
+        let name_len = food_name.chars().count();
+        if name_len == 0 || name_len > 128 {
+            let detail = "菜品名称长度需在1-128个字符之间".to_string();
+            self.conflict(Some(source_name), "INVALID", detail, "SKIPPED");
+            self.report.skipped_count += 1;
+            return Ok(None);
+        }
+        if let Err(e) = recipe::validate_ingredients(&item.ingredient_items)
+            .and_then(|_| recipe::validate_steps(&item.step_items))
+            .and_then(|_| nutrition::validate_nutrition(item.calories, item.protein, item.fat, item.carbs))
+        {
+            self.conflict(Some(source_name), "INVALID", e.to_string(), "SKIPPED");
+            self.report.skipped_count += 1;
+            return Ok(None);
+        }
+        if self.names.contains(&name_key(&food_name)) {
+            match self.on_duplicate {
+                ImportDuplicateEnum::SKIP => {
+                    let detail = "目标组已有同名菜品".to_string();
+                    self.conflict(Some(source_name), "DUPLICATE_NAME", detail, "SKIPPED");
+                    self.report.skipped_count += 1;
+                    return Ok(None);
+                }
+                ImportDuplicateEnum::RENAME => {
+                    let base = food_name.clone();
+                    let mut n = 1;
+                    while self.names.contains(&name_key(&food_name)) {
+                        food_name = if n == 1 {
+                            format!("{}（导入）", base)
+                        } else {
+                            format!("{}（导入{}）", base, n)
+                        };
+                        n += 1;
+                    }
+                    self.conflict(
+                        Some(source_name),
+                        "DUPLICATE_NAME",
+                        format!("目标组已有同名菜品，改名为「{}」", food_name),
+                        "RENAMED",
+                    );
+                }
+            }
+        }
+        let food_photo = match item.food_photo.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(url) if url.len() <= 256 && validate_upload_url(url).is_ok() => Some(url.to_string()),
+            Some(_) => {
+                let detail = "菜品图片地址无效".to_string();
+                self.conflict(Some(source_name), "INVALID_PHOTO", detail, "PHOTO_DROPPED");
+                None
+            }
+            None => None,
+        };
+        let tag_ids = self.resolve_tags(conn, Some(source_name), &item.tags).await?;
+
+        let (submit_role, apply_status, food_status) = initial_statuses(&self.role);
+        let mut rec = sqlx::query_as::<_, FoodRecord>(
+            "INSERT INTO foods (food_name, food_photo, ingredients, steps, submit_role, apply_status, food_status, created_by, owner_user_id, group_id, calories, protein, fat, carbs, dietary_labels) \
+             VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8,$9,$10,$11,$12,$13,$14) RETURNING food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id, calories, protein, fat, carbs, dietary_labels",
+        )
+        .bind(&food_name)
+        .bind(&food_photo)
+        .bind(&item.ingredients)
+        .bind(&item.steps)
+        .bind(submit_role)
+        .bind(apply_status)
+        .bind(food_status)
+        .bind(self.user_id)
+        .bind(self.group_id)
+        .bind(item.calories)
+        .bind(item.protein)
+        .bind(item.fat)
+        .bind(item.carbs)
+        .bind(DietaryLabelEnum::to_db(&item.dietary_labels))
+        .fetch_one(&mut *conn)
+        .await?;
+        if !tag_ids.is_empty() {
+            rec.tag_id = tags::replace_food_tags(conn, rec.food_id, rec.group_id, &tag_ids).await?;
+        }
+        if !item.ingredient_items.is_empty() {
+            recipe::replace_ingredients(conn, rec.food_id, &item.ingredient_items).await?;
+            rec.ingredients = recipe::ingredients_text(&item.ingredient_items);
+        }
+        if !item.step_items.is_empty() {
+            recipe::replace_steps(conn, rec.food_id, &item.step_items).await?;
+            rec.steps = recipe::steps_text(&item.step_items);
+        }
+        let audit_log_id = if matches!(submit_role, SubmitRoleEnum::ORDERING_APPLY) {
+            Some(
+                crate::foods::audit::insert_audit_log(
+                    conn,
+                    rec.food_id,
+                    crate::foods::audit::AUDIT_SUBMIT,
+                    None,
+                    ApplyStatusEnum::PENDING,
+                    self.user_id,
+                    None,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+        crate::photos::adopt_legacy_cover(conn, PhotoOwnerEnum::FOOD, rec.food_id, food_photo.as_deref(), Some(self.user_id))
+            .await?;
+        revisions::record_revision(conn, rec.food_id, FoodRevisionSourceEnum::IMPORT, self.user_id, audit_log_id)
+            .await?;
+        self.names.insert(name_key(&food_name));
+        self.report.imported.push(FoodImportedOut {
+            food_id: if self.report.dry_run { None } else { Some(rec.food_id) },
+            food_name,
+            source_name: source_name.to_string(),
+        });
+        Ok(Some(rec))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/foods/export",
+    tag = "菜品",
+    summary = "导出组内菜品与标签为可移植 JSON 包",
+    params(("group_id" = i64, Path, description = "组ID")),
+    responses((status = 200, body = FoodBundle)),
+    security(("cookie_auth" = []))
+)]
+pub async fn export_group_foods(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let food_ids: Vec<i64> =
+        sqlx::query_scalar("SELECT food_id FROM foods WHERE group_id=$1 AND is_del=0 ORDER BY food_id")
+            .bind(*group_id)
+            .fetch_all(&mut *conn)
+            .await?;
+    let foods = bundle_items(&mut conn, &food_ids).await?;
+    // 组内标签 + 菜品用到的公共标签
+    let tags: Vec<FoodBundleTag> = sqlx::query_as::<_, TagRecord>(
+        "SELECT tag_id, tag_name, group_id, sort, created_at FROM tags WHERE group_id=$1 \
+         OR (group_id IS NULL AND tag_id IN (SELECT m.tag_id FROM food_tags_map m WHERE m.food_id = ANY($2))) \
+         ORDER BY sort NULLS LAST, tag_id",
+    )
+    .bind(*group_id)
+    .bind(&food_ids)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|t| FoodBundleTag {
+        tag_name: t.tag_name,
+        sort: t.sort,
+        global: t.group_id.is_none(),
+    })
+    .collect();
+    Ok(HttpResponse::Ok().json(&FoodBundle {
+        version: FOOD_BUNDLE_VERSION,
+        exported_at: Utc::now(),
+        source_group_id: Some(*group_id),
+        tags,
+        foods,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/foods/import",
+    tag = "菜品",
+    summary = "导入菜品包（默认仅预演并报告同名、缺失标签等冲突）",
+    description = "预演（dry_run）在事务内完整执行后回滚：报告中的新建标签与导入菜品均未写入，imported 中不返回菜品ID。",
+    params(("group_id" = i64, Path, description = "目标组ID")),
+    request_body = FoodImportInput,
+    responses((status = 200, body = FoodImportReportOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn import_group_foods(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    data: Json<FoodImportInput>,
+) -> Result<impl Responder, CustomError> {
+    let bundle = &data.bundle;
+    if bundle.version > FOOD_BUNDLE_VERSION {
+        return Err(CustomError::BadRequest("不支持的导出包版本".into()));
+    }
+    if bundle.foods.is_empty() {
+        return Err(CustomError::BadRequest("导入包中没有菜品".into()));
+    }
+    if bundle.foods.len() > MAX_IMPORT_FOODS {
+        return Err(CustomError::BadRequest(format!("单次最多导入{}道菜", MAX_IMPORT_FOODS)));
+    }
+    let dry_run = data.dry_run.unwrap_or(true);
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_member(&mut tx, *group_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    let mut ctx = ImportCtx::load(
+        &mut tx,
+        *group_id,
+        token.user_id,
+        data.create_missing_tags.unwrap_or(true),
+        data.on_duplicate.unwrap_or(ImportDuplicateEnum::SKIP),
+        dry_run,
+    )
+    .await?;
+    // 包内声明但未被菜品引用的标签也一并带过来
+    if ctx.create_missing_tags {
+        let declared: Vec<String> = bundle
+            .tags
+            .iter()
+            .filter(|t| !t.global)
+            .map(|t| t.tag_name.clone())
+            .collect();
+        ctx.resolve_tags(&mut tx, None, &declared).await?;
+    }
+    for item in &bundle.foods {
+        ctx.import_item(&mut tx, item, None).await?;
+    }
+    // 预演在同一事务内执行完整流程后回滚，报告与真实导入一致
+    if dry_run {
+        tx.rollback().await.ok();
+    } else {
+        tx.commit().await?;
+    }
+    Ok(HttpResponse::Ok().json(&ctx.report))
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/copy",
+    tag = "菜品",
+    summary = "复制菜谱到我所在的另一个组（含结构化食材/步骤与标签）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodCopyInput,
+    responses((status = 201, body = FoodOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn copy_food(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodCopyInput>,
+) -> Result<impl Responder, CustomError> {
+    let uid = token.user_id;
+    let mut tx = state.db_pool.begin().await?;
+    let source = sqlx::query(
+        "SELECT f.group_id, (f.created_by=$2 OR EXISTS(SELECT 1 FROM association_group_members m \
+         WHERE m.group_id=f.group_id AND m.user_id=$2)) AS visible \
+         FROM foods f WHERE f.food_id=$1 AND f.is_del=0",
+    )
+    .bind(*id)
+    .bind(uid)
+    .fetch_optional(&mut *tx)
+    .await?;
+    match source {
+        Some(r) if !r.get::<bool, _>("visible") => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("无权复制该菜品".into()));
+        }
+        Some(r) if r.get::<Option<i64>, _>("group_id") == Some(data.group_id) => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("菜品已在该组".into()));
+        }
+        Some(_) => {}
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("未找到菜品".into()));
+        }
+    }
+    if let Err(e) = ensure_member(&mut tx, data.group_id, uid).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    let item = bundle_items(&mut tx, &[*id]).await?.pop();
+    let Some(item) = item else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("未找到菜品".into()));
+    };
+    let mut ctx =
+        ImportCtx::load(&mut tx, data.group_id, uid, true, ImportDuplicateEnum::SKIP, false).await?;
+    let rec = match ctx.import_item(&mut tx, &item, data.food_name.as_deref()).await? {
+        Some(rec) => rec,
+        None => {
+            tx.rollback().await.ok();
+            let reason = ctx
+                .report
+                .conflicts
+                .pop()
+                .map(|c| c.detail)
+                .unwrap_or_else(|| "复制失败".into());
+            return Err(CustomError::BadRequest(reason));
+        }
+    };
+    let tag_row: Option<TagRecord> = match rec.tag_id {
+        Some(tid) => sqlx::query_as("SELECT * FROM tags WHERE tag_id=$1")
+            .bind(tid)
+            .fetch_optional(&mut *tx)
+            .await?,
+        None => None,
+    };
+    let food_tags = tags::load_food_tags(&mut tx, &[rec.food_id])
+        .await?
+        .remove(&rec.food_id)
+        .unwrap_or_default();
+    tx.commit().await?;
+    let mut out = FoodOut::from((rec, tag_row, Vec::new()));
+    out.tags = food_tags;
+    Ok(HttpResponse::Created().json(&out))
+}
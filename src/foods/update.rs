@@ -1,6 +1,15 @@
 use crate::{
     errors::CustomError,
-    models::foods::{FoodOut, FoodRecord, FoodUpdateInput, MarkTypeEnum, SubmitRoleEnum, TagRecord},
+    foods::audit::{insert_audit_log, resubmit_submitter_edit, AUDIT_APPROVE, AUDIT_REJECT, AUDIT_SUBMIT},
+    foods::tags::{
+        ensure_tag_manager, ensure_unique_tag_name, is_descendant_or_self, load_tag_tree, lock_tag, move_tag_foods,
+        normalize_tag_name, validate_tag_parent,
+    },
+    models::foods::{
+        ApplyStatusEnum, DietaryLabelEnum, FoodOut, FoodRevisionSourceEnum, FoodRecord, FoodStatusEnum, FoodUpdateInput, MarkTypeEnum,
+        SubmitRoleEnum, TagMergeInput, TagOut, TagRecord, TagReorderInput, TagUpdateInput,
+    },
+    models::photos::PhotoOwnerEnum,
     models::users::UserToken,
     AppState,
 };
@@ -26,11 +35,12 @@ pub async fn update_food(
     id: ntex::web::types::Path<(i64,)>,
     data: Json<FoodUpdateInput>,
 ) -> Result<impl Responder, CustomError> {
+    crate::foods::nutrition::validate_nutrition(data.calories, data.protein, data.fat, data.carbs)?;
     let db = &state.db_pool;
     let mut tx = db.begin().await?;
     // 获取现有记录
     let rec_opt = sqlx::query_as::<_, FoodRecord>(
-		"SELECT food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id FROM foods WHERE food_id=$1 FOR UPDATE"
+		"SELECT food_id, food_name, food_photo, ingredients, steps, food_status, submit_role, apply_status, apply_remark, created_by, owner_user_id, group_id, approved_at, approved_by, is_del, created_at, updated_at, tag_id, calories, protein, fat, carbs, dietary_labels FROM foods WHERE food_id=$1 FOR UPDATE"
 	)
 	.bind(id.0)
 	.fetch_optional(&mut *tx)
@@ -81,6 +91,18 @@ pub async fn update_food(
         return Err(CustomError::BadRequest("仅接单角色可审核".into()));
     }
 
+    let prev_apply_status = rec.apply_status;
+    let content_changed = data.food_name.is_some()
+        || data.food_photo.is_some()
+        || data.ingredients.is_some()
+        || data.steps.is_some()
+        || data.tag_id.is_some()
+        || data.tag_ids.is_some();
+
+    // 文本食材/步骤实际变化时，结构化食材行/步骤随之失效
+    let ingredients_text_changed = data.ingredients.as_ref().is_some_and(|i| rec.ingredients.as_ref() != Some(i));
+    let steps_text_changed = data.steps.as_ref().is_some_and(|s| rec.steps.as_ref() != Some(s));
+
     // 应用更新字段
     if let Some(name) = &data.food_name {
         rec.food_name = name.clone();
@@ -103,13 +125,65 @@ pub async fn update_food(
     if let Some(app_status) = data.apply_status {
         rec.apply_status = app_status;
     }
+    // 营养与饮食标签（不触发重新审核）
+    if data.calories.is_some() {
+        rec.calories = data.calories;
+    }
+    if data.protein.is_some() {
+        rec.protein = data.protein;
+    }
+    if data.fat.is_some() {
+        rec.fat = data.fat;
+    }
+    if data.carbs.is_some() {
+        rec.carbs = data.carbs;
+    }
+    if let Some(labels) = &data.dietary_labels {
+        rec.dietary_labels = DietaryLabelEnum::to_db(labels);
+    }
 
-    if let Some(tid) = data.tag_id {
-        rec.tag_id = Some(tid);
+    // 标签变更（tag_ids 优先，旧字段 tag_id 视为单标签）
+    if let Some(tag_ids) = crate::foods::tags::requested_tag_ids(data.tag_ids.as_ref(), data.tag_id) {
+        rec.tag_id = crate::foods::tags::replace_food_tags(&mut tx, rec.food_id, rec.group_id, &tag_ids).await?;
     }
 
+    // 审核状态变化同步写入审核历史；提交人修改申请内容（含已通过的菜品）在写入后按重新提交处理，
+    // 待审核期间拒绝可回退到最近一次通过的版本
+    let audit_action = if rec.apply_status != prev_apply_status {
+        match rec.apply_status {
+            ApplyStatusEnum::APPROVED => {
+                rec.approved_at = Some(chrono::Utc::now());
+                rec.approved_by = Some(uid);
+                Some(AUDIT_APPROVE)
+            }
+            ApplyStatusEnum::REJECTED => {
+                rec.approved_at = None;
+                rec.approved_by = None;
+                Some(AUDIT_REJECT)
+            }
+            ApplyStatusEnum::PENDING => Some(AUDIT_SUBMIT),
+        }
+    } else {
+        None
+    };
+    let mut audit_log_id = match audit_action {
+        Some(action) => Some(
+            insert_audit_log(
+                &mut tx,
+                rec.food_id,
+                action,
+                Some(prev_apply_status),
+                rec.apply_status,
+                uid,
+                data.apply_remark.as_deref(),
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
     sqlx::query(
-		"UPDATE foods SET food_name=$2, food_photo=$3, ingredients=$4, steps=$5, apply_remark=$6, food_status=$7, apply_status=$8, tag_id=$9, updated_at=NOW() WHERE food_id=$1"
+		"UPDATE foods SET food_name=$2, food_photo=$3, ingredients=$4, steps=$5, apply_remark=$6, food_status=$7, apply_status=$8, tag_id=$9, approved_at=$10, approved_by=$11, calories=$12, protein=$13, fat=$14, carbs=$15, dietary_labels=$16, updated_at=NOW() WHERE food_id=$1"
 	)
 	.bind(rec.food_id)
 	.bind(&rec.food_name)
@@ -120,9 +194,40 @@ pub async fn update_food(
 	.bind(rec.food_status)
 	.bind(rec.apply_status)
     .bind(rec.tag_id)
+    .bind(rec.approved_at)
+    .bind(rec.approved_by)
+    .bind(rec.calories)
+    .bind(rec.protein)
+    .bind(rec.fat)
+    .bind(rec.carbs)
+    .bind(&rec.dietary_labels)
 	.execute(&mut *tx)
 	.await?;
 
+    if ingredients_text_changed {
+        crate::foods::recipe::clear_ingredients(&mut tx, rec.food_id).await?;
+    }
+    if steps_text_changed {
+        crate::foods::recipe::clear_steps(&mut tx, rec.food_id).await?;
+    }
+
+    // 新图片作为图集封面（旧客户端仍只传 food_photo）
+    if data.food_photo.is_some() {
+        crate::photos::adopt_legacy_cover(&mut tx, PhotoOwnerEnum::FOOD, rec.food_id, rec.food_photo.as_deref(), Some(uid))
+            .await?;
+    }
+
+    if audit_log_id.is_none() && content_changed {
+        audit_log_id = resubmit_submitter_edit(&mut tx, rec.food_id, uid, data.apply_remark.as_deref()).await?;
+        if audit_log_id.is_some() {
+            rec.apply_status = ApplyStatusEnum::PENDING;
+            rec.food_status = FoodStatusEnum::AUDITING;
+        }
+    }
+
+    // 修订历史（内容/状态未变化时不记录）
+    crate::foods::revisions::record_revision(&mut tx, rec.food_id, FoodRevisionSourceEnum::UPDATE, uid, audit_log_id).await?;
+
     let tag_row: Option<TagRecord> = if let Some(tid) = rec.tag_id {
         sqlx::query_as("SELECT * FROM tags WHERE tag_id=$1")
             .bind(tid)
@@ -149,9 +254,15 @@ pub async fn update_food(
             _ => None,
         })
         .collect::<Vec<_>>();
+    let tags = crate::foods::tags::load_food_tags(&mut tx, &[rec.food_id])
+        .await?
+        .remove(&rec.food_id)
+        .unwrap_or_default();
     tx.commit().await?;
 
-    Ok(HttpResponse::Ok().json(&FoodOut::from((rec, tag_row, mark_enums))))
+    let mut out = FoodOut::from((rec, tag_row, mark_enums));
+    out.tags = tags;
+    Ok(HttpResponse::Ok().json(&out))
 }
 
 use crate::models::foods::FoodMarkActionInput;
@@ -204,3 +315,192 @@ pub async fn unmark_food(
     .await?;
     Ok(HttpResponse::Ok().body("ok"))
 }
+
+// 校验标签修改：权限、重名与父标签；返回标签所属组
+async fn check_tag_update(
+    conn: &mut sqlx::PgConnection,
+    tag_id: i64,
+    user_id: i64,
+    tag_name: Option<&str>,
+    parent_id: Option<i64>,
+) -> Result<Option<i64>, CustomError> {
+    let (group_id, _) = lock_tag(conn, tag_id).await?;
+    ensure_tag_manager(conn, group_id, user_id).await?;
+    if let Some(name) = tag_name {
+        ensure_unique_tag_name(conn, name, group_id, Some(tag_id)).await?;
+    }
+    if let Some(pid) = parent_id {
+        validate_tag_parent(conn, Some(tag_id), group_id, pid).await?;
+    }
+    Ok(group_id)
+}
+
+#[utoipa::path(
+	put,
+	path = "/food_tags/{id}",
+	tag = "菜品",
+	summary = "重命名、调整排序或移动标签",
+	request_body = TagUpdateInput,
+	params(("id" = i64, Path, description = "标签ID")),
+	responses((status = 200, body = Vec<TagOut>)),
+	security(("cookie_auth" = []))
+)]
+pub async fn update_tag(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: ntex::web::types::Path<(i64,)>,
+    data: Json<TagUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let tag_id = id.0;
+    let clear_parent = data.clear_parent.unwrap_or(false);
+    if clear_parent && data.parent_id.is_some() {
+        return Err(CustomError::BadRequest("parent_id 与 clear_parent 不能同时设置".into()));
+    }
+    let tag_name = data.tag_name.as_deref().map(normalize_tag_name).transpose()?;
+    let mut tx = state.db_pool.begin().await?;
+    let group_id = match check_tag_update(&mut tx, tag_id, token.user_id, tag_name.as_deref(), data.parent_id).await {
+        Ok(g) => g,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    sqlx::query(
+        "UPDATE tags SET tag_name=COALESCE($2, tag_name), sort=COALESCE($3, sort), \
+         parent_id=CASE WHEN $4 THEN NULL ELSE COALESCE($5, parent_id) END WHERE tag_id=$1",
+    )
+    .bind(tag_id)
+    .bind(tag_name)
+    .bind(data.sort)
+    .bind(clear_parent)
+    .bind(data.parent_id)
+    .execute(&mut *tx)
+    .await?;
+    let tags = load_tag_tree(&mut tx, group_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&tags))
+}
+
+// 锁定待排序标签，须全部存在且同组；返回所属组
+async fn check_tag_reorder(conn: &mut sqlx::PgConnection, tag_ids: &[i64], user_id: i64) -> Result<Option<i64>, CustomError> {
+    let groups: Vec<Option<i64>> = sqlx::query_scalar("SELECT group_id FROM tags WHERE tag_id = ANY($1) FOR UPDATE")
+        .bind(tag_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+    if groups.len() != tag_ids.len() {
+        return Err(CustomError::BadRequest("标签不存在".into()));
+    }
+    let group_id = groups[0];
+    if groups.iter().any(|g| *g != group_id) {
+        return Err(CustomError::BadRequest("只能调整同组标签的顺序".into()));
+    }
+    ensure_tag_manager(conn, group_id, user_id).await?;
+    Ok(group_id)
+}
+
+#[utoipa::path(
+	put,
+	path = "/food_tags/order",
+	tag = "菜品",
+	summary = "批量调整标签顺序",
+	description = "按传入顺序重写 sort（越靠前越大）；未传入的标签保持原值。可传入整棵树的先序列表，同级相对顺序即为传入顺序。",
+	request_body = TagReorderInput,
+	responses((status = 200, body = Vec<TagOut>)),
+	security(("cookie_auth" = []))
+)]
+pub async fn reorder_tags(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<TagReorderInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.tag_ids.is_empty() {
+        return Err(CustomError::BadRequest("请提供标签ID".into()));
+    }
+    let mut seen = std::collections::HashSet::new();
+    if !data.tag_ids.iter().all(|id| seen.insert(*id)) {
+        return Err(CustomError::BadRequest("标签ID不能重复".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    let group_id = match check_tag_reorder(&mut tx, &data.tag_ids, token.user_id).await {
+        Ok(g) => g,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let n = data.tag_ids.len();
+    for (i, tid) in data.tag_ids.iter().enumerate() {
+        sqlx::query("UPDATE tags SET sort=$2 WHERE tag_id=$1")
+            .bind(*tid)
+            .bind((n - i) as i32)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let tags = load_tag_tree(&mut tx, group_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&tags))
+}
+
+// 校验合并：同组、有权限、目标不是源标签的子孙，源标签的子标签移到目标下不超层级；返回所属组
+async fn check_tag_merge(conn: &mut sqlx::PgConnection, source: i64, target: i64, user_id: i64) -> Result<Option<i64>, CustomError> {
+    if source == target {
+        return Err(CustomError::BadRequest("不能合并到自身".into()));
+    }
+    let (group_id, _) = lock_tag(conn, source).await?;
+    let (target_group, _) = lock_tag(conn, target).await?;
+    if target_group != group_id {
+        return Err(CustomError::BadRequest("只能合并同组标签".into()));
+    }
+    ensure_tag_manager(conn, group_id, user_id).await?;
+    if is_descendant_or_self(conn, source, target).await? {
+        return Err(CustomError::BadRequest("不能合并到其子标签".into()));
+    }
+    let children: Vec<i64> = sqlx::query_scalar("SELECT tag_id FROM tags WHERE parent_id=$1")
+        .bind(source)
+        .fetch_all(&mut *conn)
+        .await?;
+    for child in children {
+        validate_tag_parent(conn, Some(child), group_id, target).await?;
+    }
+    Ok(group_id)
+}
+
+#[utoipa::path(
+	post,
+	path = "/food_tags/{id}/merge",
+	tag = "菜品",
+	summary = "合并标签：菜品与子标签移到目标标签后删除源标签",
+	request_body = TagMergeInput,
+	params(("id" = i64, Path, description = "源标签ID")),
+	responses((status = 200, body = Vec<TagOut>)),
+	security(("cookie_auth" = []))
+)]
+pub async fn merge_tag(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: ntex::web::types::Path<(i64,)>,
+    data: Json<TagMergeInput>,
+) -> Result<impl Responder, CustomError> {
+    let (source, target) = (id.0, data.target_tag_id);
+    let mut tx = state.db_pool.begin().await?;
+    let group_id = match check_tag_merge(&mut tx, source, target, token.user_id).await {
+        Ok(g) => g,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    move_tag_foods(&mut tx, source, Some(target), token.user_id).await?;
+    sqlx::query("UPDATE tags SET parent_id=$2 WHERE parent_id=$1")
+        .bind(source)
+        .bind(target)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM tags WHERE tag_id=$1")
+        .bind(source)
+        .execute(&mut *tx)
+        .await?;
+    let tags = load_tag_tree(&mut tx, group_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&tags))
+}
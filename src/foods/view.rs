@@ -1,9 +1,14 @@
 use crate::{
     errors::CustomError,
     models::foods::{
-        BlindBoxDrawInput, BlindBoxDrawResultOut, BlindBoxFoodSnapshot, FoodCategory,
-        FoodFilterQuery, FoodOut, FoodTagOut, FoodWithStatsRecord, MarkTypeEnum, TagRecord,
+        DietaryLabelEnum, FoodCategory, FoodFilterQuery, FoodOut, FoodSortEnum, FoodWithStatsRecord, MarkTypeEnum,
+        TagMatchEnum, TagOut, TagRecord,
     },
+    foods::{
+        nutrition::parse_labels,
+        tags::{load_food_tags, load_tag_tree},
+    },
+    models::photos::PhotoOwnerEnum,
     models::users::UserToken,
     AppState,
 };
@@ -23,7 +28,14 @@ use std::sync::Arc;
 		("food_status"=Option<String>, Query),
 		("apply_status"=Option<String>, Query),
 		("tag_id"=Option<i64>, Query, description="标签ID"),
-		("group_id"=Option<i64>, Query)
+		("tag_ids"=Option<String>, Query, description="多个标签ID，逗号分隔"),
+		("tag_match"=Option<TagMatchEnum>, Query, description="ANY 命中任一（默认）/ ALL 全部命中"),
+		("group_id"=Option<i64>, Query),
+		("labels"=Option<String>, Query, description="需同时具备的饮食标签，逗号分隔"),
+		("exclude_labels"=Option<String>, Query, description="排除含任一标签的菜品，逗号分隔"),
+		("max_calories"=Option<i32>, Query, description="每份热量上限 kcal"),
+		("min_protein"=Option<f64>, Query, description="每份蛋白质下限 g"),
+		("sort"=Option<FoodSortEnum>, Query, description="CREATED_DESC 最新（默认）/ RATING_DESC 评价平均分")
 	),
 	responses((status = 200, body = Vec<FoodOut>)),
     security(("cookie_auth"=[]))
@@ -36,7 +48,7 @@ pub async fn get_foods(
     let db = &state.db_pool;
 
     let mut qb: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(
-        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id WHERE f.is_del=0"
+        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, f.calories, f.protein, f.fat, f.carbs, f.dietary_labels, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time, fs.review_count, fs.review_star_sum FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id WHERE f.is_del=0"
     );
     if let Some(kw) = &q.keyword {
         qb.push(" AND f.food_name ILIKE '%' || ")
@@ -57,13 +69,71 @@ pub async fn get_foods(
     } else if let Some(user) = q.created_by {
         qb.push(" AND f.created_by = ").push_bind(user);
     }
-    if let Some(tag_id) = q.tag_id {
-        qb.push(" AND f.tag_id = ").push_bind(tag_id);
+    // 标签筛选：tag_ids（逗号分隔）优先，ANY 命中任一 / ALL 全部命中；兼容单个 tag_id。
+    // 父标签同时命中其子孙标签（筛选“中餐”包含“川菜”）
+    let filter_tags: Vec<i64> = match q.tag_ids.as_deref() {
+        Some(raw) => raw
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<i64>().map_err(|_| CustomError::BadRequest("无效的标签ID".into())))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => q.tag_id.into_iter().collect(),
+    };
+    if !filter_tags.is_empty() {
+        let mut filter_tags = filter_tags;
+        filter_tags.sort_unstable();
+        filter_tags.dedup();
+        match q.tag_match.unwrap_or(TagMatchEnum::ANY) {
+            TagMatchEnum::ANY => {
+                qb.push(" AND EXISTS(SELECT 1 FROM food_tags_map m WHERE m.food_id=f.food_id AND m.tag_id IN (")
+                    .push("WITH RECURSIVE sub AS (SELECT tag_id FROM tags WHERE tag_id = ANY(")
+                    .push_bind(filter_tags)
+                    .push(") UNION SELECT t.tag_id FROM tags t JOIN sub ON t.parent_id=sub.tag_id) SELECT tag_id FROM sub))");
+            }
+            TagMatchEnum::ALL => {
+                for tid in filter_tags {
+                    qb.push(" AND EXISTS(SELECT 1 FROM food_tags_map m WHERE m.food_id=f.food_id AND m.tag_id IN (")
+                        .push("WITH RECURSIVE sub AS (SELECT ")
+                        .push_bind(tid)
+                        .push("::bigint AS tag_id UNION SELECT t.tag_id FROM tags t JOIN sub ON t.parent_id=sub.tag_id) SELECT tag_id FROM sub))");
+                }
+            }
+        }
     }
     if q.only_active.unwrap_or(false) {
         qb.push(" AND f.food_status='NORMAL' AND f.apply_status='APPROVED'");
     }
-    qb.push(" ORDER BY f.created_at DESC LIMIT 100");
+    // 饮食标签与营养筛选
+    if let Some(raw) = q.labels.as_deref() {
+        let labels = parse_labels(raw)?;
+        if !labels.is_empty() {
+            qb.push(" AND f.dietary_labels @> ")
+                .push_bind(DietaryLabelEnum::to_db(&labels))
+                .push("::text[]");
+        }
+    }
+    if let Some(raw) = q.exclude_labels.as_deref() {
+        let labels = parse_labels(raw)?;
+        if !labels.is_empty() {
+            qb.push(" AND NOT (f.dietary_labels && ")
+                .push_bind(DietaryLabelEnum::to_db(&labels))
+                .push("::text[])");
+        }
+    }
+    if let Some(max) = q.max_calories {
+        qb.push(" AND f.calories <= ").push_bind(max);
+    }
+    if let Some(min) = q.min_protein {
+        qb.push(" AND f.protein >= ").push_bind(min);
+    }
+    match q.sort.unwrap_or(FoodSortEnum::CREATED_DESC) {
+        FoodSortEnum::CREATED_DESC => qb.push(" ORDER BY f.created_at DESC LIMIT 100"),
+        FoodSortEnum::RATING_DESC => qb.push(
+            " ORDER BY fs.review_star_sum::float8 / NULLIF(fs.review_count, 0) DESC NULLS LAST, \
+             fs.review_count DESC NULLS LAST, f.created_at DESC LIMIT 100",
+        ),
+    };
     let rows: Vec<FoodWithStatsRecord> = qb.build_query_as().fetch_all(db).await?;
 
     // ===== 批量标签查询 =====
@@ -104,12 +174,22 @@ pub async fn get_foods(
         }
     }
 
+    // ===== 批量多标签查询 =====
+    let mut food_tags = {
+        let food_ids: Vec<i64> = rows.iter().map(|r| r.food_id).collect();
+        let mut conn = db.acquire().await?;
+        load_food_tags(&mut conn, &food_ids).await?
+    };
+
     // ===== 组装输出 =====
     let mut out_list: Vec<FoodOut> = Vec::with_capacity(rows.len());
     for rec in rows {
         let tag = rec.tag_id.and_then(|tid| tags_map.get(&tid).cloned());
         let mark_vec = marks_map.remove(&rec.food_id).unwrap_or_default();
-        out_list.push(FoodOut::from_with_stats(rec, tag, mark_vec));
+        let food_tags = food_tags.remove(&rec.food_id).unwrap_or_default();
+        let mut out = FoodOut::from_with_stats(rec, tag, mark_vec);
+        out.tags = food_tags;
+        out_list.push(out);
     }
     Ok(HttpResponse::Ok().json(&out_list))
 }
@@ -128,7 +208,7 @@ pub async fn get_food_detail(
 ) -> Result<impl Responder, CustomError> {
     let db = &state.db_pool;
     let rec_opt = sqlx::query_as::<_, FoodWithStatsRecord>(
-        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id WHERE f.food_id=$1"
+        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, f.calories, f.protein, f.fat, f.carbs, f.dietary_labels, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time, fs.review_count, fs.review_star_sum FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id WHERE f.food_id=$1"
     )
 	.bind(id.0)
 	.fetch_optional(db)
@@ -165,17 +245,27 @@ pub async fn get_food_detail(
             _ => None,
         })
         .collect();
-    Ok(HttpResponse::Ok().json(&FoodOut::from_with_stats(rec, tag_row, mark_enums)))
+    let mut out = FoodOut::from_with_stats(rec, tag_row, mark_enums);
+    let mut conn = db.acquire().await?;
+    out.tags = load_food_tags(&mut conn, &[out.food_id])
+        .await?
+        .remove(&out.food_id)
+        .unwrap_or_default();
+    out.ingredient_items = crate::foods::recipe::load_ingredients(&mut conn, out.food_id).await?;
+    out.step_items = crate::foods::recipe::load_steps(&mut conn, out.food_id).await?;
+    out.photos = crate::photos::load_photos(&mut conn, PhotoOwnerEnum::FOOD, out.food_id).await?;
+    Ok(HttpResponse::Ok().json(&out))
 }
 
 #[utoipa::path(
 	get,
 	path = "/food_tags",
 	tag = "菜品",
+    summary = "标签列表（按层级先序排列，含菜品使用数）",
     params(
         ("group_id"=Option<i64>, Query)
     ),
-	responses((status = 200, body = Vec<FoodTagOut>)),
+	responses((status = 200, body = Vec<TagOut>)),
     security(("cookie_auth" = []))
 )]
 pub async fn get_tags(
@@ -183,26 +273,10 @@ pub async fn get_tags(
     token: UserToken,
     q: Query<FoodFilterQuery>,
 ) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
-    let mut qb = QueryBuilder::new("SELECT tag_id, tag_name, group_id, sort, created_at FROM tags WHERE 1=1");
-    
+    let mut conn = state.db_pool.acquire().await?;
     let gid = q.group_id.or(token.user.as_ref().and_then(|u| u.group_id));
-    if let Some(g) = gid {
-        qb.push(" AND group_id = ").push_bind(g);
-    }
-    
-    qb.push(" ORDER BY sort NULLS LAST, tag_id");
-
-    let rows: Vec<TagRecord> = qb.build_query_as().fetch_all(db).await?;
-    Ok(HttpResponse::Ok().json(
-        &rows
-            .into_iter()
-            .map(|r| FoodTagOut {
-                tag_id: r.tag_id,
-                tag_name: r.tag_name,
-            })
-            .collect::<Vec<_>>(),
-    ))
+    let tags = load_tag_tree(&mut conn, gid).await?;
+    Ok(HttpResponse::Ok().json(&tags))
 }
 
 #[utoipa::path(
@@ -218,12 +292,17 @@ pub async fn get_marked_foods(
 ) -> Result<impl Responder, CustomError> {
     let db = &state.db_pool;
     let rows: Vec<FoodWithStatsRecord> = sqlx::query_as(
-        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time \
+        "SELECT f.food_id, f.food_name, f.food_photo, f.ingredients, f.steps, f.food_status, f.submit_role, f.apply_status, f.apply_remark, f.created_by, f.owner_user_id, f.group_id, f.approved_at, f.approved_by, f.is_del, f.created_at, f.updated_at, f.tag_id, f.calories, f.protein, f.fat, f.carbs, f.dietary_labels, fs.total_order_count, fs.completed_order_count, fs.last_order_time, fs.last_complete_time, fs.review_count, fs.review_star_sum \
          FROM foods f LEFT JOIN food_stats fs ON fs.food_id=f.food_id JOIN user_food_mark m ON f.food_id=m.food_id WHERE m.user_id=$1 AND m.mark_type='LIKE'"
     )
 	.bind(token.user_id as i64)
 	.fetch_all(db)
 	.await?;
+    let mut food_tags = {
+        let food_ids: Vec<i64> = rows.iter().map(|r| r.food_id).collect();
+        let mut conn = db.acquire().await?;
+        load_food_tags(&mut conn, &food_ids).await?
+    };
     let mut out_list = Vec::new();
     for rec in rows {
         let tag_row: Option<TagRecord> = if let Some(tid) = rec.tag_id {
@@ -234,63 +313,10 @@ pub async fn get_marked_foods(
         } else {
             None
         };
-        out_list.push(FoodOut::from_with_stats(
-            rec,
-            tag_row,
-            vec![MarkTypeEnum::LIKE],
-        ));
+        let tags = food_tags.remove(&rec.food_id).unwrap_or_default();
+        let mut out = FoodOut::from_with_stats(rec, tag_row, vec![MarkTypeEnum::LIKE]);
+        out.tags = tags;
+        out_list.push(out);
     }
     Ok(HttpResponse::Ok().json(&out_list))
 }
-
-#[utoipa::path(
-	post,
-	path = "/foods/blind_box/draw",
-	tag = "菜品",
-	request_body = BlindBoxDrawInput,
-	responses((status = 200, body = BlindBoxDrawResultOut)),
-	security(("cookie_auth" = []))
-)]
-pub async fn draw_blind_box(
-    token: UserToken,
-    state: State<Arc<AppState>>,
-    data: ntex::web::types::Json<BlindBoxDrawInput>,
-) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
-    // 获取 group_id，如果为空尝试用户所属第一个 group
-    let group_id = if let Some(gid) = data.group_id {
-        gid
-    } else {
-        sqlx::query_scalar("SELECT group_id FROM association_group_members WHERE user_id=$1 ORDER BY created_at LIMIT 1")
-			.bind(token.user_id as i64)
-			.fetch_optional(db)
-			.await?
-			.unwrap_or(0)
-    };
-    if group_id == 0 {
-        return Err(CustomError::BadRequest("未找到绑定组".into()));
-    }
-    let limit_each = data.limit_each.unwrap_or(1) as i64;
-    let mut results: Vec<BlindBoxFoodSnapshot> = Vec::new();
-    for tag_id in &data.tag_ids {
-        let rows = sqlx::query(
-			"SELECT food_id, food_name, food_photo FROM foods WHERE group_id=$1 AND tag_id=$2 AND food_status='NORMAL' AND apply_status='APPROVED' ORDER BY random() LIMIT $3"
-		)
-		.bind(group_id as i64)
-		.bind(*tag_id)
-		.bind(limit_each)
-		.fetch_all(db)
-		.await?;
-        for r in rows {
-            results.push(BlindBoxFoodSnapshot {
-                food_id: r.get::<i64, _>(0),
-                food_name: r.get::<String, _>(1),
-                food_photo: r.get::<Option<String>, _>(2),
-            });
-        }
-    }
-    Ok(HttpResponse::Ok().json(&BlindBoxDrawResultOut {
-        results,
-        requested_tags: data.tag_ids.clone(),
-    }))
-}
@@ -0,0 +1,209 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::guests::{GuestConvertInput, GuestConvertOut, GuestOut},
+    users::hash_password,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/guests",
+    tag = "访客",
+    summary = "查看组内访客及其订单数",
+    params(("group_id" = i64, Path, description = "组ID")),
+    responses((status = 200, body = [GuestOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_group_guests(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    crate::services::groups::ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let list: Vec<GuestOut> = sqlx::query_as(
+        "SELECT gs.session_id, gs.user_id AS guest_user_id, gs.nick_name, \
+         (SELECT COUNT(*) FROM orders o WHERE o.group_id=gs.group_id AND o.user_id=COALESCE(gs.converted_user_id, gs.user_id)) AS order_count, \
+         gs.expires_at, gs.revoked_at, gs.converted_at, gs.converted_user_id, gs.created_at \
+         FROM guest_sessions gs WHERE gs.group_id=$1 ORDER BY gs.created_at DESC LIMIT 200",
+    )
+    .bind(*group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(HttpResponse::Ok().json(&list))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/groups/{group_id}/guests/{session_id}",
+    tag = "访客",
+    summary = "撤销访客会话（令牌立即失效，仅接单方或管理员）",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("session_id" = i64, Path, description = "访客会话ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn revoke_guest_session(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, session_id) = path.into_inner();
+    let mut conn = state.db_pool.acquire().await?;
+    super::ensure_group_host(&mut conn, group_id, token.user_id).await?;
+    let res = sqlx::query(
+        "UPDATE guest_sessions SET revoked_at=NOW() \
+         WHERE session_id=$1 AND group_id=$2 AND revoked_at IS NULL AND converted_at IS NULL",
+    )
+    .bind(session_id)
+    .bind(group_id)
+    .execute(&mut *conn)
+    .await?;
+    if res.rows_affected() == 0 {
+        return Err(CustomError::BadRequest("访客会话不存在或已失效".into()));
+    }
+    Ok(HttpResponse::Ok().body("revoked"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/guests/{session_id}/convert",
+    tag = "访客",
+    summary = "访客转为组成员（仅接单方或管理员；合并到本人/已接受邀请的账号或升级访客账号）",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("session_id" = i64, Path, description = "访客会话ID")
+    ),
+    request_body = GuestConvertInput,
+    responses((status = 200, body = GuestConvertOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn convert_guest(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<GuestConvertInput>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, session_id) = path.into_inner();
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = super::ensure_group_host(&mut tx, group_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+
+    let session = sqlx::query(
+        "SELECT gs.user_id, u.username FROM guest_sessions gs JOIN users u ON u.user_id=gs.user_id \
+         WHERE gs.session_id=$1 AND gs.group_id=$2 AND gs.converted_at IS NULL FOR UPDATE OF gs",
+    )
+    .bind(session_id)
+    .bind(group_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (guest_user_id, guest_username): (i64, String) = match session {
+        Some(r) => (r.get("user_id"), r.get("username")),
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("访客不存在或已转正".into()));
+        }
+    };
+
+    let (member_id, username, temp_password, moved_orders) = match data.user_id {
+        // 合并：订单迁移到已注册账号，访客影子账号停用
+        Some(target_id) => {
+            // 只能合并到本人，或已接受本人绑定邀请的账号，避免未经同意把他人拉进组
+            if target_id != token.user_id {
+                let consented = sqlx::query_scalar::<_, bool>(
+                    "SELECT EXISTS(SELECT 1 FROM association_group_requests \
+                     WHERE requester_id=$1 AND target_user_id=$2 AND status=1)",
+                )
+                .bind(token.user_id)
+                .bind(target_id)
+                .fetch_one(&mut *tx)
+                .await?;
+                if !consented {
+                    tx.rollback().await.ok();
+                    return Err(CustomError::BadRequest("只能合并到本人或已接受你邀请的账号".into()));
+                }
+            }
+            let target = sqlx::query("SELECT username FROM users WHERE user_id=$1 AND status=1 AND is_guest=FALSE")
+                .bind(target_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+            let username: String = match target {
+                Some(r) => r.get("username"),
+                None => {
+                    tx.rollback().await.ok();
+                    return Err(CustomError::BadRequest("目标账号不存在".into()));
+                }
+            };
+            let moved = sqlx::query("UPDATE orders SET user_id=$2, updated_at=NOW() WHERE user_id=$1 AND group_id=$3")
+                .bind(guest_user_id)
+                .bind(target_id)
+                .bind(group_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected() as i64;
+            sqlx::query("UPDATE users SET status=0, updated_at=NOW() WHERE user_id=$1")
+                .bind(guest_user_id)
+                .execute(&mut *tx)
+                .await?;
+            (target_id, username, None, moved)
+        }
+        // 升级：访客账号转为正式账号，生成临时密码，登录后需修改
+        None => {
+            let temp_password = super::generate_temp_password();
+            let (pwd_hash, algo) = hash_password(&temp_password)
+                .map_err(CustomError::InternalError)?;
+            sqlx::query(
+                "UPDATE users SET is_guest=FALSE, password_hash=$2, password_algo=$3, is_temp_password=TRUE, updated_at=NOW() WHERE user_id=$1",
+            )
+            .bind(guest_user_id)
+            .bind(&pwd_hash)
+            .bind(&algo)
+            .execute(&mut *tx)
+            .await?;
+            let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders WHERE user_id=$1 AND group_id=$2")
+                .bind(guest_user_id)
+                .bind(group_id)
+                .fetch_one(&mut *tx)
+                .await?;
+            (guest_user_id, guest_username, Some(temp_password), count)
+        }
+    };
+
+    sqlx::query(
+        "INSERT INTO association_group_members (group_id, user_id, role_in_group, is_primary) \
+         VALUES ($1, $2, 'ORDERING', 0) ON CONFLICT (group_id, user_id) DO NOTHING",
+    )
+    .bind(group_id)
+    .bind(member_id)
+    .execute(&mut *tx)
+    .await?;
+    // 同一访客的所有会话一并失效
+    sqlx::query(
+        "UPDATE guest_sessions SET converted_at=NOW(), converted_user_id=$2 WHERE user_id=$1 AND converted_at IS NULL",
+    )
+    .bind(guest_user_id)
+    .bind(member_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    // 成员所在组变化，清理用户公开信息缓存
+    let _ = state.redis_cache.delete_user(&member_id.to_string()).await;
+    Ok(HttpResponse::Ok().json(&GuestConvertOut {
+        user_id: member_id,
+        username,
+        temp_password,
+        moved_orders,
+    }))
+}
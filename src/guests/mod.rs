@@ -0,0 +1,71 @@
+pub mod session; // 凭邀请码领取访客令牌
+pub mod orders; // 访客下单与查看自己的订单
+pub mod manage; // 组成员管理访客（列表 / 撤销 / 转正）
+
+use crate::{cache::RedisCache, errors::CustomError};
+use ntex::web::HttpRequest;
+use rand::Rng;
+use sqlx::PgConnection;
+
+// 校验当前用户是该组的接单方或管理员（撤销、转正访客需要）
+pub async fn ensure_group_host(
+    conn: &mut PgConnection,
+    group_id: i64,
+    user_id: i64,
+) -> Result<(), CustomError> {
+    let is_host = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM association_group_members WHERE group_id=$1 AND user_id=$2 \
+         AND role_in_group IN ('RECEIVING','ADMIN'))",
+    )
+    .bind(group_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !is_host {
+        return Err(CustomError::BadRequest("仅组内接单方或管理员可管理访客".into()));
+    }
+    Ok(())
+}
+
+// 客户端 IP：优先取反向代理写入的 X-Real-IP
+pub fn client_ip(req: &HttpRequest) -> String {
+    req.headers()
+        .get("X-Real-IP")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .or_else(|| req.peer_addr().map(|a| a.ip().to_string()))
+        .unwrap_or_else(|| "unknown".into())
+}
+
+// 固定窗口限流；Redis 不可用时放行，避免影响正常领取
+pub async fn check_rate_limit(
+    cache: &RedisCache,
+    key: &str,
+    limit: i64,
+    window_seconds: usize,
+) -> Result<(), CustomError> {
+    match cache.incr_ex(key, window_seconds).await {
+        Ok(count) if count > limit => Err(CustomError::TooManyRequests("操作过于频繁，请稍后再试".into())),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::warn!("guest rate limit redis error: {}", e);
+            Ok(())
+        }
+    }
+}
+
+fn random_string(charset: &[u8], len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| charset[rng.gen_range(0..charset.len())] as char)
+        .collect()
+}
+
+pub fn generate_guest_username() -> String {
+    format!("guest_{}", random_string(b"abcdefghijkmnpqrstuvwxyz23456789", 10))
+}
+
+pub fn generate_temp_password() -> String {
+    random_string(b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnpqrstuvwxyz23456789", 10)
+}
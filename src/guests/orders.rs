@@ -0,0 +1,109 @@
+use crate::{
+    errors::CustomError,
+    models::guests::{GuestOrderCreateInput, GuestToken},
+    models::orders::{OrderCreateInput, OrderOutNew, OrderRecord},
+    orders::{
+        new::{insert_order, spawn_order_push},
+        view::build_order_out,
+    },
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use std::sync::Arc;
+
+const ORDER_COLUMNS: &str = "order_id, user_id, receiver_id, group_id, status, goal_time, points_cost, points_reward, cancel_reason, reject_reason, last_status_change_at, created_at, updated_at, TRUE AS is_guest";
+
+#[utoipa::path(
+    post,
+    path = "/guest/orders",
+    tag = "访客",
+    summary = "访客在所属组内下单",
+    request_body = GuestOrderCreateInput,
+    responses((status = 201, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_guest_order(
+    guest: GuestToken,
+    state: State<Arc<AppState>>,
+    data: Json<GuestOrderCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    // 组与邀请码均取自访客会话，访客不能指定其它组，也不能设置积分
+    let input = OrderCreateInput {
+        group_id: Some(guest.group_id),
+        invite_code: Some(guest.invite_code.clone()),
+        goal_time: data.goal_time,
+        items: data.items.clone(),
+        points_cost: None,
+        points_reward: None,
+    };
+    let mut tx = state.db_pool.begin().await?;
+    let mut out = insert_order(&mut tx, guest.user_id, &input).await?;
+    tx.commit().await?;
+    out.is_guest = true;
+
+    spawn_order_push(state.db_pool.clone(), out.order_id, "guest order create");
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    get,
+    path = "/guest/orders",
+    tag = "访客",
+    summary = "访客查看自己的订单",
+    responses((status = 200, body = [OrderOutNew])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_guest_orders(
+    guest: GuestToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let orders: Vec<OrderRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM orders WHERE user_id=$1 AND group_id=$2 ORDER BY created_at DESC LIMIT 50",
+        ORDER_COLUMNS
+    ))
+    .bind(guest.user_id)
+    .bind(guest.group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut out_list: Vec<OrderOutNew> = Vec::with_capacity(orders.len());
+    for order in orders {
+        out_list.push(build_order_out(&mut conn, order).await?);
+    }
+    Ok(HttpResponse::Ok().json(&out_list))
+}
+
+#[utoipa::path(
+    get,
+    path = "/guest/orders/{id}",
+    tag = "访客",
+    summary = "访客查看自己的订单详情",
+    params(("id" = i64, Path, description = "订单ID")),
+    responses((status = 200, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_guest_order(
+    guest: GuestToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let order: Option<OrderRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM orders WHERE order_id=$1 AND user_id=$2 AND group_id=$3",
+        ORDER_COLUMNS
+    ))
+    .bind(*id)
+    .bind(guest.user_id)
+    .bind(guest.group_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let order = match order {
+        Some(o) => o,
+        None => return Err(CustomError::BadRequest("订单不存在".into())),
+    };
+    let out = build_order_out(&mut conn, order).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
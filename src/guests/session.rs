@@ -0,0 +1,119 @@
+use crate::{
+    errors::CustomError,
+    models::guests::{GuestConfig, GuestSessionCreateInput, GuestSessionOut, GuestTokenClaims, GUEST_SCOPE},
+    utils::TOKEN_SECRET_KEY,
+    AppState,
+};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use ntex::web::{
+    types::{Json, State},
+    HttpRequest, HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+#[utoipa::path(
+    post,
+    path = "/guest/sessions",
+    tag = "访客",
+    summary = "凭邀请码领取短期访客令牌",
+    request_body = GuestSessionCreateInput,
+    responses(
+        (status = 201, body = GuestSessionOut),
+        (status = 400, body = CustomError),
+        (status = 429, body = CustomError)
+    )
+)]
+pub async fn create_guest_session(
+    state: State<Arc<AppState>>,
+    req: HttpRequest,
+    data: Json<GuestSessionCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    let nick_name = data.nick_name.trim().to_string();
+    if nick_name.is_empty() || nick_name.chars().count() > 32 {
+        return Err(CustomError::BadRequest("昵称长度需在1-32个字符之间".into()));
+    }
+    let invite_code = data.invite_code.trim().to_string();
+    if invite_code.is_empty() {
+        return Err(CustomError::BadRequest("缺少邀请码".into()));
+    }
+
+    // 按 IP 与邀请码双重限流，防止刷影子账号或暴力猜邀请码
+    let config = GuestConfig::from_env();
+    super::check_rate_limit(
+        &state.redis_cache,
+        &format!("guest_session_rate:ip:{}", super::client_ip(&req)),
+        config.rate_limit_per_ip,
+        config.rate_window_seconds,
+    )
+    .await?;
+    super::check_rate_limit(
+        &state.redis_cache,
+        &format!("guest_session_rate:code:{}", invite_code),
+        config.rate_limit_per_code,
+        config.rate_window_seconds,
+    )
+    .await?;
+
+    let db = &state.db_pool;
+    let group = sqlx::query(
+        "SELECT group_id, group_name FROM association_groups WHERE invite_code=$1 AND status=1",
+    )
+    .bind(&invite_code)
+    .fetch_optional(db)
+    .await?;
+    let (group_id, group_name): (i64, Option<String>) = match group {
+        Some(r) => (r.get("group_id"), r.try_get("group_name").ok()),
+        None => return Err(CustomError::BadRequest("邀请码无效".into())),
+    };
+
+    let expires_at = Utc::now() + Duration::minutes(config.ttl_minutes);
+
+    // 访客以影子账号落库（无密码，不可登录），订单沿用 orders.user_id
+    let mut tx = db.begin().await?;
+    let guest_user_id: i64 = sqlx::query_scalar(
+        "INSERT INTO users (username, nick_name, role, love_point, status, is_guest) \
+         VALUES ($1, $2, 'ORDERING', 0, 1, TRUE) RETURNING user_id",
+    )
+    .bind(super::generate_guest_username())
+    .bind(&nick_name)
+    .fetch_one(&mut *tx)
+    .await?;
+    let session_id: i64 = sqlx::query_scalar(
+        "INSERT INTO guest_sessions (group_id, user_id, nick_name, invite_code, expires_at) \
+         VALUES ($1,$2,$3,$4,$5) RETURNING session_id",
+    )
+    .bind(group_id)
+    .bind(guest_user_id)
+    .bind(&nick_name)
+    .bind(&invite_code)
+    .bind(expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let claims = GuestTokenClaims {
+        exp: expires_at.timestamp(),
+        user_id: guest_user_id,
+        scope: GUEST_SCOPE.to_string(),
+        group_id,
+        session_id,
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(TOKEN_SECRET_KEY),
+    )
+    .map_err(|e| CustomError::InternalError(e.to_string()))?;
+
+    Ok(HttpResponse::Created().json(&GuestSessionOut {
+        token,
+        session_id,
+        guest_user_id,
+        group_id,
+        group_name,
+        nick_name,
+        expires_at,
+    }))
+}
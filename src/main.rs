@@ -16,6 +16,12 @@ mod upload;
 mod services; // 新增服务模块用于通知推送
 mod wishes; // 心愿与兑换模块
 mod dashboard; // 看板与组活动
+mod carts; // 购物车
+mod guests; // 访客会话（邀请码临时身份）
+mod meal_plans; // 组内每周菜单计划
+mod pantry; // 组内食材库存
+mod photos; // 菜品/打卡/订单图集
+mod reviews; // 菜品评价
 
 use cache::RedisCache;
 use dotenvy::dotenv;
@@ -50,6 +56,10 @@ async fn main() -> Result<(), CustomError> {
 
     let db_url = env::var("DATABASE_URL").expect("Please set DATABASE_URL");
     let redis_url = env::var("REDIS_URL").expect("Please set REDIS_URL");
+    // 图片地址（评论、菜谱步骤、图集、评价）只接受七牛上传域名
+    if upload::upload::upload_domains().is_empty() {
+        panic!("Please set UPLOAD_DOMAIN");
+    }
 
     // Tencent Cloud IM config (optional)
     // If not provided, IM endpoints will return a clear error.
@@ -78,6 +88,13 @@ async fn main() -> Result<(), CustomError> {
     });
     let app_state_clone = Arc::clone(&app_state);
 
+    // 命令行：重建菜品统计后退出（cargo run -- --rebuild-food-stats）
+    if env::args().any(|a| a == "--rebuild-food-stats") {
+        let foods = services::food_stats::rebuild_all(&app_state.db_pool).await?;
+        println!("food_stats rebuilt: {} foods", foods);
+        return Ok(());
+    }
+
     let allowed_origin = env::var("FRONTEND_ORIGIN").unwrap_or_else(|_| "*".to_string());
 
     let server = HttpServer::new(move || {
@@ -111,13 +128,19 @@ async fn main() -> Result<(), CustomError> {
     .run();
 
     // 启动订单过期后台任务（不阻塞主服务器运行）
-    let expiration_handle = tokio::spawn(orders::expiration::run_expiration_worker(app_state_clone));
+    let expiration_handle = tokio::spawn(orders::expiration::run_expiration_worker(Arc::clone(&app_state_clone)));
+    // 启动周期订单生成任务
+    let recurring_handle = tokio::spawn(orders::recurring::run_recurring_worker(Arc::clone(&app_state_clone)));
+    // 启动 goal_time 提醒/超时任务
+    let goal_time_handle = tokio::spawn(orders::reminder::run_goal_time_worker(app_state_clone));
 
     // 运行 HTTP 服务器（阻塞直到停止）
     server.await?;
 
     // 等待后台任务结束（正常情况下不会返回，除非出现 panic 或关闭）
     let _ = expiration_handle.await;
+    let _ = recurring_handle.await;
+    let _ = goal_time_handle.await;
 
     Ok(())
 }
@@ -0,0 +1,344 @@
+use crate::{
+    errors::CustomError,
+    foods::blind_box::{draw_weight, weighted_pick, Candidate},
+    foods::recommend::meal_for_hour,
+    meal_plans::{load_entry, load_plan, meal_code, plan_range, DEFAULT_MEALS},
+    models::foods::FoodCategory,
+    models::meal_plans::{MealPlanEntryOut, MealPlanFillInput, MealPlanOut, MealPlanSwapInput},
+    models::users::UserToken,
+    services::groups::ensure_member,
+    AppState,
+};
+use chrono::{Duration, NaiveDate, Utc};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+const MAX_PER_SLOT: usize = 5;
+const HISTORY_DAYS: i64 = 90;
+/// 换菜时避开前后几天内已排的菜
+const SWAP_AVOID_DAYS: i64 = 3;
+
+struct PoolFood {
+    food_id: i64,
+    food_name: String,
+    food_photo: Option<String>,
+    base_weight: f64,
+    // 按餐段（1-5）统计的历史点单次数
+    meal_counts: [i64; 6],
+}
+
+impl PoolFood {
+    // 餐段契合度：该餐段历史占比越高权重越大（0.5 ~ 1.5）；无历史时为 1
+    fn slot_weight(&self, meal: FoodCategory) -> f64 {
+        let total: i64 = self.meal_counts.iter().sum();
+        if total == 0 {
+            return self.base_weight;
+        }
+        let ratio = self.meal_counts[meal as usize] as f64 / total as f64;
+        self.base_weight * (0.5 + ratio)
+    }
+
+    fn candidate(&self, meal: FoodCategory) -> Candidate {
+        Candidate {
+            food_id: self.food_id,
+            food_name: self.food_name.clone(),
+            food_photo: self.food_photo.clone(),
+            weight: self.slot_weight(meal),
+        }
+    }
+}
+
+// 组内可下单菜品池：排除任一成员标记为不推荐的菜品；任一成员收藏即加权
+async fn load_pool(conn: &mut PgConnection, group_id: i64, tz_offset_minutes: i32) -> Result<Vec<PoolFood>, CustomError> {
+    let now = Utc::now();
+    let rows = sqlx::query(
+        "SELECT f.food_id, f.food_name, f.food_photo, \
+         EXISTS(SELECT 1 FROM user_food_mark um JOIN association_group_members gm ON gm.user_id=um.user_id AND gm.group_id=$1 \
+                WHERE um.food_id=f.food_id AND um.mark_type='LIKE') AS liked, \
+         (SELECT MAX(COALESCE(o.goal_time, o.created_at)) FROM order_items oi JOIN orders o ON o.order_id=oi.order_id \
+          WHERE oi.food_id=f.food_id AND o.group_id=$1 AND o.status NOT IN ('CANCELLED','REJECTED','EXPIRED','SYSTEM_CLOSED')) AS last_ordered_at \
+         FROM foods f \
+         WHERE f.group_id=$1 AND f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED' \
+         AND NOT EXISTS(SELECT 1 FROM user_food_mark um JOIN association_group_members gm ON gm.user_id=um.user_id AND gm.group_id=$1 \
+                        WHERE um.food_id=f.food_id AND um.mark_type='NOT_RECOMMEND')",
+    )
+    .bind(group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // 近期点单的本地小时分布，用于判断菜品更适合哪个餐段
+    let history = sqlx::query(
+        "SELECT oi.food_id, EXTRACT(HOUR FROM (COALESCE(o.goal_time, o.created_at) AT TIME ZONE 'UTC') \
+            + make_interval(mins => $3))::int AS hour, COUNT(DISTINCT o.order_id)::bigint AS cnt \
+         FROM order_items oi JOIN orders o ON o.order_id=oi.order_id \
+         WHERE o.group_id=$1 AND o.created_at >= $2 \
+         AND o.status NOT IN ('CANCELLED','REJECTED','EXPIRED','SYSTEM_CLOSED') \
+         GROUP BY oi.food_id, hour",
+    )
+    .bind(group_id)
+    .bind(now - Duration::days(HISTORY_DAYS))
+    .bind(tz_offset_minutes)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut meal_counts: HashMap<i64, [i64; 6]> = HashMap::new();
+    for r in history {
+        let hour: i32 = r.get("hour");
+        let meal = meal_for_hour(hour.rem_euclid(24) as u32);
+        meal_counts.entry(r.get("food_id")).or_default()[meal as usize] += r.get::<i64, _>("cnt");
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let food_id: i64 = r.get("food_id");
+            PoolFood {
+                food_id,
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                base_weight: draw_weight(r.get("liked"), r.get("last_ordered_at"), now),
+                meal_counts: meal_counts.get(&food_id).copied().unwrap_or_default(),
+            }
+        })
+        .collect())
+}
+
+// 从菜品池抽取 k 个：优先避开 avoid，若因此不够则放开（仍排除 exclude）
+fn pick_for_slot(pool: &[PoolFood], meal: FoodCategory, k: usize, avoid: &HashSet<i64>, exclude: &HashSet<i64>) -> Vec<Candidate> {
+    let fresh: Vec<Candidate> = pool
+        .iter()
+        .filter(|f| !avoid.contains(&f.food_id) && !exclude.contains(&f.food_id))
+        .map(|f| f.candidate(meal))
+        .collect();
+    if fresh.len() >= k {
+        return weighted_pick(fresh, k);
+    }
+    let mut picked = weighted_pick(fresh, k);
+    let rest: Vec<Candidate> = pool
+        .iter()
+        .filter(|f| !exclude.contains(&f.food_id) && !picked.iter().any(|c| c.food_id == f.food_id))
+        .map(|f| f.candidate(meal))
+        .collect();
+    let need = k - picked.len();
+    picked.extend(weighted_pick(rest, need));
+    picked
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/meal-plan/fill",
+    tag = "菜单计划",
+    summary = "自动排餐：从组内已审核菜品中加权抽取填充日期范围内的餐段",
+    description = "排除任一成员标记为不推荐的菜品，范围内尽量不重复；收藏加权、近期吃过降权，并参考历史点单时段匹配餐段。",
+    params(("group_id" = i64, Path, description = "组ID")),
+    request_body = MealPlanFillInput,
+    responses((status = 200, body = MealPlanOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn fill_meal_plan(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    data: Json<MealPlanFillInput>,
+) -> Result<impl Responder, CustomError> {
+    let group_id = *group_id;
+    let (start, end) = plan_range(data.start_date, data.days, data.tz_offset_minutes)?;
+    let per_slot = data.per_slot.unwrap_or(1);
+    if !(1..=MAX_PER_SLOT).contains(&per_slot) {
+        return Err(CustomError::BadRequest(format!("每个餐段菜品数需在1-{}之间", MAX_PER_SLOT)));
+    }
+    let meals: Vec<FoodCategory> = match &data.meals {
+        Some(m) if !m.is_empty() => {
+            let mut out: Vec<FoodCategory> = Vec::new();
+            for meal in m {
+                if !out.contains(meal) {
+                    out.push(*meal);
+                }
+            }
+            out
+        }
+        _ => DEFAULT_MEALS.to_vec(),
+    };
+    let meal_codes: Vec<i16> = meals.iter().map(|m| meal_code(*m)).collect();
+
+    let mut tx = state.db_pool.begin().await?;
+    ensure_member(&mut tx, group_id, token.user_id).await?;
+    // 串行化同组的自动排餐，避免两人同时填充产生重复
+    sqlx::query("SELECT group_id FROM association_groups WHERE group_id=$1 FOR UPDATE")
+        .bind(group_id)
+        .execute(&mut *tx)
+        .await?;
+    if data.overwrite.unwrap_or(false) {
+        sqlx::query(
+            "DELETE FROM meal_plan_entries WHERE group_id=$1 AND plan_date BETWEEN $2 AND $3 \
+             AND meal = ANY($4) AND order_id IS NULL",
+        )
+        .bind(group_id)
+        .bind(start)
+        .bind(end)
+        .bind(&meal_codes)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let existing = sqlx::query(
+        "SELECT plan_date, meal, food_id FROM meal_plan_entries WHERE group_id=$1 AND plan_date BETWEEN $2 AND $3",
+    )
+    .bind(group_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut used: HashSet<i64> = HashSet::new();
+    let mut cells: HashMap<(NaiveDate, i16), HashSet<i64>> = HashMap::new();
+    for r in existing {
+        let food_id: i64 = r.get("food_id");
+        used.insert(food_id);
+        cells.entry((r.get("plan_date"), r.get("meal"))).or_default().insert(food_id);
+    }
+
+    let pool = load_pool(&mut tx, group_id, data.tz_offset_minutes.unwrap_or(480)).await?;
+    if pool.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("组内暂无可排的菜品".into()));
+    }
+
+    let mut date = start;
+    while date <= end {
+        for meal in &meals {
+            let code = meal_code(*meal);
+            let in_cell = cells.remove(&(date, code)).unwrap_or_default();
+            if in_cell.len() >= per_slot {
+                continue;
+            }
+            let picks = pick_for_slot(&pool, *meal, per_slot - in_cell.len(), &used, &in_cell);
+            for (i, c) in picks.into_iter().enumerate() {
+                sqlx::query(
+                    "INSERT INTO meal_plan_entries (group_id, plan_date, meal, food_id, quantity, sort, created_by, updated_by) \
+                     VALUES ($1,$2,$3,$4,1,$5,$6,$6)",
+                )
+                .bind(group_id)
+                .bind(date)
+                .bind(code)
+                .bind(c.food_id)
+                .bind((in_cell.len() + i) as i32)
+                .bind(token.user_id)
+                .execute(&mut *tx)
+                .await?;
+                used.insert(c.food_id);
+            }
+        }
+        date += Duration::days(1);
+    }
+
+    let out = load_plan(&mut tx, group_id, start, end).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/meal-plan/swap",
+    tag = "菜单计划",
+    summary = "换菜：与另一条目交换日期/餐段，或为该条目重新抽取一道菜",
+    params(("group_id" = i64, Path, description = "组ID")),
+    request_body = MealPlanSwapInput,
+    responses((status = 200, body = [MealPlanEntryOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn swap_meal_plan_entries(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    data: Json<MealPlanSwapInput>,
+) -> Result<impl Responder, CustomError> {
+    let group_id = *group_id;
+    let mut ids = vec![data.entry_id];
+    if let Some(other) = data.with_entry_id {
+        if other == data.entry_id {
+            return Err(CustomError::BadRequest("不能与自身交换".into()));
+        }
+        ids.push(other);
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    ensure_member(&mut tx, group_id, token.user_id).await?;
+    let rows = sqlx::query(
+        "SELECT id, plan_date, meal, food_id, order_id FROM meal_plan_entries \
+         WHERE group_id=$1 AND id = ANY($2) ORDER BY id FOR UPDATE",
+    )
+    .bind(group_id)
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.len() != ids.len() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜单条目不存在".into()));
+    }
+    if rows.iter().any(|r| r.get::<Option<i64>, _>("order_id").is_some()) {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("已下单的条目无法调整".into()));
+    }
+    let by_id: HashMap<i64, (NaiveDate, i16, i64)> = rows
+        .iter()
+        .map(|r| (r.get("id"), (r.get("plan_date"), r.get("meal"), r.get("food_id"))))
+        .collect();
+    let (date, meal, food_id) = by_id[&data.entry_id];
+
+    match data.with_entry_id {
+        Some(other) => {
+            let (other_date, other_meal, _) = by_id[&other];
+            for (id, d, m) in [(data.entry_id, other_date, other_meal), (other, date, meal)] {
+                sqlx::query(
+                    "UPDATE meal_plan_entries SET plan_date=$3, meal=$4, updated_by=$5, updated_at=NOW() WHERE id=$1 AND group_id=$2",
+                )
+                .bind(id)
+                .bind(group_id)
+                .bind(d)
+                .bind(m)
+                .bind(token.user_id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        None => {
+            let nearby: HashSet<i64> = sqlx::query_scalar::<_, i64>(
+                "SELECT food_id FROM meal_plan_entries WHERE group_id=$1 AND plan_date BETWEEN $2 AND $3",
+            )
+            .bind(group_id)
+            .bind(date - Duration::days(SWAP_AVOID_DAYS))
+            .bind(date + Duration::days(SWAP_AVOID_DAYS))
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+            let pool = load_pool(&mut tx, group_id, data.tz_offset_minutes.unwrap_or(480)).await?;
+            let meal_cat = FoodCategory::from_i32(meal as i32).unwrap_or(FoodCategory::Lunch);
+            let exclude: HashSet<i64> = HashSet::from([food_id]);
+            let Some(pick) = pick_for_slot(&pool, meal_cat, 1, &nearby, &exclude).pop() else {
+                tx.rollback().await.ok();
+                return Err(CustomError::BadRequest("没有可替换的菜品".into()));
+            };
+            sqlx::query(
+                "UPDATE meal_plan_entries SET food_id=$3, updated_by=$4, updated_at=NOW() WHERE id=$1 AND group_id=$2",
+            )
+            .bind(data.entry_id)
+            .bind(group_id)
+            .bind(pick.food_id)
+            .bind(token.user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    let mut out: Vec<MealPlanEntryOut> = Vec::with_capacity(ids.len());
+    for id in &ids {
+        out.push(load_entry(&mut tx, group_id, *id).await?);
+    }
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
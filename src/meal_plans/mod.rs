@@ -0,0 +1,152 @@
+pub mod view;
+pub mod update;
+pub mod fill; // 按周自动排餐、换菜
+pub mod order; // 当天菜单转为订单
+
+use crate::{
+    errors::CustomError,
+    models::foods::FoodCategory,
+    models::meal_plans::{MealPlanDayOut, MealPlanEntryOut, MealPlanOut, MealPlanSlotOut},
+    services::groups::local_today,
+};
+use chrono::{Datelike, Duration, NaiveDate};
+use sqlx::{PgConnection, Row};
+use std::collections::HashMap;
+
+/// 日历默认展示/填充的餐段（对应 food_types 1-4）
+pub const DEFAULT_MEALS: [FoodCategory; 4] = [
+    FoodCategory::Breakfast,
+    FoodCategory::Lunch,
+    FoodCategory::AfternoonTea,
+    FoodCategory::Dinner,
+];
+const MAX_PLAN_DAYS: i64 = 31;
+
+/// 计算日期范围（含首尾）；默认从本周一起 7 天
+pub fn plan_range(
+    start_date: Option<NaiveDate>,
+    days: Option<i64>,
+    tz_offset_minutes: Option<i32>,
+) -> Result<(NaiveDate, NaiveDate), CustomError> {
+    let start = match start_date {
+        Some(d) => d,
+        None => {
+            let today = local_today(tz_offset_minutes)?;
+            today - Duration::days(today.weekday().num_days_from_monday() as i64)
+        }
+    };
+    let days = days.unwrap_or(7);
+    if !(1..=MAX_PLAN_DAYS).contains(&days) {
+        return Err(CustomError::BadRequest(format!("天数需在1-{}之间", MAX_PLAN_DAYS)));
+    }
+    Ok((start, start + Duration::days(days - 1)))
+}
+
+pub fn meal_code(meal: FoodCategory) -> i16 {
+    meal as i16
+}
+
+// 菜品需属于该组且可下单
+pub async fn ensure_plannable_food(conn: &mut PgConnection, group_id: i64, food_id: i64) -> Result<(), CustomError> {
+    let ok: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM foods WHERE food_id=$1 AND group_id=$2 AND is_del=0 AND food_status='NORMAL' AND apply_status='APPROVED')",
+    )
+    .bind(food_id)
+    .bind(group_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !ok {
+        return Err(CustomError::BadRequest("菜品不存在、不属于该组或暂不可点".into()));
+    }
+    Ok(())
+}
+
+// 读取单个条目（需属于该组）
+pub async fn load_entry(conn: &mut PgConnection, group_id: i64, entry_id: i64) -> Result<MealPlanEntryOut, CustomError> {
+    let r = sqlx::query(
+        "SELECT e.id, e.food_id, e.quantity, e.note, e.order_id, e.updated_by, e.updated_at, f.food_name, f.food_photo, \
+         COALESCE(f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED', FALSE) AS available \
+         FROM meal_plan_entries e LEFT JOIN foods f ON f.food_id=e.food_id WHERE e.id=$1 AND e.group_id=$2",
+    )
+    .bind(entry_id)
+    .bind(group_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| CustomError::BadRequest("菜单条目不存在".into()))?;
+    Ok(MealPlanEntryOut {
+        entry_id: r.get("id"),
+        food_id: r.get("food_id"),
+        food_name: r.get("food_name"),
+        food_photo: r.get("food_photo"),
+        quantity: r.get("quantity"),
+        note: r.get("note"),
+        available: r.get("available"),
+        order_id: r.get("order_id"),
+        updated_by: r.get("updated_by"),
+        updated_at: r.get("updated_at"),
+    })
+}
+
+/// 读取组内日期范围内的菜单，按 日期 × 餐段 组装；默认餐段始终输出（可能为空）
+pub async fn load_plan(
+    conn: &mut PgConnection,
+    group_id: i64,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<MealPlanOut, CustomError> {
+    let rows = sqlx::query(
+        "SELECT e.id, e.plan_date, e.meal, e.food_id, e.quantity, e.note, e.order_id, e.updated_by, e.updated_at, \
+         f.food_name, f.food_photo, \
+         COALESCE(f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED', FALSE) AS available \
+         FROM meal_plan_entries e LEFT JOIN foods f ON f.food_id=e.food_id \
+         WHERE e.group_id=$1 AND e.plan_date BETWEEN $2 AND $3 ORDER BY e.plan_date, e.meal, e.sort, e.id",
+    )
+    .bind(group_id)
+    .bind(start)
+    .bind(end)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut cells: HashMap<(NaiveDate, i16), Vec<MealPlanEntryOut>> = HashMap::new();
+    for r in rows {
+        cells
+            .entry((r.get("plan_date"), r.get("meal")))
+            .or_default()
+            .push(MealPlanEntryOut {
+                entry_id: r.get("id"),
+                food_id: r.get("food_id"),
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                quantity: r.get("quantity"),
+                note: r.get("note"),
+                available: r.get("available"),
+                order_id: r.get("order_id"),
+                updated_by: r.get("updated_by"),
+                updated_at: r.get("updated_at"),
+            });
+    }
+    let mut days = Vec::new();
+    let mut date = start;
+    while date <= end {
+        let mut slots = Vec::new();
+        for code in 1..=5 {
+            let Some(meal) = FoodCategory::from_i32(code) else { continue };
+            let entries = cells.remove(&(date, code as i16)).unwrap_or_default();
+            if entries.is_empty() && !DEFAULT_MEALS.contains(&meal) {
+                continue;
+            }
+            slots.push(MealPlanSlotOut {
+                meal,
+                meal_label: meal.zh_label().to_string(),
+                entries,
+            });
+        }
+        days.push(MealPlanDayOut { date, slots });
+        date += Duration::days(1);
+    }
+    Ok(MealPlanOut {
+        group_id,
+        start_date: start,
+        end_date: end,
+        days,
+    })
+}
@@ -0,0 +1,175 @@
+use crate::{
+    errors::CustomError,
+    meal_plans::meal_code,
+    models::foods::FoodCategory,
+    models::meal_plans::{MealPlanDayOrderInput, MealPlanDayOrderOut, MealPlanOrderOut, MealPlanSkippedMealOut},
+    models::orders::{OrderItemCreateInput, OrderPlaceInput},
+    models::users::UserToken,
+    orders::new::spawn_order_push,
+    orders::reorder::{place_items, split_available_items},
+    services::groups::ensure_member,
+    AppState,
+};
+use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+// 各餐段默认期望时间（本地小时）
+fn default_goal_hour(meal: FoodCategory) -> u32 {
+    match meal {
+        FoodCategory::Breakfast => 8,
+        FoodCategory::Lunch => 12,
+        FoodCategory::AfternoonTea => 15,
+        FoodCategory::Dinner => 18,
+        FoodCategory::MidnightSnack => 22,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/meal-plan/days/{date}/order",
+    tag = "菜单计划",
+    summary = "将某天的菜单按餐段转为订单",
+    description = "每个餐段生成一张订单，已下单的条目跳过；下架或删除的菜品会列入 skipped，其条目保持未下单。菜品全部不可点的餐段不生成订单，列入 skipped_meals。积分参数仅在只下单一个餐段时可用。",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("date" = String, Path, description = "日期 YYYY-MM-DD")
+    ),
+    request_body = MealPlanDayOrderInput,
+    responses((status = 201, body = MealPlanDayOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn order_meal_plan_day(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, NaiveDate)>,
+    data: Json<MealPlanDayOrderInput>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, date) = *path;
+    let offset = FixedOffset::east_opt(data.tz_offset_minutes.unwrap_or(480) * 60)
+        .ok_or_else(|| CustomError::BadRequest("无效的时区偏移".into()))?;
+    if data.order.group_id.is_some_and(|g| g != group_id) {
+        return Err(CustomError::BadRequest("菜单只能下单到所属组".into()));
+    }
+    let meal_codes: Option<Vec<i16>> = data
+        .meals
+        .as_ref()
+        .filter(|m| !m.is_empty())
+        .map(|m| m.iter().map(|meal| meal_code(*meal)).collect());
+
+    let mut tx = state.db_pool.begin().await?;
+    ensure_member(&mut tx, group_id, token.user_id).await?;
+    let rows = match &meal_codes {
+        Some(codes) => sqlx::query(
+            "SELECT id, meal, food_id, quantity FROM meal_plan_entries \
+             WHERE group_id=$1 AND plan_date=$2 AND order_id IS NULL AND meal = ANY($3) \
+             ORDER BY meal, sort, id FOR UPDATE",
+        )
+        .bind(group_id)
+        .bind(date)
+        .bind(codes)
+        .fetch_all(&mut *tx)
+        .await?,
+        None => sqlx::query(
+            "SELECT id, meal, food_id, quantity FROM meal_plan_entries \
+             WHERE group_id=$1 AND plan_date=$2 AND order_id IS NULL \
+             ORDER BY meal, sort, id FOR UPDATE",
+        )
+        .bind(group_id)
+        .bind(date)
+        .fetch_all(&mut *tx)
+        .await?,
+    };
+    if rows.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("当天没有待下单的菜单".into()));
+    }
+
+    // 按餐段分组，同一菜品合并份数；条目记录 (条目ID, 菜品ID)
+    let mut slots: Vec<(i16, Vec<(i64, i64)>, Vec<OrderItemCreateInput>)> = Vec::new();
+    for r in rows {
+        let meal: i16 = r.get("meal");
+        let food_id: i64 = r.get("food_id");
+        let quantity: i32 = r.get("quantity");
+        if slots.last().map(|s| s.0) != Some(meal) {
+            slots.push((meal, Vec::new(), Vec::new()));
+        }
+        let slot = slots.last_mut().unwrap();
+        slot.1.push((r.get("id"), food_id));
+        match slot.2.iter_mut().find(|i| i.food_id == food_id) {
+            Some(item) => item.quantity = Some(item.quantity.unwrap_or(1) + quantity),
+            None => slot.2.push(OrderItemCreateInput {
+                food_id,
+                quantity: Some(quantity),
+            }),
+        }
+    }
+
+    // 积分参数属于单张订单，拆成多张订单时无法确定归属
+    let has_points = data.order.points_cost.is_some_and(|p| p != 0) || data.order.points_reward.is_some_and(|p| p != 0);
+    if has_points && slots.len() > 1 {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("多个餐段下单时不支持设置积分，请按餐段分别下单".into()));
+    }
+
+    let mut orders = Vec::with_capacity(slots.len());
+    let mut skipped_meals = Vec::new();
+    for (code, entries, items) in slots {
+        let meal = FoodCategory::from_i32(code as i32).unwrap_or(FoodCategory::Lunch);
+        // 整个餐段都不可点：不下单，条目保持未下单
+        let (available, skipped) = split_available_items(&mut tx, &items).await?;
+        if available.is_empty() {
+            skipped_meals.push(MealPlanSkippedMealOut { meal, skipped });
+            continue;
+        }
+        let goal_time = match data.order.goal_time {
+            Some(t) => Some(t),
+            None => date
+                .and_hms_opt(default_goal_hour(meal), 0, 0)
+                .and_then(|dt| offset.from_local_datetime(&dt).single())
+                .map(|dt| dt.with_timezone(&Utc)),
+        };
+        let input = OrderPlaceInput {
+            group_id: Some(group_id),
+            goal_time,
+            ..data.order.clone()
+        };
+        let placed = match place_items(&mut tx, token.user_id, Some(group_id), &items, &input).await {
+            Ok(p) => p,
+            Err(e) => {
+                tx.rollback().await.ok();
+                return Err(e);
+            }
+        };
+        // 被跳过的菜品条目保持未下单，可在菜品恢复后再次下单
+        let entry_ids: Vec<i64> = entries
+            .iter()
+            .filter(|(_, food_id)| !placed.skipped.iter().any(|s| s.food_id == *food_id))
+            .map(|(id, _)| *id)
+            .collect();
+        sqlx::query("UPDATE meal_plan_entries SET order_id=$2, updated_by=$3, updated_at=NOW() WHERE id = ANY($1)")
+            .bind(&entry_ids)
+            .bind(placed.order.order_id)
+            .bind(token.user_id)
+            .execute(&mut *tx)
+            .await?;
+        orders.push(MealPlanOrderOut { meal, placed });
+    }
+    if orders.is_empty() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜品均已下架或删除，无法下单".into()));
+    }
+    tx.commit().await?;
+    for o in &orders {
+        spawn_order_push(state.db_pool.clone(), o.placed.order.order_id, "meal plan order");
+    }
+    Ok(HttpResponse::Created().json(&MealPlanDayOrderOut {
+        date,
+        orders,
+        skipped_meals,
+    }))
+}
@@ -0,0 +1,160 @@
+use crate::{
+    errors::CustomError,
+    meal_plans::{ensure_plannable_food, load_entry, meal_code},
+    models::meal_plans::{MealPlanEntryInput, MealPlanEntryOut, MealPlanEntryUpdateInput},
+    models::users::UserToken,
+    services::groups::ensure_member,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+const MAX_QUANTITY: i32 = 99;
+const NOTE_MAX_CHARS: usize = 64;
+
+fn validate_entry(quantity: Option<i32>, note: Option<&str>) -> Result<(), CustomError> {
+    if quantity.is_some_and(|q| !(1..=MAX_QUANTITY).contains(&q)) {
+        return Err(CustomError::BadRequest(format!("份数需在1-{}之间", MAX_QUANTITY)));
+    }
+    if note.is_some_and(|n| n.chars().count() > NOTE_MAX_CHARS) {
+        return Err(CustomError::BadRequest(format!("备注不能超过{}个字符", NOTE_MAX_CHARS)));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/meal-plan/entries",
+    tag = "菜单计划",
+    summary = "向某天某餐段添加菜品",
+    params(("group_id" = i64, Path, description = "组ID")),
+    request_body = MealPlanEntryInput,
+    responses((status = 201, body = MealPlanEntryOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_meal_plan_entry(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    data: Json<MealPlanEntryInput>,
+) -> Result<impl Responder, CustomError> {
+    validate_entry(data.quantity, data.note.as_deref())?;
+    let group_id = *group_id;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, group_id, token.user_id).await?;
+    ensure_plannable_food(&mut conn, group_id, data.food_id).await?;
+    let entry_id: i64 = sqlx::query_scalar(
+        "INSERT INTO meal_plan_entries (group_id, plan_date, meal, food_id, quantity, note, sort, created_by, updated_by) \
+         VALUES ($1,$2,$3,$4,$5,$6, \
+         (SELECT COALESCE(MAX(sort), -1) + 1 FROM meal_plan_entries WHERE group_id=$1 AND plan_date=$2 AND meal=$3), $7, $7) \
+         RETURNING id",
+    )
+    .bind(group_id)
+    .bind(data.plan_date)
+    .bind(meal_code(data.meal))
+    .bind(data.food_id)
+    .bind(data.quantity.unwrap_or(1))
+    .bind(data.note.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+    .bind(token.user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let out = load_entry(&mut conn, group_id, entry_id).await?;
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/groups/{group_id}/meal-plan/entries/{entry_id}",
+    tag = "菜单计划",
+    summary = "修改菜单条目（换菜、改份数/备注或移动到其他日期餐段）",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("entry_id" = i64, Path, description = "条目ID")
+    ),
+    request_body = MealPlanEntryUpdateInput,
+    responses((status = 200, body = MealPlanEntryOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_meal_plan_entry(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<MealPlanEntryUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    validate_entry(data.quantity, data.note.as_deref())?;
+    let (group_id, entry_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    ensure_member(&mut tx, group_id, token.user_id).await?;
+    let row = sqlx::query("SELECT order_id FROM meal_plan_entries WHERE id=$1 AND group_id=$2 FOR UPDATE")
+        .bind(entry_id)
+        .bind(group_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some(row) = row else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜单条目不存在".into()));
+    };
+    if row.get::<Option<i64>, _>("order_id").is_some() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该条目已下单，无法修改".into()));
+    }
+    if let Some(food_id) = data.food_id {
+        if let Err(e) = ensure_plannable_food(&mut tx, group_id, food_id).await {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    }
+    sqlx::query(
+        "UPDATE meal_plan_entries SET plan_date=COALESCE($3, plan_date), meal=COALESCE($4, meal), \
+         food_id=COALESCE($5, food_id), quantity=COALESCE($6, quantity), note=COALESCE($7, note), \
+         updated_by=$8, updated_at=NOW() WHERE id=$1 AND group_id=$2",
+    )
+    .bind(entry_id)
+    .bind(group_id)
+    .bind(data.plan_date)
+    .bind(data.meal.map(meal_code))
+    .bind(data.food_id)
+    .bind(data.quantity)
+    .bind(data.note.as_deref().map(str::trim))
+    .bind(token.user_id)
+    .execute(&mut *tx)
+    .await?;
+    let out = load_entry(&mut tx, group_id, entry_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/groups/{group_id}/meal-plan/entries/{entry_id}",
+    tag = "菜单计划",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("entry_id" = i64, Path, description = "条目ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_meal_plan_entry(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, entry_id) = *path;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, group_id, token.user_id).await?;
+    let deleted = sqlx::query("DELETE FROM meal_plan_entries WHERE id=$1 AND group_id=$2")
+        .bind(entry_id)
+        .bind(group_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(CustomError::BadRequest("菜单条目不存在".into()));
+    }
+    Ok(HttpResponse::Ok().body("deleted"))
+}
@@ -0,0 +1,35 @@
+use crate::{
+    errors::CustomError,
+    meal_plans::{load_plan, plan_range},
+    models::meal_plans::{MealPlanOut, MealPlanQuery},
+    models::users::UserToken,
+    services::groups::ensure_member,
+    AppState,
+};
+use ntex::web::{
+    types::{Path, Query, State},
+    HttpResponse, Responder,
+};
+use std::sync::Arc;
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/meal-plan",
+    tag = "菜单计划",
+    summary = "组内菜单日历（日期 × 餐段）",
+    params(("group_id" = i64, Path, description = "组ID"), MealPlanQuery),
+    responses((status = 200, body = MealPlanOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_meal_plan(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    query: Query<MealPlanQuery>,
+) -> Result<impl Responder, CustomError> {
+    let (start, end) = plan_range(query.start_date, query.days, query.tz_offset_minutes)?;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let out = load_plan(&mut conn, *group_id, start, end).await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
@@ -0,0 +1,31 @@
+-- =========================================================
+-- Migration: Shopping Cart
+-- Date: 2026-10-18
+-- Description:
+-- 1. `carts` 原 UNIQUE(user_id, status) 导致同一用户只能结算一次，
+--    改为仅约束 ACTIVE 购物车唯一（部分唯一索引）。
+-- 2. `cart_items` 同一购物车内同一菜品合并为一行 UNIQUE(cart_id, food_id)。
+-- =========================================================
+
+BEGIN;
+
+-- 1. carts
+ALTER TABLE carts DROP CONSTRAINT IF EXISTS carts_user_id_status_key;
+CREATE UNIQUE INDEX IF NOT EXISTS uq_carts_user_active ON carts(user_id) WHERE status = 'ACTIVE';
+
+-- 2. cart_items: merge duplicates before adding the constraint
+UPDATE cart_items ci
+SET quantity = d.total_quantity
+FROM (
+    SELECT MIN(id) AS keep_id, SUM(quantity) AS total_quantity
+    FROM cart_items
+    GROUP BY cart_id, food_id
+    HAVING COUNT(*) > 1
+) d
+WHERE ci.id = d.keep_id;
+DELETE FROM cart_items ci
+USING cart_items dup
+WHERE ci.cart_id = dup.cart_id AND ci.food_id = dup.food_id AND ci.id > dup.id;
+ALTER TABLE cart_items ADD CONSTRAINT cart_items_cart_id_food_id_key UNIQUE (cart_id, food_id);
+
+COMMIT;
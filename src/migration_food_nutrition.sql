@@ -0,0 +1,37 @@
+-- =========================================================
+-- Migration: Food Nutrition & Allergens
+-- Date: 2026-10-18
+-- Description:
+-- 1. `foods` 新增每份营养字段：calories/protein/fat/carbs（均可空）。
+-- 2. `foods` 新增 `dietary_labels` 饮食/过敏原标签数组，并建 GIN 索引用于筛选。
+-- 3. 新增 `user_allergen_prefs`：用户过敏原偏好，下单时提醒（WARN）或拦截（BLOCK）。
+-- =========================================================
+
+BEGIN;
+
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS calories INT;
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS protein DOUBLE PRECISION;
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS fat DOUBLE PRECISION;
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS carbs DOUBLE PRECISION;
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS dietary_labels TEXT[] NOT NULL DEFAULT '{}';
+COMMENT ON COLUMN foods.calories IS '每份热量（kcal）';
+COMMENT ON COLUMN foods.protein IS '每份蛋白质（g）';
+COMMENT ON COLUMN foods.fat IS '每份脂肪（g）';
+COMMENT ON COLUMN foods.carbs IS '每份碳水化合物（g）';
+COMMENT ON COLUMN foods.dietary_labels IS '饮食/过敏原标签：VEGETARIAN/SPICY/CONTAINS_NUTS 等';
+CREATE INDEX IF NOT EXISTS idx_food_dietary_labels ON foods USING GIN (dietary_labels);
+
+CREATE TABLE IF NOT EXISTS user_allergen_prefs (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    label VARCHAR(32) NOT NULL,
+    action VARCHAR(8) NOT NULL DEFAULT 'WARN' CHECK (action IN ('WARN', 'BLOCK')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, label)
+);
+COMMENT ON TABLE user_allergen_prefs IS '用户过敏原/忌口偏好（下单时提醒或拦截）';
+COMMENT ON COLUMN user_allergen_prefs.user_id IS '用户ID';
+COMMENT ON COLUMN user_allergen_prefs.label IS '饮食/过敏原标签，对应 foods.dietary_labels';
+COMMENT ON COLUMN user_allergen_prefs.action IS '处理方式：WARN 提醒 / BLOCK 禁止下单';
+COMMENT ON COLUMN user_allergen_prefs.created_at IS '创建时间';
+
+COMMIT;
@@ -0,0 +1,51 @@
+-- =========================================================
+-- Migration: Food Recipes
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `food_ingredients`：结构化食材行（名称/单份用量/单位），用于采购清单汇总。
+-- 2. 新增 `food_steps`：有序步骤（内容/配图/计时）。
+-- 3. `foods.ingredients` / `foods.steps` 文本保留，写入结构化数据时同步生成，兼容旧客户端。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS food_ingredients (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    sort INT NOT NULL DEFAULT 0,
+    name VARCHAR(64) NOT NULL,
+    quantity DOUBLE PRECISION,
+    unit VARCHAR(16),
+    note VARCHAR(64),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE food_ingredients IS '菜品结构化食材';
+COMMENT ON COLUMN food_ingredients.id IS '主键';
+COMMENT ON COLUMN food_ingredients.food_id IS '菜品ID';
+COMMENT ON COLUMN food_ingredients.sort IS '排序';
+COMMENT ON COLUMN food_ingredients.name IS '食材名称';
+COMMENT ON COLUMN food_ingredients.quantity IS '单份用量（为空表示适量）';
+COMMENT ON COLUMN food_ingredients.unit IS '单位';
+COMMENT ON COLUMN food_ingredients.note IS '备注（如切丁）';
+COMMENT ON COLUMN food_ingredients.created_at IS '创建时间';
+CREATE INDEX IF NOT EXISTS idx_food_ingredients_food ON food_ingredients(food_id, sort);
+CREATE TABLE IF NOT EXISTS food_steps (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    step_no INT NOT NULL,
+    content TEXT NOT NULL,
+    photo_url VARCHAR(512),
+    timer_seconds INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (food_id, step_no)
+);
+COMMENT ON TABLE food_steps IS '菜品结构化步骤';
+COMMENT ON COLUMN food_steps.id IS '主键';
+COMMENT ON COLUMN food_steps.food_id IS '菜品ID';
+COMMENT ON COLUMN food_steps.step_no IS '步骤序号（从1开始）';
+COMMENT ON COLUMN food_steps.content IS '步骤内容';
+COMMENT ON COLUMN food_steps.photo_url IS '步骤配图URL';
+COMMENT ON COLUMN food_steps.timer_seconds IS '计时（秒）';
+COMMENT ON COLUMN food_steps.created_at IS '创建时间';
+
+COMMIT;
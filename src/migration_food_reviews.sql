@@ -0,0 +1,45 @@
+-- =========================================================
+-- Migration: Food Reviews
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `food_reviews`：按菜品的 1-5 星评价，关联已完成订单，每单每菜每人一条。
+-- 2. 评价图片复用 `photos` 图集（owner_type = 'REVIEW'）。
+-- 3. `food_stats` 新增 `review_count` / `review_star_sum`，用于平均分展示与排序。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS food_reviews (
+    review_id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE SET NULL,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    stars SMALLINT NOT NULL CHECK (stars BETWEEN 1 AND 5),
+    content TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, food_id, user_id)
+);
+CREATE INDEX IF NOT EXISTS idx_food_reviews_food ON food_reviews(food_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_food_reviews_group ON food_reviews(group_id, created_at DESC);
+COMMENT ON TABLE food_reviews IS '菜品评价（1-5星，关联已完成订单；与订单积分评分 order_ratings 相互独立）';
+COMMENT ON COLUMN food_reviews.review_id IS '评价主键';
+COMMENT ON COLUMN food_reviews.food_id IS '菜品ID';
+COMMENT ON COLUMN food_reviews.order_id IS '来源订单ID（须已完成且包含该菜品）';
+COMMENT ON COLUMN food_reviews.group_id IS '订单所属组ID（用于组内评价动态）';
+COMMENT ON COLUMN food_reviews.user_id IS '评价人用户ID';
+COMMENT ON COLUMN food_reviews.stars IS '星级 1-5';
+COMMENT ON COLUMN food_reviews.content IS '评价内容';
+COMMENT ON COLUMN food_reviews.created_at IS '创建时间';
+COMMENT ON COLUMN food_reviews.updated_at IS '最后修改时间';
+
+ALTER TABLE food_stats ADD COLUMN IF NOT EXISTS review_count INT NOT NULL DEFAULT 0;
+ALTER TABLE food_stats ADD COLUMN IF NOT EXISTS review_star_sum INT NOT NULL DEFAULT 0;
+COMMENT ON COLUMN food_stats.review_count IS '菜品评价数';
+COMMENT ON COLUMN food_stats.review_star_sum IS '菜品评价星级合计（平均分 = 合计 / 评价数）';
+
+COMMENT ON COLUMN photos.owner_type IS '归属类型：FOOD 菜品 / CHECKIN 心愿打卡 / ORDER 订单完成 / REVIEW 菜品评价';
+COMMENT ON COLUMN photos.owner_id IS '归属对象ID：food_id / wish_claim_checkins.id / order_id / food_reviews.review_id';
+
+COMMIT;
@@ -0,0 +1,60 @@
+-- =========================================================
+-- Migration: Food Revisions
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `food_revisions`：菜品每次内容/状态变更后的完整快照，支持对比与恢复。
+-- 2. `food_audit_logs.action` 新增 5 = 回退到已通过版本。
+-- 3. 回填：为已有菜品生成修订 #1 作为基线，避免首次修改丢失原内容。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS food_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    revision_no INT NOT NULL,
+    source VARCHAR(16) NOT NULL,
+    changed_fields TEXT[] NOT NULL DEFAULT '{}',
+    snapshot JSONB NOT NULL,
+    audit_log_id BIGINT REFERENCES food_audit_logs(id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (food_id, revision_no)
+);
+COMMENT ON TABLE food_revisions IS '菜品修订历史（每次变更后的完整快照）';
+COMMENT ON COLUMN food_revisions.id IS '修订主键';
+COMMENT ON COLUMN food_revisions.food_id IS '菜品ID';
+COMMENT ON COLUMN food_revisions.revision_no IS '菜品内递增的修订号';
+COMMENT ON COLUMN food_revisions.source IS '来源：CREATE/UPDATE/AUDIT/RESTORE/IMPORT';
+COMMENT ON COLUMN food_revisions.changed_fields IS '相对上一修订变化的字段';
+COMMENT ON COLUMN food_revisions.snapshot IS '内容快照：名称/图片/食材/步骤/标签/状态';
+COMMENT ON COLUMN food_revisions.audit_log_id IS '关联的审核记录ID';
+COMMENT ON COLUMN food_revisions.created_by IS '操作人用户ID';
+COMMENT ON COLUMN food_revisions.created_at IS '创建时间';
+COMMENT ON COLUMN food_audit_logs.action IS '动作：1提交 2通过 3拒绝 4修改 5回退';
+
+INSERT INTO food_revisions (food_id, revision_no, source, changed_fields, snapshot, created_by, created_at)
+SELECT f.food_id, 1, 'CREATE',
+       ARRAY['food_name','food_photo','ingredients','steps','ingredient_items','step_items','tags','food_status','apply_status'],
+       jsonb_build_object(
+           'food_name', f.food_name,
+           'food_photo', f.food_photo,
+           'ingredients', f.ingredients,
+           'steps', f.steps,
+           'ingredient_items', COALESCE((
+               SELECT jsonb_agg(jsonb_build_object('name', i.name, 'quantity', i.quantity, 'unit', i.unit, 'note', i.note) ORDER BY i.sort, i.id)
+               FROM food_ingredients i WHERE i.food_id=f.food_id), '[]'::jsonb),
+           'step_items', COALESCE((
+               SELECT jsonb_agg(jsonb_build_object('content', s.content, 'photo_url', s.photo_url, 'timer_seconds', s.timer_seconds) ORDER BY s.step_no)
+               FROM food_steps s WHERE s.food_id=f.food_id), '[]'::jsonb),
+           'tags', COALESCE((
+               SELECT jsonb_agg(jsonb_build_object('tag_id', t.tag_id, 'tag_name', t.tag_name) ORDER BY t.sort DESC NULLS LAST, t.tag_id)
+               FROM food_tags_map m JOIN tags t ON t.tag_id=m.tag_id WHERE m.food_id=f.food_id), '[]'::jsonb),
+           'food_status', f.food_status::text,
+           'apply_status', f.apply_status::text
+       ),
+       f.created_by, f.updated_at
+FROM foods f
+WHERE NOT EXISTS (SELECT 1 FROM food_revisions r WHERE r.food_id=f.food_id);
+
+COMMIT;
@@ -0,0 +1,46 @@
+-- =========================================================
+-- Migration: Food Search
+-- Date: 2026-10-18
+-- Description:
+-- 1. 启用 pg_trgm，菜品名称建立三元组索引，支持错字容忍与前缀联想。
+-- 2. 新增函数 `zh_bigrams`：中文按相邻两字切分，弥补 simple 配置不分中文词的问题。
+-- 3. `foods` 新增生成列 `search_vector`（名称/食材/步骤分权重）及 GIN 索引。
+-- =========================================================
+
+BEGIN;
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- 中文按相邻两字切分（bigram），其余文本保留原词，供 simple 全文检索配置使用
+CREATE OR REPLACE FUNCTION zh_bigrams(src TEXT) RETURNS TEXT
+LANGUAGE plpgsql IMMUTABLE PARALLEL SAFE AS $$
+DECLARE
+    s TEXT := lower(COALESCE(src, ''));
+    out TEXT;
+    run TEXT;
+    i INT;
+BEGIN
+    out := regexp_replace(s, '[\u4e00-\u9fff]+', ' ', 'g');
+    FOR run IN SELECT m[1] FROM regexp_matches(s, '([\u4e00-\u9fff]+)', 'g') AS m LOOP
+        IF char_length(run) = 1 THEN
+            out := out || ' ' || run;
+        ELSE
+            FOR i IN 1..char_length(run) - 1 LOOP
+                out := out || ' ' || substr(run, i, 2);
+            END LOOP;
+        END IF;
+    END LOOP;
+    RETURN out;
+END $$;
+
+ALTER TABLE foods ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', zh_bigrams(food_name)), 'A') ||
+    setweight(to_tsvector('simple', zh_bigrams(COALESCE(ingredients, ''))), 'B') ||
+    setweight(to_tsvector('simple', zh_bigrams(COALESCE(steps, ''))), 'C')
+) STORED;
+COMMENT ON COLUMN foods.search_vector IS '全文检索向量（名称A/食材B/步骤C，中文二元切分）';
+
+CREATE INDEX IF NOT EXISTS idx_food_search ON foods USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS idx_food_name_trgm ON foods USING GIN (food_name gin_trgm_ops);
+
+COMMIT;
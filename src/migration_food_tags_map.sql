@@ -0,0 +1,28 @@
+-- =========================================================
+-- Migration: Food Tags Map
+-- Date: 2026-10-18
+-- Description:
+-- 1. 恢复 `food_tags_map`：菜品与标签多对多（migration_tags_update.sql 曾将其删除）。
+-- 2. 回填：已有的单个 `foods.tag_id` 写入映射表。
+-- 3. `foods.tag_id` 保留为主标签（映射中的第一个），兼容旧客户端。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS food_tags_map (
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags(tag_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (food_id, tag_id)
+);
+COMMENT ON TABLE food_tags_map IS '菜品-标签多对多映射（foods.tag_id 为主标签）';
+COMMENT ON COLUMN food_tags_map.food_id IS '菜品ID';
+COMMENT ON COLUMN food_tags_map.tag_id IS '标签ID';
+COMMENT ON COLUMN food_tags_map.created_at IS '创建时间';
+CREATE INDEX IF NOT EXISTS idx_food_tags_map_tag ON food_tags_map(tag_id);
+
+INSERT INTO food_tags_map (food_id, tag_id)
+SELECT food_id, tag_id FROM foods WHERE tag_id IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+COMMIT;
@@ -0,0 +1,42 @@
+-- =========================================================
+-- Migration: Guest Sessions
+-- Date: 2026-10-18
+-- Description:
+-- 1. `users` 新增 is_guest：访客以影子账号落库，沿用 orders.user_id。
+-- 2. 新增 `guest_sessions`：凭邀请码领取的短期访客令牌，可撤销、可转正。
+-- =========================================================
+
+BEGIN;
+
+-- 1. users
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_guest BOOLEAN NOT NULL DEFAULT FALSE;
+COMMENT ON COLUMN users.is_guest IS '是否访客影子账号（凭邀请码创建，不可登录）';
+
+-- 2. guest_sessions
+CREATE TABLE IF NOT EXISTS guest_sessions (
+    session_id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    nick_name VARCHAR(64) NOT NULL,
+    invite_code VARCHAR(32) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    converted_at TIMESTAMPTZ,
+    converted_user_id BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE guest_sessions IS '访客会话（凭邀请码领取的短期令牌）';
+COMMENT ON COLUMN guest_sessions.session_id IS '会话主键ID';
+COMMENT ON COLUMN guest_sessions.group_id IS '所属关联组ID';
+COMMENT ON COLUMN guest_sessions.user_id IS '访客影子账号ID';
+COMMENT ON COLUMN guest_sessions.nick_name IS '访客昵称';
+COMMENT ON COLUMN guest_sessions.invite_code IS '领取时使用的邀请码（组邀请码变更后无法继续下单）';
+COMMENT ON COLUMN guest_sessions.expires_at IS '令牌过期时间';
+COMMENT ON COLUMN guest_sessions.revoked_at IS '被组成员撤销时间';
+COMMENT ON COLUMN guest_sessions.converted_at IS '转为正式成员时间';
+COMMENT ON COLUMN guest_sessions.converted_user_id IS '转正后的成员用户ID';
+COMMENT ON COLUMN guest_sessions.created_at IS '创建时间';
+CREATE INDEX IF NOT EXISTS idx_guest_sessions_group ON guest_sessions(group_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_guest_sessions_user ON guest_sessions(user_id);
+
+COMMIT;
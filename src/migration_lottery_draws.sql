@@ -0,0 +1,38 @@
+-- =========================================================
+-- Migration: Lottery Draws
+-- Date: 2026-10-18
+-- Description:
+-- 1. `lottery_draws` 新增 group_id / parent_draw_id（重抽来源）/ points_cost / order_id（转为订单）。
+-- 2. `lottery_draw_results` 按标签记录：新增 tag_id，food_type 改为可空；
+--    唯一约束由 (draw_id, food_type) 改为 (draw_id, food_id)，同一标签可抽多道菜。
+-- 3. 新增索引：按用户+组查询最近抽取。
+-- 4. `lottery_draws` 新增 superseded_at：重抽后原记录被取代，不可再重抽或下单。
+-- =========================================================
+
+BEGIN;
+
+ALTER TABLE lottery_draws ADD COLUMN IF NOT EXISTS group_id BIGINT REFERENCES association_groups(group_id) ON DELETE SET NULL;
+ALTER TABLE lottery_draws ADD COLUMN IF NOT EXISTS parent_draw_id BIGINT REFERENCES lottery_draws(draw_id) ON DELETE SET NULL;
+ALTER TABLE lottery_draws ADD COLUMN IF NOT EXISTS points_cost INT NOT NULL DEFAULT 0;
+ALTER TABLE lottery_draws ADD COLUMN IF NOT EXISTS order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL;
+COMMENT ON COLUMN lottery_draws.group_id IS '抽取所在关联组ID';
+COMMENT ON COLUMN lottery_draws.parent_draw_id IS '重抽来源抽奖记录ID';
+COMMENT ON COLUMN lottery_draws.points_cost IS '本次消耗积分（重抽）';
+ALTER TABLE lottery_draws ADD COLUMN IF NOT EXISTS superseded_at TIMESTAMPTZ;
+COMMENT ON COLUMN lottery_draws.order_id IS '转为订单后的订单ID';
+COMMENT ON COLUMN lottery_draws.superseded_at IS '被重抽取代的时间，取代后不可再重抽或下单';
+CREATE INDEX IF NOT EXISTS idx_ld_user_group_time ON lottery_draws(user_id, group_id, created_at);
+
+ALTER TABLE lottery_draw_results ADD COLUMN IF NOT EXISTS tag_id BIGINT REFERENCES tags(tag_id) ON DELETE SET NULL;
+ALTER TABLE lottery_draw_results ALTER COLUMN food_type DROP NOT NULL;
+ALTER TABLE lottery_draw_results DROP CONSTRAINT IF EXISTS lottery_draw_results_draw_id_food_type_key;
+DO $$
+BEGIN
+    IF NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conname = 'lottery_draw_results_draw_id_food_id_key') THEN
+        ALTER TABLE lottery_draw_results ADD CONSTRAINT lottery_draw_results_draw_id_food_id_key UNIQUE (draw_id, food_id);
+    END IF;
+END $$;
+COMMENT ON COLUMN lottery_draw_results.food_type IS '菜品类型编号（旧数据）';
+COMMENT ON COLUMN lottery_draw_results.tag_id IS '抽中时所属标签ID';
+
+COMMIT;
@@ -0,0 +1,42 @@
+-- =========================================================
+-- Migration: Meal Plans
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `meal_plan_entries`：组内共享的每周菜单（日期 × 餐段 × 菜品）。
+-- 2. 支持自动排餐、换菜，以及将某天菜单按餐段转为订单（order_id 记录已下单）。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS meal_plan_entries (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    plan_date DATE NOT NULL,
+    meal SMALLINT NOT NULL CHECK (meal BETWEEN 1 AND 5),
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    quantity INT NOT NULL DEFAULT 1,
+    sort INT NOT NULL DEFAULT 0,
+    note VARCHAR(64),
+    order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    updated_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_meal_plan_entries_group_date ON meal_plan_entries(group_id, plan_date, meal);
+COMMENT ON TABLE meal_plan_entries IS '组内菜单计划条目（日期 × 餐段 × 菜品），组成员共享编辑';
+COMMENT ON COLUMN meal_plan_entries.id IS '条目主键';
+COMMENT ON COLUMN meal_plan_entries.group_id IS '所属组ID';
+COMMENT ON COLUMN meal_plan_entries.plan_date IS '计划日期（本地日期）';
+COMMENT ON COLUMN meal_plan_entries.meal IS '餐段：1早餐 2午餐 3下午茶 4晚餐 5夜宵（同 food_types）';
+COMMENT ON COLUMN meal_plan_entries.food_id IS '菜品ID';
+COMMENT ON COLUMN meal_plan_entries.quantity IS '份数';
+COMMENT ON COLUMN meal_plan_entries.sort IS '餐段内排序';
+COMMENT ON COLUMN meal_plan_entries.note IS '备注';
+COMMENT ON COLUMN meal_plan_entries.order_id IS '已转订单ID，非空表示已下单';
+COMMENT ON COLUMN meal_plan_entries.created_by IS '创建人用户ID';
+COMMENT ON COLUMN meal_plan_entries.updated_by IS '最后修改人用户ID';
+COMMENT ON COLUMN meal_plan_entries.created_at IS '创建时间';
+COMMENT ON COLUMN meal_plan_entries.updated_at IS '更新时间';
+
+COMMIT;
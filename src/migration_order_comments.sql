@@ -0,0 +1,32 @@
+-- =========================================================
+-- Migration: Order Comments
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `order_comments` 订单评论表（文字 + 可选图片，软删除）。
+-- 2. 评论同时出现在组动态（ORDER_COMMENTED）。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS order_comments (
+    comment_id BIGSERIAL PRIMARY KEY,
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    content TEXT NOT NULL DEFAULT '',
+    photo_url VARCHAR(512),
+    is_del SMALLINT NOT NULL DEFAULT 0,
+    edited_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE order_comments IS '订单评论';
+COMMENT ON COLUMN order_comments.comment_id IS '评论主键ID';
+COMMENT ON COLUMN order_comments.order_id IS '订单ID';
+COMMENT ON COLUMN order_comments.user_id IS '评论人ID';
+COMMENT ON COLUMN order_comments.content IS '评论内容';
+COMMENT ON COLUMN order_comments.photo_url IS '图片地址（七牛上传）';
+COMMENT ON COLUMN order_comments.is_del IS '是否删除：0否 1是';
+COMMENT ON COLUMN order_comments.edited_at IS '最后编辑时间';
+COMMENT ON COLUMN order_comments.created_at IS '创建时间';
+CREATE INDEX IF NOT EXISTS idx_order_comments_order ON order_comments(order_id, created_at DESC);
+
+COMMIT;
@@ -0,0 +1,27 @@
+-- =========================================================
+-- Migration: goal_time Reminders & Overdue Orders
+-- Date: 2026-10-18
+-- Description:
+-- 1. Add `OVERDUE` to `order_status_enum` (ACCEPTED 超过 goal_time 未完成).
+--    ALTER TYPE ... ADD VALUE 不能与使用该值的语句放在同一事务中，故不包裹 BEGIN/COMMIT。
+-- 2. `order_reminders`：按 (order_id, kind, goal_time) 记录已发送的提醒，用于去重。
+-- Runtime config (env):
+--   ORDER_REMIND_BEFORE_MINUTES  默认 30，<=0 关闭提醒
+--   ORDER_OVERDUE_GRACE_MINUTES  默认 0
+--   ORDER_OVERDUE_POLICY         OVERDUE（默认）| SYSTEM_CLOSED
+-- =========================================================
+
+ALTER TYPE order_status_enum ADD VALUE IF NOT EXISTS 'OVERDUE';
+
+CREATE TABLE IF NOT EXISTS order_reminders (
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    goal_time TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (order_id, kind, goal_time)
+);
+COMMENT ON TABLE order_reminders IS '订单提醒发送记录（去重）';
+COMMENT ON COLUMN order_reminders.order_id IS '订单ID';
+COMMENT ON COLUMN order_reminders.kind IS '提醒类型：GOAL_TIME_UPCOMING 临近期望时间';
+COMMENT ON COLUMN order_reminders.goal_time IS '提醒针对的期望时间，修改期望时间后可再次提醒';
+COMMENT ON COLUMN order_reminders.sent_at IS '发送时间';
@@ -0,0 +1,14 @@
+-- =========================================================
+-- Migration: Order Item Editing
+-- Date: 2026-10-18
+-- Description:
+-- 1. `orders` 新增 allow_item_edit：接单人允许后，ACCEPTED 订单仍可由下单人修改菜品。
+-- 2. 改菜记录写入 order_status_history（from_status = to_status，remark 描述修改内容）。
+-- =========================================================
+
+BEGIN;
+
+ALTER TABLE orders ADD COLUMN IF NOT EXISTS allow_item_edit BOOLEAN NOT NULL DEFAULT FALSE;
+COMMENT ON COLUMN orders.allow_item_edit IS '接单后是否允许下单人修改菜品（接单人设置）';
+
+COMMIT;
@@ -0,0 +1,31 @@
+-- =========================================================
+-- Migration: Order Templates
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `order_templates`：命名订单模板（如"周日早午餐"），个人或组内共享，
+--    一键下单复用 create_order 的 insert_order 路径。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS order_templates (
+    template_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    items JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE order_templates IS '命名订单模板（个人或组共享）';
+COMMENT ON COLUMN order_templates.template_id IS '模板主键ID';
+COMMENT ON COLUMN order_templates.user_id IS '创建人ID';
+COMMENT ON COLUMN order_templates.group_id IS '所属组ID（为空表示个人模板）';
+COMMENT ON COLUMN order_templates.name IS '模板名称';
+COMMENT ON COLUMN order_templates.items IS '菜品列表 JSON：[{food_id, quantity}]';
+COMMENT ON COLUMN order_templates.created_at IS '创建时间';
+COMMENT ON COLUMN order_templates.updated_at IS '更新时间';
+CREATE INDEX IF NOT EXISTS idx_order_templates_user ON order_templates(user_id);
+CREATE INDEX IF NOT EXISTS idx_order_templates_group ON order_templates(group_id);
+
+COMMIT;
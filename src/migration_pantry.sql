@@ -0,0 +1,54 @@
+-- =========================================================
+-- Migration: Pantry Inventory
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `pantry_items`：组内食材库存（数量、单位、保质期、低库存阈值）。
+-- 2. 新增 `food_pantry_usages`：菜品每份使用的库存食材及用量；订单完成时按份数扣减库存。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS pantry_items (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    unit VARCHAR(16) NOT NULL,
+    expiry_date DATE,
+    low_stock_threshold DOUBLE PRECISION,
+    note VARCHAR(128),
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    updated_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (group_id, name)
+);
+CREATE INDEX IF NOT EXISTS idx_pantry_items_group_expiry ON pantry_items(group_id, expiry_date);
+COMMENT ON TABLE pantry_items IS '组内食材库存';
+COMMENT ON COLUMN pantry_items.id IS '库存主键';
+COMMENT ON COLUMN pantry_items.group_id IS '所属组ID';
+COMMENT ON COLUMN pantry_items.name IS '食材名称（组内唯一）';
+COMMENT ON COLUMN pantry_items.quantity IS '当前数量（按 unit 计）';
+COMMENT ON COLUMN pantry_items.unit IS '库存单位';
+COMMENT ON COLUMN pantry_items.expiry_date IS '保质期截止日期';
+COMMENT ON COLUMN pantry_items.low_stock_threshold IS '低库存提醒阈值，为空不提醒';
+COMMENT ON COLUMN pantry_items.note IS '备注';
+COMMENT ON COLUMN pantry_items.created_by IS '创建人用户ID';
+COMMENT ON COLUMN pantry_items.updated_by IS '最后修改人用户ID';
+COMMENT ON COLUMN pantry_items.created_at IS '创建时间';
+COMMENT ON COLUMN pantry_items.updated_at IS '更新时间';
+CREATE TABLE IF NOT EXISTS food_pantry_usages (
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    item_id BIGINT NOT NULL REFERENCES pantry_items(id) ON DELETE CASCADE,
+    amount DOUBLE PRECISION NOT NULL,
+    unit VARCHAR(16) NOT NULL,
+    PRIMARY KEY (food_id, item_id)
+);
+CREATE INDEX IF NOT EXISTS idx_food_pantry_usages_item ON food_pantry_usages(item_id);
+COMMENT ON TABLE food_pantry_usages IS '菜品每份使用的库存食材';
+COMMENT ON COLUMN food_pantry_usages.food_id IS '菜品ID';
+COMMENT ON COLUMN food_pantry_usages.item_id IS '库存食材ID';
+COMMENT ON COLUMN food_pantry_usages.amount IS '每份用量';
+COMMENT ON COLUMN food_pantry_usages.unit IS '用量单位，需可换算为库存单位';
+
+COMMIT;
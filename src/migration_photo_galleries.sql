@@ -0,0 +1,48 @@
+-- =========================================================
+-- Migration: Photo Galleries
+-- Date: 2026-10-18
+-- Description:
+-- 1. 新增 `photos`：菜品、心愿打卡、订单完成的有序图集，支持封面与图片说明。
+-- 2. `foods.food_photo` / `wish_claim_checkins.photo_url` 保留为封面地址，兼容旧客户端。
+-- 3. 回填：已有单图作为各自图集的封面。
+-- =========================================================
+
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS photos (
+    id BIGSERIAL PRIMARY KEY,
+    owner_type VARCHAR(16) NOT NULL,
+    owner_id BIGINT NOT NULL,
+    url VARCHAR(512) NOT NULL,
+    caption VARCHAR(128),
+    sort INT NOT NULL DEFAULT 0,
+    is_cover BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS idx_photos_owner ON photos(owner_type, owner_id, sort);
+CREATE UNIQUE INDEX IF NOT EXISTS uq_photos_owner_cover ON photos(owner_type, owner_id) WHERE is_cover;
+COMMENT ON TABLE photos IS '图集（菜品/心愿打卡/订单完成照片），按 sort 排序，封面同步到旧单图字段';
+COMMENT ON COLUMN photos.id IS '图片主键';
+COMMENT ON COLUMN photos.owner_type IS '归属类型：FOOD 菜品 / CHECKIN 心愿打卡 / ORDER 订单完成';
+COMMENT ON COLUMN photos.owner_id IS '归属对象ID：food_id / wish_claim_checkins.id / order_id';
+COMMENT ON COLUMN photos.url IS '图片URL（须属于上传域名）';
+COMMENT ON COLUMN photos.caption IS '图片说明';
+COMMENT ON COLUMN photos.sort IS '图集内排序';
+COMMENT ON COLUMN photos.is_cover IS '是否封面（每个图集至多一张）';
+COMMENT ON COLUMN photos.created_by IS '上传人用户ID';
+COMMENT ON COLUMN photos.created_at IS '创建时间';
+
+INSERT INTO photos (owner_type, owner_id, url, sort, is_cover, created_by, created_at)
+SELECT 'FOOD', f.food_id, f.food_photo, 0, TRUE, f.created_by, f.created_at
+FROM foods f
+WHERE f.food_photo IS NOT NULL AND f.food_photo <> ''
+  AND NOT EXISTS (SELECT 1 FROM photos p WHERE p.owner_type='FOOD' AND p.owner_id=f.food_id);
+
+INSERT INTO photos (owner_type, owner_id, url, sort, is_cover, created_by, created_at)
+SELECT 'CHECKIN', c.id, c.photo_url, 0, TRUE, c.user_id, c.created_at
+FROM wish_claim_checkins c
+WHERE c.photo_url IS NOT NULL AND c.photo_url <> ''
+  AND NOT EXISTS (SELECT 1 FROM photos p WHERE p.owner_type='CHECKIN' AND p.owner_id=c.id);
+
+COMMIT;
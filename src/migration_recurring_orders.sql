@@ -0,0 +1,68 @@
+-- =========================================================
+-- Migration: Recurring Orders
+-- Date: 2026-10-18
+-- Description:
+-- 1. Add `recurring_status_enum` / `recurring_run_status_enum`.
+-- 2. Add `recurring_orders` (周期订单模板) and `recurring_order_runs` (每次发生的处理记录).
+-- =========================================================
+
+BEGIN;
+
+CREATE TYPE recurring_status_enum AS ENUM ('ACTIVE', 'PAUSED');
+CREATE TYPE recurring_run_status_enum AS ENUM ('CREATED', 'SKIPPED', 'FAILED');
+
+CREATE TABLE IF NOT EXISTS recurring_orders (
+    recurring_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    title VARCHAR(128),
+    items JSONB NOT NULL,
+    rrule VARCHAR(255) NOT NULL,
+    tz_offset_minutes INT NOT NULL DEFAULT 480,
+    goal_offset_minutes INT NOT NULL DEFAULT 60,
+    points_cost INT NOT NULL DEFAULT 0,
+    points_reward INT NOT NULL DEFAULT 0,
+    status recurring_status_enum NOT NULL DEFAULT 'ACTIVE',
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE recurring_orders IS '周期订单模板';
+COMMENT ON COLUMN recurring_orders.recurring_id IS '周期订单主键ID';
+COMMENT ON COLUMN recurring_orders.user_id IS '下单用户ID';
+COMMENT ON COLUMN recurring_orders.group_id IS '目标关联组ID';
+COMMENT ON COLUMN recurring_orders.title IS '模板名称';
+COMMENT ON COLUMN recurring_orders.items IS '菜品明细JSON [{food_id, quantity}]';
+COMMENT ON COLUMN recurring_orders.rrule IS '周期规则（RRULE子集：FREQ/BYDAY/BYHOUR/BYMINUTE）';
+COMMENT ON COLUMN recurring_orders.tz_offset_minutes IS '规则时区相对UTC的分钟偏移';
+COMMENT ON COLUMN recurring_orders.goal_offset_minutes IS '提前生成订单的分钟数（相对goal_time）';
+COMMENT ON COLUMN recurring_orders.points_cost IS '订单积分成本';
+COMMENT ON COLUMN recurring_orders.points_reward IS '订单奖励积分';
+COMMENT ON COLUMN recurring_orders.status IS '状态：ACTIVE/PAUSED';
+COMMENT ON COLUMN recurring_orders.next_run_at IS '下一次发生时间（即goal_time）';
+COMMENT ON COLUMN recurring_orders.last_run_at IS '最近一次处理的发生时间';
+COMMENT ON COLUMN recurring_orders.created_at IS '创建时间';
+COMMENT ON COLUMN recurring_orders.updated_at IS '更新时间';
+CREATE INDEX IF NOT EXISTS idx_ro_user ON recurring_orders(user_id);
+CREATE INDEX IF NOT EXISTS idx_ro_status_next ON recurring_orders(status, next_run_at);
+CREATE TABLE IF NOT EXISTS recurring_order_runs (
+    id BIGSERIAL PRIMARY KEY,
+    recurring_id BIGINT NOT NULL REFERENCES recurring_orders(recurring_id) ON DELETE CASCADE,
+    occurrence_at TIMESTAMPTZ NOT NULL,
+    status recurring_run_status_enum NOT NULL,
+    order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL,
+    remark VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (recurring_id, occurrence_at)
+);
+COMMENT ON TABLE recurring_order_runs IS '周期订单每次发生的处理记录（生成/跳过/失败）';
+COMMENT ON COLUMN recurring_order_runs.id IS '记录主键ID';
+COMMENT ON COLUMN recurring_order_runs.recurring_id IS '周期订单ID';
+COMMENT ON COLUMN recurring_order_runs.occurrence_at IS '发生时间（goal_time）';
+COMMENT ON COLUMN recurring_order_runs.status IS '处理结果：CREATED/SKIPPED/FAILED';
+COMMENT ON COLUMN recurring_order_runs.order_id IS '生成的订单ID';
+COMMENT ON COLUMN recurring_order_runs.remark IS '备注/失败原因';
+COMMENT ON COLUMN recurring_order_runs.created_at IS '记录创建时间';
+
+COMMIT;
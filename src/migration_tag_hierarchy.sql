@@ -0,0 +1,15 @@
+-- =========================================================
+-- Migration: Tag Hierarchy
+-- Date: 2026-10-18
+-- Description:
+-- 1. `tags` 新增 `parent_id`：父子层级标签（如 中餐 → 川菜），父标签须与子标签同组。
+-- 2. 删除父标签时子标签上移一级（由接口处理，外键兜底置空）。
+-- =========================================================
+
+BEGIN;
+
+ALTER TABLE tags ADD COLUMN IF NOT EXISTS parent_id BIGINT REFERENCES tags(tag_id) ON DELETE SET NULL;
+COMMENT ON COLUMN tags.parent_id IS '父标签ID（同组），为空表示顶级标签';
+CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent_id);
+
+COMMIT;
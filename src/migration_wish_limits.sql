@@ -0,0 +1,26 @@
+-- =========================================================
+-- Migration: Wish Stock Limits
+-- Date: 2026-10-18
+-- Description:
+-- 1. `wishes` 新增兑换限制：总库存、每人周期限额、冷却时间、开放时间窗，均为空表示不限。
+-- 2. 计数只统计未取消的兑换；新增 (wish_id, user_id, created_at) 索引用于限额统计。
+-- =========================================================
+
+BEGIN;
+
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS stock_total INT CHECK (stock_total >= 0);
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS per_user_limit INT CHECK (per_user_limit > 0);
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS limit_period VARCHAR(8);
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS cooldown_minutes INT CHECK (cooldown_minutes > 0);
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS available_from TIMESTAMPTZ;
+ALTER TABLE wishes ADD COLUMN IF NOT EXISTS available_until TIMESTAMPTZ;
+COMMENT ON COLUMN wishes.stock_total IS '总库存（含已兑换，取消的兑换不占用），为空不限';
+COMMENT ON COLUMN wishes.per_user_limit IS '每人每周期可兑换次数，为空不限';
+COMMENT ON COLUMN wishes.limit_period IS '限额周期：DAY/WEEK/MONTH/TOTAL（东八区自然周期）';
+COMMENT ON COLUMN wishes.cooldown_minutes IS '同一用户两次兑换的最小间隔（分钟），为空不限';
+COMMENT ON COLUMN wishes.available_from IS '开放兑换开始时间，为空不限';
+COMMENT ON COLUMN wishes.available_until IS '开放兑换结束时间，为空不限';
+
+CREATE INDEX IF NOT EXISTS idx_wc_wish_user_time ON wish_claims(wish_id, user_id, created_at);
+
+COMMIT;
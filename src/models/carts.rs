@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "cart_status_enum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CartStatusEnum {
+    ACTIVE,
+    SETTLED,
+    CLEARED,
+}
+
+// ================= DTOs =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartItemAddInput {
+    pub food_id: i64,
+    pub quantity: Option<i32>, // default 1，已存在则累加
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartItemUpdateInput {
+    /// 新数量；<=0 视为移除
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartCheckoutInput {
+    pub group_id: Option<i64>,
+    pub invite_code: Option<String>,
+    pub goal_time: Option<DateTime<Utc>>,
+    pub points_cost: Option<i32>,
+    pub points_reward: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartItemOut {
+    pub id: i64,
+    pub food_id: i64,
+    pub food_name: Option<String>,
+    pub food_photo: Option<String>,
+    pub quantity: i32,
+    /// 菜品当前是否可下单（NORMAL + APPROVED 且未删除）
+    pub available: bool,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CartOut {
+    pub cart_id: Option<i64>,
+    pub status: CartStatusEnum,
+    pub items: Vec<CartItemOut>,
+    pub total_quantity: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
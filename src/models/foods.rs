@@ -1,3 +1,4 @@
+use crate::models::photos::PhotoOut;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -71,6 +72,83 @@ impl FoodCategory {
     }
 }
 
+// 饮食/过敏原标签，数据库中以 TEXT[] 存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum DietaryLabelEnum {
+    VEGETARIAN,
+    VEGAN,
+    SPICY,
+    HALAL,
+    CONTAINS_NUTS,
+    CONTAINS_PEANUTS,
+    CONTAINS_DAIRY,
+    CONTAINS_EGG,
+    CONTAINS_GLUTEN,
+    CONTAINS_SEAFOOD,
+    CONTAINS_SOY,
+}
+impl DietaryLabelEnum {
+    pub const ALL: [Self; 11] = [
+        Self::VEGETARIAN,
+        Self::VEGAN,
+        Self::SPICY,
+        Self::HALAL,
+        Self::CONTAINS_NUTS,
+        Self::CONTAINS_PEANUTS,
+        Self::CONTAINS_DAIRY,
+        Self::CONTAINS_EGG,
+        Self::CONTAINS_GLUTEN,
+        Self::CONTAINS_SEAFOOD,
+        Self::CONTAINS_SOY,
+    ];
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::VEGETARIAN => "VEGETARIAN",
+            Self::VEGAN => "VEGAN",
+            Self::SPICY => "SPICY",
+            Self::HALAL => "HALAL",
+            Self::CONTAINS_NUTS => "CONTAINS_NUTS",
+            Self::CONTAINS_PEANUTS => "CONTAINS_PEANUTS",
+            Self::CONTAINS_DAIRY => "CONTAINS_DAIRY",
+            Self::CONTAINS_EGG => "CONTAINS_EGG",
+            Self::CONTAINS_GLUTEN => "CONTAINS_GLUTEN",
+            Self::CONTAINS_SEAFOOD => "CONTAINS_SEAFOOD",
+            Self::CONTAINS_SOY => "CONTAINS_SOY",
+        }
+    }
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|l| l.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+    pub fn zh_label(&self) -> &'static str {
+        match self {
+            Self::VEGETARIAN => "素食",
+            Self::VEGAN => "纯素",
+            Self::SPICY => "辣",
+            Self::HALAL => "清真",
+            Self::CONTAINS_NUTS => "坚果",
+            Self::CONTAINS_PEANUTS => "花生",
+            Self::CONTAINS_DAIRY => "乳制品",
+            Self::CONTAINS_EGG => "蛋类",
+            Self::CONTAINS_GLUTEN => "麸质",
+            Self::CONTAINS_SEAFOOD => "海鲜",
+            Self::CONTAINS_SOY => "大豆",
+        }
+    }
+    // 数据库中的文本数组 -> 枚举（忽略未知值）
+    pub fn from_db(labels: &[String]) -> Vec<Self> {
+        labels.iter().filter_map(|s| Self::parse(s)).collect()
+    }
+    pub fn to_db(labels: &[Self]) -> Vec<String> {
+        let mut out: Vec<String> = Vec::with_capacity(labels.len());
+        for l in labels {
+            if !out.iter().any(|s| s == l.as_str()) {
+                out.push(l.as_str().to_string());
+            }
+        }
+        out
+    }
+}
+
 // ================= Core DB Row Representations =================
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
@@ -94,6 +172,12 @@ pub struct FoodRecord {
     pub is_del: i16,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // 营养（每份）与饮食标签
+    pub calories: Option<i32>,
+    pub protein: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbs: Option<f64>,
+    pub dietary_labels: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
@@ -105,12 +189,6 @@ pub struct TagRecord {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
-pub struct FoodTagMapRecord {
-    pub food_id: i64,
-    pub tag_id: i64,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct FoodTagOut {
     pub tag_id: i64,
@@ -129,7 +207,14 @@ pub struct FoodOut {
     pub apply_status: ApplyStatusEnum,
     pub submit_role: SubmitRoleEnum,
     pub apply_remark: Option<String>,
-    pub tag: Option<FoodTagOut>,
+    pub tag: Option<FoodTagOut>, // 主标签（兼容旧客户端）
+    pub tags: Vec<FoodTagOut>,   // 全部标签，主标签在前
+    // 营养（每份）：热量 kcal，蛋白质/脂肪/碳水 g
+    pub calories: Option<i32>,
+    pub protein: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbs: Option<f64>,
+    pub dietary_labels: Vec<DietaryLabelEnum>,
     pub is_marked_like: bool,
     pub is_marked_not_recommend: bool,
     // 统计字段（来自 food_stats 缓存表）
@@ -137,6 +222,14 @@ pub struct FoodOut {
     pub completed_order_count: i32,
     pub last_order_time: Option<DateTime<Utc>>,
     pub last_complete_time: Option<DateTime<Utc>>,
+    // 菜品评价（1-5星）汇总；无评价时平均分为空
+    pub review_count: i32,
+    pub review_avg: Option<f64>,
+    // 结构化食材与步骤（仅详情接口填充）
+    pub ingredient_items: Vec<FoodIngredientOut>,
+    pub step_items: Vec<FoodStepOut>,
+    // 图集（仅详情接口填充；food_photo 为封面）
+    pub photos: Vec<PhotoOut>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -162,12 +255,23 @@ impl From<(FoodRecord, Option<TagRecord>, Vec<MarkTypeEnum>)> for FoodOut {
                 tag_id: t.tag_id,
                 tag_name: t.tag_name,
             }),
+            tags: Vec::new(),
+            calories: f.calories,
+            protein: f.protein,
+            fat: f.fat,
+            carbs: f.carbs,
+            dietary_labels: DietaryLabelEnum::from_db(&f.dietary_labels),
             is_marked_like: like,
             is_marked_not_recommend: not_rec,
             total_order_count: 0,
             completed_order_count: 0,
             last_order_time: None,
             last_complete_time: None,
+            review_count: 0,
+            review_avg: None,
+            ingredient_items: Vec::new(),
+            step_items: Vec::new(),
+            photos: Vec::new(),
             created_at: f.created_at,
             updated_at: f.updated_at,
         }
@@ -196,10 +300,17 @@ pub struct FoodWithStatsRecord {
     pub is_del: i16,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub calories: Option<i32>,
+    pub protein: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbs: Option<f64>,
+    pub dietary_labels: Vec<String>,
     pub total_order_count: Option<i32>,
     pub completed_order_count: Option<i32>,
     pub last_order_time: Option<DateTime<Utc>>,
     pub last_complete_time: Option<DateTime<Utc>>,
+    pub review_count: Option<i32>,
+    pub review_star_sum: Option<i32>,
 }
 
 impl FoodOut {
@@ -224,12 +335,26 @@ impl FoodOut {
                 tag_id: t.tag_id,
                 tag_name: t.tag_name,
             }),
+            tags: Vec::new(),
+            calories: row.calories,
+            protein: row.protein,
+            fat: row.fat,
+            carbs: row.carbs,
+            dietary_labels: DietaryLabelEnum::from_db(&row.dietary_labels),
             is_marked_like: like,
             is_marked_not_recommend: not_rec,
             total_order_count: row.total_order_count.unwrap_or(0),
             completed_order_count: row.completed_order_count.unwrap_or(0),
             last_order_time: row.last_order_time,
             last_complete_time: row.last_complete_time,
+            review_count: row.review_count.unwrap_or(0),
+            review_avg: match (row.review_count, row.review_star_sum) {
+                (Some(n), Some(sum)) if n > 0 => Some((sum as f64 / n as f64 * 10.0).round() / 10.0),
+                _ => None,
+            },
+            ingredient_items: Vec::new(),
+            step_items: Vec::new(),
+            photos: Vec::new(),
             created_at: row.created_at,
             updated_at: row.updated_at,
         }
@@ -245,8 +370,16 @@ pub struct FoodCreateInput {
     // food_types removed
     pub ingredients: Option<String>,
     pub steps: Option<String>,
-    pub tag_id: Option<i64>, // 关联标签
+    pub tag_id: Option<i64>, // 关联标签（旧字段，单标签）
+    pub tag_ids: Option<Vec<i64>>, // 多标签，优先于 tag_id；第一个为主标签
     pub group_id: Option<i64>,     // 归属组（可选）
+    pub ingredient_items: Option<Vec<FoodIngredientInput>>, // 结构化食材（提供时覆盖 ingredients 文本）
+    pub step_items: Option<Vec<FoodStepInput>>,             // 结构化步骤（提供时覆盖 steps 文本）
+    pub calories: Option<i32>,                              // 每份热量 kcal
+    pub protein: Option<f64>,                               // 每份蛋白质 g
+    pub fat: Option<f64>,                                   // 每份脂肪 g
+    pub carbs: Option<f64>,                                 // 每份碳水 g
+    pub dietary_labels: Option<Vec<DietaryLabelEnum>>,      // 饮食/过敏原标签
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -255,12 +388,18 @@ pub struct FoodUpdateInput {
     pub food_name: Option<String>,
     pub food_photo: Option<String>,
     // food_types removed
-    pub ingredients: Option<String>,
-    pub steps: Option<String>,
+    pub ingredients: Option<String>, // 文本有变化时清除结构化食材
+    pub steps: Option<String>,       // 文本有变化时清除结构化步骤
     pub tag_id: Option<i64>,
+    pub tag_ids: Option<Vec<i64>>, // 整体替换标签，优先于 tag_id
     pub apply_remark: Option<String>,
     pub food_status: Option<FoodStatusEnum>,
     pub apply_status: Option<ApplyStatusEnum>,
+    pub calories: Option<i32>,
+    pub protein: Option<f64>,
+    pub fat: Option<f64>,
+    pub carbs: Option<f64>,
+    pub dietary_labels: Option<Vec<DietaryLabelEnum>>, // 整体替换
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -268,6 +407,52 @@ pub struct TagCreateInput {
     pub tag_name: String,
     pub group_id: Option<i64>,
     pub sort: Option<i32>,
+    /// 父标签ID，须与新标签同组
+    pub parent_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagUpdateInput {
+    /// 重命名；组内已有同名标签时请使用合并
+    pub tag_name: Option<String>,
+    pub sort: Option<i32>,
+    /// 移动到该父标签下
+    pub parent_id: Option<i64>,
+    /// 为 true 时移为顶级标签
+    pub clear_parent: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagReorderInput {
+    /// 同组标签ID，按新顺序排列（越靠前 sort 越大）
+    pub tag_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagMergeInput {
+    /// 合并目标标签，须与源标签同组
+    pub target_tag_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::IntoParams)]
+pub struct TagDeleteQuery {
+    /// 将使用该标签的菜品改挂到此标签（同组）；不传则仅移除
+    pub reassign_to: Option<i64>,
+}
+
+// 标签列表项：按层级先序排列，depth 从 0 开始
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TagOut {
+    pub tag_id: i64,
+    pub tag_name: String,
+    pub group_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    pub depth: i32,
+    pub sort: Option<i32>,
+    /// 直接使用该标签的菜品数
+    pub food_count: i64,
+    /// 含子孙标签的菜品数（去重）
+    pub total_food_count: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -278,9 +463,416 @@ pub struct FoodFilterQuery {
     pub submit_role: Option<SubmitRoleEnum>,
     // category removed
     pub tag_id: Option<i64>,
+    /// 多标签过滤，逗号分隔
+    pub tag_ids: Option<String>,
+    /// 多标签匹配方式：ANY 命中任一（默认）/ ALL 全部命中
+    pub tag_match: Option<TagMatchEnum>,
     pub group_id: Option<i64>,
     pub only_active: Option<bool>,
     pub created_by: Option<i64>,
+    /// 需同时具备的饮食标签，逗号分隔（如 VEGETARIAN,SPICY）
+    pub labels: Option<String>,
+    /// 排除含任一标签的菜品，逗号分隔（如 CONTAINS_NUTS）
+    pub exclude_labels: Option<String>,
+    /// 每份热量上限 kcal
+    pub max_calories: Option<i32>,
+    /// 每份蛋白质下限 g
+    pub min_protein: Option<f64>,
+    /// 排序方式，默认 CREATED_DESC
+    pub sort: Option<FoodSortEnum>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum FoodSortEnum {
+    /// 最新创建在前
+    CREATED_DESC,
+    /// 评价平均分高在前，同分按评价数；无评价的排在最后
+    RATING_DESC,
+}
+
+// ================ 结构化菜谱 DTOs ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodIngredientInput {
+    pub name: String,
+    /// 用量，为空表示适量
+    pub quantity: Option<f64>,
+    /// 单位，如 g / kg / 斤 / ml / 个
+    pub unit: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FoodIngredientOut {
+    pub id: i64,
+    pub name: String,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub note: Option<String>,
+    pub sort: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodStepInput {
+    pub content: String,
+    pub photo_url: Option<String>,
+    /// 计时（秒）
+    pub timer_seconds: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FoodStepOut {
+    pub id: i64,
+    pub step_no: i32,
+    pub content: String,
+    pub photo_url: Option<String>,
+    pub timer_seconds: Option<i32>,
+}
+
+// 整体替换；未提供的部分保持不变
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRecipeInput {
+    pub ingredients: Option<Vec<FoodIngredientInput>>,
+    pub steps: Option<Vec<FoodStepInput>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRecipeOut {
+    pub food_id: i64,
+    pub ingredients: Vec<FoodIngredientOut>,
+    pub steps: Vec<FoodStepOut>,
+    /// 兼容旧客户端的文本形式
+    pub ingredients_text: Option<String>,
+    pub steps_text: Option<String>,
+}
+
+// ================ 审核 DTOs ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodAuditInput {
+    /// 审核备注；拒绝与要求修改时必填
+    pub remark: Option<String>,
+    /// 仅拒绝时有效：同时将内容回退到最近一次审核通过的修订版本
+    pub revert: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FoodAuditLogOut {
+    pub id: i64,
+    pub food_id: i64,
+    /// 1提交 2通过 3拒绝 4要求修改 5回退到已通过版本
+    pub action: i16,
+    pub from_status: Option<ApplyStatusEnum>,
+    pub to_status: ApplyStatusEnum,
+    pub acted_by: i64,
+    pub actor_name: Option<String>,
+    pub remark: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ================ 修订历史 DTOs ==================
+
+// 修订来源，数据库中以 VARCHAR 存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum FoodRevisionSourceEnum {
+    CREATE,
+    UPDATE,
+    AUDIT,
+    RESTORE,
+    IMPORT,
+}
+impl FoodRevisionSourceEnum {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::CREATE => "CREATE",
+            Self::UPDATE => "UPDATE",
+            Self::AUDIT => "AUDIT",
+            Self::RESTORE => "RESTORE",
+            Self::IMPORT => "IMPORT",
+        }
+    }
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "CREATE" => Self::CREATE,
+            "AUDIT" => Self::AUDIT,
+            "RESTORE" => Self::RESTORE,
+            "IMPORT" => Self::IMPORT,
+            _ => Self::UPDATE,
+        }
+    }
+}
+
+// 某一修订后的完整内容快照（food_revisions.snapshot）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRevisionSnapshot {
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub ingredients: Option<String>,
+    pub steps: Option<String>,
+    #[serde(default)]
+    pub ingredient_items: Vec<FoodIngredientInput>,
+    #[serde(default)]
+    pub step_items: Vec<FoodStepInput>,
+    /// 第一个为主标签
+    #[serde(default)]
+    pub tags: Vec<FoodTagOut>,
+    pub food_status: FoodStatusEnum,
+    pub apply_status: ApplyStatusEnum,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRevisionOut {
+    pub revision_no: i32,
+    pub source: FoodRevisionSourceEnum,
+    /// 相对上一修订变化的字段
+    pub changed_fields: Vec<String>,
+    pub audit_log_id: Option<i64>,
+    pub created_by: Option<i64>,
+    pub creator_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub snapshot: FoodRevisionSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::IntoParams)]
+pub struct FoodRevisionDiffQuery {
+    /// 对比起点修订号，默认为 to 的上一修订
+    pub from: Option<i32>,
+    /// 对比终点修订号，默认最新修订
+    pub to: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRevisionFieldDiff {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRevisionDiffOut {
+    pub food_id: i64,
+    pub from_revision: Option<i32>,
+    pub to_revision: i32,
+    pub changes: Vec<FoodRevisionFieldDiff>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRevisionRestoreInput {
+    pub remark: Option<String>,
+}
+
+// 待审核队列条目
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FoodReviewOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub created_by: i64,
+    pub submitter_name: Option<String>,
+    pub apply_remark: Option<String>,
+    /// 最近一次审核动作及备注
+    pub last_action: Option<i16>,
+    pub last_remark: Option<String>,
+    /// 已要求修改、等待提交人更新
+    pub awaiting_changes: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodStatsRebuildOut {
+    /// 重建的菜品数
+    pub foods: u64,
+    pub elapsed_ms: u64,
+}
+
+// ================ 搜索 DTOs ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct FoodSearchQuery {
+    /// 关键词（名称/食材/步骤，支持中文与错字）
+    pub q: String,
+    pub group_id: Option<i64>,
+    /// 仅返回可点菜品
+    pub only_active: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodSearchHitOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub food_status: FoodStatusEnum,
+    pub apply_status: ApplyStatusEnum,
+    /// 相关度得分，越大越相关
+    pub score: f32,
+    /// 名称高亮（命中部分以 <em> 包裹，其余已转义）
+    pub name_highlight: String,
+    /// 食材或步骤中的命中片段
+    pub snippet: Option<String>,
+    /// 片段来源：ingredients / steps
+    pub snippet_field: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct FoodSuggestQuery {
+    pub q: String,
+    pub group_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodSuggestOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub name_highlight: String,
+}
+
+// ================ 导入/导出 DTOs ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodBundleTag {
+    pub tag_name: String,
+    pub sort: Option<i32>,
+    /// 公共标签（不属于任何组）
+    pub global: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodBundleItem {
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub ingredients: Option<String>,
+    pub steps: Option<String>,
+    #[serde(default)]
+    pub ingredient_items: Vec<FoodIngredientInput>,
+    #[serde(default)]
+    pub step_items: Vec<FoodStepInput>,
+    /// 标签名称，第一个为主标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub calories: Option<i32>,
+    #[serde(default)]
+    pub protein: Option<f64>,
+    #[serde(default)]
+    pub fat: Option<f64>,
+    #[serde(default)]
+    pub carbs: Option<f64>,
+    #[serde(default)]
+    pub dietary_labels: Vec<DietaryLabelEnum>,
+}
+
+// 可移植的菜品包：按名称引用标签，不含任何ID
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodBundle {
+    pub version: i32,
+    pub exported_at: DateTime<Utc>,
+    pub source_group_id: Option<i64>,
+    #[serde(default)]
+    pub tags: Vec<FoodBundleTag>,
+    pub foods: Vec<FoodBundleItem>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum ImportDuplicateEnum {
+    SKIP,
+    RENAME,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodImportInput {
+    pub bundle: FoodBundle,
+    /// 仅预演并返回冲突报告，默认 true
+    pub dry_run: Option<bool>,
+    /// 缺失的标签自动在目标组创建，默认 true；否则丢弃该标签
+    pub create_missing_tags: Option<bool>,
+    /// 同名菜品处理方式，默认 SKIP
+    pub on_duplicate: Option<ImportDuplicateEnum>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodImportConflictOut {
+    pub food_name: Option<String>,
+    /// DUPLICATE_NAME / MISSING_TAG / INVALID_TAG / TOO_MANY_TAGS / INVALID / INVALID_PHOTO
+    pub kind: String,
+    pub detail: String,
+    /// SKIPPED / RENAMED / TAG_CREATED / TAG_DROPPED / PHOTO_DROPPED
+    pub resolution: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodImportedOut {
+    /// 预演时为空
+    pub food_id: Option<i64>,
+    pub food_name: String,
+    pub source_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodImportReportOut {
+    /// 为 true 时本次未写入任何数据，报告仅为预演结果
+    pub dry_run: bool,
+    pub imported: Vec<FoodImportedOut>,
+    pub skipped_count: usize,
+    pub created_tags: Vec<String>,
+    pub conflicts: Vec<FoodImportConflictOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodCopyInput {
+    /// 目标组（需为成员）
+    pub group_id: i64,
+    /// 新名称；为空沿用原名，目标组已有同名时报错
+    pub food_name: Option<String>,
+}
+
+// ================ 推荐 DTOs ==================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct FoodRecommendQuery {
+    /// 组ID，缺省取用户的主组
+    pub group_id: Option<i64>,
+    /// 餐段，缺省按当前本地时间推断
+    pub meal: Option<FoodCategory>,
+    /// 本地时区相对UTC的分钟偏移，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecommendFactorOut {
+    /// MY_LIKE / PARTNER_LIKE / PARTNER_NOT_RECOMMEND / POPULAR / MEAL_MATCH / RECENT_REPEAT / MISSED / NEW
+    pub code: String,
+    pub label: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRecommendationOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    pub score: f64,
+    /// 主理由（权重最高的正向因素）
+    pub reason: String,
+    pub factors: Vec<RecommendFactorOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodRecommendationsOut {
+    pub group_id: i64,
+    pub meal: FoodCategory,
+    pub meal_label: String,
+    pub items: Vec<FoodRecommendationOut>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum TagMatchEnum {
+    ANY,
+    ALL,
 }
 
 // ================ 收藏/标记 DTOs ==================
@@ -305,10 +897,13 @@ pub struct BlindBoxDrawInput {
     pub group_id: Option<i64>,         // 若为空则按用户所属主 group
     pub tag_ids: Vec<i64>,             // 抽取的标签ID列表
     pub limit_each: Option<u32>,       // 每个类型最多抽取数量
+    pub avoid_recent_draws: Option<u32>, // 避开最近 N 次抽中的菜品，默认 3
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BlindBoxFoodSnapshot {
+    pub result_id: i64, // lottery_draw_results.id，重抽时指定
+    pub tag_id: Option<i64>, // 抽中时所属标签
     pub food_id: i64,
     pub food_name: String,
     pub food_photo: Option<String>,
@@ -317,6 +912,39 @@ pub struct BlindBoxFoodSnapshot {
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct BlindBoxDrawResultOut {
+    pub draw_id: i64,
+    pub parent_draw_id: Option<i64>, // 重抽来源
+    pub points_cost: i32,            // 本次消耗积分（重抽）
     pub results: Vec<BlindBoxFoodSnapshot>,
     pub requested_tags: Vec<i64>,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BlindBoxRerollInput {
+    /// 需要重抽的结果ID，为空则整体重抽；其余结果原样保留
+    pub result_ids: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct BlindBoxDrawHistoryQuery {
+    pub group_id: Option<i64>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BlindBoxDrawOut {
+    pub draw_id: i64,
+    pub group_id: Option<i64>,
+    pub parent_draw_id: Option<i64>,
+    pub points_cost: i32,
+    /// 已转为订单时的订单ID
+    pub order_id: Option<i64>,
+    /// 被重抽取代的时间；取代后不可再重抽或下单
+    pub superseded_at: Option<DateTime<Utc>>,
+    pub success: bool,
+    pub fail_reason: Option<String>,
+    pub requested_tags: Vec<i64>,
+    pub results: Vec<BlindBoxFoodSnapshot>,
+    pub created_at: DateTime<Utc>,
+}
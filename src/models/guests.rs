@@ -0,0 +1,183 @@
+use crate::{errors::CustomError, utils::TOKEN_SECRET_KEY, AppState};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use ntex::{
+    http::Payload,
+    web::{ErrorRenderer, FromRequest, HttpRequest},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::{env, future::Future, sync::Arc};
+use utoipa::ToSchema;
+
+use super::orders::OrderItemCreateInput;
+
+pub const GUEST_SCOPE: &str = "guest";
+
+// ========== 输入 DTO ==========
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuestSessionCreateInput {
+    pub invite_code: String,
+    /// 访客昵称（展示给组内成员）
+    pub nick_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuestOrderCreateInput {
+    pub items: Vec<OrderItemCreateInput>,
+    pub goal_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuestConvertInput {
+    /// 合并到已注册账号（仅限本人或已接受本人绑定邀请的账号）；为空时把访客账号升级为正式成员并生成临时密码
+    pub user_id: Option<i64>,
+}
+
+// ========== 输出 DTO ==========
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuestSessionOut {
+    pub token: String,
+    pub session_id: i64,
+    pub guest_user_id: i64,
+    pub group_id: i64,
+    pub group_name: Option<String>,
+    pub nick_name: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct GuestOut {
+    pub session_id: i64,
+    pub guest_user_id: i64,
+    pub nick_name: String,
+    pub order_count: i64,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub converted_at: Option<DateTime<Utc>>,
+    pub converted_user_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GuestConvertOut {
+    /// 转正后的成员用户ID
+    pub user_id: i64,
+    pub username: String,
+    /// 仅升级访客账号时返回，需转交访客登录后修改
+    pub temp_password: Option<String>,
+    /// 迁移到该成员名下的订单数
+    pub moved_orders: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct GuestConfig {
+    /// 访客令牌有效期（分钟）
+    pub ttl_minutes: i64,
+    /// 领取令牌的限流窗口（秒）
+    pub rate_window_seconds: usize,
+    /// 窗口内同一 IP 最多领取次数
+    pub rate_limit_per_ip: i64,
+    /// 窗口内同一邀请码最多领取次数
+    pub rate_limit_per_code: i64,
+}
+
+impl GuestConfig {
+    pub fn from_env() -> Self {
+        let ttl_minutes = env::var("GUEST_TOKEN_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(720);
+        let env_or = |name: &str, default: i64| {
+            env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            ttl_minutes,
+            rate_window_seconds: env_or("GUEST_SESSION_RATE_WINDOW_SECONDS", 600) as usize,
+            rate_limit_per_ip: env_or("GUEST_SESSION_RATE_LIMIT_PER_IP", 5),
+            rate_limit_per_code: env_or("GUEST_SESSION_RATE_LIMIT_PER_CODE", 30),
+        }
+    }
+}
+
+// ========== Token Claims ==========
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuestTokenClaims {
+    pub exp: i64,
+    pub user_id: i64,
+    pub scope: String,
+    pub group_id: i64,
+    pub session_id: i64,
+}
+
+/// 访客身份：仅能在所属组内下单并查看自己的订单
+#[derive(Debug, Clone)]
+pub struct GuestToken {
+    pub exp: i64,
+    pub user_id: i64,
+    pub group_id: i64,
+    pub session_id: i64,
+    pub invite_code: String,
+}
+
+impl<E: ErrorRenderer> FromRequest<E> for GuestToken {
+    type Error = CustomError;
+
+    fn from_request(
+        req: &HttpRequest,
+        _: &mut Payload,
+    ) -> impl Future<Output = Result<Self, Self::Error>> {
+        let state = req.app_state::<Arc<AppState>>().expect("app state").clone();
+        let auth_header = req.headers().get("Authorization").cloned();
+
+        async move {
+            let mut raw = auth_header
+                .ok_or_else(|| CustomError::AuthFailed("No login authorization".into()))?
+                .to_str()
+                .map_err(|_| CustomError::AuthFailed("Invalid header".into()))?
+                .to_string();
+            if let Some(stripped) = raw.strip_prefix("Bearer ") {
+                raw = stripped.trim().to_string();
+            }
+
+            let decoding_key = DecodingKey::from_secret(TOKEN_SECRET_KEY);
+            let validation = Validation::new(Algorithm::HS256);
+            let data = decode::<GuestTokenClaims>(&raw, &decoding_key, &validation).map_err(|e| {
+                CustomError::AuthFailed(format!("decode guest token error: {}", e))
+            })?;
+            let claims = data.claims;
+            if claims.scope != GUEST_SCOPE {
+                return Err(CustomError::AuthFailed("不是访客令牌".into()));
+            }
+
+            // 会话被撤销 / 已转正 / 过期后令牌立即失效
+            let row = sqlx::query(
+                "SELECT invite_code FROM guest_sessions \
+                 WHERE session_id=$1 AND user_id=$2 AND group_id=$3 \
+                 AND revoked_at IS NULL AND converted_at IS NULL AND expires_at > NOW()",
+            )
+            .bind(claims.session_id)
+            .bind(claims.user_id)
+            .bind(claims.group_id)
+            .fetch_optional(&state.db_pool)
+            .await?;
+            let invite_code: String = match row {
+                Some(r) => r.get("invite_code"),
+                None => return Err(CustomError::AuthFailed("访客会话已失效".into())),
+            };
+
+            Ok(GuestToken {
+                exp: claims.exp,
+                user_id: claims.user_id,
+                group_id: claims.group_id,
+                session_id: claims.session_id,
+                invite_code,
+            })
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::models::foods::FoodCategory;
+use crate::models::orders::{OrderPlaceInput, PlacedOrderOut, SkippedFoodOut};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// ================= DTOs =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct MealPlanQuery {
+    /// 起始日期，默认本周一（按 tz_offset_minutes 计算）
+    pub start_date: Option<NaiveDate>,
+    /// 天数，默认7，最大31
+    pub days: Option<i64>,
+    /// 本地时区相对UTC的分钟偏移，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanEntryInput {
+    pub plan_date: NaiveDate,
+    pub meal: FoodCategory,
+    pub food_id: i64,
+    pub quantity: Option<i32>, // default 1
+    pub note: Option<String>,
+}
+
+// 未提供的字段保持不变；修改日期/餐段即移动到其他格子
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanEntryUpdateInput {
+    pub plan_date: Option<NaiveDate>,
+    pub meal: Option<FoodCategory>,
+    pub food_id: Option<i64>,
+    pub quantity: Option<i32>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanSwapInput {
+    pub entry_id: i64,
+    /// 与另一条目交换日期/餐段；为空时为该条目重新抽取一道菜
+    pub with_entry_id: Option<i64>,
+    /// 重新抽取时用于判断历史点单餐段，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanFillInput {
+    /// 起始日期，默认本周一
+    pub start_date: Option<NaiveDate>,
+    /// 天数，默认7，最大31
+    pub days: Option<i64>,
+    /// 需要填充的餐段，默认早餐/午餐/下午茶/晚餐
+    pub meals: Option<Vec<FoodCategory>>,
+    /// 每个餐段的菜品数，默认1，最大5
+    pub per_slot: Option<usize>,
+    /// 覆盖已有（未下单）条目，默认 false 仅填充空餐段
+    pub overwrite: Option<bool>,
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanDayOrderInput {
+    /// 仅下单这些餐段，默认当天全部未下单餐段
+    pub meals: Option<Vec<FoodCategory>>,
+    /// 本地时区相对UTC的分钟偏移，用于计算各餐段期望时间，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+    /// 下单参数；goal_time 为空时按餐段默认时间（早8 午12 下午茶15 晚18 夜宵22）。
+    /// points_cost / points_reward 仅在只下单一个餐段时可用
+    #[serde(flatten)]
+    pub order: OrderPlaceInput,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanEntryOut {
+    pub entry_id: i64,
+    pub food_id: i64,
+    pub food_name: Option<String>,
+    pub food_photo: Option<String>,
+    pub quantity: i32,
+    pub note: Option<String>,
+    /// 菜品当前是否可下单（NORMAL + APPROVED 且未删除）
+    pub available: bool,
+    pub order_id: Option<i64>,
+    pub updated_by: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanSlotOut {
+    pub meal: FoodCategory,
+    pub meal_label: String,
+    pub entries: Vec<MealPlanEntryOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanDayOut {
+    pub date: NaiveDate,
+    pub slots: Vec<MealPlanSlotOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanOut {
+    pub group_id: i64,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub days: Vec<MealPlanDayOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanOrderOut {
+    pub meal: FoodCategory,
+    pub placed: PlacedOrderOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanDayOrderOut {
+    pub date: NaiveDate,
+    pub orders: Vec<MealPlanOrderOut>,
+    /// 菜品全部不可点、未生成订单的餐段；其条目保持未下单
+    pub skipped_meals: Vec<MealPlanSkippedMealOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanSkippedMealOut {
+    pub meal: FoodCategory,
+    pub skipped: Vec<SkippedFoodOut>,
+}
@@ -5,6 +5,13 @@ pub mod foods;
 pub mod orders;
 pub mod wishes;
 pub mod dashboard;
+pub mod carts;
+pub mod guests;
+pub mod meal_plans;
+pub mod pantry;
+pub mod photos;
+pub mod reviews;
+pub mod pagination;
 
 pub mod game_im;
 pub mod game_ws;
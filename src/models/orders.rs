@@ -1,4 +1,5 @@
 // ================= New Order Models (Refactored to new PostgreSQL schema) =================
+use crate::models::users::AllergenWarningOut;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -14,6 +15,8 @@ pub enum OrderStatusEnum {
     EXPIRED,
     REJECTED,
     SYSTEM_CLOSED,
+    /// 已接单但超过 goal_time 仍未完成（由后台任务标记）
+    OVERDUE,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
@@ -88,11 +91,32 @@ pub struct OrderQuery {
     pub user_id: Option<i64>,     // 下单人过滤
     pub group_id: Option<i64>,    // 组过滤
     pub status: Option<OrderStatusEnum>,
+    /// 多状态过滤，逗号分隔，如 `PENDING,ACCEPTED`；与 status 同时出现时取并集
+    pub statuses: Option<String>,
+    pub receiver_id: Option<i64>, // 接单人过滤
+    /// 包含该菜品的订单
+    pub food_id: Option<i64>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub goal_from: Option<DateTime<Utc>>,
+    pub goal_to: Option<DateTime<Utc>>,
+    /// 排序方式，默认 CREATED_DESC
+    pub sort: Option<OrderSortEnum>,
+    /// 上一页返回的 next_cursor
+    pub cursor: Option<String>,
     pub limit: Option<i64>,
     /// 仅返回已经失效(状态=EXPIRED， CANCELLED， REJECTED， SYSTEM_CLOSED)的订单；与 status 同时出现时优先 status
     pub expired_only: Option<bool>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum OrderSortEnum {
+    CREATED_DESC,
+    CREATED_ASC,
+    GOAL_TIME_ASC,
+    GOAL_TIME_DESC,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OrderItemOut {
     pub id: i64,
@@ -134,6 +158,8 @@ pub struct OrderOutNew {
     pub group_info: Option<GroupInfoSimple>,
     pub receiver_nick_name: Option<String>,
     pub receiver_avatar: Option<String>,
+    /// 下单人设置为“提醒”的过敏原命中项（仅创建与改菜时返回）
+    pub allergen_warnings: Vec<AllergenWarningOut>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -166,6 +192,7 @@ impl From<(OrderRecord, Vec<OrderItemOut>, Vec<OrderStatusHistoryOut>)> for Orde
             group_info: None, // Will be filled by query
             receiver_nick_name: None, // Will be filled by query
             receiver_avatar: None, // Will be filled by query
+            allergen_warnings: Vec::new(),
         }
     }
 }
@@ -197,6 +224,7 @@ impl OrderStatusEnum {
         match (self, to) {
             (PENDING, ACCEPTED | REJECTED | CANCELLED | EXPIRED) => true,
             (ACCEPTED, FINISHED | CANCELLED | REJECTED) => true,
+            (OVERDUE, FINISHED | CANCELLED) => true,
             (REJECTED, _) => false,
             (FINISHED, _) => false,
             (CANCELLED, _) => false,
@@ -209,3 +237,277 @@ impl OrderStatusEnum {
 
 // ================= Deprecated (Removed Old Structures) =================
 // 原有旧结构全部移除，若需要兼容可在此添加 #[deprecated] stub。
+
+// ================= Recurring Orders (周期订单) =================
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "recurring_status_enum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecurringStatusEnum {
+    ACTIVE,
+    PAUSED,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "recurring_run_status_enum", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RecurringRunStatusEnum {
+    CREATED,
+    SKIPPED,
+    FAILED,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecurringOrderCreateInput {
+    pub title: Option<String>,
+    pub group_id: Option<i64>,
+    pub items: Vec<OrderItemCreateInput>,
+    /// RRULE 子集，如 `FREQ=WEEKLY;BYDAY=FR;BYHOUR=19;BYMINUTE=0`；规则时间即订单 goal_time
+    pub rrule: String,
+    /// 规则所用时区相对 UTC 的分钟偏移，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+    /// 提前多少分钟生成订单，默认 60，最少 5
+    pub goal_offset_minutes: Option<i32>,
+    pub points_cost: Option<i32>,
+    pub points_reward: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecurringSkipInput {
+    /// 要跳过的发生时间（goal_time），为空则跳过下一次
+    pub occurrence_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecurringOrderRecord {
+    pub recurring_id: i64,
+    pub user_id: i64,
+    pub group_id: Option<i64>,
+    pub title: Option<String>,
+    pub items: sqlx::types::Json<Vec<OrderItemCreateInput>>,
+    pub rrule: String,
+    pub tz_offset_minutes: i32,
+    pub goal_offset_minutes: i32,
+    pub points_cost: i32,
+    pub points_reward: i32,
+    pub status: RecurringStatusEnum,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct RecurringRunOut {
+    pub occurrence_at: DateTime<Utc>,
+    pub status: RecurringRunStatusEnum,
+    pub order_id: Option<i64>,
+    pub remark: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecurringOrderOut {
+    pub recurring_id: i64,
+    pub user_id: i64,
+    pub group_id: Option<i64>,
+    pub title: Option<String>,
+    pub items: Vec<OrderItemCreateInput>,
+    pub rrule: String,
+    pub tz_offset_minutes: i32,
+    pub goal_offset_minutes: i32,
+    pub points_cost: i32,
+    pub points_reward: i32,
+    pub status: RecurringStatusEnum,
+    /// 下一次 goal_time
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// 最近的生成/跳过记录
+    pub recent_runs: Vec<RecurringRunOut>,
+}
+
+impl From<RecurringOrderRecord> for RecurringOrderOut {
+    fn from(r: RecurringOrderRecord) -> Self {
+        Self {
+            recurring_id: r.recurring_id,
+            user_id: r.user_id,
+            group_id: r.group_id,
+            title: r.title,
+            items: r.items.0,
+            rrule: r.rrule,
+            tz_offset_minutes: r.tz_offset_minutes,
+            goal_offset_minutes: r.goal_offset_minutes,
+            points_cost: r.points_cost,
+            points_reward: r.points_reward,
+            status: r.status,
+            next_run_at: r.next_run_at,
+            last_run_at: r.last_run_at,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+            recent_runs: Vec::new(),
+        }
+    }
+}
+
+// ================= Order Item Editing (待处理订单改菜) =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderItemQuantityInput {
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderItemEditPermissionInput {
+    /// 接单后是否允许下单人继续修改菜品
+    pub allow: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderItemEditPermissionOut {
+    pub order_id: i64,
+    pub allow_item_edit: bool,
+}
+
+// ================= Order Comments (订单评论) =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderCommentInput {
+    pub content: String,
+    /// 七牛上传后的图片地址（可选）
+    pub photo_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct OrderCommentQuery {
+    /// 上一页返回的 next_cursor
+    pub cursor: Option<String>,
+    /// 返回条数，默认20，最大100
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
+pub struct OrderCommentOut {
+    pub comment_id: i64,
+    pub order_id: i64,
+    pub user_id: i64,
+    pub nick_name: Option<String>,
+    pub avatar: Option<String>,
+    pub content: String,
+    pub photo_url: Option<String>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ================= Reorder & Order Templates (再来一单 / 订单模板) =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderPlaceInput {
+    /// 下单组；为空时沿用原订单/模板的组
+    pub group_id: Option<i64>,
+    pub invite_code: Option<String>,
+    pub goal_time: Option<DateTime<Utc>>,
+    pub points_cost: Option<i32>,
+    pub points_reward: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SkippedFoodOut {
+    pub food_id: i64,
+    pub food_name: Option<String>,
+    /// 跳过原因：已下架 / 已删除 / 暂不可点
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlacedOrderOut {
+    pub order: OrderOutNew,
+    /// 因下架或删除未能加入新订单的菜品
+    pub skipped: Vec<SkippedFoodOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderTemplateCreateInput {
+    pub name: String,
+    /// 设置后为组模板（组内成员共享），否则为个人模板
+    pub group_id: Option<i64>,
+    pub items: Option<Vec<OrderItemCreateInput>>,
+    /// 从历史订单保存为模板（与 items 二选一）
+    pub from_order_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderTemplateUpdateInput {
+    pub name: Option<String>,
+    pub items: Option<Vec<OrderItemCreateInput>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct OrderTemplateQuery {
+    /// 仅返回该组的模板；为空时返回个人模板及所在组的模板
+    pub group_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OrderTemplateRecord {
+    pub template_id: i64,
+    pub user_id: i64,
+    pub group_id: Option<i64>,
+    pub name: String,
+    pub items: sqlx::types::Json<Vec<OrderItemCreateInput>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrderTemplateOut {
+    pub template_id: i64,
+    pub user_id: i64,
+    pub group_id: Option<i64>,
+    pub name: String,
+    pub items: Vec<OrderItemCreateInput>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<OrderTemplateRecord> for OrderTemplateOut {
+    fn from(r: OrderTemplateRecord) -> Self {
+        Self {
+            template_id: r.template_id,
+            user_id: r.user_id,
+            group_id: r.group_id,
+            name: r.name,
+            items: r.items.0,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+// ================= 采购清单 =================
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct ShoppingListQuery {
+    /// 仅统计该组订单
+    pub group_id: Option<i64>,
+    /// 指定订单，逗号分隔；为空时取时间窗内待处理/已接单的订单
+    pub order_ids: Option<String>,
+    /// 目标时间窗口，默认从现在起 7 天
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShoppingListItemOut {
+    pub name: String,
+    /// 合并换算后的用量；为空表示适量
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    /// 用到该食材的菜品
+    pub foods: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShoppingListOut {
+    pub order_ids: Vec<i64>,
+    /// 订单数超过上限（200）时为 true，仅汇总了 order_ids 中的订单
+    pub truncated: bool,
+    pub items: Vec<ShoppingListItemOut>,
+    /// 尚未录入结构化食材的菜品
+    pub foods_without_recipe: Vec<String>,
+}
@@ -0,0 +1,59 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::errors::CustomError;
+
+/// 游标分页通用返回结构
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    /// 下一页游标；为空表示没有更多数据
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> CursorPage<T> {
+    /// rows 需多查询一条（limit + 1）用于判断是否还有下一页
+    pub fn from_rows(mut rows: Vec<T>, limit: i64, cursor_of: impl Fn(&T) -> String) -> Self {
+        let has_more = rows.len() as i64 > limit;
+        if has_more {
+            rows.truncate(limit as usize);
+        }
+        let next_cursor = if has_more { rows.last().map(cursor_of) } else { None };
+        Self { items: rows, next_cursor, has_more }
+    }
+}
+
+/// 游标内容：排序键（时间，可空）+ 主键兜底
+#[derive(Debug, Clone, Copy)]
+pub struct CursorKey {
+    pub at: Option<DateTime<Utc>>,
+    pub id: i64,
+}
+
+impl CursorKey {
+    pub fn encode(&self) -> String {
+        let at = self
+            .at
+            .map(|t| t.timestamp_micros().to_string())
+            .unwrap_or_else(|| "-".into());
+        URL_SAFE_NO_PAD.encode(format!("{}:{}", at, self.id))
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, CustomError> {
+        let invalid = || CustomError::BadRequest("无效的分页游标".into());
+        let bytes = URL_SAFE_NO_PAD.decode(raw).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (at, id) = text.split_once(':').ok_or_else(invalid)?;
+        let id = id.parse::<i64>().map_err(|_| invalid())?;
+        let at = if at == "-" {
+            None
+        } else {
+            let micros = at.parse::<i64>().map_err(|_| invalid())?;
+            Some(Utc.timestamp_micros(micros).single().ok_or_else(invalid)?)
+        };
+        Ok(Self { at, id })
+    }
+}
@@ -0,0 +1,118 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// ================= DTOs =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PantryItemCreateInput {
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub expiry_date: Option<NaiveDate>,
+    /// 低库存提醒阈值（同 unit），为空则不提醒
+    pub low_stock_threshold: Option<f64>,
+    pub note: Option<String>,
+}
+
+// 未提供的字段保持不变
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PantryItemUpdateInput {
+    pub name: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit: Option<String>,
+    pub expiry_date: Option<NaiveDate>,
+    /// 为 true 时清除保质期
+    pub clear_expiry: Option<bool>,
+    pub low_stock_threshold: Option<f64>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PantryItemOut {
+    pub item_id: i64,
+    pub group_id: i64,
+    pub name: String,
+    pub quantity: f64,
+    pub unit: String,
+    pub expiry_date: Option<NaiveDate>,
+    pub low_stock_threshold: Option<f64>,
+    pub note: Option<String>,
+    pub updated_by: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct PantryAlertQuery {
+    /// 临期天数，默认3，最大30
+    pub expiring_days: Option<i64>,
+    /// 本地时区相对UTC的分钟偏移，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PantryAlertsOut {
+    /// 低于阈值的库存
+    pub low_stock: Vec<PantryItemOut>,
+    /// 即将过期（含今天）
+    pub expiring: Vec<PantryItemOut>,
+    /// 已过期
+    pub expired: Vec<PantryItemOut>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodPantryUsageInput {
+    pub item_id: i64,
+    /// 每份用量
+    pub amount: f64,
+    /// 用量单位，默认与库存单位相同；需可换算为库存单位
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodPantryUsagesInput {
+    /// 整体替换该菜品的库存用量
+    pub usages: Vec<FoodPantryUsageInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodPantryUsageOut {
+    pub item_id: i64,
+    pub name: String,
+    pub amount: f64,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct CookableQuery {
+    /// 返回条数，默认20，最大100
+    pub limit: Option<i64>,
+    /// 为 true 时仅返回库存可完全覆盖的菜品
+    pub only_complete: Option<bool>,
+    /// 本地时区相对UTC的分钟偏移，用于判断过期，默认 480（UTC+8）
+    pub tz_offset_minutes: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CookableMissingOut {
+    pub item_id: i64,
+    pub name: String,
+    /// 每份所需（库存单位）
+    pub need: f64,
+    /// 可用库存（已过期的不计）
+    pub have: f64,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CookableFoodOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub food_photo: Option<String>,
+    /// 库存满足的食材占比 0~1
+    pub coverage: f64,
+    pub covered: usize,
+    pub total: usize,
+    /// 按当前库存最多可做几份
+    pub max_servings: i64,
+    pub missing: Vec<CookableMissingOut>,
+}
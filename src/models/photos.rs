@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// 图集归属对象，数据库中以 VARCHAR 存储
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum PhotoOwnerEnum {
+    FOOD,
+    CHECKIN,
+    ORDER,
+    REVIEW,
+}
+impl PhotoOwnerEnum {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::FOOD => "FOOD",
+            Self::CHECKIN => "CHECKIN",
+            Self::ORDER => "ORDER",
+            Self::REVIEW => "REVIEW",
+        }
+    }
+}
+
+// ================= DTOs =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhotoInput {
+    pub url: String,
+    pub caption: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhotoAddInput {
+    /// 追加到图集末尾
+    pub photos: Vec<PhotoInput>,
+    /// 为 true 时第一张新图设为封面；图集原本为空时自动设为封面
+    pub set_cover: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhotoUpdateInput {
+    pub caption: Option<String>,
+    /// 为 true 时设为封面（不支持设为 false，改封面请设置其他图片）
+    pub is_cover: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhotoReorderInput {
+    /// 图集内全部图片ID，按新顺序排列
+    pub photo_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhotoOut {
+    pub photo_id: i64,
+    pub url: String,
+    pub caption: Option<String>,
+    pub sort: i32,
+    pub is_cover: bool,
+    pub created_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
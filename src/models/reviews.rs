@@ -0,0 +1,50 @@
+use crate::models::photos::{PhotoInput, PhotoOut};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// ================= DTOs =================
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodReviewCreateInput {
+    /// 订单中的菜品
+    pub food_id: i64,
+    /// 星级 1-5
+    pub stars: i16,
+    pub content: Option<String>,
+    /// 评价图片，第一张为封面
+    pub photos: Option<Vec<PhotoInput>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodReviewUpdateInput {
+    pub stars: Option<i16>,
+    /// 传空字符串清空内容
+    pub content: Option<String>,
+    /// 传入时整体替换图片
+    pub photos: Option<Vec<PhotoInput>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
+pub struct FoodReviewQuery {
+    /// 上一页返回的 next_cursor
+    pub cursor: Option<String>,
+    /// 返回条数，默认20，最大100
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FoodReviewOut {
+    pub review_id: i64,
+    pub food_id: i64,
+    pub food_name: String,
+    pub order_id: i64,
+    pub group_id: Option<i64>,
+    pub user_id: i64,
+    pub nick_name: Option<String>,
+    pub avatar: Option<String>,
+    pub stars: i16,
+    pub content: Option<String>,
+    pub photos: Vec<PhotoOut>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,4 +1,4 @@
-use crate::{errors::CustomError, utils::TOKEN_SECRET_KEY, AppState};
+use crate::{errors::CustomError, models::foods::DietaryLabelEnum, utils::TOKEN_SECRET_KEY, AppState};
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use ntex::{
     http::Payload,
@@ -104,6 +104,63 @@ pub struct DailyCheckinOut {
     pub balance_after: i32,
 }
 
+// ========== 过敏原偏好 ==========
+// WARN 下单时提醒；BLOCK 禁止下单含该标签的菜品
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum AllergenActionEnum {
+    WARN,
+    BLOCK,
+}
+impl AllergenActionEnum {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WARN => "WARN",
+            Self::BLOCK => "BLOCK",
+        }
+    }
+    pub fn parse(s: &str) -> Self {
+        if s == "BLOCK" {
+            Self::BLOCK
+        } else {
+            Self::WARN
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllergenPrefInput {
+    pub label: DietaryLabelEnum,
+    pub action: Option<AllergenActionEnum>, // 默认 WARN
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllergenPrefsInput {
+    /// 整体替换；传空数组清除全部偏好
+    pub items: Vec<AllergenPrefInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllergenPrefOut {
+    pub label: DietaryLabelEnum,
+    pub label_name: String,
+    pub action: AllergenActionEnum,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllergenCheckInput {
+    pub food_ids: Vec<i64>,
+}
+
+// 命中的过敏原（每个菜品 × 标签一条）
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AllergenWarningOut {
+    pub food_id: i64,
+    pub food_name: String,
+    pub label: DietaryLabelEnum,
+    pub label_name: String,
+    pub action: AllergenActionEnum,
+}
+
 // ========== 数据库映射结构 ==========
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UserRecord {
@@ -167,6 +224,9 @@ impl From<UserRecord> for UserPublic {
 pub struct UserTokenClaims {
     pub exp: i64,
     pub user_id: i64,
+    /// 令牌范围：None 为正式用户；"guest" 为访客令牌（见 models::guests）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -205,6 +265,9 @@ impl<E: ErrorRenderer> FromRequest<E> for UserToken {
                 decode::<UserTokenClaims>(&raw, &decoding_key, &validation).map_err(|e| {
                     CustomError::AuthFailed(format!("decode token error: {}", e).into())
                 })?;
+            if data.claims.scope.is_some() {
+                return Err(CustomError::AuthFailed("访客令牌无权访问该接口".into()));
+            }
             let uid = data.claims.user_id;
 
             // 从缓存或数据库获取用户信息
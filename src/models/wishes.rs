@@ -1,3 +1,4 @@
+use crate::models::photos::{ PhotoInput, PhotoOut };
 use chrono::{ DateTime, Utc };
 use serde::{ Deserialize, Serialize };
 use utoipa::ToSchema;
@@ -18,6 +19,43 @@ pub enum WishClaimStatusEnum {
     CANCELLED,
 }
 
+// 每人兑换限额的统计周期，数据库中以 VARCHAR 存储
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub enum WishLimitPeriodEnum {
+    DAY,
+    WEEK,
+    MONTH,
+    /// 不分周期，累计
+    TOTAL,
+}
+impl WishLimitPeriodEnum {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DAY => "DAY",
+            Self::WEEK => "WEEK",
+            Self::MONTH => "MONTH",
+            Self::TOTAL => "TOTAL",
+        }
+    }
+    pub fn from_db(s: &str) -> Option<Self> {
+        match s {
+            "DAY" => Some(Self::DAY),
+            "WEEK" => Some(Self::WEEK),
+            "MONTH" => Some(Self::MONTH),
+            "TOTAL" => Some(Self::TOTAL),
+            _ => None,
+        }
+    }
+    pub fn zh_label(&self) -> &'static str {
+        match self {
+            Self::DAY => "今日",
+            Self::WEEK => "本周",
+            Self::MONTH => "本月",
+            Self::TOTAL => "累计",
+        }
+    }
+}
+
 // ================= Records =================
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
 pub struct WishRecord {
@@ -28,6 +66,13 @@ pub struct WishRecord {
     pub created_by: i64, // group_id
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    // 兑换限制，为空表示不限
+    pub stock_total: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub limit_period: Option<String>,
+    pub cooldown_minutes: Option<i32>,
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, sqlx::FromRow)]
@@ -49,6 +94,17 @@ pub struct WishCreateInput {
     pub wish_name: String,
     pub wish_cost: i32,
     pub group_id: i64,
+    /// 总库存（含已兑换），不传为不限
+    pub stock_total: Option<i32>,
+    /// 每人每周期可兑换次数，不传或0为不限
+    pub per_user_limit: Option<i32>,
+    /// 限额周期，默认 TOTAL
+    pub limit_period: Option<WishLimitPeriodEnum>,
+    /// 同一用户两次兑换的最小间隔（分钟），不传或0为不限
+    pub cooldown_minutes: Option<i32>,
+    /// 开放兑换时间窗
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -57,6 +113,18 @@ pub struct WishUpdateInput {
     pub wish_name: Option<String>,
     pub wish_cost: Option<i32>,
     pub status: Option<WishStatusEnum>,
+    pub stock_total: Option<i32>,
+    /// 为 true 时取消库存限制
+    pub clear_stock: Option<bool>,
+    /// 0 表示取消每人限额
+    pub per_user_limit: Option<i32>,
+    pub limit_period: Option<WishLimitPeriodEnum>,
+    /// 0 表示取消冷却
+    pub cooldown_minutes: Option<i32>,
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_until: Option<DateTime<Utc>>,
+    /// 为 true 时取消开放时间窗
+    pub clear_window: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::IntoParams)]
@@ -76,6 +144,20 @@ pub struct WishOut {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub current_claim_status: Option<WishClaimStatusEnum>,
+    // 兑换限制设置，为空表示不限
+    pub stock_total: Option<i32>,
+    pub per_user_limit: Option<i32>,
+    pub limit_period: Option<WishLimitPeriodEnum>,
+    pub cooldown_minutes: Option<i32>,
+    pub available_from: Option<DateTime<Utc>>,
+    pub available_until: Option<DateTime<Utc>>,
+    /// 剩余库存，不限库存时为空
+    pub remaining_stock: Option<i32>,
+    /// 当前用户此刻能否兑换（不含积分校验）
+    pub available_now: bool,
+    /// 不可兑换时的最早可兑换时间；为空表示无法再兑换（已兑完/已过期/达到累计上限）
+    pub next_available_at: Option<DateTime<Utc>>,
+    pub unavailable_reason: Option<String>,
 }
 
 impl From<WishRecord> for WishOut {
@@ -89,6 +171,16 @@ impl From<WishRecord> for WishOut {
             created_at: r.created_at,
             updated_at: r.updated_at,
             current_claim_status: None,
+            stock_total: r.stock_total,
+            per_user_limit: r.per_user_limit,
+            limit_period: r.limit_period.as_deref().and_then(WishLimitPeriodEnum::from_db),
+            cooldown_minutes: r.cooldown_minutes,
+            available_from: r.available_from,
+            available_until: r.available_until,
+            remaining_stock: r.stock_total,
+            available_now: true,
+            next_available_at: None,
+            unavailable_reason: None,
         }
     }
 }
@@ -170,6 +262,7 @@ pub struct WishClaimCheckinCreateInput {
     pub mood_text: Option<String>,
     pub feeling_text: Option<String>,
     pub checkin_time: Option<DateTime<Utc>>, // 客户端可覆盖时间
+    pub photos: Option<Vec<PhotoInput>>, // 图集；photo_url 仍可用，作为封面
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -183,6 +276,7 @@ pub struct WishClaimCheckinOut {
     pub feeling_text: Option<String>,
     pub checkin_time: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub photos: Vec<PhotoOut>, // 图集（photo_url 为封面）
 }
 
 impl From<WishClaimCheckinRecord> for WishClaimCheckinOut {
@@ -197,6 +291,7 @@ impl From<WishClaimCheckinRecord> for WishClaimCheckinOut {
             feeling_text: r.feeling_text,
             checkin_time: r.checkin_time,
             created_at: r.created_at,
+            photos: Vec::new(),
         }
     }
 }
@@ -18,6 +18,9 @@ use crate::{foods, game_im, models, orders, users};
         users::view::get_user_info,
         users::view::is_register,
         users::checkin::daily_checkin,
+        users::allergens::get_allergen_prefs,
+        users::allergens::set_allergen_prefs,
+        users::allergens::check_allergen_prefs,
         users::invitation::get_invitation,
         users::invitation::new_invitation,
         users::invitation::confirm_invitation,
@@ -32,12 +35,35 @@ use crate::{foods, game_im, models, orders, users};
         foods::new::create_tag,
         foods::delete::delete_tag,
         foods::view::get_tags,
+        foods::update::update_tag,
+        foods::update::reorder_tags,
+        foods::update::merge_tag,
         foods::view::get_foods,
         foods::view::get_food_detail,
         foods::update::mark_food,
         foods::update::unmark_food,
         foods::view::get_marked_foods,
-        foods::view::draw_blind_box,
+        foods::blind_box::draw_blind_box,
+        foods::blind_box::reroll_blind_box,
+        foods::blind_box::list_blind_box_draws,
+        foods::blind_box::order_blind_box_draw,
+        foods::audit::approve_food,
+        foods::audit::reject_food,
+        foods::audit::request_food_changes,
+        foods::audit::get_food_audit_logs,
+        foods::revisions::list_food_revisions,
+        foods::revisions::diff_food_revisions,
+        foods::revisions::restore_food_revision,
+        foods::audit::list_pending_food_reviews,
+        foods::stats::rebuild_food_stats,
+        foods::search::search_foods,
+        foods::search::suggest_foods,
+        foods::recommend::get_food_recommendations,
+        foods::transfer::export_group_foods,
+        foods::transfer::import_group_foods,
+        foods::transfer::copy_food,
+        foods::recipe::get_food_recipe,
+        foods::recipe::update_food_recipe,
         // 订单相关（新结构）
         orders::new::create_order,
         orders::update::update_order_status,
@@ -45,12 +71,89 @@ use crate::{foods, game_im, models, orders, users};
         orders::view::get_order_detail,
         orders::view::get_incomplete_order,
         orders::delete::delete_order,
+        orders::items::add_order_item,
+        orders::items::update_order_item,
+        orders::items::remove_order_item,
+        orders::items::set_order_item_edit,
+        orders::comments::list_order_comments,
+        orders::comments::create_order_comment,
+        orders::comments::update_order_comment,
+        orders::comments::delete_order_comment,
+        orders::reorder::reorder,
+        orders::templates::create_order_template,
+        orders::templates::list_order_templates,
+        orders::templates::update_order_template,
+        orders::templates::delete_order_template,
+        orders::templates::place_order_template,
+        orders::shopping::get_shopping_list,
         orders::rating::create_order_rating,
         orders::rating::get_order_rating,
+        orders::recurring::create_recurring_order,
+        orders::recurring::list_recurring_orders,
+        orders::recurring::pause_recurring_order,
+        orders::recurring::resume_recurring_order,
+        orders::recurring::skip_recurring_occurrence,
+        orders::recurring::delete_recurring_order,
+        // 购物车
+        crate::carts::view::get_cart,
+        crate::carts::update::add_cart_item,
+        crate::carts::update::update_cart_item,
+        crate::carts::update::remove_cart_item,
+        crate::carts::update::clear_cart,
+        crate::carts::checkout::checkout_cart,
+        // 访客
+        crate::guests::session::create_guest_session,
+        crate::guests::orders::create_guest_order,
+        crate::guests::orders::list_guest_orders,
+        crate::guests::orders::get_guest_order,
+        crate::guests::manage::list_group_guests,
+        crate::guests::manage::revoke_guest_session,
+        crate::guests::manage::convert_guest,
+        // 菜单计划
+        crate::meal_plans::view::get_meal_plan,
+        crate::meal_plans::update::add_meal_plan_entry,
+        crate::meal_plans::update::update_meal_plan_entry,
+        crate::meal_plans::update::delete_meal_plan_entry,
+        crate::meal_plans::fill::fill_meal_plan,
+        crate::meal_plans::fill::swap_meal_plan_entries,
+        crate::meal_plans::order::order_meal_plan_day,
+        // 图集
+        crate::photos::foods::list_food_photos,
+        crate::photos::foods::add_food_photos,
+        crate::photos::foods::update_food_photo,
+        crate::photos::foods::reorder_food_photos,
+        crate::photos::foods::delete_food_photo,
+        crate::photos::checkins::list_checkin_photos,
+        crate::photos::checkins::add_checkin_photos,
+        crate::photos::checkins::update_checkin_photo,
+        crate::photos::checkins::reorder_checkin_photos,
+        crate::photos::checkins::delete_checkin_photo,
+        crate::photos::orders::list_order_photos,
+        crate::photos::orders::add_order_photos,
+        crate::photos::orders::update_order_photo,
+        crate::photos::orders::reorder_order_photos,
+        crate::photos::orders::delete_order_photo,
+        // 库存
+        crate::pantry::items::list_pantry_items,
+        crate::pantry::items::create_pantry_item,
+        crate::pantry::items::update_pantry_item,
+        crate::pantry::items::delete_pantry_item,
+        crate::pantry::items::get_pantry_alerts,
+        crate::pantry::usages::get_food_pantry_usages,
+        crate::pantry::usages::update_food_pantry_usages,
+        crate::pantry::cookable::get_cookable_foods,
+        // 菜品评价
+        crate::reviews::update::create_food_review,
+        crate::reviews::update::update_food_review,
+        crate::reviews::update::delete_food_review,
+        crate::reviews::view::list_food_reviews,
+        crate::reviews::view::list_order_reviews,
+        crate::reviews::view::get_group_review_feed,
         // 心愿相关
         crate::wishes::new::create_wish,
         crate::wishes::view::get_wishes,
         crate::wishes::view::get_wish_detail,
+        crate::wishes::update::update_wish,
         crate::wishes::update::disable_wish,
         crate::wishes::claim::claim_wish,
         crate::wishes::claim::get_claim,
@@ -61,6 +164,7 @@ use crate::{foods, game_im, models, orders, users};
         crate::dashboard::metrics::get_my_today_orders,
         crate::dashboard::metrics::get_my_order_stats,
         crate::dashboard::metrics::get_points_journey,
+        crate::dashboard::nutrition::get_my_nutrition,
         crate::dashboard::activities::get_group_activities,
 
         // IM
@@ -79,6 +183,12 @@ use crate::{foods, game_im, models, orders, users};
             models::users::UserPublic,
             models::users::IsRegisterResponse,
             models::users::DailyCheckinOut,
+            models::users::AllergenActionEnum,
+            models::users::AllergenPrefInput,
+            models::users::AllergenPrefsInput,
+            models::users::AllergenPrefOut,
+            models::users::AllergenCheckInput,
+            models::users::AllergenWarningOut,
             users::role::RoleSwitchResult,
             users::role::RoleSwitchInput,
         ),
@@ -98,11 +208,52 @@ use crate::{foods, game_im, models, orders, users};
             models::foods::FoodUpdateInput,
             models::foods::FoodOut,
             models::foods::FoodTagOut,
+            models::foods::TagMatchEnum,
+            models::foods::FoodSortEnum,
+            models::foods::DietaryLabelEnum,
             models::foods::TagCreateInput,
+            models::foods::TagUpdateInput,
+            models::foods::TagReorderInput,
+            models::foods::TagMergeInput,
+            models::foods::TagOut,
             models::foods::FoodFilterQuery,
             models::foods::FoodMarkActionInput,
             models::foods::BlindBoxDrawInput,
             models::foods::BlindBoxDrawResultOut,
+            models::foods::BlindBoxFoodSnapshot,
+            models::foods::BlindBoxRerollInput,
+            models::foods::BlindBoxDrawOut,
+            models::foods::FoodAuditInput,
+            models::foods::FoodAuditLogOut,
+            models::foods::FoodRevisionSourceEnum,
+            models::foods::FoodRevisionSnapshot,
+            models::foods::FoodRevisionOut,
+            models::foods::FoodRevisionFieldDiff,
+            models::foods::FoodRevisionDiffOut,
+            models::foods::FoodRevisionRestoreInput,
+            models::foods::FoodReviewOut,
+            models::foods::FoodStatsRebuildOut,
+            models::foods::FoodSearchHitOut,
+            models::foods::FoodSuggestOut,
+            models::foods::FoodBundle,
+            models::foods::FoodBundleTag,
+            models::foods::FoodBundleItem,
+            models::foods::ImportDuplicateEnum,
+            models::foods::FoodImportInput,
+            models::foods::FoodImportConflictOut,
+            models::foods::FoodImportedOut,
+            models::foods::FoodImportReportOut,
+            models::foods::FoodCopyInput,
+            models::foods::FoodRecommendationsOut,
+            models::foods::FoodRecommendationOut,
+            models::foods::RecommendFactorOut,
+            models::foods::FoodCategory,
+            models::foods::FoodIngredientInput,
+            models::foods::FoodIngredientOut,
+            models::foods::FoodStepInput,
+            models::foods::FoodStepOut,
+            models::foods::FoodRecipeInput,
+            models::foods::FoodRecipeOut,
         ),
         // 订单新模型
         schemas(
@@ -113,14 +264,105 @@ use crate::{foods, game_im, models, orders, users};
             models::orders::OrderOutNew,
             models::orders::OrderStatusUpdateInput,
             models::orders::OrderQuery,
+            models::orders::OrderSortEnum,
+            models::pagination::CursorPage<models::orders::OrderOutNew>,
             models::orders::OrderRatingCreateInput,
             models::orders::OrderRatingOut,
+            models::orders::RecurringStatusEnum,
+            models::orders::RecurringRunStatusEnum,
+            models::orders::RecurringOrderCreateInput,
+            models::orders::RecurringSkipInput,
+            models::orders::RecurringOrderOut,
+            models::orders::RecurringRunOut,
+            models::orders::OrderItemCreateInput,
+            models::orders::OrderItemQuantityInput,
+            models::orders::OrderItemEditPermissionInput,
+            models::orders::OrderItemEditPermissionOut,
+            models::orders::OrderCommentInput,
+            models::orders::OrderCommentQuery,
+            models::orders::OrderCommentOut,
+            models::pagination::CursorPage<models::orders::OrderCommentOut>,
+            models::orders::OrderPlaceInput,
+            models::orders::SkippedFoodOut,
+            models::orders::PlacedOrderOut,
+            models::orders::OrderTemplateCreateInput,
+            models::orders::OrderTemplateUpdateInput,
+            models::orders::OrderTemplateQuery,
+            models::orders::OrderTemplateOut,
+            models::orders::ShoppingListOut,
+            models::orders::ShoppingListItemOut,
+        ),
+        // 购物车
+        schemas(
+            models::carts::CartStatusEnum,
+            models::carts::CartItemAddInput,
+            models::carts::CartItemUpdateInput,
+            models::carts::CartCheckoutInput,
+            models::carts::CartItemOut,
+            models::carts::CartOut,
+        ),
+        // 访客
+        schemas(
+            models::guests::GuestSessionCreateInput,
+            models::guests::GuestSessionOut,
+            models::guests::GuestOrderCreateInput,
+            models::guests::GuestOut,
+            models::guests::GuestConvertInput,
+            models::guests::GuestConvertOut,
+        ),
+        // 菜单计划
+        schemas(
+            models::meal_plans::MealPlanQuery,
+            models::meal_plans::MealPlanEntryInput,
+            models::meal_plans::MealPlanEntryUpdateInput,
+            models::meal_plans::MealPlanSwapInput,
+            models::meal_plans::MealPlanFillInput,
+            models::meal_plans::MealPlanDayOrderInput,
+            models::meal_plans::MealPlanEntryOut,
+            models::meal_plans::MealPlanSlotOut,
+            models::meal_plans::MealPlanDayOut,
+            models::meal_plans::MealPlanOut,
+            models::meal_plans::MealPlanOrderOut,
+            models::meal_plans::MealPlanDayOrderOut,
+            models::meal_plans::MealPlanSkippedMealOut,
+        ),
+        // 图集
+        schemas(
+            models::photos::PhotoOwnerEnum,
+            models::photos::PhotoInput,
+            models::photos::PhotoAddInput,
+            models::photos::PhotoUpdateInput,
+            models::photos::PhotoReorderInput,
+            models::photos::PhotoOut,
+        ),
+        // 库存
+        schemas(
+            models::pantry::PantryItemCreateInput,
+            models::pantry::PantryItemUpdateInput,
+            models::pantry::PantryItemOut,
+            models::pantry::PantryAlertQuery,
+            models::pantry::PantryAlertsOut,
+            models::pantry::FoodPantryUsageInput,
+            models::pantry::FoodPantryUsagesInput,
+            models::pantry::FoodPantryUsageOut,
+            models::pantry::CookableQuery,
+            models::pantry::CookableMissingOut,
+            models::pantry::CookableFoodOut,
+        ),
+        // 菜品评价
+        schemas(
+            models::reviews::FoodReviewCreateInput,
+            models::reviews::FoodReviewUpdateInput,
+            models::reviews::FoodReviewQuery,
+            models::reviews::FoodReviewOut,
+            models::pagination::CursorPage<models::reviews::FoodReviewOut>,
         ),
         // 心愿模型
         schemas(
             models::wishes::WishCreateInput,
             models::wishes::WishUpdateInput,
             models::wishes::WishOut,
+            models::wishes::WishLimitPeriodEnum,
             models::wishes::WishQuery,
             models::wishes::WishClaimCreateInput,
             models::wishes::WishClaimUpdateInput,
@@ -135,6 +377,9 @@ use crate::{foods, game_im, models, orders, users};
             crate::dashboard::metrics::OrderStatsOut,
             crate::dashboard::metrics::JourneyOrderOut,
             crate::dashboard::metrics::PointsJourneyOut,
+            crate::dashboard::nutrition::NutritionPeriodEnum,
+            crate::dashboard::nutrition::NutritionBucketOut,
+            crate::dashboard::nutrition::NutritionSummaryOut,
 
             // IM
             models::game_im::ImUserSigOut,
@@ -150,6 +395,11 @@ use crate::{foods, game_im, models, orders, users};
         (name = "用户", description = "用户相关接口"),
         (name = "菜品", description = "菜品相关接口"),
         (name = "订单", description = "订单相关接口"),
+        (name = "购物车", description = "购物车与结算接口"),
+        (name = "访客", description = "邀请码访客会话与转正接口"),
+        (name = "菜单计划", description = "组内共享的每周菜单（日期 × 餐段）与一键下单"),
+        (name = "库存", description = "组内食材库存、菜品用量、提醒与可做菜品"),
+        (name = "菜品评价", description = "按菜品的 1-5 星评价（关联已完成订单）与组内评价动态"),
         (name = "心愿", description = "心愿与兑换相关接口"),
         (name = "看板", description = "组活动与概览接口"),
         (name = "IM", description = "腾讯云 IM（UserSig / 后台联调）"),
@@ -0,0 +1,237 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::orders::{OrderCommentInput, OrderCommentOut, OrderCommentQuery},
+    models::pagination::{CursorKey, CursorPage},
+    upload::upload::validate_upload_url,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+const COMMENT_MAX_CHARS: usize = 500;
+
+const COMMENT_SELECT: &str = "SELECT c.comment_id, c.order_id, c.user_id, u.nick_name, u.avatar, c.content, c.photo_url, c.edited_at, c.created_at \
+     FROM order_comments c LEFT JOIN users u ON u.user_id = c.user_id";
+
+// 校验当前用户能否查看/评论该订单：下单人、接单人或订单所属组成员；返回下单人ID
+async fn ensure_order_participant(
+    conn: &mut PgConnection,
+    order_id: i64,
+    user_id: i64,
+) -> Result<i64, CustomError> {
+    let row = sqlx::query(
+        "SELECT o.user_id, \
+         (o.user_id=$2 OR o.receiver_id=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=o.group_id AND m.user_id=$2)) AS allowed \
+         FROM orders o WHERE o.order_id=$1",
+    )
+    .bind(order_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match row {
+        Some(r) if r.get::<bool, _>("allowed") => Ok(r.get("user_id")),
+        Some(_) => Err(CustomError::BadRequest("无权评论该订单".into())),
+        None => Err(CustomError::BadRequest("订单不存在".into())),
+    }
+}
+
+fn normalize_input(data: &OrderCommentInput) -> Result<(String, Option<String>), CustomError> {
+    let content = data.content.trim().to_string();
+    let photo_url = data
+        .photo_url
+        .as_ref()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if content.is_empty() && photo_url.is_none() {
+        return Err(CustomError::BadRequest("评论内容不能为空".into()));
+    }
+    if content.chars().count() > COMMENT_MAX_CHARS {
+        return Err(CustomError::BadRequest(format!("评论内容不能超过{}字", COMMENT_MAX_CHARS)));
+    }
+    if let Some(url) = &photo_url {
+        validate_upload_url(url)?;
+    }
+    Ok((content, photo_url))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/{id}/comments",
+    tag = "订单",
+    params(("id" = i64, Path, description = "订单ID"), OrderCommentQuery),
+    responses((status = 200, body = CursorPage<OrderCommentOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_order_comments(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    query: Query<OrderCommentQuery>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_order_participant(&mut conn, *id, token.user_id).await?;
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let mut qb = sqlx::QueryBuilder::<sqlx::Postgres>::new(COMMENT_SELECT);
+    qb.push(" WHERE c.order_id = ");
+    qb.push_bind(*id);
+    qb.push(" AND c.is_del = 0");
+    // 最新的在前，游标为 (created_at, comment_id)
+    if let Some(raw) = &query.cursor {
+        let key = CursorKey::decode(raw)?;
+        if let Some(at) = key.at {
+            qb.push(" AND (c.created_at, c.comment_id) < (");
+            qb.push_bind(at);
+            qb.push(", ");
+            qb.push_bind(key.id);
+            qb.push(")");
+        }
+    }
+    qb.push(" ORDER BY c.created_at DESC, c.comment_id DESC LIMIT ");
+    qb.push_bind(limit + 1);
+    let rows: Vec<OrderCommentOut> = qb
+        .build_query_as::<OrderCommentOut>()
+        .fetch_all(&mut *conn)
+        .await?;
+    let page = CursorPage::from_rows(rows, limit, |c| {
+        CursorKey { at: Some(c.created_at), id: c.comment_id }.encode()
+    });
+    Ok(HttpResponse::Ok().json(&page))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/comments",
+    tag = "订单",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = OrderCommentInput,
+    responses((status = 201, body = OrderCommentOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_order_comment(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderCommentInput>,
+) -> Result<impl Responder, CustomError> {
+    let (content, photo_url) = normalize_input(&data)?;
+    let mut conn = state.db_pool.acquire().await?;
+    let orderer_id = ensure_order_participant(&mut conn, *id, token.user_id).await?;
+
+    let comment_id: i64 = sqlx::query_scalar(
+        "INSERT INTO order_comments (order_id, user_id, content, photo_url) VALUES ($1,$2,$3,$4) RETURNING comment_id",
+    )
+    .bind(*id)
+    .bind(token.user_id)
+    .bind(&content)
+    .bind(&photo_url)
+    .fetch_one(&mut *conn)
+    .await?;
+    let out: OrderCommentOut = sqlx::query_as(&format!("{} WHERE c.comment_id=$1", COMMENT_SELECT))
+        .bind(comment_id)
+        .fetch_one(&mut *conn)
+        .await?;
+
+    // 通知对方：下单人评论 -> 接单方；其他人评论 -> 下单人
+    let pool_clone = state.db_pool.clone();
+    let order_id = *id;
+    let author_id = token.user_id;
+    tokio::spawn(async move {
+        let targets = if author_id == orderer_id {
+            match crate::services::notifications::order_receiver_ids(order_id, &pool_clone).await {
+                Ok(v) => v,
+                Err(e) => {
+                    log::warn!("order comment receiver lookup error: {}", e);
+                    return;
+                }
+            }
+        } else {
+            vec![orderer_id]
+        };
+        let targets: Vec<i64> = targets.into_iter().filter(|uid| *uid != author_id).collect();
+        if let Err(e) = crate::services::notifications::push_order_notice(order_id, "订单有新评论", &targets, pool_clone).await {
+            log::warn!("order comment push error: {}", e);
+        }
+    });
+
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orders/{id}/comments/{comment_id}",
+    tag = "订单",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("comment_id" = i64, Path, description = "评论ID")
+    ),
+    request_body = OrderCommentInput,
+    responses((status = 200, body = OrderCommentOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_order_comment(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<OrderCommentInput>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, comment_id) = path.into_inner();
+    let (content, photo_url) = normalize_input(&data)?;
+    let db = &state.db_pool;
+    // 仅作者本人可编辑
+    let updated = sqlx::query(
+        "UPDATE order_comments SET content=$4, photo_url=$5, edited_at=NOW() \
+         WHERE comment_id=$1 AND order_id=$2 AND user_id=$3 AND is_del=0",
+    )
+    .bind(comment_id)
+    .bind(order_id)
+    .bind(token.user_id)
+    .bind(&content)
+    .bind(&photo_url)
+    .execute(db)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(CustomError::BadRequest("评论不存在或无权编辑".into()));
+    }
+    let out: OrderCommentOut = sqlx::query_as(&format!("{} WHERE c.comment_id=$1", COMMENT_SELECT))
+        .bind(comment_id)
+        .fetch_one(db)
+        .await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orders/{id}/comments/{comment_id}",
+    tag = "订单",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("comment_id" = i64, Path, description = "评论ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_order_comment(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, comment_id) = path.into_inner();
+    let deleted = sqlx::query(
+        "UPDATE order_comments SET is_del=1 WHERE comment_id=$1 AND order_id=$2 AND user_id=$3 AND is_del=0",
+    )
+    .bind(comment_id)
+    .bind(order_id)
+    .bind(token.user_id)
+    .execute(&state.db_pool)
+    .await?;
+    if deleted.rows_affected() == 0 {
+        return Err(CustomError::BadRequest("评论不存在或无权删除".into()));
+    }
+    Ok(HttpResponse::Ok().body("deleted"))
+}
@@ -0,0 +1,407 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::orders::{
+        OrderItemCreateInput, OrderItemEditPermissionInput, OrderItemEditPermissionOut,
+        OrderItemQuantityInput, OrderOutNew, OrderRecord, OrderStatusEnum,
+    },
+    services::food_stats,
+    users::allergens::{check_allergens, ensure_not_blocked},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{Postgres, Row, Transaction};
+use std::sync::Arc;
+
+const ORDER_COLUMNS: &str = "order_id, user_id, receiver_id, group_id, status, goal_time, points_cost, points_reward, cancel_reason, reject_reason, last_status_change_at, created_at, updated_at";
+
+// 锁定订单并校验：仅下单人可改；PENDING 可改，ACCEPTED 需接单人放开 allow_item_edit
+async fn lock_editable_order(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: i64,
+    user_id: i64,
+) -> Result<OrderRecord, CustomError> {
+    let row = sqlx::query(&format!(
+        "SELECT {}, allow_item_edit FROM orders WHERE order_id=$1 FOR UPDATE",
+        ORDER_COLUMNS
+    ))
+    .bind(order_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    let row = match row {
+        Some(r) => r,
+        None => return Err(CustomError::BadRequest("订单不存在".into())),
+    };
+    let allow_item_edit: bool = row.get("allow_item_edit");
+    let order = OrderRecord {
+        order_id: row.get("order_id"),
+        user_id: row.get("user_id"),
+        receiver_id: row.get("receiver_id"),
+        group_id: row.get("group_id"),
+        status: row.get::<OrderStatusEnum, _>("status"),
+        goal_time: row.try_get("goal_time").ok(),
+        points_cost: row.get("points_cost"),
+        points_reward: row.get("points_reward"),
+        cancel_reason: row.try_get("cancel_reason").ok(),
+        reject_reason: row.try_get("reject_reason").ok(),
+        last_status_change_at: row.try_get("last_status_change_at").ok(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        is_guest: false,
+    };
+    if order.user_id != user_id {
+        return Err(CustomError::BadRequest("只有下单人可以修改菜品".into()));
+    }
+    match order.status {
+        OrderStatusEnum::PENDING => {}
+        OrderStatusEnum::ACCEPTED if allow_item_edit => {}
+        OrderStatusEnum::ACCEPTED => {
+            return Err(CustomError::BadRequest("接单人未允许修改菜品".into()))
+        }
+        _ => return Err(CustomError::BadRequest("当前订单状态不可修改菜品".into())),
+    }
+    Ok(order)
+}
+
+async fn total_quantity(
+    tx: &mut Transaction<'_, Postgres>,
+    order_id: i64,
+) -> Result<i64, CustomError> {
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(quantity),0)::BIGINT FROM order_items WHERE order_id=$1",
+    )
+    .bind(order_id)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(total)
+}
+
+// 按份数等比重算 points_cost，写入修改历史，返回最新订单。
+// points_cost 下单时只记录不扣除，因此只更新订单上的值，不涉及积分流水。
+async fn finish_edit(
+    tx: &mut Transaction<'_, Postgres>,
+    mut order: OrderRecord,
+    user_id: i64,
+    before_qty: i64,
+    change: String,
+) -> Result<OrderOutNew, CustomError> {
+    let after_qty = total_quantity(tx, order.order_id).await?;
+    let new_cost = if before_qty > 0 {
+        ((order.points_cost as i64 * after_qty + before_qty / 2) / before_qty) as i32
+    } else {
+        order.points_cost
+    };
+    sqlx::query("UPDATE orders SET points_cost=$2, updated_at=NOW() WHERE order_id=$1")
+        .bind(order.order_id)
+        .bind(new_cost)
+        .execute(&mut **tx)
+        .await?;
+
+    // 改菜不改变状态：记录一条 from=to 的历史
+    let remark = if new_cost != order.points_cost {
+        format!("修改菜品：{}；积分消耗 {}→{}", change, order.points_cost, new_cost)
+    } else {
+        format!("修改菜品：{}", change)
+    };
+    order.points_cost = new_cost;
+    sqlx::query("INSERT INTO order_status_history (order_id, from_status, to_status, changed_by, remark) VALUES ($1,$2,$3,$4,$5)")
+        .bind(order.order_id)
+        .bind(order.status)
+        .bind(order.status)
+        .bind(user_id)
+        .bind(&remark)
+        .execute(&mut **tx)
+        .await?;
+
+    order.updated_at = chrono::Utc::now();
+    crate::orders::view::build_order_out(&mut **tx, order).await
+}
+
+fn spawn_item_edit_push(db_pool: sqlx::PgPool, order_id: i64) {
+    tokio::spawn(async move {
+        let receivers = match crate::services::notifications::order_receiver_ids(order_id, &db_pool).await {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("order item edit receiver lookup error: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = crate::services::notifications::push_order_notice(order_id, "订单菜品已修改", &receivers, db_pool).await {
+            log::warn!("order item edit push error: {}", e);
+        }
+    });
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/items",
+    tag = "订单",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = OrderItemCreateInput,
+    responses((status = 200, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_order_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderItemCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    let qty = data.quantity.unwrap_or(1);
+    if qty <= 0 {
+        return Err(CustomError::BadRequest("数量必须大于0".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    let order = match lock_editable_order(&mut tx, *id, token.user_id).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let food_name: Option<String> = sqlx::query_scalar(
+        "SELECT food_name FROM foods WHERE food_id=$1 AND is_del=0 AND food_status='NORMAL' AND apply_status='APPROVED'",
+    )
+    .bind(data.food_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let food_name = match food_name {
+        Some(n) => n,
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("菜品不存在或不可点".into()));
+        }
+    };
+    // 与下单相同的过敏原校验：BLOCK 拒绝，WARN 随结果返回
+    let allergen_hits = check_allergens(&mut tx, token.user_id, &[data.food_id]).await?;
+    if let Err(e) = ensure_not_blocked(&allergen_hits) {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    let before_qty = total_quantity(&mut tx, order.order_id).await?;
+
+    // 已有同一菜品则合并数量
+    let merged = sqlx::query(
+        "UPDATE order_items SET quantity = quantity + $3 WHERE id = (SELECT id FROM order_items WHERE order_id=$1 AND food_id=$2 ORDER BY id LIMIT 1)",
+    )
+    .bind(order.order_id)
+    .bind(data.food_id)
+    .bind(qty)
+    .execute(&mut *tx)
+    .await?;
+    if merged.rows_affected() == 0 {
+        sqlx::query("INSERT INTO order_items (order_id, food_id, quantity) VALUES ($1,$2,$3)")
+            .bind(order.order_id)
+            .bind(data.food_id)
+            .bind(qty)
+            .execute(&mut *tx)
+            .await?;
+        food_stats::record_order_foods(&mut tx, order.order_id, &[data.food_id]).await?;
+    }
+
+    let change = format!("新增 {} x{}", food_name, qty);
+    let mut out = match finish_edit(&mut tx, order, token.user_id, before_qty, change).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    out.allergen_warnings = allergen_hits;
+    tx.commit().await?;
+    spawn_item_edit_push(state.db_pool.clone(), out.order_id);
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orders/{id}/items/{item_id}",
+    tag = "订单",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("item_id" = i64, Path, description = "订单明细ID")
+    ),
+    request_body = OrderItemQuantityInput,
+    responses((status = 200, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_order_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<OrderItemQuantityInput>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, item_id) = path.into_inner();
+    if data.quantity <= 0 {
+        return Err(CustomError::BadRequest("数量必须大于0，如需移除请使用删除接口".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    let order = match lock_editable_order(&mut tx, order_id, token.user_id).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let row = sqlx::query(
+        "SELECT oi.quantity, oi.food_id, f.food_name FROM order_items oi LEFT JOIN foods f ON f.food_id = oi.food_id WHERE oi.id=$1 AND oi.order_id=$2",
+    )
+    .bind(item_id)
+    .bind(order_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (old_qty, food_id, food_name) = match row {
+        Some(r) => (
+            r.get::<i32, _>("quantity"),
+            r.get::<i64, _>("food_id"),
+            r.try_get::<Option<String>, _>("food_name").ok().flatten().unwrap_or_default(),
+        ),
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("订单明细不存在".into()));
+        }
+    };
+    if old_qty == data.quantity {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("数量未变化".into()));
+    }
+    // 加量按新点菜处理，需重新校验过敏原（偏好可能在下单后才设置）；减量不拦截
+    let allergen_hits = if data.quantity > old_qty {
+        check_allergens(&mut tx, token.user_id, &[food_id]).await?
+    } else {
+        Vec::new()
+    };
+    if let Err(e) = ensure_not_blocked(&allergen_hits) {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    let before_qty = total_quantity(&mut tx, order_id).await?;
+    sqlx::query("UPDATE order_items SET quantity=$2 WHERE id=$1")
+        .bind(item_id)
+        .bind(data.quantity)
+        .execute(&mut *tx)
+        .await?;
+
+    let change = format!("{} 数量 {} -> {}", food_name, old_qty, data.quantity);
+    let mut out = match finish_edit(&mut tx, order, token.user_id, before_qty, change).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    out.allergen_warnings = allergen_hits;
+    tx.commit().await?;
+    spawn_item_edit_push(state.db_pool.clone(), out.order_id);
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orders/{id}/items/{item_id}",
+    tag = "订单",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("item_id" = i64, Path, description = "订单明细ID")
+    ),
+    responses((status = 200, body = OrderOutNew)),
+    security(("cookie_auth" = []))
+)]
+pub async fn remove_order_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, item_id) = path.into_inner();
+    let mut tx = state.db_pool.begin().await?;
+    let order = match lock_editable_order(&mut tx, order_id, token.user_id).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let item_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM order_items WHERE order_id=$1")
+        .bind(order_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if item_count <= 1 {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("订单至少保留一个菜品，如需放弃请取消订单".into()));
+    }
+    let before_qty = total_quantity(&mut tx, order_id).await?;
+    let row = sqlx::query(
+        "DELETE FROM order_items oi USING (SELECT id, food_id FROM order_items WHERE id=$1 AND order_id=$2) d \
+         LEFT JOIN foods f ON f.food_id = d.food_id \
+         WHERE oi.id = d.id RETURNING oi.quantity, oi.food_id, f.food_name",
+    )
+    .bind(item_id)
+    .bind(order_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let change = match row {
+        Some(r) => {
+            food_stats::record_food_removed(&mut tx, order_id, r.get("food_id")).await?;
+            format!(
+                "移除 {} x{}",
+                r.try_get::<Option<String>, _>("food_name").ok().flatten().unwrap_or_default(),
+                r.get::<i32, _>("quantity")
+            )
+        }
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("订单明细不存在".into()));
+        }
+    };
+
+    let out = match finish_edit(&mut tx, order, token.user_id, before_qty, change).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    tx.commit().await?;
+    spawn_item_edit_push(state.db_pool.clone(), out.order_id);
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orders/{id}/item-edit",
+    tag = "订单",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = OrderItemEditPermissionInput,
+    responses((status = 200, body = OrderItemEditPermissionOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn set_order_item_edit(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderItemEditPermissionInput>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    // 仅接单方（receiver_id 或群内 RECEIVING 成员）可设置
+    let receivers = crate::services::notifications::order_receiver_ids(*id, db).await?;
+    if !receivers.contains(&token.user_id) {
+        return Err(CustomError::BadRequest("只有接单人可以设置改菜权限".into()));
+    }
+    let updated: Option<bool> = sqlx::query_scalar(
+        "UPDATE orders SET allow_item_edit=$2, updated_at=NOW() WHERE order_id=$1 RETURNING allow_item_edit",
+    )
+    .bind(*id)
+    .bind(data.allow)
+    .fetch_optional(db)
+    .await?;
+    match updated {
+        Some(allow_item_edit) => Ok(HttpResponse::Ok().json(&OrderItemEditPermissionOut {
+            order_id: *id,
+            allow_item_edit,
+        })),
+        None => Err(CustomError::BadRequest("订单不存在".into())),
+    }
+}
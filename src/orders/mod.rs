@@ -5,4 +5,11 @@ pub mod delete;
 // Optional legacy / auxiliary modules
 // pub mod footprints; // disabled until confirmed needed
 pub mod expiration;
+pub mod comments; // 订单评论
+pub mod items; // 待处理订单改菜
 pub mod rating; // 订单完成后的评分加减分
+pub mod recurring; // 周期订单（模板 + 定时生成）
+pub mod reorder; // 再来一单
+pub mod shopping; // 采购清单汇总
+pub mod templates; // 命名订单模板
+pub mod reminder; // goal_time 到期提醒与超时处理
@@ -11,29 +11,50 @@ use crate::{
     },
     AppState,
 };
+use crate::services::idempotency;
+use ntex::http::StatusCode;
 use ntex::web::{
     types::{Json, State},
-    HttpResponse, Responder,
+    HttpRequest, Responder,
 };
-use sqlx::Row;
+use sqlx::{Postgres, Row, Transaction};
 
 #[utoipa::path(
 	post,
 	path = "/orders",
 	tag = "订单",
+	params(("Idempotency-Key" = Option<String>, Header, description = "幂等键，重试时复用")),
 	request_body = OrderCreateInput,
 	responses((status = 201, body = OrderOutNew))
 )]
 pub async fn create_order(
     user_token: UserToken,
     state: State<Arc<AppState>>,
+    req: HttpRequest,
     data: Json<OrderCreateInput>,
 ) -> Result<impl Responder, CustomError> {
+    idempotency::run(&state.redis_cache, &req, user_token.user_id, &*data, StatusCode::CREATED, || async {
+        let db = &state.db_pool;
+        let mut tx = db.begin().await?;
+        let out = insert_order(&mut tx, user_token.user_id, &data).await?;
+        tx.commit().await?;
+
+        // 异步推送
+        spawn_order_push(state.db_pool.clone(), out.order_id, "order create");
+        Ok::<_, CustomError>(out)
+    }).await
+}
+
+/// 下单核心逻辑：校验组成员/邀请码，写入订单、明细与初始状态历史。
+/// 事务提交与推送由调用方负责，供周期订单、购物车结算等复用。
+pub async fn insert_order(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    data: &OrderCreateInput,
+) -> Result<OrderOutNew, CustomError> {
     if data.items.is_empty() {
         return Err(CustomError::BadRequest("缺少菜品".into()));
     }
-    let db = &state.db_pool;
-    let mut tx = db.begin().await?;
 
     // 校验 group_id 成员关系（如提供）
     if let Some(gid) = data.group_id {
@@ -41,8 +62,8 @@ pub async fn create_order(
             "SELECT EXISTS(SELECT 1 FROM association_group_members WHERE group_id=$1 AND user_id=$2)"
         )
         .bind(gid)
-        .bind(user_token.user_id as i64)
-        .fetch_one(&mut *tx)
+        .bind(user_id)
+        .fetch_one(&mut **tx)
         .await?;
         
         if !is_member {
@@ -53,7 +74,7 @@ pub async fn create_order(
                     "SELECT invite_code FROM association_groups WHERE group_id=$1"
                 )
                 .bind(gid)
-                .fetch_optional(&mut *tx)
+                .fetch_optional(&mut **tx)
                 .await?
                 .flatten();
                 
@@ -65,12 +86,16 @@ pub async fn create_order(
             }
 
             if !allowed {
-                tx.rollback().await.ok();
                 return Err(CustomError::BadRequest("你不是该组成员且邀请码无效".into()));
             }
         }
     }
 
+    // 过敏原偏好：BLOCK 直接拒绝，WARN 随结果返回
+    let food_ids: Vec<i64> = data.items.iter().map(|i| i.food_id).collect();
+    let allergen_hits = crate::users::allergens::check_allergens(&mut **tx, user_id, &food_ids).await?;
+    crate::users::allergens::ensure_not_blocked(&allergen_hits)?;
+
     let points_cost = data.points_cost.unwrap_or(0);
     let points_reward = data.points_reward.unwrap_or(0);
 
@@ -80,13 +105,13 @@ pub async fn create_order(
          VALUES ($1,$2,$3,$4,$5,$6) \
          RETURNING order_id, user_id, receiver_id, group_id, status, goal_time, points_cost, points_reward, cancel_reason, reject_reason, last_status_change_at, created_at, updated_at"
     )
-    .bind(user_token.user_id as i64)
+    .bind(user_id)
     .bind::<Option<i64>>(None)
     .bind(data.group_id)
     .bind(data.goal_time)
     .bind(points_cost)
     .bind(points_reward)
-    .fetch_one(&mut *tx)
+    .fetch_one(&mut **tx)
     .await?;
 
     // 批量插入条目
@@ -96,9 +121,10 @@ pub async fn create_order(
             .bind(rec.order_id)
             .bind(item.food_id)
             .bind(qty)
-            .execute(&mut *tx)
+            .execute(&mut **tx)
             .await?;
     }
+    crate::services::food_stats::record_order_foods(&mut **tx, rec.order_id, &food_ids).await?;
 
     // 初始状态历史
     sqlx::query(
@@ -107,8 +133,8 @@ pub async fn create_order(
     .bind(rec.order_id)
     .bind::<Option<OrderStatusEnum>>(None)
     .bind(OrderStatusEnum::PENDING)
-    .bind(user_token.user_id as i64)
-    .execute(&mut *tx)
+    .bind(user_id)
+    .execute(&mut **tx)
     .await?;
 
     // 读取条目并附加食品信息
@@ -117,7 +143,7 @@ pub async fn create_order(
          FROM order_items oi LEFT JOIN foods f ON f.food_id = oi.food_id WHERE oi.order_id=$1"
     )
     .bind(rec.order_id)
-    .fetch_all(&mut *tx)
+    .fetch_all(&mut **tx)
     .await?
     .into_iter()
     .map(|r| OrderItemOut {
@@ -136,7 +162,7 @@ pub async fn create_order(
          WHERE h.order_id=$1 ORDER BY h.changed_at"
     )
     .bind(rec.order_id)
-    .fetch_all(&mut *tx)
+    .fetch_all(&mut **tx)
     .await?
     .into_iter()
     .map(|row| {
@@ -150,18 +176,16 @@ pub async fn create_order(
     })
     .collect();
 
-    tx.commit().await?;
-
-    // 异步推送
-    {
-        let pool_clone = state.db_pool.clone();
-        let oid = rec.order_id;
-        tokio::spawn(async move {
-            if let Err(e) = crate::services::notifications::push_order_status(oid, pool_clone).await {
-                log::warn!("order create push error: {}", e);
-            }
-        });
-    }
+    let mut out = OrderOutNew::from((rec, items_out, history_rows));
+    out.allergen_warnings = allergen_hits;
+    Ok(out)
+}
 
-    Ok(HttpResponse::Created().json(&OrderOutNew::from((rec, items_out, history_rows))))
+/// 异步推送订单状态（失败只记录日志）
+pub fn spawn_order_push(db_pool: sqlx::PgPool, order_id: i64, scene: &'static str) {
+    tokio::spawn(async move {
+        if let Err(e) = crate::services::notifications::push_order_status(order_id, db_pool).await {
+            log::warn!("{} push error: {}", scene, e);
+        }
+    });
 }
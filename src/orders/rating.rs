@@ -3,7 +3,9 @@ use crate::{
     models::{ orders::{ OrderRatingCreateInput, OrderRatingOut, OrderStatusEnum }, users::UserToken },
     AppState,
 };
-use ntex::web::{ types::{ Json, Path, State }, HttpResponse, Responder };
+use crate::services::idempotency;
+use ntex::http::StatusCode;
+use ntex::web::{ types::{ Json, Path, State }, HttpRequest, HttpResponse, Responder };
 use sqlx::Row;
 use std::sync::Arc;
 
@@ -11,16 +13,31 @@ use std::sync::Arc;
     post,
     path = "/orders-rating/{order_id}",
     tag = "订单",
-    params(("order_id" = i64, Path, description = "订单ID")),
+    params(
+        ("order_id" = i64, Path, description = "订单ID"),
+        ("Idempotency-Key" = Option<String>, Header, description = "幂等键，重试时复用")
+    ),
     request_body = OrderRatingCreateInput,
     responses((status = 201, body = OrderRatingOut))
 )]
 pub async fn create_order_rating(
     user_token: UserToken,
     state: State<Arc<AppState>>,
+    req: HttpRequest,
     order_id: Path<i64>,
     body: Json<OrderRatingCreateInput>
 ) -> Result<impl Responder, CustomError> {
+    idempotency::run(&state.redis_cache, &req, user_token.user_id, &*body, StatusCode::CREATED, || {
+        do_create_order_rating(&user_token, &state, *order_id, &body)
+    }).await
+}
+
+async fn do_create_order_rating(
+    user_token: &UserToken,
+    state: &AppState,
+    order_id: i64,
+    body: &OrderRatingCreateInput
+) -> Result<OrderRatingOut, CustomError> {
     let db = &state.db_pool;
     // 校验 delta
     if body.delta == 0 || body.delta.abs() > 5 {
@@ -43,7 +60,7 @@ pub async fn create_order_rating(
             WHERE
                 o.order_id = $1 AND agm.role_in_group = 'RECEIVING' FOR UPDATE"
         )
-        .bind(*order_id)
+        .bind(order_id)
         .fetch_optional(db).await?;
     let Some(or) = order_row else {
         return Err(CustomError::BadRequest("订单不存在".into()));
@@ -61,7 +78,7 @@ pub async fn create_order_rating(
     // 检查是否已有评分
     let existing = sqlx
         ::query("SELECT rating_id FROM order_ratings WHERE order_id=$1")
-        .bind(*order_id)
+        .bind(order_id)
         .fetch_optional(db).await?;
     if existing.is_some() {
         return Err(CustomError::BadRequest("该订单已评分".into()));
@@ -92,7 +109,7 @@ pub async fn create_order_rating(
         )
         .bind(receiver_id)
         .bind(body.delta)
-        .bind(*order_id)
+        .bind(order_id)
         .bind(balance_after)
         .execute(&mut *tx).await?;
     // 插入评分记录
@@ -100,7 +117,7 @@ pub async fn create_order_rating(
         ::query(
             "INSERT INTO order_ratings (order_id, rater_user_id, target_user_id, delta, remark) VALUES ($1,$2,$3,$4,$5) RETURNING rating_id, order_id, rater_user_id, target_user_id, delta, remark, created_at"
         )
-        .bind(*order_id)
+        .bind(order_id)
         .bind(user_token.user_id)
         .bind(receiver_id)
         .bind(body.delta)
@@ -116,7 +133,7 @@ pub async fn create_order_rating(
         remark: rating_row.try_get("remark").ok(),
         created_at: rating_row.get("created_at"),
     };
-    Ok(HttpResponse::Created().json(&out))
+    Ok(out)
 }
 
 #[utoipa::path(
@@ -0,0 +1,620 @@
+use crate::{
+    errors::CustomError,
+    models::{
+        orders::{
+            OrderCreateInput, RecurringOrderCreateInput, RecurringOrderOut, RecurringOrderRecord,
+            RecurringRunOut, RecurringRunStatusEnum, RecurringSkipInput, RecurringStatusEnum,
+        },
+        users::UserToken,
+    },
+    services::groups::ensure_member,
+    AppState,
+};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, TimeZone, Utc, Weekday};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgPool, Postgres, Row};
+use std::sync::Arc;
+
+// 提前生成的最小分钟数：需大于后台扫描间隔，保证订单在 goal_time 之前生成
+const MIN_GOAL_OFFSET_MINUTES: i32 = 5;
+// goal_time 已过但在该宽限时间内仍补生成订单，超过才视为错过
+const MISSED_GRACE_MINUTES: i64 = 10;
+
+const RECURRING_COLUMNS: &str = "recurring_id, user_id, group_id, title, items, rrule, tz_offset_minutes, goal_offset_minutes, points_cost, points_reward, status, next_run_at, last_run_at, created_at, updated_at";
+
+// ================= 周期规则（RRULE 子集） =================
+// 支持：FREQ=DAILY|WEEKLY; BYDAY=MO,TU,...; BYHOUR=0..23; BYMINUTE=0..59
+// 例：FREQ=WEEKLY;BYDAY=FR;BYHOUR=19;BYMINUTE=0  => 每周五 19:00
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleFreq {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: RuleFreq,
+    pub by_day: Vec<Weekday>,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl RecurrenceRule {
+    pub fn parse(raw: &str) -> Result<Self, CustomError> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix("RRULE:").unwrap_or(raw);
+        let mut freq: Option<RuleFreq> = None;
+        let mut by_day: Vec<Weekday> = Vec::new();
+        let mut hour: u32 = 0;
+        let mut minute: u32 = 0;
+        for part in raw.split(';').filter(|p| !p.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| CustomError::BadRequest(format!("周期规则格式错误: {}", part)))?;
+            match key.trim().to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    freq = match value.trim().to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(RuleFreq::Daily),
+                        "WEEKLY" => Some(RuleFreq::Weekly),
+                        other => {
+                            return Err(CustomError::BadRequest(format!("不支持的周期频率: {}", other)))
+                        }
+                    }
+                }
+                "BYDAY" => {
+                    for d in value.split(',') {
+                        let wd = match d.trim().to_ascii_uppercase().as_str() {
+                            "MO" => Weekday::Mon,
+                            "TU" => Weekday::Tue,
+                            "WE" => Weekday::Wed,
+                            "TH" => Weekday::Thu,
+                            "FR" => Weekday::Fri,
+                            "SA" => Weekday::Sat,
+                            "SU" => Weekday::Sun,
+                            other => {
+                                return Err(CustomError::BadRequest(format!("无效的星期: {}", other)))
+                            }
+                        };
+                        if !by_day.contains(&wd) {
+                            by_day.push(wd);
+                        }
+                    }
+                }
+                "BYHOUR" => {
+                    hour = value.trim().parse::<u32>()?;
+                    if hour > 23 {
+                        return Err(CustomError::BadRequest("BYHOUR 范围为 0..23".into()));
+                    }
+                }
+                "BYMINUTE" => {
+                    minute = value.trim().parse::<u32>()?;
+                    if minute > 59 {
+                        return Err(CustomError::BadRequest("BYMINUTE 范围为 0..59".into()));
+                    }
+                }
+                other => {
+                    return Err(CustomError::BadRequest(format!("不支持的周期规则字段: {}", other)))
+                }
+            }
+        }
+        let freq = freq.ok_or_else(|| CustomError::BadRequest("周期规则缺少 FREQ".into()))?;
+        if freq == RuleFreq::Weekly && by_day.is_empty() {
+            return Err(CustomError::BadRequest("每周规则需指定 BYDAY".into()));
+        }
+        Ok(Self {
+            freq,
+            by_day,
+            hour,
+            minute,
+        })
+    }
+
+    /// 计算严格晚于 `after` 的下一次发生时间（按 tz_offset_minutes 所在时区解释小时/星期）
+    pub fn next_after(&self, after: DateTime<Utc>, tz_offset_minutes: i32) -> Option<DateTime<Utc>> {
+        let offset = FixedOffset::east_opt(tz_offset_minutes * 60)?;
+        let local_date = after.with_timezone(&offset).date_naive();
+        for d in 0..=7 {
+            let date = local_date + Duration::days(d);
+            if !self.by_day.is_empty() && !self.by_day.contains(&date.weekday()) {
+                continue;
+            }
+            let naive = date.and_hms_opt(self.hour, self.minute, 0)?;
+            let candidate = offset.from_local_datetime(&naive).single()?.with_timezone(&Utc);
+            if candidate > after {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+}
+
+// ================= 接口 =================
+
+#[utoipa::path(
+    post,
+    path = "/recurring-orders",
+    tag = "订单",
+    request_body = RecurringOrderCreateInput,
+    responses((status = 201, body = RecurringOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_recurring_order(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<RecurringOrderCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.items.is_empty() {
+        return Err(CustomError::BadRequest("缺少菜品".into()));
+    }
+    let rule = RecurrenceRule::parse(&data.rrule)?;
+    let tz_offset = data.tz_offset_minutes.unwrap_or(480);
+    if tz_offset.abs() > 14 * 60 {
+        return Err(CustomError::BadRequest("时区偏移无效".into()));
+    }
+    let goal_offset = data.goal_offset_minutes.unwrap_or(60);
+    if !(MIN_GOAL_OFFSET_MINUTES..=24 * 60).contains(&goal_offset) {
+        return Err(CustomError::BadRequest(format!("提前生成时间范围为 {}..1440 分钟", MIN_GOAL_OFFSET_MINUTES)));
+    }
+    let db = &state.db_pool;
+    if let Some(gid) = data.group_id {
+        ensure_member(&mut *db.acquire().await?, gid, user_token.user_id).await?;
+    }
+    let next_run_at = rule.next_after(Utc::now(), tz_offset);
+
+    let rec = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "INSERT INTO recurring_orders (user_id, group_id, title, items, rrule, tz_offset_minutes, goal_offset_minutes, points_cost, points_reward, next_run_at) \
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10) RETURNING {}",
+        RECURRING_COLUMNS
+    ))
+    .bind(user_token.user_id)
+    .bind(data.group_id)
+    .bind(&data.title)
+    .bind(sqlx::types::Json(&data.items))
+    .bind(data.rrule.trim())
+    .bind(tz_offset)
+    .bind(goal_offset)
+    .bind(data.points_cost.unwrap_or(0))
+    .bind(data.points_reward.unwrap_or(0))
+    .bind(next_run_at)
+    .fetch_one(db)
+    .await?;
+    Ok(HttpResponse::Created().json(&RecurringOrderOut::from(rec)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/recurring-orders",
+    tag = "订单",
+    responses((status = 200, body = [RecurringOrderOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_recurring_orders(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let rows = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "SELECT {} FROM recurring_orders WHERE user_id=$1 ORDER BY created_at DESC",
+        RECURRING_COLUMNS
+    ))
+    .bind(user_token.user_id)
+    .fetch_all(db)
+    .await?;
+    let mut out_list: Vec<RecurringOrderOut> = Vec::with_capacity(rows.len());
+    for rec in rows {
+        let runs = sqlx::query_as::<_, RecurringRunOut>(
+            "SELECT occurrence_at, status, order_id, remark, created_at FROM recurring_order_runs WHERE recurring_id=$1 ORDER BY occurrence_at DESC LIMIT 5"
+        )
+        .bind(rec.recurring_id)
+        .fetch_all(db)
+        .await?;
+        let mut out = RecurringOrderOut::from(rec);
+        out.recent_runs = runs;
+        out_list.push(out);
+    }
+    Ok(HttpResponse::Ok().json(&out_list))
+}
+
+#[utoipa::path(
+    put,
+    path = "/recurring-orders/{id}/pause",
+    tag = "订单",
+    params(("id" = i64, Path, description = "周期订单ID")),
+    responses((status = 200, body = RecurringOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn pause_recurring_order(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let rec = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "UPDATE recurring_orders SET status='PAUSED', updated_at=NOW() WHERE recurring_id=$1 AND user_id=$2 RETURNING {}",
+        RECURRING_COLUMNS
+    ))
+    .bind(*id)
+    .bind(user_token.user_id)
+    .fetch_optional(&state.db_pool)
+    .await?;
+    let Some(rec) = rec else {
+        return Err(CustomError::BadRequest("周期订单不存在".into()));
+    };
+    Ok(HttpResponse::Ok().json(&RecurringOrderOut::from(rec)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/recurring-orders/{id}/resume",
+    tag = "订单",
+    params(("id" = i64, Path, description = "周期订单ID")),
+    responses((status = 200, body = RecurringOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn resume_recurring_order(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let rec = fetch_own(db, *id, user_token.user_id).await?;
+    let rule = RecurrenceRule::parse(&rec.rrule)?;
+    // 暂停期间的发生时间不再补单，从当前时间重新计算
+    let next_run_at = rule.next_after(Utc::now(), rec.tz_offset_minutes);
+    let rec = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "UPDATE recurring_orders SET status='ACTIVE', next_run_at=$2, updated_at=NOW() WHERE recurring_id=$1 RETURNING {}",
+        RECURRING_COLUMNS
+    ))
+    .bind(rec.recurring_id)
+    .bind(next_run_at)
+    .fetch_one(db)
+    .await?;
+    Ok(HttpResponse::Ok().json(&RecurringOrderOut::from(rec)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/recurring-orders/{id}/skip",
+    tag = "订单",
+    params(("id" = i64, Path, description = "周期订单ID")),
+    request_body = RecurringSkipInput,
+    responses((status = 200, body = RecurringOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn skip_recurring_occurrence(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<RecurringSkipInput>,
+) -> Result<impl Responder, CustomError> {
+    let db = &state.db_pool;
+    let mut tx = db.begin().await?;
+    let rec = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "SELECT {} FROM recurring_orders WHERE recurring_id=$1 AND user_id=$2 FOR UPDATE",
+        RECURRING_COLUMNS
+    ))
+    .bind(*id)
+    .bind(user_token.user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(mut rec) = rec else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("周期订单不存在".into()));
+    };
+    let rule = RecurrenceRule::parse(&rec.rrule)?;
+    let occurrence = match data.occurrence_at.or(rec.next_run_at) {
+        Some(o) => o,
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("没有可跳过的发生时间".into()));
+        }
+    };
+    if occurrence <= Utc::now() {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("只能跳过未来的发生时间".into()));
+    }
+    // 校验该时间确实是规则上的一次发生
+    if rule.next_after(occurrence - Duration::seconds(1), rec.tz_offset_minutes) != Some(occurrence) {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该时间不在周期规则内".into()));
+    }
+    let inserted = sqlx::query(
+        "INSERT INTO recurring_order_runs (recurring_id, occurrence_at, status, remark) VALUES ($1,$2,$3,$4) ON CONFLICT (recurring_id, occurrence_at) DO NOTHING"
+    )
+    .bind(rec.recurring_id)
+    .bind(occurrence)
+    .bind(RecurringRunStatusEnum::SKIPPED)
+    .bind(Some("用户跳过".to_string()))
+    .execute(&mut *tx)
+    .await?;
+    if inserted.rows_affected() == 0 {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("该次已处理或已跳过".into()));
+    }
+    if rec.next_run_at == Some(occurrence) {
+        rec.next_run_at = rule.next_after(occurrence, rec.tz_offset_minutes);
+        sqlx::query("UPDATE recurring_orders SET next_run_at=$2, updated_at=NOW() WHERE recurring_id=$1")
+            .bind(rec.recurring_id)
+            .bind(rec.next_run_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let runs = sqlx::query_as::<_, RecurringRunOut>(
+        "SELECT occurrence_at, status, order_id, remark, created_at FROM recurring_order_runs WHERE recurring_id=$1 ORDER BY occurrence_at DESC LIMIT 5"
+    )
+    .bind(rec.recurring_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    let mut out = RecurringOrderOut::from(rec);
+    out.recent_runs = runs;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/recurring-orders/{id}",
+    tag = "订单",
+    params(("id" = i64, Path, description = "周期订单ID")),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_recurring_order(
+    user_token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let res = sqlx::query("DELETE FROM recurring_orders WHERE recurring_id=$1 AND user_id=$2")
+        .bind(*id)
+        .bind(user_token.user_id)
+        .execute(&state.db_pool)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(CustomError::BadRequest("周期订单不存在".into()));
+    }
+    Ok(HttpResponse::Ok().body("deleted"))
+}
+
+async fn fetch_own(db: &PgPool, recurring_id: i64, user_id: i64) -> Result<RecurringOrderRecord, CustomError> {
+    sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "SELECT {} FROM recurring_orders WHERE recurring_id=$1 AND user_id=$2",
+        RECURRING_COLUMNS
+    ))
+    .bind(recurring_id)
+    .bind(user_id)
+    .fetch_optional(db)
+    .await?
+    .ok_or_else(|| CustomError::BadRequest("周期订单不存在".into()))
+}
+
+// ================= 后台任务 =================
+
+// 每分钟扫描：到达提前生成时间（next_run_at - goal_offset_minutes）的 ACTIVE 模板，通过 insert_order 生成真实订单。
+pub async fn run_recurring_worker(state: Arc<AppState>) {
+    let db = &state.db_pool;
+    loop {
+        if let Err(e) = materialise_due(db).await {
+            log::warn!("recurring order task error: {}", e);
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+    }
+}
+
+async fn materialise_due(db: &PgPool) -> Result<(), CustomError> {
+    let rows = sqlx::query(
+        "SELECT recurring_id FROM recurring_orders WHERE status='ACTIVE' AND next_run_at IS NOT NULL \
+         AND next_run_at - make_interval(mins => goal_offset_minutes) <= NOW() ORDER BY next_run_at LIMIT 100"
+    )
+    .fetch_all(db)
+    .await?;
+    for r in rows {
+        let rid: i64 = r.get("recurring_id");
+        if let Err(e) = materialise_one(db, rid).await {
+            log::warn!("recurring order {} materialise error: {}", rid, e);
+        }
+    }
+    Ok(())
+}
+
+async fn materialise_one(db: &PgPool, recurring_id: i64) -> Result<(), CustomError> {
+    let mut tx = db.begin().await?;
+    // SKIP LOCKED：多实例部署时避免重复处理同一模板
+    let rec = sqlx::query_as::<_, RecurringOrderRecord>(&format!(
+        "SELECT {} FROM recurring_orders WHERE recurring_id=$1 AND status='ACTIVE' FOR UPDATE SKIP LOCKED",
+        RECURRING_COLUMNS
+    ))
+    .bind(recurring_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(rec) = rec else {
+        tx.rollback().await.ok();
+        return Ok(());
+    };
+    let Some(occurrence) = rec.next_run_at else {
+        tx.rollback().await.ok();
+        return Ok(());
+    };
+    if occurrence - Duration::minutes(rec.goal_offset_minutes as i64) > Utc::now() {
+        tx.rollback().await.ok();
+        return Ok(());
+    }
+    let rule = RecurrenceRule::parse(&rec.rrule)?;
+    let next_run_at = rule.next_after(occurrence, rec.tz_offset_minutes);
+
+    let already = sqlx::query("SELECT status FROM recurring_order_runs WHERE recurring_id=$1 AND occurrence_at=$2")
+        .bind(rec.recurring_id)
+        .bind(occurrence)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    let mut created_order: Option<i64> = None;
+    if already.is_none() {
+        if occurrence + Duration::minutes(MISSED_GRACE_MINUTES) <= Utc::now() {
+            // 服务停机等原因错过了生成时间（超过宽限期）：不再补发过期订单
+            record_run(&mut tx, rec.recurring_id, occurrence, RecurringRunStatusEnum::SKIPPED, None, Some("错过生成时间"))
+                .await?;
+        } else {
+            let input = OrderCreateInput {
+                group_id: rec.group_id,
+                invite_code: None,
+                goal_time: Some(occurrence),
+                items: rec.items.0.clone(),
+                points_cost: Some(rec.points_cost),
+                points_reward: Some(rec.points_reward),
+            };
+            match crate::orders::new::insert_order(&mut tx, rec.user_id, &input).await {
+                Ok(out) => {
+                    record_run(&mut tx, rec.recurring_id, occurrence, RecurringRunStatusEnum::CREATED, Some(out.order_id), None)
+                        .await?;
+                    created_order = Some(out.order_id);
+                }
+                Err(e) => {
+                    // 下单失败（如已退出组、菜品被删除）：回滚后单独记录失败并推进到下一次
+                    tx.rollback().await.ok();
+                    let mut tx = db.begin().await?;
+                    let reason = e.to_string();
+                    record_run(&mut tx, rec.recurring_id, occurrence, RecurringRunStatusEnum::FAILED, None, Some(&reason))
+                        .await?;
+                    advance(&mut tx, rec.recurring_id, occurrence, next_run_at).await?;
+                    tx.commit().await?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+    advance(&mut tx, rec.recurring_id, occurrence, next_run_at).await?;
+    tx.commit().await?;
+
+    if let Some(oid) = created_order {
+        crate::orders::new::spawn_order_push(db.clone(), oid, "recurring order");
+    }
+    Ok(())
+}
+
+async fn record_run(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    recurring_id: i64,
+    occurrence: DateTime<Utc>,
+    status: RecurringRunStatusEnum,
+    order_id: Option<i64>,
+    remark: Option<&str>,
+) -> Result<(), CustomError> {
+    sqlx::query(
+        "INSERT INTO recurring_order_runs (recurring_id, occurrence_at, status, order_id, remark) VALUES ($1,$2,$3,$4,$5) ON CONFLICT (recurring_id, occurrence_at) DO NOTHING"
+    )
+    .bind(recurring_id)
+    .bind(occurrence)
+    .bind(status)
+    .bind(order_id)
+    .bind(remark)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+async fn advance(
+    tx: &mut sqlx::Transaction<'_, Postgres>,
+    recurring_id: i64,
+    occurrence: DateTime<Utc>,
+    next_run_at: Option<DateTime<Utc>>,
+) -> Result<(), CustomError> {
+    sqlx::query("UPDATE recurring_orders SET last_run_at=$2, next_run_at=$3, updated_at=NOW() WHERE recurring_id=$1")
+        .bind(recurring_id)
+        .bind(occurrence)
+        .bind(next_run_at)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn parse_weekly_byday() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;BYDAY=mo,FR,MO;BYHOUR=19;BYMINUTE=30").unwrap();
+        assert_eq!(rule.freq, RuleFreq::Weekly);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Fri]);
+        assert_eq!((rule.hour, rule.minute), (19, 30));
+    }
+
+    #[test]
+    fn parse_daily_defaults_to_midnight() {
+        let rule = RecurrenceRule::parse(" FREQ=DAILY; ").unwrap();
+        assert_eq!(rule.freq, RuleFreq::Daily);
+        assert!(rule.by_day.is_empty());
+        assert_eq!((rule.hour, rule.minute), (0, 0));
+    }
+
+    #[test]
+    fn parse_rejects_interval() {
+        // INTERVAL 不在支持的子集中，需明确拒绝而不是静默按每周/每天执行
+        assert!(RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=FR").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;INTERVAL=1").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_bad_input() {
+        for raw in [
+            "",
+            "BYDAY=FR",
+            "FREQ=MONTHLY",
+            "FREQ=WEEKLY",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=DAILY;BYHOUR=24",
+            "FREQ=DAILY;BYMINUTE=60",
+            "FREQ=DAILY;BYHOUR=abc",
+            "FREQ=DAILY;BYHOUR",
+        ] {
+            assert!(RecurrenceRule::parse(raw).is_err(), "{raw}");
+        }
+    }
+
+    #[test]
+    fn next_after_crosses_week_boundary_in_local_time() {
+        // 每周五 19:00（UTC+8），恰好在本周五 19:00 时下一次为下周五
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=FR;BYHOUR=19;BYMINUTE=0").unwrap();
+        assert_eq!(
+            rule.next_after(utc("2024-06-07T11:00:00Z"), 480),
+            Some(utc("2024-06-14T11:00:00Z"))
+        );
+        // 本地周六晚上 => 下周五
+        assert_eq!(
+            rule.next_after(utc("2024-06-08T14:00:00Z"), 480),
+            Some(utc("2024-06-14T11:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_after_uses_local_weekday() {
+        // 每周一 08:00（UTC+8）：UTC 仍是周日 17:00 时本地已是周一 01:00
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=MO;BYHOUR=8").unwrap();
+        assert_eq!(
+            rule.next_after(utc("2024-06-02T17:00:00Z"), 480),
+            Some(utc("2024-06-03T00:00:00Z"))
+        );
+        // 每周日 22:00（UTC-5）：UTC 已是周一 02:00 时本地仍是周日 21:00
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;BYDAY=SU;BYHOUR=22").unwrap();
+        assert_eq!(
+            rule.next_after(utc("2024-06-03T02:00:00Z"), -300),
+            Some(utc("2024-06-03T03:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn next_after_daily_rolls_to_next_day() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;BYHOUR=7;BYMINUTE=15").unwrap();
+        assert_eq!(
+            rule.next_after(utc("2024-06-02T23:15:00Z"), 480),
+            Some(utc("2024-06-03T23:15:00Z"))
+        );
+    }
+}
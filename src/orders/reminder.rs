@@ -0,0 +1,164 @@
+use crate::models::orders::OrderStatusEnum;
+use crate::{errors::CustomError, AppState};
+use sqlx::{PgPool, Row};
+use std::{env, sync::Arc};
+
+/// 到期提醒写入 order_status_history 的备注
+const REMIND_REMARK: &str = "临近期望时间提醒";
+/// order_reminders.kind：临近期望时间提醒
+const REMIND_KIND_UPCOMING: &str = "GOAL_TIME_UPCOMING";
+const OVERDUE_REMARK: &str = "超过期望时间未完成";
+
+/// 超时订单处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverduePolicy {
+    /// 标记为 OVERDUE，接单人仍可完成
+    MarkOverdue,
+    /// 直接关闭为 SYSTEM_CLOSED
+    SystemClose,
+}
+
+#[derive(Debug, Clone)]
+pub struct GoalTimeConfig {
+    /// goal_time 前多少分钟提醒接单人
+    pub remind_before_minutes: i64,
+    /// goal_time 之后的宽限分钟数，超过才判定超时
+    pub overdue_grace_minutes: i64,
+    pub overdue_policy: OverduePolicy,
+}
+
+impl GoalTimeConfig {
+    pub fn from_env() -> Self {
+        let remind_before_minutes = env::var("ORDER_REMIND_BEFORE_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(30);
+        let overdue_grace_minutes = env::var("ORDER_OVERDUE_GRACE_MINUTES")
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .unwrap_or(0);
+        let overdue_policy = match env::var("ORDER_OVERDUE_POLICY")
+            .unwrap_or_default()
+            .to_ascii_uppercase()
+            .as_str()
+        {
+            "SYSTEM_CLOSED" | "CLOSE" => OverduePolicy::SystemClose,
+            _ => OverduePolicy::MarkOverdue,
+        };
+        Self {
+            remind_before_minutes,
+            overdue_grace_minutes,
+            overdue_policy,
+        }
+    }
+}
+
+// Runs periodic goal_time checks: remind receivers of ACCEPTED orders shortly before goal_time,
+// and flag ACCEPTED orders still unfinished after goal_time according to the configured policy.
+pub async fn run_goal_time_worker(state: Arc<AppState>) {
+    let db = &state.db_pool;
+    let config = GoalTimeConfig::from_env();
+    loop {
+        if let Err(e) = remind_upcoming(db, &config).await {
+            log::warn!("order goal_time remind task error: {}", e);
+        }
+        if let Err(e) = flag_overdue(db, &config).await {
+            log::warn!("order overdue task error: {}", e);
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(60)).await; // run each minute
+    }
+}
+
+async fn remind_upcoming(db: &PgPool, config: &GoalTimeConfig) -> Result<(), CustomError> {
+    if config.remind_before_minutes <= 0 {
+        return Ok(());
+    }
+    // 写入提醒记录即占位：同一订单、同一期望时间只提醒一次，多实例并发时也只有一个能写入
+    let mut tx = db.begin().await?;
+    let rows = sqlx::query(
+        "INSERT INTO order_reminders (order_id, kind, goal_time) \
+         SELECT o.order_id, $2, o.goal_time FROM orders o WHERE o.status='ACCEPTED' AND o.goal_time IS NOT NULL \
+         AND o.goal_time > NOW() AND o.goal_time <= NOW() + make_interval(mins => $1) \
+         ON CONFLICT (order_id, kind, goal_time) DO NOTHING RETURNING order_id"
+    )
+    .bind(config.remind_before_minutes as i32)
+    .bind(REMIND_KIND_UPCOMING)
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.is_empty() {
+        tx.rollback().await.ok();
+        return Ok(());
+    }
+    let ids: Vec<i64> = rows.iter().map(|r| r.get::<i64, _>("order_id")).collect();
+
+    // 提醒不改变状态：记录一条 ACCEPTED -> ACCEPTED 的系统历史
+    for oid in &ids {
+        sqlx::query("INSERT INTO order_status_history (order_id, from_status, to_status, changed_by, remark) VALUES ($1,$2,$3,$4,$5)")
+            .bind(oid)
+            .bind(OrderStatusEnum::ACCEPTED)
+            .bind(OrderStatusEnum::ACCEPTED)
+            .bind(None::<i64>) // system
+            .bind(REMIND_REMARK)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    for oid in ids {
+        let pool_clone = db.clone();
+        tokio::spawn(async move {
+            let receivers = match crate::services::notifications::order_receiver_ids(oid, &pool_clone).await {
+                Ok(v) => v,
+                Err(e) => {
+                    log::warn!("order remind receiver lookup error: {}", e);
+                    return;
+                }
+            };
+            if let Err(e) = crate::services::notifications::push_order_notice(oid, "订单即将到达期望时间", &receivers, pool_clone).await {
+                log::warn!("order remind push error: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+async fn flag_overdue(db: &PgPool, config: &GoalTimeConfig) -> Result<(), CustomError> {
+    let to_status = match config.overdue_policy {
+        OverduePolicy::MarkOverdue => OrderStatusEnum::OVERDUE,
+        OverduePolicy::SystemClose => OrderStatusEnum::SYSTEM_CLOSED,
+    };
+    let mut tx = db.begin().await?;
+    let rows = sqlx::query(
+        "SELECT order_id FROM orders WHERE status='ACCEPTED' AND goal_time IS NOT NULL \
+         AND goal_time + make_interval(mins => $1) < NOW() FOR UPDATE SKIP LOCKED"
+    )
+    .bind(config.overdue_grace_minutes.max(0) as i32)
+    .fetch_all(&mut *tx)
+    .await?;
+    if rows.is_empty() {
+        tx.rollback().await.ok();
+        return Ok(());
+    }
+    let ids: Vec<i64> = rows.iter().map(|r| r.get::<i64, _>("order_id")).collect();
+    for oid in &ids {
+        sqlx::query("UPDATE orders SET status=$2, last_status_change_at=NOW(), updated_at=NOW() WHERE order_id=$1")
+            .bind(oid)
+            .bind(to_status)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO order_status_history (order_id, from_status, to_status, changed_by, remark) VALUES ($1,$2,$3,$4,$5)")
+            .bind(oid)
+            .bind(OrderStatusEnum::ACCEPTED)
+            .bind(to_status)
+            .bind(None::<i64>) // system
+            .bind(OVERDUE_REMARK)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    // 异步推送超时状态
+    for oid in ids {
+        crate::orders::new::spawn_order_push(db.clone(), oid, "order overdue");
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::orders::{
+        OrderCreateInput, OrderItemCreateInput, OrderPlaceInput, PlacedOrderOut, SkippedFoodOut,
+    },
+    orders::new::{insert_order, spawn_order_push},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// 按菜品当前状态拆分：可点的保留，下架/删除/未审核通过的记录跳过原因
+pub async fn split_available_items(
+    conn: &mut PgConnection,
+    items: &[OrderItemCreateInput],
+) -> Result<(Vec<OrderItemCreateInput>, Vec<SkippedFoodOut>), CustomError> {
+    let ids: Vec<i64> = items.iter().map(|i| i.food_id).collect();
+    let rows = sqlx::query(
+        "SELECT food_id, food_name, is_del, food_status::text AS food_status, apply_status::text AS apply_status \
+         FROM foods WHERE food_id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let foods: HashMap<i64, (String, i16, String, String)> = rows
+        .into_iter()
+        .map(|r| {
+            (
+                r.get::<i64, _>("food_id"),
+                (
+                    r.get::<String, _>("food_name"),
+                    r.get::<i16, _>("is_del"),
+                    r.get::<String, _>("food_status"),
+                    r.get::<String, _>("apply_status"),
+                ),
+            )
+        })
+        .collect();
+
+    let mut available = Vec::new();
+    let mut skipped = Vec::new();
+    for item in items {
+        let reason = match foods.get(&item.food_id) {
+            None => Some("已删除"),
+            Some((_, is_del, _, _)) if *is_del != 0 => Some("已删除"),
+            Some((_, _, status, _)) if status == "OFF" => Some("已下架"),
+            Some((_, _, status, apply)) if status != "NORMAL" || apply != "APPROVED" => Some("暂不可点"),
+            Some(_) => None,
+        };
+        match reason {
+            Some(reason) => skipped.push(SkippedFoodOut {
+                food_id: item.food_id,
+                food_name: foods.get(&item.food_id).map(|f| f.0.clone()),
+                reason: reason.into(),
+            }),
+            None => available.push(item.clone()),
+        }
+    }
+    Ok((available, skipped))
+}
+
+// 过滤不可点菜品后经 insert_order 下单（与 create_order 同一路径）
+pub async fn place_items(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i64,
+    group_id: Option<i64>,
+    items: &[OrderItemCreateInput],
+    input: &OrderPlaceInput,
+) -> Result<PlacedOrderOut, CustomError> {
+    let (available, skipped) = split_available_items(tx, items).await?;
+    if available.is_empty() {
+        return Err(CustomError::BadRequest("菜品均已下架或删除，无法下单".into()));
+    }
+    let data = OrderCreateInput {
+        group_id: input.group_id.or(group_id),
+        invite_code: input.invite_code.clone(),
+        goal_time: input.goal_time,
+        items: available,
+        points_cost: input.points_cost,
+        points_reward: input.points_reward,
+    };
+    let order = insert_order(tx, user_id, &data).await?;
+    Ok(PlacedOrderOut { order, skipped })
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/reorder",
+    tag = "订单",
+    summary = "再来一单：复制历史订单菜品下新单",
+    params(("id" = i64, Path, description = "原订单ID")),
+    request_body = OrderPlaceInput,
+    responses((status = 201, body = PlacedOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn reorder(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderPlaceInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    let source = sqlx::query(
+        "SELECT o.group_id, \
+         (o.user_id=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=o.group_id AND m.user_id=$2)) AS allowed \
+         FROM orders o WHERE o.order_id=$1",
+    )
+    .bind(*id)
+    .bind(token.user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let group_id: Option<i64> = match source {
+        Some(r) if r.get::<bool, _>("allowed") => r.get("group_id"),
+        Some(_) => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("无权复制该订单".into()));
+        }
+        None => {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("订单不存在".into()));
+        }
+    };
+    let items: Vec<OrderItemCreateInput> = sqlx::query(
+        "SELECT food_id, quantity FROM order_items WHERE order_id=$1 ORDER BY id",
+    )
+    .bind(*id)
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|r| OrderItemCreateInput {
+        food_id: r.get("food_id"),
+        quantity: Some(r.get("quantity")),
+    })
+    .collect();
+
+    let out = match place_items(&mut tx, token.user_id, group_id, &items, &data).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    tx.commit().await?;
+    spawn_order_push(state.db_pool.clone(), out.order.order_id, "order reorder");
+    Ok(HttpResponse::Created().json(&out))
+}
@@ -0,0 +1,223 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::orders::{ShoppingListItemOut, ShoppingListOut, ShoppingListQuery},
+    services::{
+        groups::ensure_member,
+        units::{unit_base, UnitKind},
+    },
+    AppState,
+};
+use chrono::{Duration, Utc};
+use ntex::web::{
+    types::{Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{Postgres, QueryBuilder, Row};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+// 单次汇总的订单数上限，超出时返回 truncated
+const MAX_ORDERS: usize = 200;
+
+// 基准单位总量转成便于采购的单位
+fn display_amount(kind: UnitKind, base: f64) -> (f64, &'static str) {
+    match kind {
+        UnitKind::Mass if base >= 1000.0 => (base / 1000.0, "kg"),
+        UnitKind::Mass => (base, "g"),
+        UnitKind::Volume if base >= 1000.0 => (base / 1000.0, "L"),
+        UnitKind::Volume => (base, "ml"),
+    }
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+// 合并键：同名食材按可换算类别合并；不可换算的按原单位合并；无用量的归为“适量”
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum MergeKey {
+    Convertible(String, UnitKind),
+    Unit(String, String),
+    AsNeeded(String),
+}
+
+struct Line {
+    name: String,
+    kind: Option<UnitKind>,
+    unit: Option<String>,
+    amount: f64,
+    foods: BTreeSet<String>,
+}
+
+fn parse_ids(raw: &str) -> Result<Vec<i64>, CustomError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<i64>().map_err(|_| CustomError::BadRequest("无效的订单ID".into())))
+        .collect()
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/shopping-list",
+    tag = "订单",
+    summary = "按待处理/已接单订单汇总采购清单（同名食材合并并换算单位）",
+    params(ShoppingListQuery),
+    responses((status = 200, body = ShoppingListOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_shopping_list(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<ShoppingListQuery>,
+) -> Result<impl Responder, CustomError> {
+    let uid = token.user_id;
+    let mut conn = state.db_pool.acquire().await?;
+
+    let mut qb: QueryBuilder<Postgres> = QueryBuilder::new(
+        "SELECT o.order_id FROM orders o WHERE o.status IN ('PENDING','ACCEPTED')",
+    );
+    match query.group_id {
+        Some(gid) => {
+            ensure_member(&mut conn, gid, uid).await?;
+            qb.push(" AND o.group_id = ").push_bind(gid);
+        }
+        None => {
+            qb.push(" AND (o.user_id = ")
+                .push_bind(uid)
+                .push(" OR o.receiver_id = ")
+                .push_bind(uid)
+                .push(" OR o.group_id IN (SELECT group_id FROM association_group_members WHERE user_id = ")
+                .push_bind(uid)
+                .push("))");
+        }
+    }
+    match query.order_ids.as_deref().map(parse_ids).transpose()? {
+        Some(ids) if !ids.is_empty() => {
+            qb.push(" AND o.order_id = ANY(").push_bind(ids).push(")");
+        }
+        _ => {
+            let from = query.from.unwrap_or_else(Utc::now);
+            let to = query.to.unwrap_or(from + Duration::days(7));
+            if to <= from {
+                return Err(CustomError::BadRequest("结束时间需晚于开始时间".into()));
+            }
+            qb.push(" AND (o.goal_time IS NULL OR (o.goal_time >= ")
+                .push_bind(from)
+                .push(" AND o.goal_time < ")
+                .push_bind(to)
+                .push("))");
+        }
+    }
+    // 多取一条用于判断是否截断
+    qb.push(" ORDER BY o.order_id LIMIT ").push_bind((MAX_ORDERS + 1) as i64);
+    let mut order_ids: Vec<i64> = qb
+        .build()
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|r| r.get("order_id"))
+        .collect();
+    let truncated = order_ids.len() > MAX_ORDERS;
+    order_ids.truncate(MAX_ORDERS);
+    if order_ids.is_empty() {
+        return Ok(HttpResponse::Ok().json(&ShoppingListOut {
+            order_ids,
+            truncated,
+            items: Vec::new(),
+            foods_without_recipe: Vec::new(),
+        }));
+    }
+
+    // 各菜品总份数
+    let dishes: Vec<(i64, String, i64)> = sqlx::query(
+        "SELECT oi.food_id, f.food_name, SUM(oi.quantity)::bigint AS servings \
+         FROM order_items oi JOIN foods f ON f.food_id=oi.food_id \
+         WHERE oi.order_id = ANY($1) GROUP BY oi.food_id, f.food_name ORDER BY f.food_name",
+    )
+    .bind(&order_ids)
+    .fetch_all(&mut *conn)
+    .await?
+    .into_iter()
+    .map(|r| (r.get("food_id"), r.get("food_name"), r.get("servings")))
+    .collect();
+    let food_ids: Vec<i64> = dishes.iter().map(|d| d.0).collect();
+    let ingredient_rows = sqlx::query(
+        "SELECT food_id, name, quantity, unit FROM food_ingredients WHERE food_id = ANY($1) ORDER BY food_id, sort, id",
+    )
+    .bind(&food_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut ingredients: HashMap<i64, Vec<(String, Option<f64>, Option<String>)>> = HashMap::new();
+    for r in ingredient_rows {
+        ingredients
+            .entry(r.get("food_id"))
+            .or_default()
+            .push((r.get("name"), r.get("quantity"), r.get("unit")));
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut index: HashMap<MergeKey, usize> = HashMap::new();
+    let mut foods_without_recipe: Vec<String> = Vec::new();
+    for (food_id, food_name, servings) in &dishes {
+        let Some(list) = ingredients.get(food_id) else {
+            foods_without_recipe.push(food_name.clone());
+            continue;
+        };
+        for (name, quantity, unit) in list {
+            let norm = name.trim().to_lowercase();
+            let unit = unit.as_deref().map(str::trim).filter(|u| !u.is_empty());
+            let (key, kind, amount) = match (quantity, unit) {
+                (None, _) => (MergeKey::AsNeeded(norm), None, 0.0),
+                (Some(q), Some(u)) => match unit_base(u) {
+                    Some((kind, factor)) => (MergeKey::Convertible(norm, kind), Some(kind), q * factor * *servings as f64),
+                    None => (MergeKey::Unit(norm, u.to_string()), None, q * *servings as f64),
+                },
+                (Some(q), None) => (MergeKey::Unit(norm, String::new()), None, q * *servings as f64),
+            };
+            let idx = *index.entry(key).or_insert_with(|| {
+                lines.push(Line {
+                    name: name.trim().to_string(),
+                    kind,
+                    unit: quantity.and(unit.map(str::to_string)),
+                    amount: 0.0,
+                    foods: BTreeSet::new(),
+                });
+                lines.len() - 1
+            });
+            lines[idx].amount += amount;
+            lines[idx].foods.insert(food_name.clone());
+        }
+    }
+
+    let mut items: Vec<ShoppingListItemOut> = lines
+        .into_iter()
+        .map(|l| {
+            let (quantity, unit) = match (l.kind, &l.unit) {
+                (Some(kind), _) => {
+                    let (q, u) = display_amount(kind, l.amount);
+                    (Some(round2(q)), Some(u.to_string()))
+                }
+                (None, Some(u)) => (Some(round2(l.amount)), Some(u.clone())),
+                // 无单位的计数（如 “鸡蛋 2”）
+                (None, None) if l.amount > 0.0 => (Some(round2(l.amount)), None),
+                (None, None) => (None, None),
+            };
+            ShoppingListItemOut {
+                name: l.name,
+                quantity,
+                unit,
+                foods: l.foods.into_iter().collect(),
+            }
+        })
+        .collect();
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(HttpResponse::Ok().json(&ShoppingListOut {
+        order_ids,
+        truncated,
+        items,
+        foods_without_recipe,
+    }))
+}
@@ -0,0 +1,258 @@
+use crate::models::users::UserToken;
+use crate::{
+    errors::CustomError,
+    models::orders::{
+        OrderItemCreateInput, OrderPlaceInput, OrderTemplateCreateInput, OrderTemplateOut,
+        OrderTemplateQuery, OrderTemplateRecord, OrderTemplateUpdateInput, PlacedOrderOut,
+    },
+    orders::{new::spawn_order_push, reorder::place_items},
+    services::groups::{ensure_member, is_member},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+const TEMPLATE_COLUMNS: &str = "template_id, user_id, group_id, name, items, created_at, updated_at";
+
+fn validate_name(name: &str) -> Result<String, CustomError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > 64 {
+        return Err(CustomError::BadRequest("模板名称长度需在1-64个字符之间".into()));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_items(items: &[OrderItemCreateInput]) -> Result<(), CustomError> {
+    if items.is_empty() {
+        return Err(CustomError::BadRequest("缺少菜品".into()));
+    }
+    if items.iter().any(|i| i.quantity.unwrap_or(1) <= 0) {
+        return Err(CustomError::BadRequest("数量必须大于0".into()));
+    }
+    Ok(())
+}
+
+// 读取模板并校验可见性：个人模板仅本人，组模板组内成员可见
+async fn load_visible_template(
+    conn: &mut PgConnection,
+    template_id: i64,
+    user_id: i64,
+) -> Result<OrderTemplateRecord, CustomError> {
+    let rec: Option<OrderTemplateRecord> = sqlx::query_as(&format!(
+        "SELECT {} FROM order_templates WHERE template_id=$1",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(template_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let rec = match rec {
+        Some(r) => r,
+        None => return Err(CustomError::BadRequest("模板不存在".into())),
+    };
+    let visible = match rec.group_id {
+        Some(gid) => rec.user_id == user_id || is_member(conn, gid, user_id).await?,
+        None => rec.user_id == user_id,
+    };
+    if !visible {
+        return Err(CustomError::BadRequest("模板不存在".into()));
+    }
+    Ok(rec)
+}
+
+#[utoipa::path(
+    post,
+    path = "/order-templates",
+    tag = "订单",
+    request_body = OrderTemplateCreateInput,
+    responses((status = 201, body = OrderTemplateOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_order_template(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<OrderTemplateCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    let name = validate_name(&data.name)?;
+    let mut conn = state.db_pool.acquire().await?;
+    if let Some(gid) = data.group_id {
+        ensure_member(&mut conn, gid, token.user_id).await?;
+    }
+    let items: Vec<OrderItemCreateInput> = match (&data.items, data.from_order_id) {
+        (Some(items), None) => items.clone(),
+        (None, Some(order_id)) => {
+            let owner: Option<i64> = sqlx::query_scalar("SELECT user_id FROM orders WHERE order_id=$1")
+                .bind(order_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+            if owner != Some(token.user_id) {
+                return Err(CustomError::BadRequest("只能从自己的订单保存模板".into()));
+            }
+            sqlx::query("SELECT food_id, quantity FROM order_items WHERE order_id=$1 ORDER BY id")
+                .bind(order_id)
+                .fetch_all(&mut *conn)
+                .await?
+                .into_iter()
+                .map(|r| OrderItemCreateInput {
+                    food_id: r.get("food_id"),
+                    quantity: Some(r.get("quantity")),
+                })
+                .collect()
+        }
+        _ => return Err(CustomError::BadRequest("items 与 from_order_id 需二选一".into())),
+    };
+    validate_items(&items)?;
+
+    let rec: OrderTemplateRecord = sqlx::query_as(&format!(
+        "INSERT INTO order_templates (user_id, group_id, name, items) VALUES ($1,$2,$3,$4) RETURNING {}",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(token.user_id)
+    .bind(data.group_id)
+    .bind(&name)
+    .bind(sqlx::types::Json(&items))
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(HttpResponse::Created().json(&OrderTemplateOut::from(rec)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/order-templates",
+    tag = "订单",
+    params(OrderTemplateQuery),
+    responses((status = 200, body = [OrderTemplateOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_order_templates(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    query: Query<OrderTemplateQuery>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let list: Vec<OrderTemplateRecord> = match query.group_id {
+        Some(gid) => {
+            ensure_member(&mut conn, gid, token.user_id).await?;
+            sqlx::query_as(&format!(
+                "SELECT {} FROM order_templates WHERE group_id=$1 ORDER BY updated_at DESC",
+                TEMPLATE_COLUMNS
+            ))
+            .bind(gid)
+            .fetch_all(&mut *conn)
+            .await?
+        }
+        None => {
+            sqlx::query_as(&format!(
+                "SELECT {} FROM order_templates WHERE (user_id=$1 AND group_id IS NULL) \
+                 OR group_id IN (SELECT group_id FROM association_group_members WHERE user_id=$1) \
+                 ORDER BY updated_at DESC",
+                TEMPLATE_COLUMNS
+            ))
+            .bind(token.user_id)
+            .fetch_all(&mut *conn)
+            .await?
+        }
+    };
+    let out: Vec<OrderTemplateOut> = list.into_iter().map(OrderTemplateOut::from).collect();
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/order-templates/{id}",
+    tag = "订单",
+    params(("id" = i64, Path, description = "模板ID")),
+    request_body = OrderTemplateUpdateInput,
+    responses((status = 200, body = OrderTemplateOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_order_template(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderTemplateUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let name = data.name.as_deref().map(validate_name).transpose()?;
+    if let Some(items) = &data.items {
+        validate_items(items)?;
+    }
+    // 仅创建人可修改
+    let rec: Option<OrderTemplateRecord> = sqlx::query_as(&format!(
+        "UPDATE order_templates SET name=COALESCE($3, name), items=COALESCE($4, items), updated_at=NOW() \
+         WHERE template_id=$1 AND user_id=$2 RETURNING {}",
+        TEMPLATE_COLUMNS
+    ))
+    .bind(*id)
+    .bind(token.user_id)
+    .bind(name)
+    .bind(data.items.as_ref().map(sqlx::types::Json))
+    .fetch_optional(&state.db_pool)
+    .await?;
+    match rec {
+        Some(r) => Ok(HttpResponse::Ok().json(&OrderTemplateOut::from(r))),
+        None => Err(CustomError::BadRequest("模板不存在或无权修改".into())),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/order-templates/{id}",
+    tag = "订单",
+    params(("id" = i64, Path, description = "模板ID")),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_order_template(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let res = sqlx::query("DELETE FROM order_templates WHERE template_id=$1 AND user_id=$2")
+        .bind(*id)
+        .bind(token.user_id)
+        .execute(&state.db_pool)
+        .await?;
+    if res.rows_affected() == 0 {
+        return Err(CustomError::BadRequest("模板不存在或无权删除".into()));
+    }
+    Ok(HttpResponse::Ok().body("deleted"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/order-templates/{id}/place",
+    tag = "订单",
+    summary = "按模板一键下单",
+    params(("id" = i64, Path, description = "模板ID")),
+    request_body = OrderPlaceInput,
+    responses((status = 201, body = PlacedOrderOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn place_order_template(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<OrderPlaceInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    let rec = match load_visible_template(&mut tx, *id, token.user_id).await {
+        Ok(r) => r,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let out = match place_items(&mut tx, token.user_id, rec.group_id, &rec.items.0, &data).await {
+        Ok(o) => o,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    tx.commit().await?;
+    spawn_order_push(state.db_pool.clone(), out.order.order_id, "order template place");
+    Ok(HttpResponse::Created().json(&out))
+}
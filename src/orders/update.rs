@@ -1,5 +1,6 @@
 use chrono::Utc;
-use ntex::web::{types::{Json, State}, HttpResponse, Responder};
+use ntex::http::StatusCode;
+use ntex::web::{types::{Json, State}, HttpRequest, Responder};
 use sqlx::Row;
 use std::sync::Arc;
 use crate::{
@@ -8,6 +9,7 @@ use crate::{
         orders::{OrderStatusUpdateInput, OrderStatusEnum, OrderRecord, OrderItemRecord, OrderItemOut, OrderStatusHistoryOut, OrderOutNew},
         users::UserToken,
     },
+    services::{food_stats, idempotency, pantry_stock},
     AppState
 };
 
@@ -15,14 +17,26 @@ use crate::{
     put,
     path = "/orders/status",
     tag = "订单",
+    params(("Idempotency-Key" = Option<String>, Header, description = "幂等键，重试时复用")),
     request_body = OrderStatusUpdateInput,
     responses((status = 200, body = OrderOutNew))
 )]
 pub async fn update_order_status(
     user_token: UserToken,
     state: State<Arc<AppState>>,
+    req: HttpRequest,
     data: Json<OrderStatusUpdateInput>,
 ) -> Result<impl Responder, CustomError> {
+    idempotency::run(&state.redis_cache, &req, user_token.user_id, &*data, StatusCode::OK, || {
+        do_update_order_status(&user_token, &state, &data)
+    }).await
+}
+
+async fn do_update_order_status(
+    user_token: &UserToken,
+    state: &AppState,
+    data: &OrderStatusUpdateInput,
+) -> Result<OrderOutNew, CustomError> {
     let db = &state.db_pool;
     let mut tx = db.begin().await?;
 
@@ -78,6 +92,8 @@ pub async fn update_order_status(
 
     // 积分奖励处理（完成时）
     if data.to_status == OrderStatusEnum::FINISHED {
+        food_stats::record_order_finished(&mut tx, order.order_id).await?;
+        pantry_stock::consume_order_stock(&mut tx, order.order_id).await?;
         if let Some(points) = data.points_reward.or(Some(order.points_reward)).filter(|p| *p > 0) {
             // 获取当前积分并更新
             if let Ok(user_row) = sqlx::query("SELECT love_point FROM users WHERE user_id=$1")
@@ -175,5 +191,5 @@ pub async fn update_order_status(
     }
 
     let out = OrderOutNew::from((order, items_out, history_rows));
-    Ok(HttpResponse::Ok().json(&out))
+    Ok(out)
 }
@@ -2,9 +2,10 @@ use crate::models::users::UserToken;
 use crate::{
     errors::CustomError,
     models::orders::{
-        GroupInfoSimple, OrderItemOut, OrderOutNew, OrderQuery, OrderRecord, OrderStatusEnum,
-        OrderStatusHistoryOut,
+        GroupInfoSimple, OrderItemOut, OrderOutNew, OrderQuery, OrderRecord, OrderSortEnum,
+        OrderStatusEnum, OrderStatusHistoryOut,
     },
+    models::pagination::{CursorKey, CursorPage},
     AppState,
 };
 use chrono::{DateTime, Utc};
@@ -20,7 +21,7 @@ use std::sync::Arc;
     path = "/orders",
     tag = "订单",
     params(OrderQuery),
-    responses((status = 200, body = [OrderOutNew]))
+    responses((status = 200, body = CursorPage<OrderOutNew>))
 )]
 pub async fn get_orders(
     token: UserToken,
@@ -68,16 +69,90 @@ pub async fn get_orders(
         qb.push(") ");
     }
 
+    if let Some(uid) = query.user_id {
+        qb.push(" AND o.user_id = ");
+        qb.push_bind(uid);
+    }
+    if let Some(rid) = query.receiver_id {
+        qb.push(" AND o.receiver_id = ");
+        qb.push_bind(rid);
+    }
+
+    // 多状态：status 与 statuses 取并集
+    let mut statuses: Vec<String> = Vec::new();
     if let Some(st) = query.status {
-        // 直接绑定枚举，让 sqlx 以 order_status_enum 类型传参，避免 enum=text 比较错误
-        qb.push(" AND o.status = ");
-        qb.push_bind(st); // st: OrderStatusEnum implements sqlx::Type + Encode
+        statuses.push(status_text(st));
+    }
+    if let Some(raw) = &query.statuses {
+        for part in raw.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+            let st: OrderStatusEnum =
+                serde_json::from_value(serde_json::Value::String(part.to_ascii_uppercase()))
+                    .map_err(|_| CustomError::BadRequest(format!("未知的订单状态: {}", part)))?;
+            statuses.push(status_text(st));
+        }
+    }
+    if !statuses.is_empty() {
+        qb.push(" AND o.status::text = ANY(");
+        qb.push_bind(statuses);
+        qb.push(") ");
     } else if query.expired_only.unwrap_or(false) {
         qb.push(" AND o.status IN ('EXPIRED', 'CANCELLED', 'REJECTED', 'SYSTEM_CLOSED') ");
     }
-    qb.push(" ORDER BY o.created_at DESC ");
+
+    if let Some(fid) = query.food_id {
+        qb.push(" AND EXISTS (SELECT 1 FROM order_items oi WHERE oi.order_id = o.order_id AND oi.food_id = ");
+        qb.push_bind(fid);
+        qb.push(") ");
+    }
+    if let Some(t) = query.created_from {
+        qb.push(" AND o.created_at >= ");
+        qb.push_bind(t);
+    }
+    if let Some(t) = query.created_to {
+        qb.push(" AND o.created_at < ");
+        qb.push_bind(t);
+    }
+    if let Some(t) = query.goal_from {
+        qb.push(" AND o.goal_time >= ");
+        qb.push_bind(t);
+    }
+    if let Some(t) = query.goal_to {
+        qb.push(" AND o.goal_time < ");
+        qb.push_bind(t);
+    }
+
+    // 游标分页：按 (排序时间, order_id) 做 keyset，goal_time 为空的排在最后
+    let sort = query.sort.unwrap_or(OrderSortEnum::CREATED_DESC);
+    let (col, desc) = match sort {
+        OrderSortEnum::CREATED_DESC => ("o.created_at", true),
+        OrderSortEnum::CREATED_ASC => ("o.created_at", false),
+        OrderSortEnum::GOAL_TIME_ASC => ("o.goal_time", false),
+        OrderSortEnum::GOAL_TIME_DESC => ("o.goal_time", true),
+    };
+    let cmp = if desc { " < " } else { " > " };
+    if let Some(raw) = &query.cursor {
+        let key = CursorKey::decode(raw)?;
+        match key.at {
+            Some(at) => {
+                qb.push(format!(" AND ({} {} ", col, cmp));
+                qb.push_bind(at);
+                qb.push(format!(" OR ({} = ", col));
+                qb.push_bind(at);
+                qb.push(format!(" AND o.order_id {} ", cmp));
+                qb.push_bind(key.id);
+                qb.push(format!(") OR {} IS NULL) ", col));
+            }
+            None => {
+                qb.push(format!(" AND {} IS NULL AND o.order_id {} ", col, cmp));
+                qb.push_bind(key.id);
+            }
+        }
+    }
+    let dir = if desc { "DESC" } else { "ASC" };
+    qb.push(format!(" ORDER BY {} {} NULLS LAST, o.order_id {} ", col, dir, dir));
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
     qb.push(" LIMIT ");
-    qb.push_bind(query.limit.unwrap_or(50));
+    qb.push_bind(limit + 1);
     let orders_rows = qb.build().fetch_all(db).await?;
     let mut out_list: Vec<OrderOutNew> = Vec::new();
     for row in orders_rows {
@@ -146,7 +221,21 @@ pub async fn get_orders(
         }
         out_list.push(out);
     }
-    Ok(HttpResponse::Ok().json(&out_list))
+    let page = CursorPage::from_rows(out_list, limit, |o| {
+        let at = match sort {
+            OrderSortEnum::CREATED_DESC | OrderSortEnum::CREATED_ASC => Some(o.created_at),
+            OrderSortEnum::GOAL_TIME_ASC | OrderSortEnum::GOAL_TIME_DESC => o.goal_time,
+        };
+        CursorKey { at, id: o.order_id }.encode()
+    });
+    Ok(HttpResponse::Ok().json(&page))
+}
+
+fn status_text(st: OrderStatusEnum) -> String {
+    match serde_json::to_value(st) {
+        Ok(serde_json::Value::String(s)) => s,
+        _ => format!("{:?}", st),
+    }
 }
 
 #[utoipa::path(
@@ -291,3 +380,38 @@ pub fn map_history_row(row: sqlx::postgres::PgRow) -> OrderStatusHistoryOut {
         changed_at: row.get::<DateTime<Utc>, _>("changed_at"),
     }
 }
+
+// 根据订单主记录补齐明细与完整状态历史（供修改类接口返回）
+pub async fn build_order_out(
+    conn: &mut sqlx::PgConnection,
+    order: OrderRecord,
+) -> Result<OrderOutNew, CustomError> {
+    let item_rows = sqlx::query(
+        "SELECT oi.id, oi.order_id, oi.food_id, oi.quantity, oi.price, oi.snapshot_json, oi.created_at, f.food_name, f.food_photo \
+         FROM order_items oi LEFT JOIN foods f ON f.food_id = oi.food_id WHERE oi.order_id=$1 ORDER BY oi.id",
+    )
+    .bind(order.order_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let items = item_rows
+        .into_iter()
+        .map(|r| OrderItemOut {
+            id: r.get("id"),
+            food_id: r.get("food_id"),
+            food_name: r.try_get("food_name").ok(),
+            food_photo: r.try_get::<Option<String>, _>("food_photo").ok().flatten(),
+            quantity: r.get("quantity"),
+            price: r.try_get("price").ok(),
+        })
+        .collect();
+    let hist_rows = sqlx::query(
+        "SELECT h.from_status, h.to_status, u.nick_name, h.remark, h.changed_at \
+         FROM order_status_history h LEFT JOIN users u ON h.changed_by = u.user_id \
+         WHERE h.order_id=$1 ORDER BY h.changed_at",
+    )
+    .bind(order.order_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let history = hist_rows.into_iter().map(map_history_row).collect();
+    Ok(OrderOutNew::from((order, items, history)))
+}
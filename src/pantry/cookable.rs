@@ -0,0 +1,111 @@
+use crate::{
+    errors::CustomError,
+    models::pantry::{CookableFoodOut, CookableMissingOut, CookableQuery},
+    models::users::UserToken,
+    services::groups::{ensure_member, local_today},
+    services::units::convert_amount,
+    AppState,
+};
+use chrono::NaiveDate;
+use ntex::web::{
+    types::{Path, Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::Row;
+use std::sync::Arc;
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/pantry/cookable",
+    tag = "库存",
+    summary = "用现有库存能做什么：按库存覆盖率排序组内菜品",
+    description = "仅统计已声明库存用量的可点菜品；已过期的库存不计入。按覆盖率、可做份数降序。",
+    params(("group_id" = i64, Path, description = "组ID"), CookableQuery),
+    responses((status = 200, body = [CookableFoodOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_cookable_foods(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    query: Query<CookableQuery>,
+) -> Result<impl Responder, CustomError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let today = local_today(query.tz_offset_minutes)?;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let rows = sqlx::query(
+        "SELECT f.food_id, f.food_name, f.food_photo, u.item_id, u.amount, u.unit AS usage_unit, \
+         p.name AS item_name, p.quantity, p.unit AS item_unit, p.expiry_date \
+         FROM foods f JOIN food_pantry_usages u ON u.food_id=f.food_id \
+         JOIN pantry_items p ON p.id=u.item_id AND p.group_id=f.group_id \
+         WHERE f.group_id=$1 AND f.is_del=0 AND f.food_status='NORMAL' AND f.apply_status='APPROVED' \
+         ORDER BY f.food_id, p.name",
+    )
+    .bind(*group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut foods: Vec<CookableFoodOut> = Vec::new();
+    for r in rows {
+        let food_id: i64 = r.get("food_id");
+        if foods.last().map(|f| f.food_id) != Some(food_id) {
+            foods.push(CookableFoodOut {
+                food_id,
+                food_name: r.get("food_name"),
+                food_photo: r.get("food_photo"),
+                coverage: 0.0,
+                covered: 0,
+                total: 0,
+                max_servings: i64::MAX,
+                missing: Vec::new(),
+            });
+        }
+        let food = foods.last_mut().unwrap();
+        let item_unit: String = r.get("item_unit");
+        let usage_unit: String = r.get("usage_unit");
+        let expiry: Option<NaiveDate> = r.get("expiry_date");
+        let have = if expiry.is_some_and(|d| d < today) { 0.0 } else { r.get::<f64, _>("quantity") };
+        // 单位无法换算时视为缺少该食材
+        let need = convert_amount(r.get("amount"), &usage_unit, &item_unit);
+        food.total += 1;
+        match need {
+            Some(need) if need > 0.0 && have >= need => {
+                food.covered += 1;
+                food.max_servings = food.max_servings.min((have / need).floor() as i64);
+            }
+            _ => {
+                food.max_servings = 0;
+                food.missing.push(CookableMissingOut {
+                    item_id: r.get("item_id"),
+                    name: r.get("item_name"),
+                    need: round2(need.unwrap_or(0.0)),
+                    have: round2(have),
+                    unit: item_unit,
+                });
+            }
+        }
+    }
+    for f in foods.iter_mut() {
+        f.coverage = round2(f.covered as f64 / f.total as f64);
+    }
+    if query.only_complete.unwrap_or(false) {
+        foods.retain(|f| f.missing.is_empty());
+    }
+    foods.sort_by(|a, b| {
+        b.coverage
+            .partial_cmp(&a.coverage)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.max_servings.cmp(&a.max_servings))
+            .then(a.missing.len().cmp(&b.missing.len()))
+    });
+    foods.truncate(limit);
+    Ok(HttpResponse::Ok().json(&foods))
+}
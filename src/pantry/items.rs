@@ -0,0 +1,255 @@
+use crate::{
+    errors::CustomError,
+    models::pantry::{PantryAlertQuery, PantryAlertsOut, PantryItemCreateInput, PantryItemOut, PantryItemUpdateInput},
+    models::users::UserToken,
+    pantry::{
+        load_items, row_to_item, validate_amount, validate_name, validate_note,
+        validate_unit, PANTRY_COLUMNS,
+    },
+    services::groups::{ensure_member, local_today},
+    services::units::convert_amount,
+    AppState,
+};
+use chrono::Duration;
+use ntex::web::{
+    types::{Json, Path, Query, State},
+    HttpResponse, Responder,
+};
+use std::sync::Arc;
+
+const DEFAULT_EXPIRING_DAYS: i64 = 3;
+const MAX_EXPIRING_DAYS: i64 = 30;
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/pantry",
+    tag = "库存",
+    summary = "组内库存列表",
+    params(("group_id" = i64, Path, description = "组ID")),
+    responses((status = 200, body = [PantryItemOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_pantry_items(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let items = load_items(&mut conn, *group_id).await?;
+    Ok(HttpResponse::Ok().json(&items))
+}
+
+#[utoipa::path(
+    post,
+    path = "/groups/{group_id}/pantry",
+    tag = "库存",
+    summary = "新增库存食材",
+    params(("group_id" = i64, Path, description = "组ID")),
+    request_body = PantryItemCreateInput,
+    responses((status = 201, body = PantryItemOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_pantry_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    data: Json<PantryItemCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    let name = validate_name(&data.name)?;
+    let unit = validate_unit(&data.unit)?;
+    validate_amount(data.quantity, "数量")?;
+    if let Some(t) = data.low_stock_threshold {
+        validate_amount(t, "提醒阈值")?;
+    }
+    validate_note(data.note.as_deref())?;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let row = sqlx::query(&format!(
+        "INSERT INTO pantry_items (group_id, name, quantity, unit, expiry_date, low_stock_threshold, note, created_by, updated_by) \
+         VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$8) ON CONFLICT (group_id, name) DO NOTHING RETURNING {}",
+        PANTRY_COLUMNS
+    ))
+    .bind(*group_id)
+    .bind(&name)
+    .bind(data.quantity)
+    .bind(&unit)
+    .bind(data.expiry_date)
+    .bind(data.low_stock_threshold)
+    .bind(data.note.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+    .bind(token.user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("该食材已在库存中".into()));
+    };
+    Ok(HttpResponse::Created().json(&row_to_item(&row)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/groups/{group_id}/pantry/{item_id}",
+    tag = "库存",
+    summary = "修改库存食材（数量、单位、保质期等）",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("item_id" = i64, Path, description = "库存ID")
+    ),
+    request_body = PantryItemUpdateInput,
+    responses((status = 200, body = PantryItemOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_pantry_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<PantryItemUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, item_id) = *path;
+    let name = data.name.as_deref().map(validate_name).transpose()?;
+    let unit = data.unit.as_deref().map(validate_unit).transpose()?;
+    if let Some(q) = data.quantity {
+        validate_amount(q, "数量")?;
+    }
+    if let Some(t) = data.low_stock_threshold {
+        validate_amount(t, "提醒阈值")?;
+    }
+    validate_note(data.note.as_deref())?;
+
+    let mut tx = state.db_pool.begin().await?;
+    ensure_member(&mut tx, group_id, token.user_id).await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pantry_items WHERE id=$1 AND group_id=$2)")
+        .bind(item_id)
+        .bind(group_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !exists {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("库存食材不存在".into()));
+    }
+    if let Some(name) = &name {
+        let dup: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM pantry_items WHERE group_id=$1 AND name=$2 AND id<>$3)",
+        )
+        .bind(group_id)
+        .bind(name)
+        .bind(item_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if dup {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest("该食材已在库存中".into()));
+        }
+    }
+    // 改单位时，已声明的菜品用量需仍可换算
+    if let Some(unit) = &unit {
+        let usage_units: Vec<String> =
+            sqlx::query_scalar("SELECT DISTINCT unit FROM food_pantry_usages WHERE item_id=$1")
+                .bind(item_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        if let Some(bad) = usage_units.iter().find(|u| convert_amount(1.0, u, unit).is_none()) {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest(format!("已有菜品以“{}”声明用量，无法换算为“{}”", bad, unit)));
+        }
+    }
+    let clear_expiry = data.clear_expiry.unwrap_or(false);
+    let row = sqlx::query(&format!(
+        "UPDATE pantry_items SET name=COALESCE($3, name), quantity=COALESCE($4, quantity), unit=COALESCE($5, unit), \
+         expiry_date=CASE WHEN $6 THEN NULL ELSE COALESCE($7, expiry_date) END, \
+         low_stock_threshold=COALESCE($8, low_stock_threshold), note=COALESCE($9, note), \
+         updated_by=$10, updated_at=NOW() WHERE id=$1 AND group_id=$2 RETURNING {}",
+        PANTRY_COLUMNS
+    ))
+    .bind(item_id)
+    .bind(group_id)
+    .bind(name)
+    .bind(data.quantity)
+    .bind(unit)
+    .bind(clear_expiry)
+    .bind(data.expiry_date)
+    .bind(data.low_stock_threshold)
+    .bind(data.note.as_deref().map(str::trim))
+    .bind(token.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&row_to_item(&row)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/groups/{group_id}/pantry/{item_id}",
+    tag = "库存",
+    summary = "删除库存食材（同时移除菜品中对它的用量声明）",
+    params(
+        ("group_id" = i64, Path, description = "组ID"),
+        ("item_id" = i64, Path, description = "库存ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_pantry_item(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (group_id, item_id) = *path;
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, group_id, token.user_id).await?;
+    let deleted = sqlx::query("DELETE FROM pantry_items WHERE id=$1 AND group_id=$2")
+        .bind(item_id)
+        .bind(group_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(CustomError::BadRequest("库存食材不存在".into()));
+    }
+    Ok(HttpResponse::Ok().body("deleted"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/pantry/alerts",
+    tag = "库存",
+    summary = "库存提醒：低库存、临期与已过期",
+    params(("group_id" = i64, Path, description = "组ID"), PantryAlertQuery),
+    responses((status = 200, body = PantryAlertsOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_pantry_alerts(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    query: Query<PantryAlertQuery>,
+) -> Result<impl Responder, CustomError> {
+    let days = query.expiring_days.unwrap_or(DEFAULT_EXPIRING_DAYS);
+    if !(0..=MAX_EXPIRING_DAYS).contains(&days) {
+        return Err(CustomError::BadRequest(format!("临期天数需在0-{}之间", MAX_EXPIRING_DAYS)));
+    }
+    let today = local_today(query.tz_offset_minutes)?;
+    let horizon = today + Duration::days(days);
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let items = load_items(&mut conn, *group_id).await?;
+
+    let mut out = PantryAlertsOut {
+        low_stock: Vec::new(),
+        expiring: Vec::new(),
+        expired: Vec::new(),
+    };
+    for item in items {
+        if item.low_stock_threshold.is_some_and(|t| item.quantity <= t) {
+            out.low_stock.push(item.clone());
+        }
+        match item.expiry_date {
+            Some(d) if d < today => out.expired.push(item),
+            Some(d) if d <= horizon => out.expiring.push(item),
+            _ => {}
+        }
+    }
+    out.expiring.sort_by_key(|i| i.expiry_date);
+    out.expired.sort_by_key(|i| i.expiry_date);
+    Ok(HttpResponse::Ok().json(&out))
+}
@@ -0,0 +1,69 @@
+pub mod items;
+pub mod usages; // 菜品声明库存用量
+pub mod cookable; // 按库存覆盖率推荐可做的菜
+
+use crate::{errors::CustomError, models::pantry::PantryItemOut};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+pub const PANTRY_COLUMNS: &str =
+    "id, group_id, name, quantity, unit, expiry_date, low_stock_threshold, note, updated_by, updated_at";
+
+const NAME_MAX_CHARS: usize = 64;
+const UNIT_MAX_CHARS: usize = 16;
+const NOTE_MAX_CHARS: usize = 128;
+
+pub fn validate_name(name: &str) -> Result<String, CustomError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > NAME_MAX_CHARS {
+        return Err(CustomError::BadRequest(format!("食材名称需为1-{}个字符", NAME_MAX_CHARS)));
+    }
+    Ok(name.to_string())
+}
+
+pub fn validate_unit(unit: &str) -> Result<String, CustomError> {
+    let unit = unit.trim();
+    if unit.is_empty() || unit.chars().count() > UNIT_MAX_CHARS {
+        return Err(CustomError::BadRequest(format!("单位需为1-{}个字符", UNIT_MAX_CHARS)));
+    }
+    Ok(unit.to_string())
+}
+
+pub fn validate_amount(v: f64, label: &str) -> Result<(), CustomError> {
+    if !v.is_finite() || v < 0.0 {
+        return Err(CustomError::BadRequest(format!("{}不能为负数", label)));
+    }
+    Ok(())
+}
+
+pub fn validate_note(note: Option<&str>) -> Result<(), CustomError> {
+    if note.is_some_and(|n| n.chars().count() > NOTE_MAX_CHARS) {
+        return Err(CustomError::BadRequest(format!("备注不能超过{}个字符", NOTE_MAX_CHARS)));
+    }
+    Ok(())
+}
+
+pub fn row_to_item(r: &PgRow) -> PantryItemOut {
+    PantryItemOut {
+        item_id: r.get("id"),
+        group_id: r.get("group_id"),
+        name: r.get("name"),
+        quantity: r.get("quantity"),
+        unit: r.get("unit"),
+        expiry_date: r.get("expiry_date"),
+        low_stock_threshold: r.get("low_stock_threshold"),
+        note: r.get("note"),
+        updated_by: r.get("updated_by"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+pub async fn load_items(conn: &mut PgConnection, group_id: i64) -> Result<Vec<PantryItemOut>, CustomError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM pantry_items WHERE group_id=$1 ORDER BY name, id",
+        PANTRY_COLUMNS
+    ))
+    .bind(group_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.iter().map(row_to_item).collect())
+}
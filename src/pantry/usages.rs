@@ -0,0 +1,160 @@
+use crate::{
+    errors::CustomError,
+    foods::audit::ensure_food_editor,
+    models::pantry::{FoodPantryUsageOut, FoodPantryUsagesInput},
+    models::users::UserToken,
+    pantry::validate_unit,
+    services::groups::ensure_member,
+    services::units::convert_amount,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+const MAX_USAGES: usize = 50;
+
+async fn load_usages(conn: &mut PgConnection, food_id: i64) -> Result<Vec<FoodPantryUsageOut>, CustomError> {
+    let rows = sqlx::query(
+        "SELECT u.item_id, p.name, u.amount, u.unit FROM food_pantry_usages u JOIN pantry_items p ON p.id=u.item_id \
+         WHERE u.food_id=$1 ORDER BY p.name, u.item_id",
+    )
+    .bind(food_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| FoodPantryUsageOut {
+            item_id: r.get("item_id"),
+            name: r.get("name"),
+            amount: r.get("amount"),
+            unit: r.get("unit"),
+        })
+        .collect())
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/pantry-usages",
+    tag = "库存",
+    summary = "菜品每份使用的库存食材",
+    params(("id" = i64, Path, description = "菜品ID")),
+    responses((status = 200, body = [FoodPantryUsageOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_food_pantry_usages(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let group_id: Option<Option<i64>> = sqlx::query_scalar("SELECT group_id FROM foods WHERE food_id=$1 AND is_del=0")
+        .bind(*id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(group_id) = group_id else {
+        return Err(CustomError::BadRequest("菜品不存在".into()));
+    };
+    if let Some(gid) = group_id {
+        ensure_member(&mut conn, gid, token.user_id).await?;
+    }
+    let usages = load_usages(&mut conn, *id).await?;
+    Ok(HttpResponse::Ok().json(&usages))
+}
+
+#[utoipa::path(
+    put,
+    path = "/foods/{id}/pantry-usages",
+    tag = "库存",
+    summary = "设置菜品每份使用的库存食材（整体替换）",
+    description = "食材需属于菜品所在组；用量单位需可换算为库存单位。订单完成时按 份数 × 用量 扣减库存。",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = FoodPantryUsagesInput,
+    responses((status = 200, body = [FoodPantryUsageOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_food_pantry_usages(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodPantryUsagesInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.usages.len() > MAX_USAGES {
+        return Err(CustomError::BadRequest(format!("用量最多{}项", MAX_USAGES)));
+    }
+    let mut seen = HashSet::new();
+    for u in &data.usages {
+        if !u.amount.is_finite() || u.amount <= 0.0 {
+            return Err(CustomError::BadRequest("用量需大于0".into()));
+        }
+        if !seen.insert(u.item_id) {
+            return Err(CustomError::BadRequest("同一食材不能重复声明".into()));
+        }
+    }
+
+    let mut tx = state.db_pool.begin().await?;
+    let group_id = match ensure_food_editor(&mut tx, *id, token.user_id).await {
+        Ok(g) => g,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    let Some(group_id) = group_id else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("菜品未归属任何组，无法关联库存".into()));
+    };
+
+    let item_ids: Vec<i64> = data.usages.iter().map(|u| u.item_id).collect();
+    let item_units: HashMap<i64, String> = sqlx::query("SELECT id, unit FROM pantry_items WHERE group_id=$1 AND id = ANY($2)")
+        .bind(group_id)
+        .bind(&item_ids)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .map(|r| (r.get("id"), r.get("unit")))
+        .collect();
+    let mut rows: Vec<(i64, f64, String)> = Vec::with_capacity(data.usages.len());
+    for u in &data.usages {
+        let Some(item_unit) = item_units.get(&u.item_id) else {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest(format!("库存食材{}不存在或不属于该组", u.item_id)));
+        };
+        let unit = match u.unit.as_deref() {
+            Some(raw) => match validate_unit(raw) {
+                Ok(unit) => unit,
+                Err(e) => {
+                    tx.rollback().await.ok();
+                    return Err(e);
+                }
+            },
+            None => item_unit.clone(),
+        };
+        if convert_amount(u.amount, &unit, item_unit).is_none() {
+            tx.rollback().await.ok();
+            return Err(CustomError::BadRequest(format!("单位“{}”无法换算为库存单位“{}”", unit, item_unit)));
+        }
+        rows.push((u.item_id, u.amount, unit));
+    }
+
+    sqlx::query("DELETE FROM food_pantry_usages WHERE food_id=$1")
+        .bind(*id)
+        .execute(&mut *tx)
+        .await?;
+    for (item_id, amount, unit) in &rows {
+        sqlx::query("INSERT INTO food_pantry_usages (food_id, item_id, amount, unit) VALUES ($1,$2,$3,$4)")
+            .bind(*id)
+            .bind(*item_id)
+            .bind(*amount)
+            .bind(unit)
+            .execute(&mut *tx)
+            .await?;
+    }
+    let usages = load_usages(&mut tx, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&usages))
+}
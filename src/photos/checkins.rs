@@ -0,0 +1,172 @@
+// 心愿打卡图集：封面同步到 wish_claim_checkins.photo_url
+use crate::{
+    errors::CustomError,
+    models::photos::{PhotoAddInput, PhotoOut, PhotoOwnerEnum, PhotoReorderInput, PhotoUpdateInput},
+    models::users::UserToken,
+    photos::{add_photos, delete_photo, load_photos, reorder_photos, update_photo, validate_photos},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::PgConnection;
+use std::sync::Arc;
+
+const OWNER: PhotoOwnerEnum = PhotoOwnerEnum::CHECKIN;
+
+// 只有打卡人本人可编辑图集
+async fn ensure_checkin_owner(conn: &mut PgConnection, checkin_id: i64, user_id: i64) -> Result<(), CustomError> {
+    let owner: Option<i64> = sqlx::query_scalar("SELECT user_id FROM wish_claim_checkins WHERE id=$1 FOR UPDATE")
+        .bind(checkin_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    match owner {
+        Some(uid) if uid == user_id => Ok(()),
+        Some(_) => Err(CustomError::BadRequest("只能编辑自己的打卡".into())),
+        None => Err(CustomError::BadRequest("打卡记录不存在".into())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/wish_claims/checkins/{id}/photos",
+    tag = "心愿",
+    summary = "打卡图集（按顺序，含封面标记）",
+    params(("id" = i64, Path, description = "打卡ID")),
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_checkin_photos(
+    _: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM wish_claim_checkins WHERE id=$1)")
+        .bind(*id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !exists {
+        return Err(CustomError::BadRequest("打卡记录不存在".into()));
+    }
+    let photos = load_photos(&mut conn, OWNER, *id).await?;
+    Ok(HttpResponse::Ok().json(&photos))
+}
+
+#[utoipa::path(
+    post,
+    path = "/wish_claims/checkins/{id}/photos",
+    tag = "心愿",
+    summary = "追加打卡图片",
+    params(("id" = i64, Path, description = "打卡ID")),
+    request_body = PhotoAddInput,
+    responses((status = 201, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_checkin_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoAddInput>,
+) -> Result<impl Responder, CustomError> {
+    let photos = validate_photos(&data.photos)?;
+    if photos.is_empty() {
+        return Err(CustomError::BadRequest("请至少上传一张图片".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_checkin_owner(&mut tx, *id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    add_photos(&mut tx, OWNER, *id, &photos, data.set_cover.unwrap_or(false), token.user_id).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/wish_claims/checkins/{id}/photos/{photo_id}",
+    tag = "心愿",
+    summary = "修改图片说明或设为封面",
+    params(
+        ("id" = i64, Path, description = "打卡ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    request_body = PhotoUpdateInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_checkin_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<PhotoUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let (checkin_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_checkin_owner(&mut tx, checkin_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    update_photo(&mut tx, OWNER, checkin_id, photo_id, data.caption.as_deref(), data.is_cover).await?;
+    let out = load_photos(&mut tx, OWNER, checkin_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/wish_claims/checkins/{id}/photos/order",
+    tag = "心愿",
+    summary = "调整打卡图片顺序",
+    params(("id" = i64, Path, description = "打卡ID")),
+    request_body = PhotoReorderInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn reorder_checkin_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoReorderInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_checkin_owner(&mut tx, *id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    reorder_photos(&mut tx, OWNER, *id, &data.photo_ids).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/wish_claims/checkins/{id}/photos/{photo_id}",
+    tag = "心愿",
+    summary = "删除打卡图片（删除封面时由第一张接替）",
+    params(
+        ("id" = i64, Path, description = "打卡ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_checkin_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (checkin_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_checkin_owner(&mut tx, checkin_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    delete_photo(&mut tx, OWNER, checkin_id, photo_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().body("deleted"))
+}
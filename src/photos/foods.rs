@@ -0,0 +1,169 @@
+// 菜品图集：封面同步到 foods.food_photo，变更记入修订历史
+use crate::{
+    errors::CustomError,
+    foods::{
+        audit::{ensure_food_editor, resubmit_submitter_edit},
+        revisions::record_revision,
+    },
+    models::foods::FoodRevisionSourceEnum,
+    models::photos::{PhotoAddInput, PhotoOut, PhotoOwnerEnum, PhotoReorderInput, PhotoUpdateInput},
+    models::users::UserToken,
+    photos::{add_photos, delete_photo, load_photos, reorder_photos, update_photo, validate_photos},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use std::sync::Arc;
+
+const OWNER: PhotoOwnerEnum = PhotoOwnerEnum::FOOD;
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/photos",
+    tag = "菜品",
+    summary = "菜品图集（按顺序，含封面标记）",
+    params(("id" = i64, Path, description = "菜品ID")),
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_food_photos(
+    _: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM foods WHERE food_id=$1 AND is_del=0)")
+        .bind(*id)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !exists {
+        return Err(CustomError::BadRequest("菜品不存在".into()));
+    }
+    let photos = load_photos(&mut conn, OWNER, *id).await?;
+    Ok(HttpResponse::Ok().json(&photos))
+}
+
+#[utoipa::path(
+    post,
+    path = "/foods/{id}/photos",
+    tag = "菜品",
+    summary = "追加菜品图片",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = PhotoAddInput,
+    responses((status = 201, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_food_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoAddInput>,
+) -> Result<impl Responder, CustomError> {
+    let photos = validate_photos(&data.photos)?;
+    if photos.is_empty() {
+        return Err(CustomError::BadRequest("请至少上传一张图片".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_food_editor(&mut tx, *id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    add_photos(&mut tx, OWNER, *id, &photos, data.set_cover.unwrap_or(false), token.user_id).await?;
+    let audit_log_id = resubmit_submitter_edit(&mut tx, *id, token.user_id, None).await?;
+    record_revision(&mut tx, *id, FoodRevisionSourceEnum::UPDATE, token.user_id, audit_log_id).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/foods/{id}/photos/{photo_id}",
+    tag = "菜品",
+    summary = "修改图片说明或设为封面",
+    params(
+        ("id" = i64, Path, description = "菜品ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    request_body = PhotoUpdateInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_food_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<PhotoUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let (food_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_food_editor(&mut tx, food_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    update_photo(&mut tx, OWNER, food_id, photo_id, data.caption.as_deref(), data.is_cover).await?;
+    let audit_log_id = resubmit_submitter_edit(&mut tx, food_id, token.user_id, None).await?;
+    record_revision(&mut tx, food_id, FoodRevisionSourceEnum::UPDATE, token.user_id, audit_log_id).await?;
+    let out = load_photos(&mut tx, OWNER, food_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/foods/{id}/photos/order",
+    tag = "菜品",
+    summary = "调整菜品图片顺序",
+    params(("id" = i64, Path, description = "菜品ID")),
+    request_body = PhotoReorderInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn reorder_food_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoReorderInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_food_editor(&mut tx, *id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    reorder_photos(&mut tx, OWNER, *id, &data.photo_ids).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/foods/{id}/photos/{photo_id}",
+    tag = "菜品",
+    summary = "删除菜品图片（删除封面时由第一张接替）",
+    params(
+        ("id" = i64, Path, description = "菜品ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_food_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (food_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_food_editor(&mut tx, food_id, token.user_id).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    delete_photo(&mut tx, OWNER, food_id, photo_id).await?;
+    let audit_log_id = resubmit_submitter_edit(&mut tx, food_id, token.user_id, None).await?;
+    record_revision(&mut tx, food_id, FoodRevisionSourceEnum::UPDATE, token.user_id, audit_log_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().body("deleted"))
+}
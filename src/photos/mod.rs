@@ -0,0 +1,326 @@
+pub mod foods;
+pub mod checkins;
+pub mod orders;
+
+use crate::{
+    errors::CustomError,
+    models::photos::{PhotoInput, PhotoOut, PhotoOwnerEnum},
+    upload::upload::validate_upload_url,
+};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use std::collections::HashMap;
+
+/// 单个图集最多图片数
+pub const MAX_PHOTOS: i64 = 9;
+const CAPTION_MAX_CHARS: usize = 128;
+
+const PHOTO_COLUMNS: &str = "id, owner_id, url, caption, sort, is_cover, created_by, created_at";
+
+fn row_to_photo(r: &PgRow) -> PhotoOut {
+    PhotoOut {
+        photo_id: r.get("id"),
+        url: r.get("url"),
+        caption: r.get("caption"),
+        sort: r.get("sort"),
+        is_cover: r.get("is_cover"),
+        created_by: r.get("created_by"),
+        created_at: r.get("created_at"),
+    }
+}
+
+fn normalize_caption(caption: Option<&str>) -> Result<Option<String>, CustomError> {
+    let caption = caption.map(str::trim).filter(|c| !c.is_empty());
+    if caption.is_some_and(|c| c.chars().count() > CAPTION_MAX_CHARS) {
+        return Err(CustomError::BadRequest(format!("图片说明不能超过{}个字符", CAPTION_MAX_CHARS)));
+    }
+    Ok(caption.map(str::to_string))
+}
+
+// 校验并规整图片：地址须属于上传域名
+pub fn validate_photos(photos: &[PhotoInput]) -> Result<Vec<(String, Option<String>)>, CustomError> {
+    if photos.len() as i64 > MAX_PHOTOS {
+        return Err(CustomError::BadRequest(format!("图集最多{}张图片", MAX_PHOTOS)));
+    }
+    photos
+        .iter()
+        .map(|p| {
+            let url = p.url.trim();
+            validate_upload_url(url)?;
+            Ok((url.to_string(), normalize_caption(p.caption.as_deref())?))
+        })
+        .collect()
+}
+
+pub async fn load_photos(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64) -> Result<Vec<PhotoOut>, CustomError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM photos WHERE owner_type=$1 AND owner_id=$2 ORDER BY sort, id",
+        PHOTO_COLUMNS
+    ))
+    .bind(owner.as_str())
+    .bind(owner_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.iter().map(row_to_photo).collect())
+}
+
+pub async fn load_photos_batch(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_ids: &[i64],
+) -> Result<HashMap<i64, Vec<PhotoOut>>, CustomError> {
+    let mut out: HashMap<i64, Vec<PhotoOut>> = HashMap::new();
+    if owner_ids.is_empty() {
+        return Ok(out);
+    }
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM photos WHERE owner_type=$1 AND owner_id = ANY($2) ORDER BY owner_id, sort, id",
+        PHOTO_COLUMNS
+    ))
+    .bind(owner.as_str())
+    .bind(owner_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    for r in &rows {
+        out.entry(r.get("owner_id")).or_default().push(row_to_photo(r));
+    }
+    Ok(out)
+}
+
+// 旧字段（foods.food_photo / wish_claim_checkins.photo_url）始终为封面地址，兼容旧客户端
+pub async fn sync_legacy_field(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64) -> Result<(), CustomError> {
+    let sql = match owner {
+        PhotoOwnerEnum::FOOD => {
+            "UPDATE foods SET food_photo=(SELECT url FROM photos WHERE owner_type='FOOD' AND owner_id=$1 AND is_cover), \
+             updated_at=NOW() WHERE food_id=$1"
+        }
+        PhotoOwnerEnum::CHECKIN => {
+            "UPDATE wish_claim_checkins SET photo_url=(SELECT url FROM photos WHERE owner_type='CHECKIN' AND owner_id=$1 AND is_cover) \
+             WHERE id=$1"
+        }
+        PhotoOwnerEnum::ORDER | PhotoOwnerEnum::REVIEW => return Ok(()),
+    };
+    sqlx::query(sql).bind(owner_id).execute(&mut *conn).await?;
+    Ok(())
+}
+
+async fn set_cover(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64, photo_id: i64) -> Result<(), CustomError> {
+    // 先清除旧封面再设置，避免唯一索引冲突
+    sqlx::query("UPDATE photos SET is_cover=FALSE WHERE owner_type=$1 AND owner_id=$2 AND is_cover AND id<>$3")
+        .bind(owner.as_str())
+        .bind(owner_id)
+        .bind(photo_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("UPDATE photos SET is_cover=TRUE WHERE id=$1")
+        .bind(photo_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+// 没有封面时以排序第一张为封面
+async fn ensure_cover(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64) -> Result<(), CustomError> {
+    sqlx::query(
+        "UPDATE photos SET is_cover=TRUE WHERE id=(SELECT id FROM photos WHERE owner_type=$1 AND owner_id=$2 ORDER BY sort, id LIMIT 1) \
+         AND NOT EXISTS(SELECT 1 FROM photos WHERE owner_type=$1 AND owner_id=$2 AND is_cover)",
+    )
+    .bind(owner.as_str())
+    .bind(owner_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// 追加图片；set_cover 时第一张新图为封面
+pub async fn add_photos(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_id: i64,
+    photos: &[(String, Option<String>)],
+    set_cover_first: bool,
+    user_id: i64,
+) -> Result<(), CustomError> {
+    if photos.is_empty() {
+        return Ok(());
+    }
+    let (count, max_sort): (i64, Option<i32>) =
+        sqlx::query_as("SELECT COUNT(*), MAX(sort) FROM photos WHERE owner_type=$1 AND owner_id=$2")
+            .bind(owner.as_str())
+            .bind(owner_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if count + photos.len() as i64 > MAX_PHOTOS {
+        return Err(CustomError::BadRequest(format!("图集最多{}张图片", MAX_PHOTOS)));
+    }
+    let mut first_id = None;
+    for (i, (url, caption)) in photos.iter().enumerate() {
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO photos (owner_type, owner_id, url, caption, sort, created_by) VALUES ($1,$2,$3,$4,$5,$6) RETURNING id",
+        )
+        .bind(owner.as_str())
+        .bind(owner_id)
+        .bind(url)
+        .bind(caption)
+        .bind(max_sort.map_or(0, |s| s + 1) + i as i32)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        first_id.get_or_insert(id);
+    }
+    match first_id {
+        Some(id) if set_cover_first => set_cover(conn, owner, owner_id, id).await?,
+        _ => ensure_cover(conn, owner, owner_id).await?,
+    }
+    sync_legacy_field(conn, owner, owner_id).await
+}
+
+/// 整体替换图集，第一张为封面
+pub async fn replace_photos(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_id: i64,
+    photos: &[(String, Option<String>)],
+    user_id: i64,
+) -> Result<(), CustomError> {
+    delete_all_photos(conn, owner, owner_id).await?;
+    add_photos(conn, owner, owner_id, photos, true, user_id).await
+}
+
+// 归属对象删除时清理图集（photos 无外键，需手动删除）
+pub async fn delete_all_photos(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64) -> Result<(), CustomError> {
+    sqlx::query("DELETE FROM photos WHERE owner_type=$1 AND owner_id=$2")
+        .bind(owner.as_str())
+        .bind(owner_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// 旧接口直接写入了单图字段：已在图集中则设为封面，否则校验地址与数量后插入到首位作为封面
+pub async fn adopt_legacy_cover(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_id: i64,
+    url: Option<&str>,
+    user_id: Option<i64>,
+) -> Result<(), CustomError> {
+    let Some(url) = url.map(str::trim).filter(|u| !u.is_empty()) else {
+        return Ok(());
+    };
+    let existing: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM photos WHERE owner_type=$1 AND owner_id=$2 AND url=$3 ORDER BY sort, id LIMIT 1")
+            .bind(owner.as_str())
+            .bind(owner_id)
+            .bind(url)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let photo_id = match existing {
+        Some(id) => id,
+        None => {
+            // 与图集接口相同的规则：地址须属于上传域名，且不超过数量上限
+            validate_upload_url(url)?;
+            let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM photos WHERE owner_type=$1 AND owner_id=$2")
+                .bind(owner.as_str())
+                .bind(owner_id)
+                .fetch_one(&mut *conn)
+                .await?;
+            if count >= MAX_PHOTOS {
+                return Err(CustomError::BadRequest(format!("图集最多{}张图片", MAX_PHOTOS)));
+            }
+            sqlx::query("UPDATE photos SET sort=sort+1 WHERE owner_type=$1 AND owner_id=$2")
+                .bind(owner.as_str())
+                .bind(owner_id)
+                .execute(&mut *conn)
+                .await?;
+            sqlx::query_scalar(
+                "INSERT INTO photos (owner_type, owner_id, url, sort, created_by) VALUES ($1,$2,$3,0,$4) RETURNING id",
+            )
+            .bind(owner.as_str())
+            .bind(owner_id)
+            .bind(url)
+            .bind(user_id)
+            .fetch_one(&mut *conn)
+            .await?
+        }
+    };
+    set_cover(conn, owner, owner_id, photo_id).await
+}
+
+pub async fn update_photo(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_id: i64,
+    photo_id: i64,
+    caption: Option<&str>,
+    is_cover: Option<bool>,
+) -> Result<(), CustomError> {
+    if is_cover == Some(false) {
+        return Err(CustomError::BadRequest("请将其他图片设为封面".into()));
+    }
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM photos WHERE id=$1 AND owner_type=$2 AND owner_id=$3)")
+            .bind(photo_id)
+            .bind(owner.as_str())
+            .bind(owner_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if !exists {
+        return Err(CustomError::BadRequest("图片不存在".into()));
+    }
+    if caption.is_some() {
+        sqlx::query("UPDATE photos SET caption=$2 WHERE id=$1")
+            .bind(photo_id)
+            .bind(normalize_caption(caption)?)
+            .execute(&mut *conn)
+            .await?;
+    }
+    if is_cover == Some(true) {
+        set_cover(conn, owner, owner_id, photo_id).await?;
+        sync_legacy_field(conn, owner, owner_id).await?;
+    }
+    Ok(())
+}
+
+// 调整顺序：需提供图集内全部图片ID
+pub async fn reorder_photos(
+    conn: &mut PgConnection,
+    owner: PhotoOwnerEnum,
+    owner_id: i64,
+    photo_ids: &[i64],
+) -> Result<(), CustomError> {
+    let mut current: Vec<i64> = sqlx::query_scalar("SELECT id FROM photos WHERE owner_type=$1 AND owner_id=$2")
+        .bind(owner.as_str())
+        .bind(owner_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let mut requested = photo_ids.to_vec();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(CustomError::BadRequest("需提供图集内全部图片且不能重复".into()));
+    }
+    for (i, id) in photo_ids.iter().enumerate() {
+        sqlx::query("UPDATE photos SET sort=$2 WHERE id=$1")
+            .bind(*id)
+            .bind(i as i32)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+// 删除图片；删除封面后由排序第一张接替
+pub async fn delete_photo(conn: &mut PgConnection, owner: PhotoOwnerEnum, owner_id: i64, photo_id: i64) -> Result<(), CustomError> {
+    let deleted = sqlx::query("DELETE FROM photos WHERE id=$1 AND owner_type=$2 AND owner_id=$3")
+        .bind(photo_id)
+        .bind(owner.as_str())
+        .bind(owner_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected();
+    if deleted == 0 {
+        return Err(CustomError::BadRequest("图片不存在".into()));
+    }
+    ensure_cover(conn, owner, owner_id).await?;
+    sync_legacy_field(conn, owner, owner_id).await
+}
@@ -0,0 +1,176 @@
+// 订单完成照片图集
+use crate::{
+    errors::CustomError,
+    models::photos::{PhotoAddInput, PhotoOut, PhotoOwnerEnum, PhotoReorderInput, PhotoUpdateInput},
+    models::users::UserToken,
+    photos::{add_photos, delete_photo, load_photos, reorder_photos, update_photo, validate_photos},
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+const OWNER: PhotoOwnerEnum = PhotoOwnerEnum::ORDER;
+
+// 下单人、接单人或订单所属组成员可查看；编辑还需订单已完成
+async fn ensure_order_access(conn: &mut PgConnection, order_id: i64, user_id: i64, write: bool) -> Result<(), CustomError> {
+    let row = sqlx::query(
+        "SELECT o.status::text AS status, \
+         (o.user_id=$2 OR o.receiver_id=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=o.group_id AND m.user_id=$2)) AS allowed \
+         FROM orders o WHERE o.order_id=$1",
+    )
+    .bind(order_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("订单不存在".into()));
+    };
+    if !row.get::<bool, _>("allowed") {
+        return Err(CustomError::BadRequest("无权访问该订单".into()));
+    }
+    if write && row.get::<String, _>("status") != "FINISHED" {
+        return Err(CustomError::BadRequest("订单完成后才能上传完成照片".into()));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/{id}/photos",
+    tag = "订单",
+    summary = "订单完成照片（按顺序，含封面标记）",
+    params(("id" = i64, Path, description = "订单ID")),
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_order_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_order_access(&mut conn, *id, token.user_id, false).await?;
+    let photos = load_photos(&mut conn, OWNER, *id).await?;
+    Ok(HttpResponse::Ok().json(&photos))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/photos",
+    tag = "订单",
+    summary = "追加订单完成照片（仅已完成订单）",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = PhotoAddInput,
+    responses((status = 201, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn add_order_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoAddInput>,
+) -> Result<impl Responder, CustomError> {
+    let photos = validate_photos(&data.photos)?;
+    if photos.is_empty() {
+        return Err(CustomError::BadRequest("请至少上传一张图片".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_order_access(&mut tx, *id, token.user_id, true).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    add_photos(&mut tx, OWNER, *id, &photos, data.set_cover.unwrap_or(false), token.user_id).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orders/{id}/photos/{photo_id}",
+    tag = "订单",
+    summary = "修改图片说明或设为封面",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    request_body = PhotoUpdateInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_order_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+    data: Json<PhotoUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_order_access(&mut tx, order_id, token.user_id, true).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    update_photo(&mut tx, OWNER, order_id, photo_id, data.caption.as_deref(), data.is_cover).await?;
+    let out = load_photos(&mut tx, OWNER, order_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/orders/{id}/photos/order",
+    tag = "订单",
+    summary = "调整订单照片顺序",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = PhotoReorderInput,
+    responses((status = 200, body = [PhotoOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn reorder_order_photos(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<PhotoReorderInput>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_order_access(&mut tx, *id, token.user_id, true).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    reorder_photos(&mut tx, OWNER, *id, &data.photo_ids).await?;
+    let out = load_photos(&mut tx, OWNER, *id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orders/{id}/photos/{photo_id}",
+    tag = "订单",
+    summary = "删除订单照片（删除封面时由第一张接替）",
+    params(
+        ("id" = i64, Path, description = "订单ID"),
+        ("photo_id" = i64, Path, description = "图片ID")
+    ),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_order_photo(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    path: Path<(i64, i64)>,
+) -> Result<impl Responder, CustomError> {
+    let (order_id, photo_id) = *path;
+    let mut tx = state.db_pool.begin().await?;
+    if let Err(e) = ensure_order_access(&mut tx, order_id, token.user_id, true).await {
+        tx.rollback().await.ok();
+        return Err(e);
+    }
+    delete_photo(&mut tx, OWNER, order_id, photo_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().body("deleted"))
+}
@@ -0,0 +1,71 @@
+pub mod update;
+pub mod view; // 菜品评价列表与组内评价动态
+
+use crate::{
+    errors::CustomError,
+    models::{photos::PhotoOwnerEnum, reviews::FoodReviewOut},
+    photos::load_photos_batch,
+};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+const CONTENT_MAX_CHARS: usize = 500;
+
+pub const REVIEW_SELECT: &str = "SELECT r.review_id, r.food_id, f.food_name, r.order_id, r.group_id, r.user_id, u.nick_name, u.avatar, \
+     r.stars, r.content, r.created_at, r.updated_at \
+     FROM food_reviews r JOIN foods f ON f.food_id=r.food_id LEFT JOIN users u ON u.user_id=r.user_id";
+
+pub fn validate_stars(stars: i16) -> Result<i16, CustomError> {
+    if !(1..=5).contains(&stars) {
+        return Err(CustomError::BadRequest("评分需为1-5星".into()));
+    }
+    Ok(stars)
+}
+
+pub fn normalize_content(content: Option<&str>) -> Result<Option<String>, CustomError> {
+    let content = content.map(str::trim).filter(|c| !c.is_empty());
+    if content.is_some_and(|c| c.chars().count() > CONTENT_MAX_CHARS) {
+        return Err(CustomError::BadRequest(format!("评价内容不能超过{}字", CONTENT_MAX_CHARS)));
+    }
+    Ok(content.map(str::to_string))
+}
+
+fn row_to_review(r: &PgRow) -> FoodReviewOut {
+    FoodReviewOut {
+        review_id: r.get("review_id"),
+        food_id: r.get("food_id"),
+        food_name: r.get("food_name"),
+        order_id: r.get("order_id"),
+        group_id: r.get("group_id"),
+        user_id: r.get("user_id"),
+        nick_name: r.get("nick_name"),
+        avatar: r.get("avatar"),
+        stars: r.get("stars"),
+        content: r.get("content"),
+        photos: Vec::new(),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+    }
+}
+
+// 行转评价并批量附上图片
+pub async fn attach_photos(conn: &mut PgConnection, rows: &[PgRow]) -> Result<Vec<FoodReviewOut>, CustomError> {
+    let mut out: Vec<FoodReviewOut> = rows.iter().map(row_to_review).collect();
+    let ids: Vec<i64> = out.iter().map(|r| r.review_id).collect();
+    let mut photos = load_photos_batch(conn, PhotoOwnerEnum::REVIEW, &ids).await?;
+    for r in out.iter_mut() {
+        r.photos = photos.remove(&r.review_id).unwrap_or_default();
+    }
+    Ok(out)
+}
+
+pub async fn load_review(conn: &mut PgConnection, review_id: i64) -> Result<FoodReviewOut, CustomError> {
+    let row = sqlx::query(&format!("{} WHERE r.review_id=$1", REVIEW_SELECT))
+        .bind(review_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("评价不存在".into()));
+    };
+    let mut out = attach_photos(conn, &[row]).await?;
+    Ok(out.remove(0))
+}
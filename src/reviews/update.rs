@@ -0,0 +1,215 @@
+// 菜品评价：1-5 星 + 文字 + 图片，须关联包含该菜品的已完成订单。
+// 与 order_ratings（对接单人的积分加减分）相互独立；汇总写入 food_stats 供展示与排序。
+use crate::{
+    errors::CustomError,
+    models::photos::PhotoOwnerEnum,
+    models::reviews::{FoodReviewCreateInput, FoodReviewOut, FoodReviewUpdateInput},
+    models::users::UserToken,
+    photos::{add_photos, delete_all_photos, replace_photos, validate_photos},
+    reviews::{load_review, normalize_content, validate_stars},
+    services::food_stats::refresh_food_reviews,
+    AppState,
+};
+use ntex::web::{
+    types::{Json, Path, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+const OWNER: PhotoOwnerEnum = PhotoOwnerEnum::REVIEW;
+
+// 可评价：下单人或订单所属组成员（接单人除外），订单已完成且包含该菜品；返回订单所属组
+async fn check_review_order(
+    conn: &mut PgConnection,
+    order_id: i64,
+    food_id: i64,
+    user_id: i64,
+) -> Result<Option<i64>, CustomError> {
+    let row = sqlx::query(
+        "SELECT o.status::text AS status, o.group_id, o.receiver_id, \
+         (o.user_id=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=o.group_id AND m.user_id=$2)) AS allowed, \
+         EXISTS(SELECT 1 FROM order_items oi WHERE oi.order_id=o.order_id AND oi.food_id=$3) AS has_food, \
+         EXISTS(SELECT 1 FROM food_reviews r WHERE r.order_id=o.order_id AND r.food_id=$3 AND r.user_id=$2) AS reviewed \
+         FROM orders o WHERE o.order_id=$1",
+    )
+    .bind(order_id)
+    .bind(user_id)
+    .bind(food_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("订单不存在".into()));
+    };
+    if !row.get::<bool, _>("allowed") {
+        return Err(CustomError::BadRequest("无权评价该订单".into()));
+    }
+    if row.get::<Option<i64>, _>("receiver_id") == Some(user_id) {
+        return Err(CustomError::BadRequest("接单人不能评价自己做的菜".into()));
+    }
+    if row.get::<String, _>("status") != "FINISHED" {
+        return Err(CustomError::BadRequest("仅完成的订单可评价菜品".into()));
+    }
+    if !row.get::<bool, _>("has_food") {
+        return Err(CustomError::BadRequest("订单中没有该菜品".into()));
+    }
+    if row.get::<bool, _>("reviewed") {
+        return Err(CustomError::BadRequest("已评价过该菜品，请修改原评价".into()));
+    }
+    Ok(row.get("group_id"))
+}
+
+// 锁定评价并校验权限：本人可修改；删除时系统管理员也可操作。返回菜品ID
+async fn ensure_review_editor(
+    conn: &mut PgConnection,
+    review_id: i64,
+    user_id: i64,
+    allow_admin: bool,
+) -> Result<i64, CustomError> {
+    let row = sqlx::query(
+        "SELECT r.food_id, r.user_id=$2 AS is_author, \
+         EXISTS(SELECT 1 FROM users u WHERE u.user_id=$2 AND u.role='ADMIN') AS is_admin \
+         FROM food_reviews r WHERE r.review_id=$1 FOR UPDATE OF r",
+    )
+    .bind(review_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Err(CustomError::BadRequest("评价不存在".into()));
+    };
+    if !row.get::<bool, _>("is_author") && !(allow_admin && row.get::<bool, _>("is_admin")) {
+        return Err(CustomError::BadRequest("只能修改自己的评价".into()));
+    }
+    Ok(row.get("food_id"))
+}
+
+#[utoipa::path(
+    post,
+    path = "/orders/{id}/reviews",
+    tag = "菜品评价",
+    summary = "评价订单中的菜品（1-5星，可带文字与图片）",
+    description = "订单须已完成且包含该菜品；下单人或组成员可评价，接单人除外。每人每单每道菜一条。",
+    params(("id" = i64, Path, description = "订单ID")),
+    request_body = FoodReviewCreateInput,
+    responses((status = 201, body = FoodReviewOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn create_food_review(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    data: Json<FoodReviewCreateInput>,
+) -> Result<impl Responder, CustomError> {
+    let stars = validate_stars(data.stars)?;
+    let content = normalize_content(data.content.as_deref())?;
+    let photos = validate_photos(data.photos.as_deref().unwrap_or_default())?;
+    let mut tx = state.db_pool.begin().await?;
+    let group_id = match check_review_order(&mut tx, *id, data.food_id, token.user_id).await {
+        Ok(g) => g,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    // 并发重复提交由唯一约束兜底
+    let review_id: Option<i64> = sqlx::query_scalar(
+        "INSERT INTO food_reviews (food_id, order_id, group_id, user_id, stars, content) VALUES ($1,$2,$3,$4,$5,$6) \
+         ON CONFLICT (order_id, food_id, user_id) DO NOTHING RETURNING review_id",
+    )
+    .bind(data.food_id)
+    .bind(*id)
+    .bind(group_id)
+    .bind(token.user_id)
+    .bind(stars)
+    .bind(&content)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(review_id) = review_id else {
+        tx.rollback().await.ok();
+        return Err(CustomError::BadRequest("已评价过该菜品，请修改原评价".into()));
+    };
+    add_photos(&mut tx, OWNER, review_id, &photos, true, token.user_id).await?;
+    refresh_food_reviews(&mut tx, data.food_id).await?;
+    let out = load_review(&mut tx, review_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Created().json(&out))
+}
+
+#[utoipa::path(
+    put,
+    path = "/food_reviews/{review_id}",
+    tag = "菜品评价",
+    summary = "修改自己的菜品评价",
+    params(("review_id" = i64, Path, description = "评价ID")),
+    request_body = FoodReviewUpdateInput,
+    responses((status = 200, body = FoodReviewOut)),
+    security(("cookie_auth" = []))
+)]
+pub async fn update_food_review(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    review_id: Path<i64>,
+    data: Json<FoodReviewUpdateInput>,
+) -> Result<impl Responder, CustomError> {
+    let stars = data.stars.map(validate_stars).transpose()?;
+    let content = data.content.as_deref().map(|c| normalize_content(Some(c))).transpose()?;
+    let photos = data.photos.as_deref().map(validate_photos).transpose()?;
+    let mut tx = state.db_pool.begin().await?;
+    let food_id = match ensure_review_editor(&mut tx, *review_id, token.user_id, false).await {
+        Ok(f) => f,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    sqlx::query(
+        "UPDATE food_reviews SET stars=COALESCE($2, stars), content=CASE WHEN $3 THEN $4 ELSE content END, updated_at=NOW() \
+         WHERE review_id=$1",
+    )
+    .bind(*review_id)
+    .bind(stars)
+    .bind(content.is_some())
+    .bind(content.flatten())
+    .execute(&mut *tx)
+    .await?;
+    if let Some(photos) = &photos {
+        replace_photos(&mut tx, OWNER, *review_id, photos, token.user_id).await?;
+    }
+    refresh_food_reviews(&mut tx, food_id).await?;
+    let out = load_review(&mut tx, *review_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&out))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/food_reviews/{review_id}",
+    tag = "菜品评价",
+    summary = "删除菜品评价（本人或系统管理员）",
+    params(("review_id" = i64, Path, description = "评价ID")),
+    responses((status = 200, body = String)),
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_food_review(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    review_id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut tx = state.db_pool.begin().await?;
+    let food_id = match ensure_review_editor(&mut tx, *review_id, token.user_id, true).await {
+        Ok(f) => f,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    delete_all_photos(&mut tx, OWNER, *review_id).await?;
+    sqlx::query("DELETE FROM food_reviews WHERE review_id=$1")
+        .bind(*review_id)
+        .execute(&mut *tx)
+        .await?;
+    refresh_food_reviews(&mut tx, food_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().body("deleted"))
+}
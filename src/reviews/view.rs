@@ -0,0 +1,134 @@
+use crate::{
+    errors::CustomError,
+    models::pagination::{CursorKey, CursorPage},
+    models::reviews::{FoodReviewOut, FoodReviewQuery},
+    models::users::UserToken,
+    reviews::{attach_photos, REVIEW_SELECT},
+    services::groups::ensure_member,
+    AppState,
+};
+use ntex::web::{
+    types::{Path, Query, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Postgres, QueryBuilder};
+use std::sync::Arc;
+
+// 最新的在前，游标为 (created_at, review_id)
+async fn fetch_page(
+    conn: &mut PgConnection,
+    mut qb: QueryBuilder<'_, Postgres>,
+    query: &FoodReviewQuery,
+) -> Result<CursorPage<FoodReviewOut>, CustomError> {
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    if let Some(raw) = &query.cursor {
+        let key = CursorKey::decode(raw)?;
+        if let Some(at) = key.at {
+            qb.push(" AND (r.created_at, r.review_id) < (");
+            qb.push_bind(at);
+            qb.push(", ");
+            qb.push_bind(key.id);
+            qb.push(")");
+        }
+    }
+    qb.push(" ORDER BY r.created_at DESC, r.review_id DESC LIMIT ");
+    qb.push_bind(limit + 1);
+    let rows = qb.build().fetch_all(&mut *conn).await?;
+    let reviews = attach_photos(conn, &rows).await?;
+    Ok(CursorPage::from_rows(reviews, limit, |r| {
+        CursorKey { at: Some(r.created_at), id: r.review_id }.encode()
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/foods/{id}/reviews",
+    tag = "菜品评价",
+    summary = "菜品评价列表（最新在前）",
+    description = "平均分与评价数见菜品详情的 review_avg / review_count。组内菜品仅组成员可查看。",
+    params(("id" = i64, Path, description = "菜品ID"), FoodReviewQuery),
+    responses((status = 200, body = CursorPage<FoodReviewOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_food_reviews(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+    query: Query<FoodReviewQuery>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let group_id: Option<Option<i64>> = sqlx::query_scalar("SELECT group_id FROM foods WHERE food_id=$1 AND is_del=0")
+        .bind(*id)
+        .fetch_optional(&mut *conn)
+        .await?;
+    let Some(group_id) = group_id else {
+        return Err(CustomError::BadRequest("菜品不存在".into()));
+    };
+    if let Some(gid) = group_id {
+        ensure_member(&mut conn, gid, token.user_id).await?;
+    }
+    let mut qb = QueryBuilder::new(REVIEW_SELECT);
+    qb.push(" WHERE r.food_id = ").push_bind(*id);
+    let page = fetch_page(&mut conn, qb, &query).await?;
+    Ok(HttpResponse::Ok().json(&page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/reviews",
+    tag = "菜品评价",
+    summary = "组内最新菜品评价动态",
+    params(("group_id" = i64, Path, description = "组ID"), FoodReviewQuery),
+    responses((status = 200, body = CursorPage<FoodReviewOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_group_review_feed(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    group_id: Path<i64>,
+    query: Query<FoodReviewQuery>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_member(&mut conn, *group_id, token.user_id).await?;
+    let mut qb = QueryBuilder::new(REVIEW_SELECT);
+    qb.push(" WHERE r.group_id = ").push_bind(*group_id).push(" AND f.is_del=0");
+    let page = fetch_page(&mut conn, qb, &query).await?;
+    Ok(HttpResponse::Ok().json(&page))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders/{id}/reviews",
+    tag = "菜品评价",
+    summary = "订单内的菜品评价",
+    params(("id" = i64, Path, description = "订单ID")),
+    responses((status = 200, body = [FoodReviewOut])),
+    security(("cookie_auth" = []))
+)]
+pub async fn list_order_reviews(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    id: Path<i64>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    // 下单人、接单人或订单所属组成员可查看
+    let allowed: Option<bool> = sqlx::query_scalar(
+        "SELECT (o.user_id=$2 OR o.receiver_id=$2 OR EXISTS(SELECT 1 FROM association_group_members m WHERE m.group_id=o.group_id AND m.user_id=$2)) \
+         FROM orders o WHERE o.order_id=$1",
+    )
+    .bind(*id)
+    .bind(token.user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match allowed {
+        Some(true) => {}
+        Some(false) => return Err(CustomError::BadRequest("无权访问该订单".into())),
+        None => return Err(CustomError::BadRequest("订单不存在".into())),
+    }
+    let rows = sqlx::query(&format!("{} WHERE r.order_id=$1 ORDER BY r.created_at, r.review_id", REVIEW_SELECT))
+        .bind(*id)
+        .fetch_all(&mut *conn)
+        .await?;
+    let reviews = attach_photos(&mut conn, &rows).await?;
+    Ok(HttpResponse::Ok().json(&reviews))
+}
@@ -1,7 +1,7 @@
 use crate::{
-    dashboard, foods, game_im, game_ws,
+    carts, dashboard, foods, game_im, game_ws, guests, meal_plans,
     openapi::{openapi_json, serve_swagger},
-    orders, upload, users, wishes, AppState,
+    orders, pantry, photos, reviews, upload, users, wishes, AppState,
 };
 use ntex::web;
 use std::sync::Arc;
@@ -43,6 +43,9 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
                 .route("/is-register", web::get().to(users::view::is_register))
                 .route("/role-switch", web::post().to(users::role::switch_role))
                 .route("/checkin", web::post().to(users::checkin::daily_checkin))
+                .route("/allergens", web::get().to(users::allergens::get_allergen_prefs))
+                .route("/allergens", web::put().to(users::allergens::set_allergen_prefs))
+                .route("/allergens/check", web::post().to(users::allergens::check_allergen_prefs))
                 .route(
                     "/getInfoByUsername",
                     web::get().to(users::view::get_user_info),
@@ -82,9 +85,27 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
             .route("", web::post().to(foods::new::create_food))
             .route("", web::get().to(foods::view::get_foods))
             .route("/marks", web::get().to(foods::view::get_marked_foods))
+            .route("/search", web::get().to(foods::search::search_foods))
+            .route("/suggest", web::get().to(foods::search::suggest_foods))
+            .route(
+                "/recommendations",
+                web::get().to(foods::recommend::get_food_recommendations),
+            )
             .route(
                 "/blind_box/draw",
-                web::post().to(foods::view::draw_blind_box),
+                web::post().to(foods::blind_box::draw_blind_box),
+            )
+            .route(
+                "/blind_box/draws",
+                web::get().to(foods::blind_box::list_blind_box_draws),
+            )
+            .route(
+                "/blind_box/draws/{id}/reroll",
+                web::post().to(foods::blind_box::reroll_blind_box),
+            )
+            .route(
+                "/blind_box/draws/{id}/order",
+                web::post().to(foods::blind_box::order_blind_box_draw),
             )
             .route("/mark", web::post().to(foods::update::mark_food))
             .route(
@@ -93,13 +114,68 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
             )
             .route("/{id}", web::get().to(foods::view::get_food_detail))
             .route("/{id}", web::put().to(foods::update::update_food))
-            .route("/{id}", web::delete().to(foods::delete::delete_food)),
+            .route("/{id}", web::delete().to(foods::delete::delete_food))
+            .route("/{id}/recipe", web::get().to(foods::recipe::get_food_recipe))
+            .route("/{id}/recipe", web::put().to(foods::recipe::update_food_recipe))
+            .route(
+                "/{id}/pantry-usages",
+                web::get().to(pantry::usages::get_food_pantry_usages),
+            )
+            .route(
+                "/{id}/pantry-usages",
+                web::put().to(pantry::usages::update_food_pantry_usages),
+            )
+            .route("/{id}/copy", web::post().to(foods::transfer::copy_food))
+            // 图集
+            .route("/{id}/photos", web::get().to(photos::foods::list_food_photos))
+            .route("/{id}/photos", web::post().to(photos::foods::add_food_photos))
+            .route(
+                "/{id}/photos/order",
+                web::put().to(photos::foods::reorder_food_photos),
+            )
+            .route(
+                "/{id}/photos/{photo_id}",
+                web::put().to(photos::foods::update_food_photo),
+            )
+            .route(
+                "/{id}/photos/{photo_id}",
+                web::delete().to(photos::foods::delete_food_photo),
+            )
+            // 菜品评价
+            .route("/{id}/reviews", web::get().to(reviews::view::list_food_reviews))
+            // 审核
+            .route("/{id}/approve", web::post().to(foods::audit::approve_food))
+            .route("/{id}/reject", web::post().to(foods::audit::reject_food))
+            .route(
+                "/{id}/request-changes",
+                web::post().to(foods::audit::request_food_changes),
+            )
+            .route(
+                "/{id}/audit-logs",
+                web::get().to(foods::audit::get_food_audit_logs),
+            )
+            // 修订历史
+            .route(
+                "/{id}/revisions",
+                web::get().to(foods::revisions::list_food_revisions),
+            )
+            .route(
+                "/{id}/revisions/diff",
+                web::get().to(foods::revisions::diff_food_revisions),
+            )
+            .route(
+                "/{id}/revisions/{revision_no}/restore",
+                web::post().to(foods::revisions::restore_food_revision),
+            ),
     );
     cfg.service(
         web::scope("/food_tags")
             .route("", web::post().to(foods::new::create_tag))
             .route("", web::get().to(foods::view::get_tags))
-            .route("/{id}", web::delete().to(foods::delete::delete_tag)),
+            .route("/order", web::put().to(foods::update::reorder_tags))
+            .route("/{id}", web::put().to(foods::update::update_tag))
+            .route("/{id}", web::delete().to(foods::delete::delete_tag))
+            .route("/{id}/merge", web::post().to(foods::update::merge_tag)),
     );
 
     // 订单相关路由
@@ -111,8 +187,78 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
                 "/status",
                 web::put().to(orders::update::update_order_status),
             )
+            .route(
+                "/shopping-list",
+                web::get().to(orders::shopping::get_shopping_list),
+            )
             .route("/{id}", web::get().to(orders::view::get_order_detail))
-            .route("/{id}", web::delete().to(orders::delete::delete_order)),
+            .route("/{id}", web::delete().to(orders::delete::delete_order))
+            .route("/{id}/items", web::post().to(orders::items::add_order_item))
+            .route(
+                "/{id}/items/{item_id}",
+                web::put().to(orders::items::update_order_item),
+            )
+            .route(
+                "/{id}/items/{item_id}",
+                web::delete().to(orders::items::remove_order_item),
+            )
+            .route(
+                "/{id}/item-edit",
+                web::put().to(orders::items::set_order_item_edit),
+            )
+            .route(
+                "/{id}/comments",
+                web::get().to(orders::comments::list_order_comments),
+            )
+            // 完成照片
+            .route("/{id}/photos", web::get().to(photos::orders::list_order_photos))
+            .route("/{id}/photos", web::post().to(photos::orders::add_order_photos))
+            .route(
+                "/{id}/photos/order",
+                web::put().to(photos::orders::reorder_order_photos),
+            )
+            .route(
+                "/{id}/photos/{photo_id}",
+                web::put().to(photos::orders::update_order_photo),
+            )
+            .route(
+                "/{id}/photos/{photo_id}",
+                web::delete().to(photos::orders::delete_order_photo),
+            )
+            // 菜品评价
+            .route("/{id}/reviews", web::get().to(reviews::view::list_order_reviews))
+            .route("/{id}/reviews", web::post().to(reviews::update::create_food_review))
+            .route(
+                "/{id}/comments",
+                web::post().to(orders::comments::create_order_comment),
+            )
+            .route(
+                "/{id}/comments/{comment_id}",
+                web::put().to(orders::comments::update_order_comment),
+            )
+            .route(
+                "/{id}/comments/{comment_id}",
+                web::delete().to(orders::comments::delete_order_comment),
+            )
+            .route("/{id}/reorder", web::post().to(orders::reorder::reorder)),
+    );
+    // 订单模板
+    cfg.service(
+        web::scope("/order-templates")
+            .route("", web::post().to(orders::templates::create_order_template))
+            .route("", web::get().to(orders::templates::list_order_templates))
+            .route(
+                "/{id}",
+                web::put().to(orders::templates::update_order_template),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(orders::templates::delete_order_template),
+            )
+            .route(
+                "/{id}/place",
+                web::post().to(orders::templates::place_order_template),
+            ),
     );
     // 订单评分
     cfg.service(
@@ -126,16 +272,56 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
                 web::get().to(orders::rating::get_order_rating),
             ),
     );
+    // 周期订单
+    cfg.service(
+        web::scope("/recurring-orders")
+            .route("", web::post().to(orders::recurring::create_recurring_order))
+            .route("", web::get().to(orders::recurring::list_recurring_orders))
+            .route(
+                "/{id}/pause",
+                web::put().to(orders::recurring::pause_recurring_order),
+            )
+            .route(
+                "/{id}/resume",
+                web::put().to(orders::recurring::resume_recurring_order),
+            )
+            .route(
+                "/{id}/skip",
+                web::post().to(orders::recurring::skip_recurring_occurrence),
+            )
+            .route(
+                "/{id}",
+                web::delete().to(orders::recurring::delete_recurring_order),
+            ),
+    );
     cfg.service(
         web::scope("/orders-incomplete")
             .route("/{id}", web::get().to(orders::view::get_incomplete_order)),
     );
 
+    // 购物车
+    cfg.service(
+        web::scope("/cart")
+            .route("", web::get().to(carts::view::get_cart))
+            .route("", web::delete().to(carts::update::clear_cart))
+            .route("/items", web::post().to(carts::update::add_cart_item))
+            .route(
+                "/items/{item_id}",
+                web::put().to(carts::update::update_cart_item),
+            )
+            .route(
+                "/items/{item_id}",
+                web::delete().to(carts::update::remove_cart_item),
+            )
+            .route("/checkout", web::post().to(carts::checkout::checkout_cart)),
+    );
+
     // 心愿相关路由
     cfg.service(
         web::scope("/wishes")
             .route("", web::post().to(wishes::new::create_wish))
             .route("", web::get().to(wishes::view::get_wishes))
+            .route("", web::put().to(wishes::update::update_wish))
             .route("/{id}", web::get().to(wishes::view::get_wish_detail))
             .route("/{id}", web::delete().to(wishes::update::disable_wish)),
     );
@@ -151,6 +337,21 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
             .route(
                 "/{claim_id}/checkins",
                 web::get().to(wishes::checkin::list_wish_claim_checkins),
+            )
+            // 打卡图集
+            .route("/checkins/{id}/photos", web::get().to(photos::checkins::list_checkin_photos))
+            .route("/checkins/{id}/photos", web::post().to(photos::checkins::add_checkin_photos))
+            .route(
+                "/checkins/{id}/photos/order",
+                web::put().to(photos::checkins::reorder_checkin_photos),
+            )
+            .route(
+                "/checkins/{id}/photos/{photo_id}",
+                web::put().to(photos::checkins::update_checkin_photo),
+            )
+            .route(
+                "/checkins/{id}/photos/{photo_id}",
+                web::delete().to(photos::checkins::delete_checkin_photo),
             ),
     );
     cfg.service(
@@ -158,10 +359,107 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
     );
 
     // 看板 / 组活动
-    cfg.service(web::scope("/groups").route(
-        "/{group_id}/activities",
-        web::get().to(dashboard::activities::get_group_activities),
-    ));
+    cfg.service(
+        web::scope("/groups")
+            .route(
+                "/{group_id}/activities",
+                web::get().to(dashboard::activities::get_group_activities),
+            )
+            // 组内菜品导出/导入
+            .route(
+                "/{group_id}/foods/export",
+                web::get().to(foods::transfer::export_group_foods),
+            )
+            .route(
+                "/{group_id}/foods/import",
+                web::post().to(foods::transfer::import_group_foods),
+            )
+            // 组内待审核菜品
+            .route(
+                "/{group_id}/food-reviews",
+                web::get().to(foods::audit::list_pending_food_reviews),
+            )
+            // 组内访客管理
+            .route(
+                "/{group_id}/guests",
+                web::get().to(guests::manage::list_group_guests),
+            )
+            .route(
+                "/{group_id}/guests/{session_id}",
+                web::delete().to(guests::manage::revoke_guest_session),
+            )
+            .route(
+                "/{group_id}/guests/{session_id}/convert",
+                web::post().to(guests::manage::convert_guest),
+            )
+            // 组内菜单计划
+            .route("/{group_id}/meal-plan", web::get().to(meal_plans::view::get_meal_plan))
+            .route(
+                "/{group_id}/meal-plan/entries",
+                web::post().to(meal_plans::update::add_meal_plan_entry),
+            )
+            .route(
+                "/{group_id}/meal-plan/entries/{entry_id}",
+                web::put().to(meal_plans::update::update_meal_plan_entry),
+            )
+            .route(
+                "/{group_id}/meal-plan/entries/{entry_id}",
+                web::delete().to(meal_plans::update::delete_meal_plan_entry),
+            )
+            .route("/{group_id}/meal-plan/fill", web::post().to(meal_plans::fill::fill_meal_plan))
+            .route(
+                "/{group_id}/meal-plan/swap",
+                web::post().to(meal_plans::fill::swap_meal_plan_entries),
+            )
+            .route(
+                "/{group_id}/meal-plan/days/{date}/order",
+                web::post().to(meal_plans::order::order_meal_plan_day),
+            )
+            // 组内食材库存
+            .route("/{group_id}/pantry", web::get().to(pantry::items::list_pantry_items))
+            .route("/{group_id}/pantry", web::post().to(pantry::items::create_pantry_item))
+            .route(
+                "/{group_id}/pantry/alerts",
+                web::get().to(pantry::items::get_pantry_alerts),
+            )
+            .route(
+                "/{group_id}/pantry/cookable",
+                web::get().to(pantry::cookable::get_cookable_foods),
+            )
+            .route(
+                "/{group_id}/pantry/{item_id}",
+                web::put().to(pantry::items::update_pantry_item),
+            )
+            .route(
+                "/{group_id}/pantry/{item_id}",
+                web::delete().to(pantry::items::delete_pantry_item),
+            )
+            // 组内菜品评价动态
+            .route(
+                "/{group_id}/reviews",
+                web::get().to(reviews::view::get_group_review_feed),
+            ),
+    );
+    cfg.service(
+        web::scope("/food_reviews")
+            .route("/{review_id}", web::put().to(reviews::update::update_food_review))
+            .route("/{review_id}", web::delete().to(reviews::update::delete_food_review)),
+    );
+    // 访客（凭邀请码的临时身份）
+    cfg.service(
+        web::scope("/guest")
+            .route("/sessions", web::post().to(guests::session::create_guest_session))
+            .route("/orders", web::post().to(guests::orders::create_guest_order))
+            .route("/orders", web::get().to(guests::orders::list_guest_orders))
+            .route("/orders/{id}", web::get().to(guests::orders::get_guest_order)),
+    );
+    // 管理
+    cfg.service(
+        web::scope("/admin").route(
+            "/food-stats/rebuild",
+            web::post().to(foods::stats::rebuild_food_stats),
+        ),
+    );
     // 看板 / 综合指标
     cfg.service(
         web::scope("/dashboard")
@@ -180,6 +478,10 @@ pub fn route(_state: Arc<AppState>, cfg: &mut web::ServiceConfig) {
             .route(
                 "/my/points-journey",
                 web::get().to(dashboard::metrics::get_points_journey),
+            )
+            .route(
+                "/my/nutrition",
+                web::get().to(dashboard::nutrition::get_my_nutrition),
             ),
     );
 }
@@ -19,7 +19,8 @@ CREATE TYPE order_status_enum AS ENUM (
     'CANCELLED',
     'EXPIRED',
     'REJECTED',
-    'SYSTEM_CLOSED'
+    'SYSTEM_CLOSED',
+    'OVERDUE'
 );
 CREATE TYPE point_tx_type_enum AS ENUM (
     'ORDER_REWARD',
@@ -39,6 +40,31 @@ CREATE TYPE cart_status_enum AS ENUM ('ACTIVE', 'SETTLED', 'CLEARED');
 CREATE TYPE mark_type_enum AS ENUM ('LIKE', 'NOT_RECOMMEND');
 CREATE TYPE gender_enum AS ENUM ('MALE', 'FEMALE', 'OTHER', 'UNKNOWN');
 CREATE TYPE login_method_enum AS ENUM ('PASSWORD', 'PHONE_CODE', 'OAUTH', 'MIXED', 'WEIXIN');
+CREATE TYPE recurring_status_enum AS ENUM ('ACTIVE', 'PAUSED');
+CREATE TYPE recurring_run_status_enum AS ENUM ('CREATED', 'SKIPPED', 'FAILED');
+-- ================= EXTENSIONS & FUNCTIONS =================
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+-- 中文按相邻两字切分（bigram），其余文本保留原词，供 simple 全文检索配置使用
+CREATE OR REPLACE FUNCTION zh_bigrams(src TEXT) RETURNS TEXT
+LANGUAGE plpgsql IMMUTABLE PARALLEL SAFE AS $$
+DECLARE
+    s TEXT := lower(COALESCE(src, ''));
+    out TEXT;
+    run TEXT;
+    i INT;
+BEGIN
+    out := regexp_replace(s, '[\u4e00-\u9fff]+', ' ', 'g');
+    FOR run IN SELECT m[1] FROM regexp_matches(s, '([\u4e00-\u9fff]+)', 'g') AS m LOOP
+        IF char_length(run) = 1 THEN
+            out := out || ' ' || run;
+        ELSE
+            FOR i IN 1..char_length(run) - 1 LOOP
+                out := out || ' ' || substr(run, i, 2);
+            END LOOP;
+        END IF;
+    END LOOP;
+    RETURN out;
+END $$;
 -- ================= USERS =================
 CREATE TABLE users (
     user_id BIGSERIAL PRIMARY KEY,
@@ -64,6 +90,7 @@ CREATE TABLE users (
     push_id VARCHAR(255),
     last_role_switch_at TIMESTAMPTZ,
     -- 最近一次角色互换时间（半年冷却）
+    is_guest BOOLEAN NOT NULL DEFAULT FALSE,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -89,6 +116,7 @@ COMMENT ON COLUMN users.password_updated_at IS '最近密码更新时间';
 COMMENT ON COLUMN users.is_temp_password IS '是否临时密码需修改';
 COMMENT ON COLUMN users.push_id IS '推送ID用于消息通知';
 COMMENT ON COLUMN users.last_role_switch_at IS '最近一次下单/接单角色对换时间（半年冷却）';
+COMMENT ON COLUMN users.is_guest IS '是否访客影子账号（凭邀请码创建，不可登录）';
 COMMENT ON COLUMN users.created_at IS '创建时间';
 COMMENT ON COLUMN users.updated_at IS '更新时间';
 CREATE INDEX idx_users_role ON users(role);
@@ -175,7 +203,17 @@ CREATE TABLE foods (
     SET NULL,
         is_del SMALLINT NOT NULL DEFAULT 0,
         created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        calories INT,
+        protein DOUBLE PRECISION,
+        fat DOUBLE PRECISION,
+        carbs DOUBLE PRECISION,
+        dietary_labels TEXT[] NOT NULL DEFAULT '{}',
+        search_vector tsvector GENERATED ALWAYS AS (
+            setweight(to_tsvector('simple', zh_bigrams(food_name)), 'A') ||
+            setweight(to_tsvector('simple', zh_bigrams(COALESCE(ingredients, ''))), 'B') ||
+            setweight(to_tsvector('simple', zh_bigrams(COALESCE(steps, ''))), 'C')
+        ) STORED
 );
 COMMENT ON TABLE foods IS '菜品（含申请与审核）';
 COMMENT ON COLUMN foods.food_id IS '菜品主键ID';
@@ -194,14 +232,24 @@ COMMENT ON COLUMN foods.approved_by IS '审核人用户ID';
 COMMENT ON COLUMN foods.is_del IS '逻辑删除标记';
 COMMENT ON COLUMN foods.created_at IS '创建时间';
 COMMENT ON COLUMN foods.updated_at IS '更新时间';
+COMMENT ON COLUMN foods.calories IS '每份热量（kcal）';
+COMMENT ON COLUMN foods.protein IS '每份蛋白质（g）';
+COMMENT ON COLUMN foods.fat IS '每份脂肪（g）';
+COMMENT ON COLUMN foods.carbs IS '每份碳水化合物（g）';
+COMMENT ON COLUMN foods.dietary_labels IS '饮食/过敏原标签：VEGETARIAN/SPICY/CONTAINS_NUTS 等';
+COMMENT ON COLUMN foods.search_vector IS '全文检索向量（名称A/食材B/步骤C，中文二元切分）';
 CREATE INDEX idx_food_group_apply ON foods(group_id, apply_status);
 CREATE INDEX idx_food_owner ON foods(owner_user_id);
+CREATE INDEX idx_food_search ON foods USING GIN (search_vector);
+CREATE INDEX idx_food_name_trgm ON foods USING GIN (food_name gin_trgm_ops);
 CREATE INDEX idx_food_types ON foods(food_types);
+CREATE INDEX idx_food_dietary_labels ON foods USING GIN (dietary_labels);
 CREATE TABLE tags (
     tag_id BIGSERIAL PRIMARY KEY,
     tag_name VARCHAR(64) NOT NULL,
     group_id BIGINT REFERENCES association_groups(group_id) ON DELETE CASCADE,
     sort INT DEFAULT 0,
+    parent_id BIGINT REFERENCES tags(tag_id) ON DELETE SET NULL,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
     UNIQUE (tag_name, group_id)
 );
@@ -209,7 +257,9 @@ COMMENT ON TABLE tags IS '菜品标签';
 COMMENT ON COLUMN tags.tag_id IS '标签主键ID';
 COMMENT ON COLUMN tags.tag_name IS '标签名称唯一';
 COMMENT ON COLUMN tags.sort IS '排序值-越大越靠前';
+COMMENT ON COLUMN tags.parent_id IS '父标签ID（同组），为空表示顶级标签';
 COMMENT ON COLUMN tags.created_at IS '创建时间';
+CREATE INDEX idx_tags_parent ON tags(parent_id);
 
 CREATE TABLE user_food_mark (
     user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
@@ -228,7 +278,7 @@ CREATE TABLE food_audit_logs (
     id BIGSERIAL PRIMARY KEY,
     food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
     action SMALLINT NOT NULL,
-    -- 1提交 2通过 3拒绝 4修改
+    -- 1提交 2通过 3拒绝 4修改 5回退
     from_status apply_status_enum,
     to_status apply_status_enum NOT NULL,
     acted_by BIGINT NOT NULL REFERENCES users(user_id) ON DELETE RESTRICT,
@@ -238,7 +288,7 @@ CREATE TABLE food_audit_logs (
 COMMENT ON TABLE food_audit_logs IS '菜品审核历史';
 COMMENT ON COLUMN food_audit_logs.id IS '审核记录主键';
 COMMENT ON COLUMN food_audit_logs.food_id IS '菜品ID';
-COMMENT ON COLUMN food_audit_logs.action IS '动作：1提交 2通过 3拒绝 4修改';
+COMMENT ON COLUMN food_audit_logs.action IS '动作：1提交 2通过 3拒绝 4修改 5回退';
 COMMENT ON COLUMN food_audit_logs.from_status IS '变更前状态';
 COMMENT ON COLUMN food_audit_logs.to_status IS '变更后状态';
 COMMENT ON COLUMN food_audit_logs.acted_by IS '操作人用户ID';
@@ -261,6 +311,7 @@ CREATE TABLE orders (
         cancel_reason VARCHAR(255),
         reject_reason VARCHAR(255),
         last_status_change_at TIMESTAMPTZ,
+        allow_item_edit BOOLEAN NOT NULL DEFAULT FALSE,
         created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
         updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
@@ -276,6 +327,7 @@ COMMENT ON COLUMN orders.points_reward IS '奖励积分（完成时可能发放
 COMMENT ON COLUMN orders.cancel_reason IS '取消原因';
 COMMENT ON COLUMN orders.reject_reason IS '拒绝原因';
 COMMENT ON COLUMN orders.last_status_change_at IS '最后状态变更时间';
+COMMENT ON COLUMN orders.allow_item_edit IS '接单后是否允许下单人修改菜品（接单人设置）';
 COMMENT ON COLUMN orders.created_at IS '创建时间';
 COMMENT ON COLUMN orders.updated_at IS '更新时间';
 CREATE INDEX idx_order_user ON orders(user_id);
@@ -321,6 +373,18 @@ COMMENT ON COLUMN order_status_history.remark IS '变更备注';
 COMMENT ON COLUMN order_status_history.changed_at IS '变更时间';
 CREATE INDEX idx_osh_order ON order_status_history(order_id);
 CREATE INDEX idx_osh_changed ON order_status_history(changed_at);
+CREATE TABLE order_reminders (
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    kind VARCHAR(32) NOT NULL,
+    goal_time TIMESTAMPTZ NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (order_id, kind, goal_time)
+);
+COMMENT ON TABLE order_reminders IS '订单提醒发送记录（去重）';
+COMMENT ON COLUMN order_reminders.order_id IS '订单ID';
+COMMENT ON COLUMN order_reminders.kind IS '提醒类型：GOAL_TIME_UPCOMING 临近期望时间';
+COMMENT ON COLUMN order_reminders.goal_time IS '提醒针对的期望时间，修改期望时间后可再次提醒';
+COMMENT ON COLUMN order_reminders.sent_at IS '发送时间';
 -- ================= POINT TRANSACTIONS =================
 CREATE TABLE point_transactions (
     id BIGSERIAL PRIMARY KEY,
@@ -352,7 +416,13 @@ CREATE TABLE wishes (
     status wish_status_enum NOT NULL DEFAULT 'ON',
     created_by BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE RESTRICT,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    stock_total INT CHECK (stock_total >= 0),
+    per_user_limit INT CHECK (per_user_limit > 0),
+    limit_period VARCHAR(8),
+    cooldown_minutes INT CHECK (cooldown_minutes > 0),
+    available_from TIMESTAMPTZ,
+    available_until TIMESTAMPTZ
 );
 COMMENT ON TABLE wishes IS '心愿模板';
 COMMENT ON COLUMN wishes.wish_id IS '心愿ID';
@@ -362,6 +432,12 @@ COMMENT ON COLUMN wishes.status IS '心愿状态';
 COMMENT ON COLUMN wishes.created_by IS '创建者团队ID（关联组ID）';
 COMMENT ON COLUMN wishes.created_at IS '创建时间';
 COMMENT ON COLUMN wishes.updated_at IS '更新时间';
+COMMENT ON COLUMN wishes.stock_total IS '总库存（含已兑换，取消的兑换不占用），为空不限';
+COMMENT ON COLUMN wishes.per_user_limit IS '每人每周期可兑换次数，为空不限';
+COMMENT ON COLUMN wishes.limit_period IS '限额周期：DAY/WEEK/MONTH/TOTAL（东八区自然周期）';
+COMMENT ON COLUMN wishes.cooldown_minutes IS '同一用户两次兑换的最小间隔（分钟），为空不限';
+COMMENT ON COLUMN wishes.available_from IS '开放兑换开始时间，为空不限';
+COMMENT ON COLUMN wishes.available_until IS '开放兑换结束时间，为空不限';
 CREATE INDEX idx_wish_status ON wishes(status);
 CREATE INDEX idx_wish_created_by ON wishes(created_by);
 CREATE TABLE wish_claims (
@@ -386,6 +462,7 @@ COMMENT ON COLUMN wish_claims.fulfill_at IS '完成时间';
 COMMENT ON COLUMN wish_claims.created_at IS '创建时间';
 COMMENT ON COLUMN wish_claims.updated_at IS '更新时间';
 CREATE INDEX idx_wc_user ON wish_claims(user_id);
+CREATE INDEX idx_wc_wish_user_time ON wish_claims(wish_id, user_id, created_at);
 CREATE INDEX idx_wc_status ON wish_claims(status);
 CREATE TABLE wish_claim_checkins (
     id BIGSERIAL PRIMARY KEY,
@@ -440,6 +517,11 @@ CREATE TABLE lottery_draws (
     request_payload JSONB,
     is_success lottery_success_enum NOT NULL DEFAULT 'SUCCESS',
     fail_reason VARCHAR(255),
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE SET NULL,
+    parent_draw_id BIGINT REFERENCES lottery_draws(draw_id) ON DELETE SET NULL,
+    points_cost INT NOT NULL DEFAULT 0,
+    order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL,
+    superseded_at TIMESTAMPTZ,
     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 COMMENT ON TABLE lottery_draws IS '抽奖主记录';
@@ -449,22 +531,30 @@ COMMENT ON COLUMN lottery_draws.food_types_requested IS '请求的菜品类型
 COMMENT ON COLUMN lottery_draws.request_payload IS '请求参数快照JSON';
 COMMENT ON COLUMN lottery_draws.is_success IS '成功/失败标记';
 COMMENT ON COLUMN lottery_draws.fail_reason IS '失败原因';
+COMMENT ON COLUMN lottery_draws.group_id IS '抽取所在关联组ID';
+COMMENT ON COLUMN lottery_draws.parent_draw_id IS '重抽来源抽奖记录ID';
+COMMENT ON COLUMN lottery_draws.points_cost IS '本次消耗积分（重抽）';
+COMMENT ON COLUMN lottery_draws.order_id IS '转为订单后的订单ID';
+COMMENT ON COLUMN lottery_draws.superseded_at IS '被重抽取代的时间，取代后不可再重抽或下单';
 COMMENT ON COLUMN lottery_draws.created_at IS '创建时间';
 CREATE INDEX idx_ld_user_time ON lottery_draws(user_id, created_at);
+CREATE INDEX idx_ld_user_group_time ON lottery_draws(user_id, group_id, created_at);
 CREATE TABLE lottery_draw_results (
     id BIGSERIAL PRIMARY KEY,
     draw_id BIGINT NOT NULL REFERENCES lottery_draws(draw_id) ON DELETE CASCADE,
-    food_type SMALLINT NOT NULL,
+    food_type SMALLINT,
+    tag_id BIGINT REFERENCES tags(tag_id) ON DELETE SET NULL,
     food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE RESTRICT,
     food_name_snapshot VARCHAR(128),
     food_photo_snapshot VARCHAR(256),
     allocated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    UNIQUE(draw_id, food_type)
+    UNIQUE(draw_id, food_id)
 );
 COMMENT ON TABLE lottery_draw_results IS '抽奖结果明细';
 COMMENT ON COLUMN lottery_draw_results.id IS '结果明细主键';
 COMMENT ON COLUMN lottery_draw_results.draw_id IS '所属抽奖记录ID';
-COMMENT ON COLUMN lottery_draw_results.food_type IS '菜品类型编号';
+COMMENT ON COLUMN lottery_draw_results.food_type IS '菜品类型编号（旧数据）';
+COMMENT ON COLUMN lottery_draw_results.tag_id IS '抽中时所属标签ID';
 COMMENT ON COLUMN lottery_draw_results.food_id IS '菜品ID';
 COMMENT ON COLUMN lottery_draw_results.food_name_snapshot IS '菜品名称快照';
 COMMENT ON COLUMN lottery_draw_results.food_photo_snapshot IS '菜品图片快照';
@@ -540,20 +630,22 @@ CREATE TABLE carts (
     cart_id BIGSERIAL PRIMARY KEY,
     user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
     status cart_status_enum NOT NULL DEFAULT 'ACTIVE',
-    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
-    UNIQUE (user_id, status)
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 COMMENT ON TABLE carts IS '购物车主表';
 COMMENT ON COLUMN carts.cart_id IS '购物车主键ID';
 COMMENT ON COLUMN carts.user_id IS '用户ID';
 COMMENT ON COLUMN carts.status IS '购物车状态';
 COMMENT ON COLUMN carts.updated_at IS '更新时间';
+-- 每个用户仅一个 ACTIVE 购物车；SETTLED/CLEARED 可保留多条历史
+CREATE UNIQUE INDEX uq_carts_user_active ON carts(user_id) WHERE status = 'ACTIVE';
 CREATE TABLE cart_items (
     id BIGSERIAL PRIMARY KEY,
     cart_id BIGINT NOT NULL REFERENCES carts(cart_id) ON DELETE CASCADE,
     food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE RESTRICT,
     quantity INT NOT NULL DEFAULT 1,
-    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cart_id, food_id)
 );
 COMMENT ON TABLE cart_items IS '购物车明细';
 COMMENT ON COLUMN cart_items.id IS '明细主键ID';
@@ -570,6 +662,8 @@ CREATE TABLE food_stats (
     completed_order_count INT NOT NULL DEFAULT 0,
     last_order_time TIMESTAMPTZ,
     last_complete_time TIMESTAMPTZ,
+    review_count INT NOT NULL DEFAULT 0,
+    review_star_sum INT NOT NULL DEFAULT 0,
     updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
 );
 COMMENT ON TABLE food_stats IS '菜品统计宽表（缓存排行榜）';
@@ -578,9 +672,333 @@ COMMENT ON COLUMN food_stats.total_order_count IS '总下单次数';
 COMMENT ON COLUMN food_stats.completed_order_count IS '完成订单次数';
 COMMENT ON COLUMN food_stats.last_order_time IS '最近下单时间';
 COMMENT ON COLUMN food_stats.last_complete_time IS '最近完成时间';
+COMMENT ON COLUMN food_stats.review_count IS '菜品评价数';
+COMMENT ON COLUMN food_stats.review_star_sum IS '菜品评价星级合计（平均分 = 合计 / 评价数）';
 COMMENT ON COLUMN food_stats.updated_at IS '统计更新时间';
 CREATE INDEX idx_fs_order_count ON food_stats(total_order_count);
 CREATE INDEX idx_fs_complete_count ON food_stats(completed_order_count);
+-- ================= RECURRING ORDERS =================
+CREATE TABLE recurring_orders (
+    recurring_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    title VARCHAR(128),
+    items JSONB NOT NULL,
+    rrule VARCHAR(255) NOT NULL,
+    tz_offset_minutes INT NOT NULL DEFAULT 480,
+    goal_offset_minutes INT NOT NULL DEFAULT 60,
+    points_cost INT NOT NULL DEFAULT 0,
+    points_reward INT NOT NULL DEFAULT 0,
+    status recurring_status_enum NOT NULL DEFAULT 'ACTIVE',
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE recurring_orders IS '周期订单模板';
+COMMENT ON COLUMN recurring_orders.recurring_id IS '周期订单主键ID';
+COMMENT ON COLUMN recurring_orders.user_id IS '下单用户ID';
+COMMENT ON COLUMN recurring_orders.group_id IS '目标关联组ID';
+COMMENT ON COLUMN recurring_orders.title IS '模板名称';
+COMMENT ON COLUMN recurring_orders.items IS '菜品明细JSON [{food_id, quantity}]';
+COMMENT ON COLUMN recurring_orders.rrule IS '周期规则（RRULE子集：FREQ/BYDAY/BYHOUR/BYMINUTE）';
+COMMENT ON COLUMN recurring_orders.tz_offset_minutes IS '规则时区相对UTC的分钟偏移';
+COMMENT ON COLUMN recurring_orders.goal_offset_minutes IS '提前生成订单的分钟数（相对goal_time）';
+COMMENT ON COLUMN recurring_orders.points_cost IS '订单积分成本';
+COMMENT ON COLUMN recurring_orders.points_reward IS '订单奖励积分';
+COMMENT ON COLUMN recurring_orders.status IS '状态：ACTIVE/PAUSED';
+COMMENT ON COLUMN recurring_orders.next_run_at IS '下一次发生时间（即goal_time）';
+COMMENT ON COLUMN recurring_orders.last_run_at IS '最近一次处理的发生时间';
+COMMENT ON COLUMN recurring_orders.created_at IS '创建时间';
+COMMENT ON COLUMN recurring_orders.updated_at IS '更新时间';
+CREATE INDEX idx_ro_user ON recurring_orders(user_id);
+CREATE INDEX idx_ro_status_next ON recurring_orders(status, next_run_at);
+CREATE TABLE recurring_order_runs (
+    id BIGSERIAL PRIMARY KEY,
+    recurring_id BIGINT NOT NULL REFERENCES recurring_orders(recurring_id) ON DELETE CASCADE,
+    occurrence_at TIMESTAMPTZ NOT NULL,
+    status recurring_run_status_enum NOT NULL,
+    order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL,
+    remark VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (recurring_id, occurrence_at)
+);
+COMMENT ON TABLE recurring_order_runs IS '周期订单每次发生的处理记录（生成/跳过/失败）';
+COMMENT ON COLUMN recurring_order_runs.id IS '记录主键ID';
+COMMENT ON COLUMN recurring_order_runs.recurring_id IS '周期订单ID';
+COMMENT ON COLUMN recurring_order_runs.occurrence_at IS '发生时间（goal_time）';
+COMMENT ON COLUMN recurring_order_runs.status IS '处理结果：CREATED/SKIPPED/FAILED';
+COMMENT ON COLUMN recurring_order_runs.order_id IS '生成的订单ID';
+COMMENT ON COLUMN recurring_order_runs.remark IS '备注/失败原因';
+COMMENT ON COLUMN recurring_order_runs.created_at IS '记录创建时间';
+CREATE TABLE order_comments (
+    comment_id BIGSERIAL PRIMARY KEY,
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    content TEXT NOT NULL DEFAULT '',
+    photo_url VARCHAR(512),
+    is_del SMALLINT NOT NULL DEFAULT 0,
+    edited_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE order_comments IS '订单评论';
+COMMENT ON COLUMN order_comments.comment_id IS '评论主键ID';
+COMMENT ON COLUMN order_comments.order_id IS '订单ID';
+COMMENT ON COLUMN order_comments.user_id IS '评论人ID';
+COMMENT ON COLUMN order_comments.content IS '评论内容';
+COMMENT ON COLUMN order_comments.photo_url IS '图片地址（七牛上传）';
+COMMENT ON COLUMN order_comments.is_del IS '是否删除：0否 1是';
+COMMENT ON COLUMN order_comments.edited_at IS '最后编辑时间';
+COMMENT ON COLUMN order_comments.created_at IS '创建时间';
+CREATE INDEX idx_order_comments_order ON order_comments(order_id, created_at DESC);
+CREATE TABLE guest_sessions (
+    session_id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    nick_name VARCHAR(64) NOT NULL,
+    invite_code VARCHAR(32) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    converted_at TIMESTAMPTZ,
+    converted_user_id BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE guest_sessions IS '访客会话（凭邀请码领取的短期令牌）';
+COMMENT ON COLUMN guest_sessions.session_id IS '会话主键ID';
+COMMENT ON COLUMN guest_sessions.group_id IS '所属关联组ID';
+COMMENT ON COLUMN guest_sessions.user_id IS '访客影子账号ID';
+COMMENT ON COLUMN guest_sessions.nick_name IS '访客昵称';
+COMMENT ON COLUMN guest_sessions.invite_code IS '领取时使用的邀请码（组邀请码变更后无法继续下单）';
+COMMENT ON COLUMN guest_sessions.expires_at IS '令牌过期时间';
+COMMENT ON COLUMN guest_sessions.revoked_at IS '被组成员撤销时间';
+COMMENT ON COLUMN guest_sessions.converted_at IS '转为正式成员时间';
+COMMENT ON COLUMN guest_sessions.converted_user_id IS '转正后的成员用户ID';
+COMMENT ON COLUMN guest_sessions.created_at IS '创建时间';
+CREATE INDEX idx_guest_sessions_group ON guest_sessions(group_id, created_at DESC);
+CREATE INDEX idx_guest_sessions_user ON guest_sessions(user_id);
+CREATE TABLE order_templates (
+    template_id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    items JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE order_templates IS '命名订单模板（个人或组共享）';
+COMMENT ON COLUMN order_templates.template_id IS '模板主键ID';
+COMMENT ON COLUMN order_templates.user_id IS '创建人ID';
+COMMENT ON COLUMN order_templates.group_id IS '所属组ID（为空表示个人模板）';
+COMMENT ON COLUMN order_templates.name IS '模板名称';
+COMMENT ON COLUMN order_templates.items IS '菜品列表 JSON：[{food_id, quantity}]';
+COMMENT ON COLUMN order_templates.created_at IS '创建时间';
+COMMENT ON COLUMN order_templates.updated_at IS '更新时间';
+CREATE INDEX idx_order_templates_user ON order_templates(user_id);
+CREATE INDEX idx_order_templates_group ON order_templates(group_id);
+-- ================= FOOD RECIPES =================
+CREATE TABLE food_ingredients (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    sort INT NOT NULL DEFAULT 0,
+    name VARCHAR(64) NOT NULL,
+    quantity DOUBLE PRECISION,
+    unit VARCHAR(16),
+    note VARCHAR(64),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+COMMENT ON TABLE food_ingredients IS '菜品结构化食材';
+COMMENT ON COLUMN food_ingredients.id IS '主键';
+COMMENT ON COLUMN food_ingredients.food_id IS '菜品ID';
+COMMENT ON COLUMN food_ingredients.sort IS '排序';
+COMMENT ON COLUMN food_ingredients.name IS '食材名称';
+COMMENT ON COLUMN food_ingredients.quantity IS '单份用量（为空表示适量）';
+COMMENT ON COLUMN food_ingredients.unit IS '单位';
+COMMENT ON COLUMN food_ingredients.note IS '备注（如切丁）';
+COMMENT ON COLUMN food_ingredients.created_at IS '创建时间';
+CREATE INDEX idx_food_ingredients_food ON food_ingredients(food_id, sort);
+CREATE TABLE food_steps (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    step_no INT NOT NULL,
+    content TEXT NOT NULL,
+    photo_url VARCHAR(512),
+    timer_seconds INT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (food_id, step_no)
+);
+COMMENT ON TABLE food_steps IS '菜品结构化步骤';
+COMMENT ON COLUMN food_steps.id IS '主键';
+COMMENT ON COLUMN food_steps.food_id IS '菜品ID';
+COMMENT ON COLUMN food_steps.step_no IS '步骤序号（从1开始）';
+COMMENT ON COLUMN food_steps.content IS '步骤内容';
+COMMENT ON COLUMN food_steps.photo_url IS '步骤配图URL';
+COMMENT ON COLUMN food_steps.timer_seconds IS '计时（秒）';
+COMMENT ON COLUMN food_steps.created_at IS '创建时间';
+CREATE TABLE food_tags_map (
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    tag_id BIGINT NOT NULL REFERENCES tags(tag_id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (food_id, tag_id)
+);
+COMMENT ON TABLE food_tags_map IS '菜品-标签多对多映射（foods.tag_id 为主标签）';
+COMMENT ON COLUMN food_tags_map.food_id IS '菜品ID';
+COMMENT ON COLUMN food_tags_map.tag_id IS '标签ID';
+COMMENT ON COLUMN food_tags_map.created_at IS '创建时间';
+CREATE INDEX idx_food_tags_map_tag ON food_tags_map(tag_id);
+CREATE TABLE user_allergen_prefs (
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    label VARCHAR(32) NOT NULL,
+    action VARCHAR(8) NOT NULL DEFAULT 'WARN' CHECK (action IN ('WARN', 'BLOCK')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, label)
+);
+COMMENT ON TABLE user_allergen_prefs IS '用户过敏原/忌口偏好（下单时提醒或拦截）';
+COMMENT ON COLUMN user_allergen_prefs.user_id IS '用户ID';
+COMMENT ON COLUMN user_allergen_prefs.label IS '饮食/过敏原标签，对应 foods.dietary_labels';
+COMMENT ON COLUMN user_allergen_prefs.action IS '处理方式：WARN 提醒 / BLOCK 禁止下单';
+COMMENT ON COLUMN user_allergen_prefs.created_at IS '创建时间';
+CREATE TABLE food_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    revision_no INT NOT NULL,
+    source VARCHAR(16) NOT NULL,
+    changed_fields TEXT[] NOT NULL DEFAULT '{}',
+    snapshot JSONB NOT NULL,
+    audit_log_id BIGINT REFERENCES food_audit_logs(id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (food_id, revision_no)
+);
+COMMENT ON TABLE food_revisions IS '菜品修订历史（每次变更后的完整快照）';
+COMMENT ON COLUMN food_revisions.id IS '修订主键';
+COMMENT ON COLUMN food_revisions.food_id IS '菜品ID';
+COMMENT ON COLUMN food_revisions.revision_no IS '菜品内递增的修订号';
+COMMENT ON COLUMN food_revisions.source IS '来源：CREATE/UPDATE/AUDIT/RESTORE/IMPORT';
+COMMENT ON COLUMN food_revisions.changed_fields IS '相对上一修订变化的字段';
+COMMENT ON COLUMN food_revisions.snapshot IS '内容快照：名称/图片/食材/步骤/标签/状态';
+COMMENT ON COLUMN food_revisions.audit_log_id IS '关联的审核记录ID';
+COMMENT ON COLUMN food_revisions.created_by IS '操作人用户ID';
+COMMENT ON COLUMN food_revisions.created_at IS '创建时间';
+CREATE TABLE meal_plan_entries (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    plan_date DATE NOT NULL,
+    meal SMALLINT NOT NULL CHECK (meal BETWEEN 1 AND 5),
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    quantity INT NOT NULL DEFAULT 1,
+    sort INT NOT NULL DEFAULT 0,
+    note VARCHAR(64),
+    order_id BIGINT REFERENCES orders(order_id) ON DELETE SET NULL,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    updated_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_meal_plan_entries_group_date ON meal_plan_entries(group_id, plan_date, meal);
+COMMENT ON TABLE meal_plan_entries IS '组内菜单计划条目（日期 × 餐段 × 菜品），组成员共享编辑';
+COMMENT ON COLUMN meal_plan_entries.id IS '条目主键';
+COMMENT ON COLUMN meal_plan_entries.group_id IS '所属组ID';
+COMMENT ON COLUMN meal_plan_entries.plan_date IS '计划日期（本地日期）';
+COMMENT ON COLUMN meal_plan_entries.meal IS '餐段：1早餐 2午餐 3下午茶 4晚餐 5夜宵（同 food_types）';
+COMMENT ON COLUMN meal_plan_entries.food_id IS '菜品ID';
+COMMENT ON COLUMN meal_plan_entries.quantity IS '份数';
+COMMENT ON COLUMN meal_plan_entries.sort IS '餐段内排序';
+COMMENT ON COLUMN meal_plan_entries.note IS '备注';
+COMMENT ON COLUMN meal_plan_entries.order_id IS '已转订单ID，非空表示已下单';
+COMMENT ON COLUMN meal_plan_entries.created_by IS '创建人用户ID';
+COMMENT ON COLUMN meal_plan_entries.updated_by IS '最后修改人用户ID';
+COMMENT ON COLUMN meal_plan_entries.created_at IS '创建时间';
+COMMENT ON COLUMN meal_plan_entries.updated_at IS '更新时间';
+CREATE TABLE pantry_items (
+    id BIGSERIAL PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES association_groups(group_id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    unit VARCHAR(16) NOT NULL,
+    expiry_date DATE,
+    low_stock_threshold DOUBLE PRECISION,
+    note VARCHAR(128),
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    updated_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (group_id, name)
+);
+CREATE INDEX idx_pantry_items_group_expiry ON pantry_items(group_id, expiry_date);
+COMMENT ON TABLE pantry_items IS '组内食材库存';
+COMMENT ON COLUMN pantry_items.id IS '库存主键';
+COMMENT ON COLUMN pantry_items.group_id IS '所属组ID';
+COMMENT ON COLUMN pantry_items.name IS '食材名称（组内唯一）';
+COMMENT ON COLUMN pantry_items.quantity IS '当前数量（按 unit 计）';
+COMMENT ON COLUMN pantry_items.unit IS '库存单位';
+COMMENT ON COLUMN pantry_items.expiry_date IS '保质期截止日期';
+COMMENT ON COLUMN pantry_items.low_stock_threshold IS '低库存提醒阈值，为空不提醒';
+COMMENT ON COLUMN pantry_items.note IS '备注';
+COMMENT ON COLUMN pantry_items.created_by IS '创建人用户ID';
+COMMENT ON COLUMN pantry_items.updated_by IS '最后修改人用户ID';
+COMMENT ON COLUMN pantry_items.created_at IS '创建时间';
+COMMENT ON COLUMN pantry_items.updated_at IS '更新时间';
+CREATE TABLE food_pantry_usages (
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    item_id BIGINT NOT NULL REFERENCES pantry_items(id) ON DELETE CASCADE,
+    amount DOUBLE PRECISION NOT NULL,
+    unit VARCHAR(16) NOT NULL,
+    PRIMARY KEY (food_id, item_id)
+);
+CREATE INDEX idx_food_pantry_usages_item ON food_pantry_usages(item_id);
+COMMENT ON TABLE food_pantry_usages IS '菜品每份使用的库存食材';
+COMMENT ON COLUMN food_pantry_usages.food_id IS '菜品ID';
+COMMENT ON COLUMN food_pantry_usages.item_id IS '库存食材ID';
+COMMENT ON COLUMN food_pantry_usages.amount IS '每份用量';
+COMMENT ON COLUMN food_pantry_usages.unit IS '用量单位，需可换算为库存单位';
+CREATE TABLE photos (
+    id BIGSERIAL PRIMARY KEY,
+    owner_type VARCHAR(16) NOT NULL,
+    owner_id BIGINT NOT NULL,
+    url VARCHAR(512) NOT NULL,
+    caption VARCHAR(128),
+    sort INT NOT NULL DEFAULT 0,
+    is_cover BOOLEAN NOT NULL DEFAULT FALSE,
+    created_by BIGINT REFERENCES users(user_id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_photos_owner ON photos(owner_type, owner_id, sort);
+CREATE UNIQUE INDEX uq_photos_owner_cover ON photos(owner_type, owner_id) WHERE is_cover;
+COMMENT ON TABLE photos IS '图集（菜品/心愿打卡/订单完成照片），按 sort 排序，封面同步到旧单图字段';
+COMMENT ON COLUMN photos.id IS '图片主键';
+COMMENT ON COLUMN photos.owner_type IS '归属类型：FOOD 菜品 / CHECKIN 心愿打卡 / ORDER 订单完成 / REVIEW 菜品评价';
+COMMENT ON COLUMN photos.owner_id IS '归属对象ID：food_id / wish_claim_checkins.id / order_id / food_reviews.review_id';
+COMMENT ON COLUMN photos.url IS '图片URL（须属于上传域名）';
+COMMENT ON COLUMN photos.caption IS '图片说明';
+COMMENT ON COLUMN photos.sort IS '图集内排序';
+COMMENT ON COLUMN photos.is_cover IS '是否封面（每个图集至多一张）';
+COMMENT ON COLUMN photos.created_by IS '上传人用户ID';
+COMMENT ON COLUMN photos.created_at IS '创建时间';
+-- ================= FOOD REVIEWS =================
+CREATE TABLE food_reviews (
+    review_id BIGSERIAL PRIMARY KEY,
+    food_id BIGINT NOT NULL REFERENCES foods(food_id) ON DELETE CASCADE,
+    order_id BIGINT NOT NULL REFERENCES orders(order_id) ON DELETE CASCADE,
+    group_id BIGINT REFERENCES association_groups(group_id) ON DELETE SET NULL,
+    user_id BIGINT NOT NULL REFERENCES users(user_id) ON DELETE CASCADE,
+    stars SMALLINT NOT NULL CHECK (stars BETWEEN 1 AND 5),
+    content TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, food_id, user_id)
+);
+CREATE INDEX idx_food_reviews_food ON food_reviews(food_id, created_at DESC);
+CREATE INDEX idx_food_reviews_group ON food_reviews(group_id, created_at DESC);
+COMMENT ON TABLE food_reviews IS '菜品评价（1-5星，关联已完成订单；与订单积分评分 order_ratings 相互独立）';
+COMMENT ON COLUMN food_reviews.review_id IS '评价主键';
+COMMENT ON COLUMN food_reviews.food_id IS '菜品ID';
+COMMENT ON COLUMN food_reviews.order_id IS '来源订单ID（须已完成且包含该菜品）';
+COMMENT ON COLUMN food_reviews.group_id IS '订单所属组ID（用于组内评价动态）';
+COMMENT ON COLUMN food_reviews.user_id IS '评价人用户ID';
+COMMENT ON COLUMN food_reviews.stars IS '星级 1-5';
+COMMENT ON COLUMN food_reviews.content IS '评价内容';
+COMMENT ON COLUMN food_reviews.created_at IS '创建时间';
+COMMENT ON COLUMN food_reviews.updated_at IS '最后修改时间';
 -- ========= OPTIONAL TRIGGERS (COMMENTED OUT) =========
 -- CREATE OR REPLACE FUNCTION touch_updated_at()
 -- RETURNS trigger AS $$
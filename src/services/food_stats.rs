@@ -0,0 +1,121 @@
+// 菜品统计（food_stats）增量维护与全量重建
+use crate::errors::CustomError;
+use sqlx::{PgConnection, PgPool};
+
+// 订单新增了这些菜品：下单次数 +1，刷新最近下单时间。同一订单内重复菜品只计一次。
+pub async fn record_order_foods(
+    conn: &mut PgConnection,
+    order_id: i64,
+    food_ids: &[i64],
+) -> Result<(), CustomError> {
+    if food_ids.is_empty() {
+        return Ok(());
+    }
+    sqlx::query(
+        "INSERT INTO food_stats (food_id, total_order_count, last_order_time, updated_at) \
+         SELECT f.food_id, 1, o.created_at, NOW() \
+         FROM (SELECT DISTINCT UNNEST($2::bigint[]) AS food_id) f JOIN orders o ON o.order_id=$1 \
+         ON CONFLICT (food_id) DO UPDATE SET \
+         total_order_count = food_stats.total_order_count + 1, \
+         last_order_time = GREATEST(food_stats.last_order_time, EXCLUDED.last_order_time), \
+         updated_at = NOW()",
+    )
+    .bind(order_id)
+    .bind(food_ids)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// 菜品从订单中移除：订单内已无该菜品时回退下单次数（最近下单时间留待重建修正）
+pub async fn record_food_removed(
+    conn: &mut PgConnection,
+    order_id: i64,
+    food_id: i64,
+) -> Result<(), CustomError> {
+    sqlx::query(
+        "UPDATE food_stats SET total_order_count = GREATEST(total_order_count - 1, 0), updated_at = NOW() \
+         WHERE food_id=$2 AND NOT EXISTS(SELECT 1 FROM order_items WHERE order_id=$1 AND food_id=$2)",
+    )
+    .bind(order_id)
+    .bind(food_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// 订单完成：订单内每个菜品完成次数 +1，刷新最近完成时间
+pub async fn record_order_finished(conn: &mut PgConnection, order_id: i64) -> Result<(), CustomError> {
+    sqlx::query(
+        "INSERT INTO food_stats (food_id, completed_order_count, last_complete_time, updated_at) \
+         SELECT DISTINCT oi.food_id, 1, NOW(), NOW() FROM order_items oi WHERE oi.order_id=$1 \
+         ON CONFLICT (food_id) DO UPDATE SET \
+         completed_order_count = food_stats.completed_order_count + 1, \
+         last_complete_time = NOW(), \
+         updated_at = NOW()",
+    )
+    .bind(order_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// 菜品评价变更：按 food_reviews 重算该菜品的评价数与星级合计。
+// 先锁定菜品行，使同一菜品的并发重算串行执行，后者能看到前者已提交的评价。
+// 用 FOR NO KEY UPDATE 而非 FOR UPDATE：评价插入时外键会对菜品行加 KEY SHARE 锁，FOR UPDATE 会与之互相等待。
+pub async fn refresh_food_reviews(conn: &mut PgConnection, food_id: i64) -> Result<(), CustomError> {
+    sqlx::query("SELECT 1 FROM foods WHERE food_id=$1 FOR NO KEY UPDATE")
+        .bind(food_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "INSERT INTO food_stats (food_id, review_count, review_star_sum, updated_at) \
+         SELECT $1, COUNT(*)::int, COALESCE(SUM(stars), 0)::int, NOW() FROM food_reviews WHERE food_id=$1 \
+         ON CONFLICT (food_id) DO UPDATE SET \
+         review_count = EXCLUDED.review_count, \
+         review_star_sum = EXCLUDED.review_star_sum, \
+         updated_at = NOW()",
+    )
+    .bind(food_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+// 由 order_items、order_status_history 与 food_reviews 全量重算所有菜品统计，返回写入的菜品数
+pub async fn rebuild_all(db: &PgPool) -> Result<u64, CustomError> {
+    let mut tx = db.begin().await?;
+    // 避免与增量更新交错导致计数偏差
+    sqlx::query("LOCK TABLE food_stats IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+    let res = sqlx::query(
+        "INSERT INTO food_stats (food_id, total_order_count, completed_order_count, last_order_time, last_complete_time, review_count, review_star_sum, updated_at) \
+         SELECT f.food_id, COALESCE(t.cnt, 0), COALESCE(c.cnt, 0), t.last_at, c.last_at, COALESCE(r.cnt, 0), COALESCE(r.star_sum, 0), NOW() \
+         FROM foods f \
+         LEFT JOIN ( \
+             SELECT oi.food_id, COUNT(DISTINCT oi.order_id)::int AS cnt, MAX(o.created_at) AS last_at \
+             FROM order_items oi JOIN orders o ON o.order_id=oi.order_id GROUP BY oi.food_id \
+         ) t ON t.food_id=f.food_id \
+         LEFT JOIN ( \
+             SELECT oi.food_id, COUNT(DISTINCT h.order_id)::int AS cnt, MAX(h.changed_at) AS last_at \
+             FROM order_status_history h JOIN order_items oi ON oi.order_id=h.order_id \
+             WHERE h.to_status='FINISHED' GROUP BY oi.food_id \
+         ) c ON c.food_id=f.food_id \
+         LEFT JOIN ( \
+             SELECT food_id, COUNT(*)::int AS cnt, SUM(stars)::int AS star_sum FROM food_reviews GROUP BY food_id \
+         ) r ON r.food_id=f.food_id \
+         ON CONFLICT (food_id) DO UPDATE SET \
+         total_order_count = EXCLUDED.total_order_count, \
+         completed_order_count = EXCLUDED.completed_order_count, \
+         last_order_time = EXCLUDED.last_order_time, \
+         last_complete_time = EXCLUDED.last_complete_time, \
+         review_count = EXCLUDED.review_count, \
+         review_star_sum = EXCLUDED.review_star_sum, \
+         updated_at = NOW()",
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(res.rows_affected())
+}
@@ -0,0 +1,44 @@
+// 组内共享数据（菜单、库存、评价、菜谱导入导出、访客）的成员校验与组本地日期
+use crate::errors::CustomError;
+use chrono::{FixedOffset, NaiveDate, Utc};
+use sqlx::PgConnection;
+
+// 用户是否为该组成员
+pub async fn is_member(conn: &mut PgConnection, group_id: i64, user_id: i64) -> Result<bool, CustomError> {
+    let is_member = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM association_group_members WHERE group_id=$1 AND user_id=$2)",
+    )
+    .bind(group_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(is_member)
+}
+
+// 校验当前用户是该组成员
+pub async fn ensure_member(conn: &mut PgConnection, group_id: i64, user_id: i64) -> Result<(), CustomError> {
+    if !is_member(conn, group_id, user_id).await? {
+        return Err(CustomError::BadRequest("你不是该组成员".into()));
+    }
+    Ok(())
+}
+
+// 用户的主组：优先 is_primary，其次组ID最小；已解散的组不计
+pub async fn primary_group_id(conn: &mut PgConnection, user_id: i64) -> Result<Option<i64>, CustomError> {
+    let group_id = sqlx::query_scalar::<_, i64>(
+        "SELECT agm.group_id FROM association_group_members agm \
+         JOIN association_groups g ON g.group_id=agm.group_id AND g.status=1 \
+         WHERE agm.user_id=$1 ORDER BY agm.is_primary DESC, agm.group_id ASC LIMIT 1",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    Ok(group_id)
+}
+
+// 按客户端时区偏移（分钟，默认 UTC+8）计算当天日期
+pub fn local_today(tz_offset_minutes: Option<i32>) -> Result<NaiveDate, CustomError> {
+    let offset = FixedOffset::east_opt(tz_offset_minutes.unwrap_or(480) * 60)
+        .ok_or_else(|| CustomError::BadRequest("无效的时区偏移".into()))?;
+    Ok(Utc::now().with_timezone(&offset).date_naive())
+}
@@ -0,0 +1,140 @@
+// Idempotency-Key 支持：首个成功响应写入 Redis，窗口期内相同 key 的重试直接回放
+use crate::{cache::RedisCache, errors::CustomError};
+use ntex::http::StatusCode;
+use ntex::web::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{env, future::Future};
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyConfig {
+    /// 响应保留时长（秒）
+    pub ttl_seconds: usize,
+    /// 处理中标记的保留时长（秒）；进程崩溃或重启后 key 在此之后可重试
+    pub pending_ttl_seconds: usize,
+}
+
+impl IdempotencyConfig {
+    pub fn from_env() -> Self {
+        let ttl_seconds = env::var("IDEMPOTENCY_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(86400);
+        let pending_ttl_seconds = env::var("IDEMPOTENCY_PENDING_TTL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(60);
+        Self {
+            ttl_seconds,
+            pending_ttl_seconds,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredResponse {
+    fingerprint: String,
+    /// 为空表示首个请求仍在处理中
+    status: Option<u16>,
+    body: Option<String>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 以幂等方式执行 handler：无 Idempotency-Key 时直接执行；
+/// 有 key 时按 (用户, 方法, 路径, key) 去重，请求体不同返回 409。
+pub async fn run<T, F, Fut>(
+    cache: &RedisCache,
+    req: &HttpRequest,
+    user_id: i64,
+    payload: &impl Serialize,
+    status: StatusCode,
+    handler: F,
+) -> Result<HttpResponse, CustomError>
+where
+    T: Serialize,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, CustomError>>,
+{
+    let key = match req.headers().get(IDEMPOTENCY_HEADER) {
+        Some(v) => v
+            .to_str()
+            .map_err(|_| CustomError::BadRequest("Idempotency-Key 无效".into()))?
+            .trim()
+            .to_string(),
+        None => {
+            let out = handler().await?;
+            return Ok(HttpResponse::build(status).json(&out));
+        }
+    };
+    if key.is_empty() || key.len() > 128 {
+        return Err(CustomError::BadRequest("Idempotency-Key 长度需在1-128之间".into()));
+    }
+
+    let config = IdempotencyConfig::from_env();
+    let redis_key = format!("idem:{}:{}:{}:{}", user_id, req.method(), req.path(), key);
+    let fingerprint = sha256_hex(&serde_json::to_vec(payload)?);
+    let pending = serde_json::to_string(&StoredResponse {
+        fingerprint: fingerprint.clone(),
+        status: None,
+        body: None,
+    })?;
+
+    // 抢占 key（短 TTL，成功后改写为完整保留时长）；Redis 不可用时降级为普通请求
+    match cache.set_nx_ex(&redis_key, &pending, config.pending_ttl_seconds).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let stored: Option<StoredResponse> = cache
+                .get_string(&redis_key)
+                .await?
+                .and_then(|s| serde_json::from_str(&s).ok());
+            return match stored {
+                Some(s) if s.fingerprint != fingerprint => Err(CustomError::Conflict(
+                    "Idempotency-Key 已用于不同的请求内容".into(),
+                )),
+                Some(StoredResponse { status: Some(code), body: Some(body), .. }) => {
+                    Ok(HttpResponse::build(StatusCode::from_u16(code).unwrap_or(status))
+                        .content_type("application/json")
+                        .header("Idempotent-Replayed", "true")
+                        .body(body))
+                }
+                _ => Err(CustomError::Conflict(
+                    "相同 Idempotency-Key 的请求正在处理，请稍后重试".into(),
+                )),
+            };
+        }
+        Err(e) => {
+            log::warn!("idempotency redis error, fallback to plain request: {}", e);
+            let out = handler().await?;
+            return Ok(HttpResponse::build(status).json(&out));
+        }
+    }
+
+    match handler().await {
+        Ok(out) => {
+            let body = serde_json::to_string(&out)?;
+            let stored = serde_json::to_string(&StoredResponse {
+                fingerprint,
+                status: Some(status.as_u16()),
+                body: Some(body.clone()),
+            })?;
+            if let Err(e) = cache.set_string_ex(&redis_key, &stored, config.ttl_seconds).await {
+                log::warn!("idempotency store response error: {}", e);
+            }
+            Ok(HttpResponse::build(status)
+                .content_type("application/json")
+                .body(body))
+        }
+        Err(e) => {
+            // 失败不缓存，允许客户端用同一 key 重试
+            let _ = cache.delete_key(&redis_key).await;
+            Err(e)
+        }
+    }
+}
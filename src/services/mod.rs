@@ -1 +1,6 @@
 pub mod notifications;
+pub mod idempotency; // Idempotency-Key 重试去重
+pub mod food_stats; // 菜品统计增量维护与重建
+pub mod pantry_stock; // 订单完成扣减库存
+pub mod groups; // 组成员校验与组本地日期
+pub mod units; // 食材用量单位换算
@@ -13,9 +13,28 @@ use crate::wx_official::auth::{fetch_set_access_token, get_access_token};
 // 推送订单状态变更（根据 order_id 查询订单、菜品、用户 push_id 并发送模板消息）
 // 失败时只记录日志，不影响主流程。
 pub async fn push_order_status(order_id: i64, db_pool: PgPool) -> Result<(), CustomError> {
-    // 查询订单 + 相关用户 push_id
+    // 查询订单相关用户（下单人 + 接单人）
+    let order_row = sqlx::query("SELECT user_id, receiver_id FROM orders WHERE order_id=$1")
+        .bind(order_id)
+        .fetch_optional(&db_pool)
+        .await?;
+    let Some(row) = order_row else { return Ok(()); };
+    let user_id: i64 = row.get("user_id");
+    let receiver_id: Option<i64> = row.try_get("receiver_id").ok().flatten();
+    let mut targets = vec![user_id];
+    if let Some(rid) = receiver_id { targets.push(rid); }
+    push_order_notice(order_id, "订单状态更新", &targets, db_pool).await
+}
+
+// 向指定用户推送订单相关模板消息（标题自定义，如到期提醒），内容为订单号、菜品与当前状态。
+pub async fn push_order_notice(
+    order_id: i64,
+    msg_title: &str,
+    target_user_ids: &[i64],
+    db_pool: PgPool,
+) -> Result<(), CustomError> {
     let order_row = sqlx::query(
-        "SELECT order_id, user_id, receiver_id, status FROM orders WHERE order_id=$1"
+        "SELECT order_id, status::text AS status FROM orders WHERE order_id=$1"
     )
         .bind(order_id)
         .fetch_optional(&db_pool)
@@ -30,12 +49,10 @@ pub async fn push_order_status(order_id: i64, db_pool: PgPool) -> Result<(), Cus
         "EXPIRED" => OrderStatusEnum::EXPIRED,
         "REJECTED" => OrderStatusEnum::REJECTED,
         "SYSTEM_CLOSED" => OrderStatusEnum::SYSTEM_CLOSED,
+        "OVERDUE" => OrderStatusEnum::OVERDUE,
         _ => OrderStatusEnum::PENDING,
     };
 
-    let user_id: i64 = row.get("user_id");
-    let receiver_id: Option<i64> = row.try_get("receiver_id").ok();
-
     // 聚合菜品名称（最多取5个）
     let food_rows = sqlx::query(
         "SELECT f.food_name FROM order_items oi JOIN foods f ON oi.food_id=f.food_id WHERE oi.order_id=$1 LIMIT 5"
@@ -47,10 +64,63 @@ pub async fn push_order_status(order_id: i64, db_pool: PgPool) -> Result<(), Cus
     for fr in food_rows { names.push(fr.get::<String, _>("food_name")); }
     let foods_summary = if names.is_empty() { "-".to_string() } else { names.join(" / ") };
 
-    // 获取 push_id（下单人 + 接单人）
+    let status_cn = status_to_cn(status);
+    send_template_messages(
+        target_user_ids,
+        msg_title,
+        &order_id.to_string(),
+        &foods_summary,
+        status_cn,
+        &db_pool,
+    )
+    .await
+}
+
+// 向菜品提交人推送审核结果：复用订单模板，单号位填菜品ID，状态位填审核状态
+pub async fn push_food_notice(
+    food_id: i64,
+    msg_title: &str,
+    target_user_ids: &[i64],
+    db_pool: PgPool,
+) -> Result<(), CustomError> {
+    let row = sqlx::query("SELECT food_name, apply_status::text AS apply_status FROM foods WHERE food_id=$1")
+        .bind(food_id)
+        .fetch_optional(&db_pool)
+        .await?;
+    let Some(row) = row else { return Ok(()); };
+    let food_name: String = row.get("food_name");
+    let apply_status: String = row.get("apply_status");
+    let status_cn = match apply_status.as_str() {
+        "APPROVED" => "审核通过",
+        "REJECTED" => "审核未通过",
+        _ => "待审核",
+    };
+    send_template_messages(
+        target_user_ids,
+        msg_title,
+        &food_id.to_string(),
+        &food_name,
+        status_cn,
+        &db_pool,
+    )
+    .await
+}
+
+async fn send_template_messages(
+    target_user_ids: &[i64],
+    msg_title: &str,
+    order_no: &str,
+    foods_summary: &str,
+    status_cn: &str,
+    db_pool: &PgPool,
+) -> Result<(), CustomError> {
+    // 获取 push_id
     let mut push_ids: Vec<String> = Vec::new();
-    if let Some(pid) = fetch_push_id(user_id, &db_pool).await? { push_ids.push(pid); }
-    if let Some(rid) = receiver_id { if let Some(pid) = fetch_push_id(rid, &db_pool).await? { push_ids.push(pid); } }
+    for uid in target_user_ids {
+        if let Some(pid) = fetch_push_id(*uid, db_pool).await? {
+            if !push_ids.contains(&pid) { push_ids.push(pid); }
+        }
+    }
     if push_ids.is_empty() { return Ok(()); }
 
     // 获取 access_token
@@ -59,17 +129,16 @@ pub async fn push_order_status(order_id: i64, db_pool: PgPool) -> Result<(), Cus
     let Some(access_token) = token_opt else { return Ok(()); };
 
     let client = Client::new();
-    let status_cn = status_to_cn(status);
     let now_str = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
     for pid in push_ids {
         let msg = TemplateMessage {
             template_id: ORDER_TEMPLATE_ID.to_string(),
             push_id: pid.clone(),
-            msg_title: "订单状态更新".to_string(),
-            order_no: order_id.to_string(),
+            msg_title: msg_title.to_string(),
+            order_no: order_no.to_string(),
             date_time: now_str.clone(),
-            foods: foods_summary.clone(),
+            foods: foods_summary.to_string(),
             order_status: status_cn.to_string(),
         };
         // 发送
@@ -88,12 +157,26 @@ pub async fn push_order_status(order_id: i64, db_pool: PgPool) -> Result<(), Cus
         });
         if let Err(e) = client.post(
             format!("https://api.weixin.qq.com/cgi-bin/message/template/send?access_token={}", access_token)
-        ).json(&json_data).send().await { log::warn!("push order status send error: {}", e); }
+        ).json(&json_data).send().await { log::warn!("push order notice send error: {}", e); }
     }
 
     Ok(())
 }
 
+// 订单接单方：优先 orders.receiver_id，否则取所在组的 RECEIVING 成员
+pub async fn order_receiver_ids(order_id: i64, db_pool: &PgPool) -> Result<Vec<i64>, CustomError> {
+    let rows = sqlx::query(
+        "SELECT o.receiver_id AS uid FROM orders o WHERE o.order_id=$1 AND o.receiver_id IS NOT NULL \
+         UNION \
+         SELECT agm.user_id AS uid FROM orders o JOIN association_group_members agm ON agm.group_id=o.group_id \
+         WHERE o.order_id=$1 AND o.receiver_id IS NULL AND agm.role_in_group='RECEIVING' AND agm.user_id<>o.user_id"
+    )
+        .bind(order_id)
+        .fetch_all(db_pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| r.get::<i64, _>("uid")).collect())
+}
+
 async fn fetch_push_id(user_id: i64, db_pool: &PgPool) -> Result<Option<String>, CustomError> {
     let row = sqlx::query("SELECT push_id FROM users WHERE user_id=$1")
         .bind(user_id)
@@ -111,5 +194,6 @@ fn status_to_cn(s: OrderStatusEnum) -> &'static str {
         OrderStatusEnum::EXPIRED => "已过期",
         OrderStatusEnum::REJECTED => "已拒绝",
         OrderStatusEnum::SYSTEM_CLOSED => "系统关闭",
+        OrderStatusEnum::OVERDUE => "已超时",
     }
 }
@@ -0,0 +1,40 @@
+// 订单完成后按菜品声明的用量扣减组内库存（pantry_items）
+use crate::{errors::CustomError, services::units::convert_amount};
+use sqlx::{PgConnection, Row};
+use std::collections::HashMap;
+
+// 订单完成：订单内每个菜品按 份数 × 每份用量 扣减库存，最低扣到 0
+pub async fn consume_order_stock(conn: &mut PgConnection, order_id: i64) -> Result<(), CustomError> {
+    let rows = sqlx::query(
+        "SELECT p.id AS item_id, p.unit AS item_unit, u.amount, u.unit AS usage_unit, oi.quantity \
+         FROM order_items oi JOIN foods f ON f.food_id=oi.food_id \
+         JOIN food_pantry_usages u ON u.food_id=oi.food_id \
+         JOIN pantry_items p ON p.id=u.item_id AND p.group_id=f.group_id \
+         WHERE oi.order_id=$1",
+    )
+    .bind(order_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut deltas: HashMap<i64, f64> = HashMap::new();
+    for r in rows {
+        let item_id: i64 = r.get("item_id");
+        let item_unit: String = r.get("item_unit");
+        let usage_unit: String = r.get("usage_unit");
+        let amount: f64 = r.get("amount");
+        let quantity: i32 = r.get("quantity");
+        // 库存单位被改为不可换算的单位时跳过，不阻断订单完成
+        let Some(per_serving) = convert_amount(amount, &usage_unit, &item_unit) else {
+            log::warn!("pantry item {} unit {} incompatible with usage unit {}", item_id, item_unit, usage_unit);
+            continue;
+        };
+        *deltas.entry(item_id).or_default() += per_serving * quantity as f64;
+    }
+    for (item_id, delta) in deltas {
+        sqlx::query("UPDATE pantry_items SET quantity=GREATEST(quantity - $2, 0), updated_at=NOW() WHERE id=$1")
+            .bind(item_id)
+            .bind(delta)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,39 @@
+// 食材用量单位换算：质量/体积单位统一换算到基准单位 g / ml
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitKind {
+    Mass,
+    Volume,
+}
+
+// 可换算单位 -> (类别, 换算到基准单位 g / ml 的系数)
+pub fn unit_base(unit: &str) -> Option<(UnitKind, f64)> {
+    let u = unit.trim().to_lowercase();
+    let v = match u.as_str() {
+        "g" | "克" => (UnitKind::Mass, 1.0),
+        "kg" | "千克" | "公斤" => (UnitKind::Mass, 1000.0),
+        "mg" | "毫克" => (UnitKind::Mass, 0.001),
+        "斤" => (UnitKind::Mass, 500.0),
+        "两" => (UnitKind::Mass, 50.0),
+        "lb" | "磅" => (UnitKind::Mass, 453.592),
+        "oz" | "盎司" => (UnitKind::Mass, 28.3495),
+        "ml" | "毫升" => (UnitKind::Volume, 1.0),
+        "l" | "升" => (UnitKind::Volume, 1000.0),
+        "tbsp" | "汤匙" | "大勺" => (UnitKind::Volume, 15.0),
+        "tsp" | "茶匙" | "小勺" => (UnitKind::Volume, 5.0),
+        "cup" | "杯" => (UnitKind::Volume, 240.0),
+        _ => return None,
+    };
+    Some(v)
+}
+
+// 用量从 from 单位换算到 to 单位；单位相同直接返回，不同类别或不可换算时为 None
+pub fn convert_amount(amount: f64, from: &str, to: &str) -> Option<f64> {
+    if from.trim().eq_ignore_ascii_case(to.trim()) {
+        return Some(amount);
+    }
+    match (unit_base(from), unit_base(to)) {
+        (Some((k1, f1)), Some((k2, f2))) if k1 == k2 => Some(amount * f1 / f2),
+        _ => None,
+    }
+}
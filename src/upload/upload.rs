@@ -1,5 +1,6 @@
 use qiniu_upload_manager::apis::credential::Credential;
 use qiniu_upload_token::{FileType, UploadPolicy, UploadTokenProvider};
+use std::sync::OnceLock;
 use std::time::Duration;
 
 use crate::{
@@ -20,3 +21,30 @@ pub async fn get_qiniu_token(_: UserToken) -> Result<String, CustomError> {
 
     Ok(token_string.to_string())
 }
+
+// 图片地址允许的上传域名，取自 UPLOAD_DOMAIN（逗号分隔，可带协议前缀）；启动时校验非空
+pub fn upload_domains() -> &'static [String] {
+    static DOMAINS: OnceLock<Vec<String>> = OnceLock::new();
+    DOMAINS.get_or_init(|| {
+        std::env::var("UPLOAD_DOMAIN")
+            .unwrap_or_default()
+            .split(',')
+            .map(|d| d.trim().trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/'))
+            .filter(|d| !d.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+// 校验前端回传的图片地址：必须为 http(s) 且属于 UPLOAD_DOMAIN
+pub fn validate_upload_url(url: &str) -> Result<(), CustomError> {
+    let url = url.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://")) || url.len() > 512 {
+        return Err(CustomError::BadRequest("图片地址无效".into()));
+    }
+    let host = url.split("://").nth(1).unwrap_or("").split(['/', '?', '#']).next().unwrap_or("");
+    if !upload_domains().iter().any(|d| host.eq_ignore_ascii_case(d)) {
+        return Err(CustomError::BadRequest("图片地址不属于上传域名".into()));
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+// 用户过敏原/忌口偏好：命中 foods.dietary_labels 时下单提醒（WARN）或拦截（BLOCK）
+use crate::{
+    errors::CustomError,
+    models::foods::DietaryLabelEnum,
+    models::users::{
+        AllergenActionEnum, AllergenCheckInput, AllergenPrefOut, AllergenPrefsInput,
+        AllergenWarningOut, UserToken,
+    },
+    AppState,
+};
+use ntex::web::{
+    types::{Json, State},
+    HttpResponse, Responder,
+};
+use sqlx::{PgConnection, Row};
+use std::sync::Arc;
+
+async fn load_prefs(conn: &mut PgConnection, user_id: i64) -> Result<Vec<AllergenPrefOut>, CustomError> {
+    let rows = sqlx::query("SELECT label, action FROM user_allergen_prefs WHERE user_id=$1 ORDER BY created_at, label")
+        .bind(user_id)
+        .fetch_all(&mut *conn)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            let label = DietaryLabelEnum::parse(r.get::<String, _>("label").as_str())?;
+            Some(AllergenPrefOut {
+                label,
+                label_name: label.zh_label().to_string(),
+                action: AllergenActionEnum::parse(r.get::<String, _>("action").as_str()),
+            })
+        })
+        .collect())
+}
+
+/// 检查菜品是否命中用户的过敏原偏好，按菜品、标签顺序返回全部命中项
+pub async fn check_allergens(
+    conn: &mut PgConnection,
+    user_id: i64,
+    food_ids: &[i64],
+) -> Result<Vec<AllergenWarningOut>, CustomError> {
+    if food_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = sqlx::query(
+        "SELECT f.food_id, f.food_name, p.label, p.action FROM foods f \
+         JOIN user_allergen_prefs p ON p.user_id=$1 AND p.label = ANY(f.dietary_labels) \
+         WHERE f.food_id = ANY($2) ORDER BY f.food_id, p.label",
+    )
+    .bind(user_id)
+    .bind(food_ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| {
+            let label = DietaryLabelEnum::parse(r.get::<String, _>("label").as_str())?;
+            Some(AllergenWarningOut {
+                food_id: r.get("food_id"),
+                food_name: r.get("food_name"),
+                label,
+                label_name: label.zh_label().to_string(),
+                action: AllergenActionEnum::parse(r.get::<String, _>("action").as_str()),
+            })
+        })
+        .collect())
+}
+
+/// 命中 BLOCK 级过敏原时拒绝下单/改菜
+pub fn ensure_not_blocked(hits: &[AllergenWarningOut]) -> Result<(), CustomError> {
+    match hits.iter().find(|h| h.action == AllergenActionEnum::BLOCK) {
+        Some(hit) => Err(CustomError::BadRequest(format!(
+            "「{}」含有你设置为禁止的过敏原：{}",
+            hit.food_name, hit.label_name
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/allergens",
+    tag = "用户",
+    summary = "获取我的过敏原偏好",
+    responses((status = 200, body = Vec<AllergenPrefOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn get_allergen_prefs(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
+    let prefs = load_prefs(&mut conn, token.user_id).await?;
+    Ok(HttpResponse::Ok().json(&prefs))
+}
+
+#[utoipa::path(
+    put,
+    path = "/users/allergens",
+    tag = "用户",
+    summary = "设置我的过敏原偏好（整体替换）",
+    request_body = AllergenPrefsInput,
+    responses((status = 200, body = Vec<AllergenPrefOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn set_allergen_prefs(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<AllergenPrefsInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.items.len() > DietaryLabelEnum::ALL.len() {
+        return Err(CustomError::BadRequest("过敏原偏好数量超出上限".into()));
+    }
+    let mut tx = state.db_pool.begin().await?;
+    sqlx::query("DELETE FROM user_allergen_prefs WHERE user_id=$1")
+        .bind(token.user_id)
+        .execute(&mut *tx)
+        .await?;
+    // 同一标签重复提交时以最后一次为准
+    for item in &data.items {
+        sqlx::query(
+            "INSERT INTO user_allergen_prefs (user_id, label, action) VALUES ($1,$2,$3) \
+             ON CONFLICT (user_id, label) DO UPDATE SET action=EXCLUDED.action",
+        )
+        .bind(token.user_id)
+        .bind(item.label.as_str())
+        .bind(item.action.unwrap_or(AllergenActionEnum::WARN).as_str())
+        .execute(&mut *tx)
+        .await?;
+    }
+    let prefs = load_prefs(&mut tx, token.user_id).await?;
+    tx.commit().await?;
+    Ok(HttpResponse::Ok().json(&prefs))
+}
+
+#[utoipa::path(
+    post,
+    path = "/users/allergens/check",
+    tag = "用户",
+    summary = "下单前检查菜品是否命中我的过敏原偏好",
+    request_body = AllergenCheckInput,
+    responses((status = 200, body = Vec<AllergenWarningOut>)),
+    security(("cookie_auth" = []))
+)]
+pub async fn check_allergen_prefs(
+    token: UserToken,
+    state: State<Arc<AppState>>,
+    data: Json<AllergenCheckInput>,
+) -> Result<impl Responder, CustomError> {
+    if data.food_ids.len() > 100 {
+        return Err(CustomError::BadRequest("一次最多检查100个菜品".into()));
+    }
+    let mut conn = state.db_pool.acquire().await?;
+    let hits = check_allergens(&mut conn, token.user_id, &data.food_ids).await?;
+    Ok(HttpResponse::Ok().json(&hits))
+}
@@ -88,6 +88,7 @@ pub async fn login(
     let claims = UserTokenClaims {
         user_id: record.user_id,
         exp,
+        scope: None,
     };
     let token = encode(
         &Header::default(),
@@ -2,6 +2,7 @@ use crate::{
     errors::CustomError,
     models::{
         users::UserToken,
+        photos::PhotoOwnerEnum,
         wishes::{
             WishClaimCheckinCreateInput,
             WishClaimCheckinOut,
@@ -9,6 +10,8 @@ use crate::{
             WishClaimStatusEnum,
         },
     },
+    photos::{ add_photos, adopt_legacy_cover, load_photos, load_photos_batch, validate_photos },
+    upload::upload::validate_upload_url,
     AppState,
 };
 use chrono::Utc;
@@ -30,6 +33,12 @@ pub async fn create_wish_claim_checkin(
     claim_id: Path<i64>,
     body: Json<WishClaimCheckinCreateInput>
 ) -> Result<impl Responder, CustomError> {
+    // 图片地址须属于上传域名
+    let photo_url = body.photo_url.as_deref().map(str::trim).filter(|u| !u.is_empty());
+    if let Some(url) = photo_url {
+        validate_upload_url(url)?;
+    }
+    let photos = validate_photos(body.photos.as_deref().unwrap_or_default())?;
     let db = &state.db_pool;
     let mut tx = db.begin().await?;
     // 校验兑换记录存在且属于当前用户
@@ -60,13 +69,19 @@ pub async fn create_wish_claim_checkin(
         )
         .bind(*claim_id)
         .bind(user_token.user_id)
-        .bind(&body.photo_url)
+        .bind(photo_url)
         .bind(&body.location_text)
         .bind(&body.mood_text)
         .bind(&body.feeling_text)
         .bind(checkin_time)
         .fetch_one(&mut *tx).await?;
 
+    // 图集：photo_url 作为封面，其余追加在后
+    let checkin_id: i64 = row.get("id");
+    adopt_legacy_cover(&mut tx, PhotoOwnerEnum::CHECKIN, checkin_id, photo_url, Some(user_token.user_id)).await?;
+    add_photos(&mut tx, PhotoOwnerEnum::CHECKIN, checkin_id, &photos, false, user_token.user_id).await?;
+    let gallery = load_photos(&mut tx, PhotoOwnerEnum::CHECKIN, checkin_id).await?;
+
     // 自动更新状态为 DONE
     if status != WishClaimStatusEnum::DONE {
         sqlx::query("UPDATE wish_claims SET status=$1, fulfill_at=NOW(), updated_at=NOW() WHERE id=$2")
@@ -81,14 +96,16 @@ pub async fn create_wish_claim_checkin(
         id: row.get("id"),
         claim_id: row.get("claim_id"),
         user_id: row.get("user_id"),
-        photo_url: row.try_get("photo_url").ok(),
+        photo_url: gallery.iter().find(|p| p.is_cover).map(|p| p.url.clone()),
         location_text: row.try_get("location_text").ok(),
         mood_text: row.try_get("mood_text").ok(),
         feeling_text: row.try_get("feeling_text").ok(),
         checkin_time: row.get("checkin_time"),
         created_at: row.get("created_at"),
     };
-    Ok(HttpResponse::Created().json(&WishClaimCheckinOut::from(rec)))
+    let mut out = WishClaimCheckinOut::from(rec);
+    out.photos = gallery;
+    Ok(HttpResponse::Created().json(&out))
 }
 
 #[utoipa::path(
@@ -118,6 +135,9 @@ pub async fn list_wish_claim_checkins(
         )
         .bind(*claim_id)
         .fetch_all(db).await?;
+    let ids: Vec<i64> = rows.iter().map(|r| r.get("id")).collect();
+    let mut conn = db.acquire().await?;
+    let mut galleries = load_photos_batch(&mut conn, PhotoOwnerEnum::CHECKIN, &ids).await?;
     let list: Vec<WishClaimCheckinOut> = rows
         .into_iter()
         .map(|row| WishClaimCheckinOut {
@@ -130,6 +150,7 @@ pub async fn list_wish_claim_checkins(
             feeling_text: row.try_get("feeling_text").ok(),
             checkin_time: row.get("checkin_time"),
             created_at: row.get("created_at"),
+            photos: galleries.remove(&row.get::<i64, _>("id")).unwrap_or_default(),
         })
         .collect();
     Ok(HttpResponse::Ok().json(&list))
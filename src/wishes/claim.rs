@@ -7,13 +7,16 @@ use crate::{
             WishClaimOut,
             WishClaimStatusEnum,
             WishClaimUpdateInput,
-            WishStatusEnum,
+            WishOut,
         },
     },
+    wishes::limits::{ apply_availability, load_usage, row_to_record, WISH_COLUMNS },
     AppState,
 };
 use chrono::Utc;
-use ntex::web::{ types::{ Json, State, Query }, HttpResponse, Responder };
+use crate::services::idempotency;
+use ntex::http::StatusCode;
+use ntex::web::{ types::{ Json, State, Query }, HttpRequest, HttpResponse, Responder };
 use sqlx::Row;
 use std::sync::Arc;
 
@@ -71,32 +74,52 @@ WHERE
     post,
     path = "/wish_claims",
     tag = "心愿",
+    params(("Idempotency-Key" = Option<String>, Header, description = "幂等键，重试时复用")),
     request_body = WishClaimCreateInput,
     responses((status = 201, body = WishClaimOut))
 )]
 pub async fn claim_wish(
     user_token: UserToken,
     state: State<Arc<AppState>>,
+    req: HttpRequest,
     data: Json<WishClaimCreateInput>
 ) -> Result<impl Responder, CustomError> {
+    idempotency::run(&state.redis_cache, &req, user_token.user_id, &*data, StatusCode::CREATED, || {
+        do_claim_wish(&user_token, &state, &data)
+    }).await
+}
+
+async fn do_claim_wish(
+    user_token: &UserToken,
+    state: &AppState,
+    data: &WishClaimCreateInput
+) -> Result<WishClaimOut, CustomError> {
     let db = &state.db_pool;
     let mut tx = db.begin().await?;
-    // 获取心愿
+    // 获取心愿并加排他锁，使库存与限额的计数在并发兑换下串行
     let wish_row = sqlx
-        ::query(
-            "SELECT wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at FROM wishes WHERE wish_id=$1 FOR SHARE"
-        )
+        ::query(&format!("SELECT {} FROM wishes WHERE wish_id=$1 FOR UPDATE", WISH_COLUMNS))
         .bind(data.wish_id)
         .fetch_optional(&mut *tx).await?;
     let Some(wr) = wish_row else {
         tx.rollback().await.ok();
         return Err(CustomError::BadRequest("心愿不存在".into()));
     };
-    let wish_cost: i32 = wr.get("wish_cost");
-    let status: WishStatusEnum = wr.get("status");
-    if status != WishStatusEnum::ON {
+    let mut wish = WishOut::from(row_to_record(&wr));
+    let wish_cost = wish.wish_cost;
+    // 校验开关、时间窗、库存、每人限额与冷却
+    let now = Utc::now();
+    let usage = match load_usage(&mut tx, &[data.wish_id], user_token.user_id, now).await {
+        Ok(u) => u,
+        Err(e) => {
+            tx.rollback().await.ok();
+            return Err(e);
+        }
+    };
+    apply_availability(&mut wish, &usage.get(&data.wish_id).cloned().unwrap_or_default(), now);
+    if !wish.available_now {
         tx.rollback().await.ok();
-        return Err(CustomError::BadRequest("心愿已关闭".into()));
+        return Err(CustomError::BadRequest(wish.unavailable_reason.unwrap_or_else(|| "心愿暂不可兑换".into())));
     }
     // 检查是否已有进行中的兑换
     let existing = sqlx
@@ -158,7 +181,7 @@ pub async fn claim_wish(
         created_at: claim_row.get("created_at"),
         updated_at: claim_row.get("updated_at"),
     };
-    Ok(HttpResponse::Created().json(&out))
+    Ok(out)
 }
 
 #[utoipa::path(
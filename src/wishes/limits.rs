@@ -0,0 +1,205 @@
+// 心愿兑换限制：总库存、每人周期限额、冷却时间与开放时间窗。
+// 计数只统计未取消的兑换（PROCESSING / DONE）；兑换时需先锁定心愿行再计算，保证并发下不超卖。
+use crate::{
+    errors::CustomError,
+    models::wishes::{WishLimitPeriodEnum, WishOut, WishRecord, WishStatusEnum},
+};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Utc};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use std::collections::HashMap;
+
+pub const WISH_COLUMNS: &str = "wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at, \
+     stock_total, per_user_limit, limit_period, cooldown_minutes, available_from, available_until";
+
+// 周期按东八区自然日/周（周一起）/月计算
+const PERIOD_TZ_OFFSET_MINUTES: i32 = 480;
+
+pub fn row_to_record(r: &PgRow) -> WishRecord {
+    WishRecord {
+        wish_id: r.get("wish_id"),
+        wish_name: r.get("wish_name"),
+        wish_cost: r.get("wish_cost"),
+        status: r.get("status"),
+        created_by: r.get("created_by"),
+        created_at: r.get("created_at"),
+        updated_at: r.get("updated_at"),
+        stock_total: r.get("stock_total"),
+        per_user_limit: r.get("per_user_limit"),
+        limit_period: r.get("limit_period"),
+        cooldown_minutes: r.get("cooldown_minutes"),
+        available_from: r.get("available_from"),
+        available_until: r.get("available_until"),
+    }
+}
+
+pub fn validate_limits(
+    stock_total: Option<i32>,
+    per_user_limit: Option<i32>,
+    cooldown_minutes: Option<i32>,
+    available_from: Option<DateTime<Utc>>,
+    available_until: Option<DateTime<Utc>>,
+) -> Result<(), CustomError> {
+    if stock_total.is_some_and(|s| s < 0) {
+        return Err(CustomError::BadRequest("库存不能为负数".into()));
+    }
+    if per_user_limit.is_some_and(|n| n < 0) {
+        return Err(CustomError::BadRequest("每人限额不能为负数".into()));
+    }
+    if cooldown_minutes.is_some_and(|m| m < 0) {
+        return Err(CustomError::BadRequest("冷却时间不能为负数".into()));
+    }
+    if let (Some(from), Some(until)) = (available_from, available_until) {
+        if from >= until {
+            return Err(CustomError::BadRequest("开放开始时间需早于结束时间".into()));
+        }
+    }
+    Ok(())
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Utc>> {
+    let offset = FixedOffset::east_opt(PERIOD_TZ_OFFSET_MINUTES * 60)?;
+    offset
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .single()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+// 当前周期的 [开始, 下一周期开始)；TOTAL 无周期
+fn period_bounds(period: WishLimitPeriodEnum, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let offset = FixedOffset::east_opt(PERIOD_TZ_OFFSET_MINUTES * 60)?;
+    let today = now.with_timezone(&offset).date_naive();
+    let (start, next) = match period {
+        WishLimitPeriodEnum::DAY => (today, today + Duration::days(1)),
+        WishLimitPeriodEnum::WEEK => {
+            let start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+            (start, start + Duration::days(7))
+        }
+        WishLimitPeriodEnum::MONTH => {
+            let start = today.with_day(1)?;
+            (start, (start + Duration::days(32)).with_day(1)?)
+        }
+        WishLimitPeriodEnum::TOTAL => return None,
+    };
+    Some((local_midnight(start)?, local_midnight(next)?))
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClaimUsage {
+    /// 全部用户未取消的兑换数
+    pub claimed: i64,
+    /// 当前用户本周期内的兑换数
+    pub user_period_claims: i64,
+    pub user_last_claim_at: Option<DateTime<Utc>>,
+}
+
+/// 批量统计心愿的兑换占用，周期按各心愿的 limit_period 计算
+pub async fn load_usage(
+    conn: &mut PgConnection,
+    wish_ids: &[i64],
+    user_id: i64,
+    now: DateTime<Utc>,
+) -> Result<HashMap<i64, ClaimUsage>, CustomError> {
+    let mut out = HashMap::new();
+    if wish_ids.is_empty() {
+        return Ok(out);
+    }
+    let start_of = |p| period_bounds(p, now).map(|(s, _)| s);
+    let rows = sqlx::query(
+        "SELECT c.wish_id, COUNT(*) AS claimed, \
+         COUNT(*) FILTER (WHERE c.user_id=$2 AND (COALESCE(w.limit_period, 'TOTAL')='TOTAL' \
+           OR c.created_at >= CASE w.limit_period WHEN 'DAY' THEN $3 WHEN 'WEEK' THEN $4 ELSE $5 END)) AS user_period_claims, \
+         MAX(c.created_at) FILTER (WHERE c.user_id=$2) AS user_last_claim_at \
+         FROM wish_claims c JOIN wishes w ON w.wish_id=c.wish_id \
+         WHERE c.wish_id = ANY($1) AND c.status<>'CANCELLED' GROUP BY c.wish_id",
+    )
+    .bind(wish_ids)
+    .bind(user_id)
+    .bind(start_of(WishLimitPeriodEnum::DAY))
+    .bind(start_of(WishLimitPeriodEnum::WEEK))
+    .bind(start_of(WishLimitPeriodEnum::MONTH))
+    .fetch_all(&mut *conn)
+    .await?;
+    for r in rows {
+        out.insert(
+            r.get("wish_id"),
+            ClaimUsage {
+                claimed: r.get("claimed"),
+                user_period_claims: r.get("user_period_claims"),
+                user_last_claim_at: r.get("user_last_claim_at"),
+            },
+        );
+    }
+    Ok(out)
+}
+
+fn set_unavailable(wish: &mut WishOut, next: Option<DateTime<Utc>>, reason: String) {
+    wish.available_now = false;
+    wish.next_available_at = next;
+    wish.unavailable_reason = Some(reason);
+}
+
+/// 计算剩余库存与当前用户的可兑换状态（不含积分校验）
+pub fn apply_availability(wish: &mut WishOut, usage: &ClaimUsage, now: DateTime<Utc>) {
+    wish.remaining_stock = wish.stock_total.map(|s| (s as i64 - usage.claimed).max(0) as i32);
+    wish.available_now = true;
+    wish.next_available_at = None;
+    wish.unavailable_reason = None;
+    if wish.status != WishStatusEnum::ON {
+        return set_unavailable(wish, None, "心愿已关闭".into());
+    }
+    if wish.available_until.is_some_and(|t| t <= now) {
+        return set_unavailable(wish, None, "心愿已过兑换期".into());
+    }
+    if wish.remaining_stock == Some(0) {
+        return set_unavailable(wish, None, "心愿已兑完".into());
+    }
+    // 需等待的限制取最晚的一个
+    let mut wait: Option<(DateTime<Utc>, String)> = None;
+    let mut push_wait = |at: DateTime<Utc>, reason: String| {
+        let later = match &wait {
+            Some((t, _)) => at > *t,
+            None => true,
+        };
+        if later {
+            wait = Some((at, reason));
+        }
+    };
+    if let Some(from) = wish.available_from.filter(|t| *t > now) {
+        push_wait(from, "心愿尚未开放兑换".into());
+    }
+    if let Some(limit) = wish.per_user_limit.filter(|n| *n > 0) {
+        if usage.user_period_claims >= limit as i64 {
+            let period = wish.limit_period.unwrap_or(WishLimitPeriodEnum::TOTAL);
+            match period_bounds(period, now) {
+                Some((_, next)) => push_wait(next, format!("{}兑换次数已达上限（{}次）", period.zh_label(), limit)),
+                None => return set_unavailable(wish, None, format!("兑换次数已达上限（{}次）", limit)),
+            }
+        }
+    }
+    if let (Some(minutes), Some(last)) = (wish.cooldown_minutes.filter(|m| *m > 0), usage.user_last_claim_at) {
+        let ready = last + Duration::minutes(minutes as i64);
+        if ready > now {
+            push_wait(ready, "兑换冷却中".into());
+        }
+    }
+    if let Some((at, reason)) = wait {
+        // 等到可兑换时已过兑换期，则视为无法再兑换
+        let next = match wish.available_until {
+            Some(until) if until <= at => None,
+            _ => Some(at),
+        };
+        set_unavailable(wish, next, reason);
+    }
+}
+
+/// 批量填充列表/详情的库存与可兑换状态
+pub async fn fill_availability(conn: &mut PgConnection, wishes: &mut [WishOut], user_id: i64) -> Result<(), CustomError> {
+    let now = Utc::now();
+    let ids: Vec<i64> = wishes.iter().map(|w| w.wish_id).collect();
+    let usage = load_usage(conn, &ids, user_id, now).await?;
+    for w in wishes.iter_mut() {
+        let u = usage.get(&w.wish_id).cloned().unwrap_or_default();
+        apply_availability(w, &u, now);
+    }
+    Ok(())
+}
@@ -4,3 +4,4 @@ pub mod view;
 pub mod delete; // reserved for future claim deletion or archival
 pub mod claim; // 新增心愿兑换相关
 pub mod checkin; // 心愿兑换打卡反馈
+pub mod limits; // 库存、限额、冷却与开放时间窗
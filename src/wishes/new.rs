@@ -1,10 +1,10 @@
 use crate::{
     errors::CustomError,
-    models::{ users::UserToken, wishes::{ WishCreateInput, WishOut, WishRecord, WishStatusEnum } },
+    models::{ users::UserToken, wishes::{ WishCreateInput, WishLimitPeriodEnum, WishOut } },
+    wishes::limits::{ fill_availability, row_to_record, validate_limits, WISH_COLUMNS },
     AppState,
 };
 use ntex::web::{ types::{ Json, State }, HttpResponse, Responder };
-use sqlx::Row;
 use std::sync::Arc;
 
 #[utoipa::path(
@@ -15,7 +15,7 @@ use std::sync::Arc;
     responses((status = 201, body = WishOut))
 )]
 pub async fn create_wish(
-    _: UserToken,
+    user_token: UserToken,
     state: State<Arc<AppState>>,
     data: Json<WishCreateInput>
 ) -> Result<impl Responder, CustomError> {
@@ -25,23 +25,36 @@ pub async fn create_wish(
     if data.wish_cost <= 0 {
         return Err(CustomError::BadRequest("心愿积分必须大于0".into()));
     }
-    let db = &state.db_pool;
+    validate_limits(
+        data.stock_total,
+        data.per_user_limit,
+        data.cooldown_minutes,
+        data.available_from,
+        data.available_until
+    )?;
+    // 0 表示不限，按空值存储
+    let per_user_limit = data.per_user_limit.filter(|n| *n > 0);
+    let limit_period = per_user_limit.map(|_| data.limit_period.unwrap_or(WishLimitPeriodEnum::TOTAL).as_str());
+    let mut conn = state.db_pool.acquire().await?;
     let row = sqlx
         ::query(
-            "INSERT INTO wishes (wish_name, wish_cost, created_by) VALUES ($1,$2,$3) RETURNING wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at"
+            &format!(
+                "INSERT INTO wishes (wish_name, wish_cost, created_by, stock_total, per_user_limit, limit_period, cooldown_minutes, available_from, available_until) \
+                 VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9) RETURNING {}",
+                WISH_COLUMNS
+            )
         )
         .bind(&data.wish_name)
         .bind(data.wish_cost)
         .bind(data.group_id)
-        .fetch_one(db).await?;
-    let rec = WishRecord {
-        wish_id: row.get("wish_id"),
-        wish_name: row.get("wish_name"),
-        wish_cost: row.get("wish_cost"),
-        status: WishStatusEnum::ON,
-        created_by: row.get("created_by"),
-        created_at: row.get("created_at"),
-        updated_at: row.get("updated_at"),
-    };
-    Ok(HttpResponse::Created().json(&WishOut::from(rec)))
+        .bind(data.stock_total)
+        .bind(per_user_limit)
+        .bind(limit_period)
+        .bind(data.cooldown_minutes.filter(|m| *m > 0))
+        .bind(data.available_from)
+        .bind(data.available_until)
+        .fetch_one(&mut *conn).await?;
+    let mut out = WishOut::from(row_to_record(&row));
+    fill_availability(&mut conn, std::slice::from_mut(&mut out), user_token.user_id).await?;
+    Ok(HttpResponse::Created().json(&out))
 }
@@ -1,11 +1,11 @@
 use crate::{
     errors::CustomError,
-    models::{ users::UserToken, wishes::{ WishOut, WishRecord, WishStatusEnum, WishUpdateInput } },
+    models::{ users::UserToken, wishes::{ WishLimitPeriodEnum, WishOut, WishStatusEnum, WishUpdateInput } },
+    wishes::limits::{ fill_availability, row_to_record, validate_limits, WISH_COLUMNS },
     AppState,
 };
 use ntex::web::{ types::{ Json, State }, HttpResponse, Responder };
 use sqlx::QueryBuilder;
-use sqlx::Row;
 use std::sync::Arc;
 
 #[utoipa::path(
@@ -20,23 +20,53 @@ pub async fn update_wish(
     state: State<Arc<AppState>>,
     data: Json<WishUpdateInput>
 ) -> Result<impl Responder, CustomError> {
-    if data.wish_name.is_none() && data.wish_cost.is_none() && data.status.is_none() {
+    let clear_stock = data.clear_stock.unwrap_or(false);
+    let clear_window = data.clear_window.unwrap_or(false);
+    if
+        data.wish_name.is_none() &&
+        data.wish_cost.is_none() &&
+        data.status.is_none() &&
+        data.stock_total.is_none() &&
+        !clear_stock &&
+        data.per_user_limit.is_none() &&
+        data.limit_period.is_none() &&
+        data.cooldown_minutes.is_none() &&
+        data.available_from.is_none() &&
+        data.available_until.is_none() &&
+        !clear_window
+    {
         return Err(CustomError::BadRequest("无修改内容".into()));
     }
-    let db = &state.db_pool;
+    if clear_stock && data.stock_total.is_some() {
+        return Err(CustomError::BadRequest("stock_total 与 clear_stock 不能同时设置".into()));
+    }
+    if clear_window && (data.available_from.is_some() || data.available_until.is_some()) {
+        return Err(CustomError::BadRequest("开放时间与 clear_window 不能同时设置".into()));
+    }
+    validate_limits(data.stock_total, data.per_user_limit, data.cooldown_minutes, None, None)?;
+    let mut conn = state.db_pool.acquire().await?;
     let row = sqlx
-        ::query(
-            "SELECT wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at FROM wishes WHERE wish_id=$1"
-        )
+        ::query(&format!("SELECT {} FROM wishes WHERE wish_id=$1", WISH_COLUMNS))
         .bind(data.wish_id)
-        .fetch_optional(db).await?;
+        .fetch_optional(&mut *conn).await?;
     let Some(r) = row else {
         return Err(CustomError::BadRequest("心愿不存在".into()));
     };
-    let created_by: i64 = r.get("created_by");
+    let current = row_to_record(&r);
+    let created_by = current.created_by;
     if created_by != user_token.user_id {
         return Err(CustomError::BadRequest("只能修改自己创建的心愿".into()));
     }
+    // 与未修改的一端组合后校验时间窗
+    if !clear_window {
+        validate_limits(
+            None,
+            None,
+            None,
+            data.available_from.or(current.available_from),
+            data.available_until.or(current.available_until)
+        )?;
+    }
     // Build dynamic update
     let mut qb: QueryBuilder<sqlx::Postgres> = QueryBuilder::new("UPDATE wishes SET ");
     let mut first = true;
@@ -61,25 +91,82 @@ pub async fn update_wish(
         first = false;
         qb.push(" status = ").push_bind(st);
     }
+    if clear_stock || data.stock_total.is_some() {
+        if !first {
+            qb.push(", ");
+        }
+        first = false;
+        qb.push(" stock_total = ").push_bind(data.stock_total);
+    }
+    // 每人限额：0 表示取消，周期随之清空；仅改周期时沿用原限额
+    match data.per_user_limit {
+        Some(0) => {
+            if !first {
+                qb.push(", ");
+            }
+            first = false;
+            qb.push(" per_user_limit = NULL, limit_period = NULL");
+        }
+        Some(n) => {
+            if !first {
+                qb.push(", ");
+            }
+            first = false;
+            let period = data.limit_period
+                .or(current.limit_period.as_deref().and_then(WishLimitPeriodEnum::from_db))
+                .unwrap_or(WishLimitPeriodEnum::TOTAL);
+            qb.push(" per_user_limit = ").push_bind(n);
+            qb.push(", limit_period = ").push_bind(period.as_str());
+        }
+        None => {
+            if let Some(period) = data.limit_period.filter(|_| current.per_user_limit.is_some()) {
+                if !first {
+                    qb.push(", ");
+                }
+                first = false;
+                qb.push(" limit_period = ").push_bind(period.as_str());
+            }
+        }
+    }
+    if let Some(minutes) = data.cooldown_minutes {
+        if !first {
+            qb.push(", ");
+        }
+        first = false;
+        qb.push(" cooldown_minutes = ").push_bind(Some(minutes).filter(|m| *m > 0));
+    }
+    if clear_window {
+        if !first {
+            qb.push(", ");
+        }
+        first = false;
+        qb.push(" available_from = NULL, available_until = NULL");
+    }
+    if let Some(from) = data.available_from {
+        if !first {
+            qb.push(", ");
+        }
+        first = false;
+        qb.push(" available_from = ").push_bind(from);
+    }
+    if let Some(until) = data.available_until {
+        if !first {
+            qb.push(", ");
+        }
+        first = false;
+        qb.push(" available_until = ").push_bind(until);
+    }
     if first {
         return Err(CustomError::BadRequest("无修改内容".into()));
     }
     qb.push(", updated_at = NOW() WHERE wish_id = ")
         .push_bind(data.wish_id)
-        .push(
-            " RETURNING wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at"
-        );
-    let updated = qb.build().fetch_one(db).await?;
-    let rec = WishRecord {
-        wish_id: updated.get("wish_id"),
-        wish_name: updated.get("wish_name"),
-        wish_cost: updated.get("wish_cost"),
-        status: updated.get("status"),
-        created_by,
-        created_at: updated.get("created_at"),
-        updated_at: updated.get("updated_at"),
-    };
-    Ok(HttpResponse::Ok().json(&WishOut::from(rec)))
+        .push(" RETURNING ")
+        .push(WISH_COLUMNS);
+    let updated = qb.build().fetch_one(&mut *conn).await?;
+    let mut out = WishOut::from(row_to_record(&updated));
+    fill_availability(&mut conn, std::slice::from_mut(&mut out), user_token.user_id).await?;
+    Ok(HttpResponse::Ok().json(&out))
 }
 
 #[utoipa::path(
@@ -94,32 +181,25 @@ pub async fn disable_wish(
     state: State<Arc<AppState>>,
     id: ntex::web::types::Path<i64>
 ) -> Result<impl Responder, CustomError> {
-    let db = &state.db_pool;
+    let mut conn = state.db_pool.acquire().await?;
     let row = sqlx
-        ::query(
-            "SELECT wish_id, wish_name, wish_cost, status, created_by, created_at, updated_at FROM wishes WHERE wish_id=$1"
-        )
+        ::query(&format!("SELECT {} FROM wishes WHERE wish_id=$1", WISH_COLUMNS))
         .bind(*id)
-        .fetch_optional(db).await?;
+        .fetch_optional(&mut *conn).await?;
     let Some(r) = row else {
         return Err(CustomError::BadRequest("心愿不存在".into()));
     };
-    let created_by: i64 = r.get("created_by");
-    if created_by != user_token.user_id {
+    let mut rec = row_to_record(&r);
+    if rec.created_by != user_token.user_id {
         return Err(CustomError::BadRequest("只能关闭自己创建的心愿".into()));
     }
     sqlx
         ::query("UPDATE wishes SET status='OFF', updated_at=NOW() WHERE wish_id=$1")
         .bind(*id)
-        .execute(db).await?;
-    let rec = WishRecord {
-        wish_id: r.get("wish_id"),
-        wish_name: r.get("wish_name"),
-        wish_cost: r.get("wish_cost"),
-        status: WishStatusEnum::OFF,
-        created_by,
-        created_at: r.get("created_at"),
-        updated_at: chrono::Utc::now(),
-    };
-    Ok(HttpResponse::Ok().json(&WishOut::from(rec)))
+        .execute(&mut *conn).await?;
+    rec.status = WishStatusEnum::OFF;
+    rec.updated_at = chrono::Utc::now();
+    let mut out = WishOut::from(rec);
+    fill_availability(&mut conn, std::slice::from_mut(&mut out), user_token.user_id).await?;
+    Ok(HttpResponse::Ok().json(&out))
 }
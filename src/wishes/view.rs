@@ -1,6 +1,7 @@
 use crate::{
     errors::CustomError,
     models::{ users::UserToken, wishes::{ WishOut, WishQuery, WishClaimStatusEnum } },
+    wishes::limits::{ fill_availability, row_to_record },
     AppState,
 };
 use ntex::web::{ types::{ Path, Query, State }, HttpResponse, Responder };
@@ -15,12 +16,13 @@ use std::sync::Arc;
     responses((status = 200, body = [WishOut]))
 )]
 pub async fn get_wishes(
-    _: UserToken,
+    user_token: UserToken,
     state: State<Arc<AppState>>,
     query: Query<WishQuery>
 ) -> Result<impl Responder, CustomError> {
     let mut qb: QueryBuilder<sqlx::Postgres> = QueryBuilder::new(
-        "SELECT w.wish_id, w.wish_name, w.wish_cost, w.status, w.created_by, w.created_at, w.updated_at, wc.status as claim_status \
+        "SELECT w.wish_id, w.wish_name, w.wish_cost, w.status, w.created_by, w.created_at, w.updated_at, \
+         w.stock_total, w.per_user_limit, w.limit_period, w.cooldown_minutes, w.available_from, w.available_until, wc.status as claim_status \
          FROM wishes w LEFT JOIN wish_claims wc ON w.wish_id = wc.wish_id"
     );
     
@@ -49,23 +51,18 @@ pub async fn get_wishes(
         qb.push_bind(limit);
     }
     let query_final = qb.build();
-    let rows = query_final.fetch_all(&state.db_pool).await?;
-    let list: Vec<WishOut> = rows
+    let mut conn = state.db_pool.acquire().await?;
+    let rows = query_final.fetch_all(&mut *conn).await?;
+    let mut list: Vec<WishOut> = rows
         .into_iter()
         .map(|r| {
             let claim_status: Option<WishClaimStatusEnum> = r.try_get("claim_status").ok().flatten();
-            WishOut {
-                wish_id: r.get("wish_id"),
-                wish_name: r.get("wish_name"),
-                wish_cost: r.get("wish_cost"),
-                status: r.get("status"),
-                created_by: r.get("created_by"),
-                created_at: r.get("created_at"),
-                updated_at: r.get("updated_at"),
-                current_claim_status: claim_status,
-            }
+            let mut out = WishOut::from(row_to_record(&r));
+            out.current_claim_status = claim_status;
+            out
         })
         .collect();
+    fill_availability(&mut conn, &mut list, user_token.user_id).await?;
     Ok(HttpResponse::Ok().json(&list))
 }
 
@@ -81,28 +78,23 @@ pub async fn get_wish_detail(
     state: State<Arc<AppState>>,
     id: Path<i64>
 ) -> Result<impl Responder, CustomError> {
+    let mut conn = state.db_pool.acquire().await?;
     let row = sqlx
         ::query(
-            "SELECT w.wish_id, w.wish_name, w.wish_cost, w.status, w.created_by, w.created_at, w.updated_at, wc.status as claim_status \
+            "SELECT w.wish_id, w.wish_name, w.wish_cost, w.status, w.created_by, w.created_at, w.updated_at, \
+         w.stock_total, w.per_user_limit, w.limit_period, w.cooldown_minutes, w.available_from, w.available_until, wc.status as claim_status \
          FROM wishes w LEFT JOIN wish_claims wc ON w.wish_id = wc.wish_id AND wc.user_id = $2 \
          WHERE w.wish_id=$1"
         )
         .bind(*id)
         .bind(user_token.user_id)
-        .fetch_optional(&state.db_pool).await?;
+        .fetch_optional(&mut *conn).await?;
     let Some(r) = row else {
         return Err(CustomError::BadRequest("心愿不存在".into()));
     };
     let claim_status: Option<WishClaimStatusEnum> = r.try_get("claim_status").ok().flatten();
-    let out = WishOut {
-        wish_id: r.get("wish_id"),
-        wish_name: r.get("wish_name"),
-        wish_cost: r.get("wish_cost"),
-        status: r.get("status"),
-        created_by: r.get("created_by"),
-        created_at: r.get("created_at"),
-        updated_at: r.get("updated_at"),
-        current_claim_status: claim_status,
-    };
+    let mut out = WishOut::from(row_to_record(&r));
+    out.current_claim_status = claim_status;
+    fill_availability(&mut conn, std::slice::from_mut(&mut out), user_token.user_id).await?;
     Ok(HttpResponse::Ok().json(&out))
 }